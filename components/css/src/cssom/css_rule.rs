@@ -1,6 +1,39 @@
+use super::font_face_rule::FontFaceRule;
+use super::import_rule::ImportRule;
+use super::layer_rule::{LayerBlockRule, LayerStatementRule};
+use super::media_rule::MediaRule;
 use super::style_rule::StyleRule;
+use super::supports_rule::SupportsRule;
+use std::fmt::{self, Display, Formatter};
 
 #[derive(Debug, PartialEq)]
 pub enum CSSRule {
     Style(StyleRule),
+    Media(MediaRule),
+    Import(ImportRule),
+    FontFace(FontFaceRule),
+    Supports(SupportsRule),
+    Layer(LayerBlockRule),
+    LayerStatement(LayerStatementRule),
+}
+
+impl CSSRule {
+    pub fn css_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-rule
+impl Display for CSSRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CSSRule::Style(rule) => write!(f, "{}", rule),
+            CSSRule::Media(rule) => write!(f, "{}", rule),
+            CSSRule::Import(rule) => write!(f, "{}", rule),
+            CSSRule::FontFace(rule) => write!(f, "{}", rule),
+            CSSRule::Supports(rule) => write!(f, "{}", rule),
+            CSSRule::Layer(rule) => write!(f, "{}", rule),
+            CSSRule::LayerStatement(rule) => write!(f, "{}", rule),
+        }
+    }
 }
@@ -1,9 +1,23 @@
 use super::css_rule::CSSRule;
-use std::ops::Deref;
+use crate::parser::parse_rule_str;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, PartialEq)]
 pub struct CSSRuleList(pub Vec<CSSRule>);
 
+/// Errors of inserting & deleting rules
+/// https://www.w3.org/TR/cssom-1/#insert-a-css-rule
+#[derive(Debug, PartialEq)]
+pub enum RuleListError {
+    /// The index is out of the list
+    IndexSize,
+    /// The rule can't be parsed
+    Syntax,
+    /// The rule isn't allowed at the index, e.g. `@import` after a style rule
+    HierarchyRequest,
+}
+
 impl CSSRuleList {
     pub fn new() -> Self {
         Self(Vec::new())
@@ -12,6 +26,59 @@ impl CSSRuleList {
     pub fn append_rule(&mut self, rule: CSSRule) {
         self.0.push(rule);
     }
+
+    /// Parse the rule & insert it at `index`. `@import` rules are only
+    /// allowed at the top level of a stylesheet, before any other rule
+    /// except `@layer` statements.
+    /// Returns the index of the inserted rule.
+    pub fn insert_rule(
+        &mut self,
+        rule: &str,
+        index: usize,
+        top_level: bool,
+    ) -> Result<usize, RuleListError> {
+        if index > self.0.len() {
+            return Err(RuleListError::IndexSize);
+        }
+
+        let rule = parse_rule_str(rule).map_err(|_| RuleListError::Syntax)?;
+        let is_import = |rule: &CSSRule| matches!(rule, CSSRule::Import(_));
+        let is_import_or_statement =
+            |rule: &CSSRule| matches!(rule, CSSRule::Import(_) | CSSRule::LayerStatement(_));
+        let allowed = if is_import(&rule) {
+            top_level && self.0[..index].iter().all(is_import_or_statement)
+        } else if is_import_or_statement(&rule) {
+            true
+        } else {
+            !self.0[index..].iter().any(is_import)
+        };
+        if !allowed {
+            return Err(RuleListError::HierarchyRequest);
+        }
+
+        self.0.insert(index, rule);
+        Ok(index)
+    }
+
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), RuleListError> {
+        if index >= self.0.len() {
+            return Err(RuleListError::IndexSize);
+        }
+        self.0.remove(index);
+        Ok(())
+    }
+}
+
+impl Display for CSSRuleList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, rule) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
 }
 
 impl Deref for CSSRuleList {
@@ -20,3 +87,68 @@ impl Deref for CSSRuleList {
         &self.0
     }
 }
+
+impl DerefMut for CSSRuleList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cssom::stylesheet::StyleSheet;
+    use crate::parser::Parser;
+    use crate::tokenizer::token::Token;
+    use crate::tokenizer::Tokenizer;
+
+    fn parse(css: &str) -> StyleSheet {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parser.parse_a_css_stylesheet()
+    }
+
+    #[test]
+    fn insert_and_delete_rules() {
+        let mut stylesheet = parse("@import url(a.css); p { color: red; }");
+
+        assert_eq!(stylesheet.insert_rule("div { margin: 0 }", 2), Ok(2));
+        assert_eq!(stylesheet.insert_rule("@import 'b.css';", 1), Ok(1));
+        assert_eq!(
+            stylesheet.insert_rule("@import 'c.css';", 3),
+            Err(RuleListError::HierarchyRequest)
+        );
+        assert_eq!(
+            stylesheet.insert_rule("a {}", 0),
+            Err(RuleListError::HierarchyRequest)
+        );
+        assert_eq!(
+            stylesheet.insert_rule("a {} b {}", 4),
+            Err(RuleListError::Syntax)
+        );
+        assert_eq!(
+            stylesheet.insert_rule("a {}", 9),
+            Err(RuleListError::IndexSize)
+        );
+
+        assert_eq!(stylesheet.delete_rule(0), Ok(()));
+        assert_eq!(stylesheet.delete_rule(3), Err(RuleListError::IndexSize));
+        assert_eq!(
+            stylesheet.css_text(),
+            "@import url(\"b.css\");\np { color: red; }\ndiv { margin: 0; }"
+        );
+
+        let mut stylesheet = parse("@media print { p { color: red; } }");
+        if let CSSRule::Media(media) = &mut stylesheet.css_rules[0] {
+            assert_eq!(media.insert_rule("a { color: blue; }", 1), Ok(1));
+            assert_eq!(
+                media.insert_rule("@import 'b.css';", 0),
+                Err(RuleListError::HierarchyRequest)
+            );
+        }
+        assert_eq!(
+            stylesheet.css_text(),
+            "@media print {\n  p { color: red; }\n  a { color: blue; }\n}"
+        );
+    }
+}
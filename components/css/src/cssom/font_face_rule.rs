@@ -0,0 +1,248 @@
+use crate::parser::structs::{ComponentValue, Declaration};
+use crate::serializer::{serialize_string, serialize_url};
+use crate::tokenizer::token::Token;
+use shared::font::{stretch_keyword, FontStyle};
+use std::fmt::{self, Display, Formatter};
+
+/// `@font-face` rule
+/// https://www.w3.org/TR/css-fonts-4/#font-face-rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFaceRule {
+    pub family: String,
+    /// Sources in order of preference
+    pub sources: Vec<FontFaceSource>,
+    pub weight: (f32, f32),
+    pub style: FontStyle,
+    pub stretch: (f32, f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontFaceSource {
+    /// `url(...) format(...)?`
+    Url(String, Option<String>),
+    /// `local(...)`
+    Local(String),
+}
+
+impl FontFaceRule {
+    /// Interpret the descriptors of the rule. The rule is invalid without
+    /// `font-family` & `src`.
+    pub fn parse(declarations: &[Declaration]) -> Option<Self> {
+        let mut family = None;
+        let mut sources = None;
+        let mut weight = (400., 400.);
+        let mut style = FontStyle::Normal;
+        let mut stretch = (100., 100.);
+
+        for declaration in declarations {
+            let values = declaration
+                .value
+                .iter()
+                .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+                .collect::<Vec<&ComponentValue>>();
+
+            // invalid descriptors are ignored
+            match declaration.name.to_lowercase().as_str() {
+                "font-family" => family = parse_family(&values).or(family),
+                "src" => sources = parse_sources(&declaration.value).or(sources),
+                "font-weight" => weight = parse_range(&values, parse_weight).unwrap_or(weight),
+                "font-style" => style = parse_style(&values).unwrap_or(style),
+                "font-stretch" => stretch = parse_range(&values, parse_stretch).unwrap_or(stretch),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            family: family?,
+            sources: sources?,
+            weight,
+            style,
+            stretch,
+        })
+    }
+}
+
+impl Display for FontFaceRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "@font-face {{ font-family: {}; src: ",
+            serialize_string(&self.family)
+        )?;
+        for (index, source) in self.sources.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            match source {
+                FontFaceSource::Url(url, format) => {
+                    f.write_str(&serialize_url(url))?;
+                    if let Some(format) = format {
+                        write!(f, " format({})", serialize_string(format))?;
+                    }
+                }
+                FontFaceSource::Local(family) => write!(f, "local({})", serialize_string(family))?,
+            }
+        }
+        f.write_str(";")?;
+
+        write_range(f, "font-weight", self.weight, 400., "")?;
+        match self.style {
+            FontStyle::Normal => {}
+            FontStyle::Italic => f.write_str(" font-style: italic;")?,
+            FontStyle::Oblique => f.write_str(" font-style: oblique;")?,
+        }
+        write_range(f, "font-stretch", self.stretch, 100., "%")?;
+        f.write_str(" }")
+    }
+}
+
+/// Only the descriptors with a non-initial value are serialized
+fn write_range(
+    f: &mut Formatter<'_>,
+    name: &str,
+    (min, max): (f32, f32),
+    initial: f32,
+    unit: &str,
+) -> fmt::Result {
+    if min == max && min == initial {
+        Ok(())
+    } else if min == max {
+        write!(f, " {}: {}{};", name, min, unit)
+    } else {
+        write!(f, " {}: {}{} {}{};", name, min, unit, max, unit)
+    }
+}
+
+fn parse_family(values: &[&ComponentValue]) -> Option<String> {
+    match values {
+        [ComponentValue::PerservedToken(Token::Str(family))] => Some(family.clone()),
+        [] => None,
+        values => values
+            .iter()
+            .map(|value| match value {
+                ComponentValue::PerservedToken(Token::Ident(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<String>>>()
+            .map(|names| names.join(" ")),
+    }
+}
+
+/// Parse a comma separated list of sources. Unsupported sources are
+/// skipped.
+fn parse_sources(values: &[ComponentValue]) -> Option<Vec<FontFaceSource>> {
+    let sources = values
+        .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+        .filter_map(parse_source)
+        .collect::<Vec<FontFaceSource>>();
+
+    if sources.is_empty() {
+        None
+    } else {
+        Some(sources)
+    }
+}
+
+fn parse_source(values: &[ComponentValue]) -> Option<FontFaceSource> {
+    let values = values
+        .iter()
+        .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+        .collect::<Vec<&ComponentValue>>();
+
+    let (source, format) = match values.as_slice() {
+        [source] => (*source, None),
+        [source, ComponentValue::Function(format)]
+            if format.name.eq_ignore_ascii_case("format") =>
+        {
+            match format.value.as_slice() {
+                [ComponentValue::PerservedToken(Token::Str(format))]
+                | [ComponentValue::PerservedToken(Token::Ident(format))] => {
+                    (*source, Some(format.to_lowercase()))
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    match source {
+        ComponentValue::PerservedToken(Token::Url(url)) => {
+            Some(FontFaceSource::Url(url.clone(), format))
+        }
+        ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("url") => {
+            match function.value.as_slice() {
+                [ComponentValue::PerservedToken(Token::Str(url))] => {
+                    Some(FontFaceSource::Url(url.clone(), format))
+                }
+                _ => None,
+            }
+        }
+        ComponentValue::Function(function)
+            if function.name.eq_ignore_ascii_case("local") && format.is_none() =>
+        {
+            let values = function
+                .value
+                .iter()
+                .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+                .collect::<Vec<&ComponentValue>>();
+            parse_family(&values).map(FontFaceSource::Local)
+        }
+        _ => None,
+    }
+}
+
+/// Parse a single value or a range of two values
+fn parse_range<F: Fn(&ComponentValue) -> Option<f32>>(
+    values: &[&ComponentValue],
+    parse: F,
+) -> Option<(f32, f32)> {
+    match values {
+        [value] => parse(value).map(|value| (value, value)),
+        [min, max] => {
+            let (min, max) = (parse(min)?, parse(max)?);
+            Some((min.min(max), min.max(max)))
+        }
+        _ => None,
+    }
+}
+
+fn parse_weight(value: &ComponentValue) -> Option<f32> {
+    match value {
+        ComponentValue::PerservedToken(Token::Ident(value)) => {
+            match value.to_lowercase().as_str() {
+                "normal" => Some(400.),
+                "bold" => Some(700.),
+                _ => None,
+            }
+        }
+        ComponentValue::PerservedToken(Token::Number { value, .. })
+            if *value >= 1. && *value <= 1000. =>
+        {
+            Some(*value)
+        }
+        _ => None,
+    }
+}
+
+fn parse_stretch(value: &ComponentValue) -> Option<f32> {
+    match value {
+        ComponentValue::PerservedToken(Token::Ident(value)) => stretch_keyword(value),
+        ComponentValue::PerservedToken(Token::Percentage(value)) if *value >= 0. => Some(*value),
+        _ => None,
+    }
+}
+
+/// Oblique angles are ignored
+fn parse_style(values: &[&ComponentValue]) -> Option<FontStyle> {
+    match values.first() {
+        Some(ComponentValue::PerservedToken(Token::Ident(value))) => {
+            match value.to_lowercase().as_str() {
+                "normal" => Some(FontStyle::Normal),
+                "italic" => Some(FontStyle::Italic),
+                "oblique" => Some(FontStyle::Oblique),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,49 @@
+use super::layer_rule::LayerName;
+use super::stylesheet::StyleSheet;
+use crate::media_query::structs::MediaQueryList;
+use crate::serializer::serialize_url;
+use std::fmt::{self, Display, Formatter};
+
+/// `@import` rule
+/// https://www.w3.org/TR/css-cascade-4/#at-import
+#[derive(Debug, PartialEq)]
+pub struct ImportRule {
+    /// The URL of the imported stylesheet, relative to the importing stylesheet
+    pub href: String,
+    pub media: MediaQueryList,
+    /// The cascade layer that the imported rules are put in, `Some(None)`
+    /// for an anonymous layer
+    pub layer: Option<Option<LayerName>>,
+    /// The imported stylesheet. `None` until it's loaded or if it failed to load
+    pub stylesheet: Option<StyleSheet>,
+}
+
+impl ImportRule {
+    pub fn new(href: String, media: MediaQueryList) -> Self {
+        Self {
+            href,
+            media,
+            layer: None,
+            stylesheet: None,
+        }
+    }
+
+    pub fn set_stylesheet(&mut self, stylesheet: StyleSheet) {
+        self.stylesheet = Some(stylesheet);
+    }
+}
+
+impl Display for ImportRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "@import {}", serialize_url(&self.href))?;
+        match &self.layer {
+            Some(Some(name)) => write!(f, " layer({})", name)?,
+            Some(None) => f.write_str(" layer")?,
+            None => {}
+        }
+        if !self.media.0.is_empty() {
+            write!(f, " {}", self.media)?;
+        }
+        f.write_str(";")
+    }
+}
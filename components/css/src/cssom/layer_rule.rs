@@ -0,0 +1,134 @@
+use super::css_rule_list::{CSSRuleList, RuleListError};
+use crate::parser::structs::ComponentValue;
+use crate::serializer::serialize_identifier;
+use crate::tokenizer::token::Token;
+use std::fmt::{self, Display, Formatter};
+
+/// Name of a cascade layer, e.g. `framework.base` for the layer `base`
+/// nested in the layer `framework`
+/// https://www.w3.org/TR/css-cascade-5/#typedef-layer-name
+#[derive(Debug, PartialEq, Clone)]
+pub struct LayerName(pub Vec<String>);
+
+/// `@layer` rule with a block. A layer without a name is anonymous, i.e.
+/// rules can't be added to it from elsewhere.
+/// https://www.w3.org/TR/css-cascade-5/#layer-block
+#[derive(Debug, PartialEq)]
+pub struct LayerBlockRule {
+    pub name: Option<LayerName>,
+    pub css_rules: CSSRuleList,
+}
+
+/// `@layer` rule without a block, which declares the order of the layers
+/// https://www.w3.org/TR/css-cascade-5/#layer-empty
+#[derive(Debug, PartialEq)]
+pub struct LayerStatementRule {
+    pub names: Vec<LayerName>,
+}
+
+impl LayerName {
+    /// Parse `<ident> ['.' <ident>]*` without whitespaces in between. The
+    /// CSS-wide keywords are reserved.
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let values = trim_whitespaces(values);
+        let mut names = Vec::new();
+
+        for (index, value) in values.iter().enumerate() {
+            match (index % 2, value) {
+                (0, ComponentValue::PerservedToken(Token::Ident(name))) => names.push(name.clone()),
+                (1, ComponentValue::PerservedToken(Token::Delim('.'))) => {}
+                _ => return None,
+            }
+        }
+
+        let is_reserved = |name: &String| {
+            matches!(
+                name.to_lowercase().as_str(),
+                "initial" | "inherit" | "unset" | "revert" | "revert-layer" | "default"
+            )
+        };
+        // a name can't end with a dot
+        if names.is_empty() || values.len() != names.len() * 2 - 1 || names.iter().any(is_reserved)
+        {
+            return None;
+        }
+        Some(Self(names))
+    }
+
+    /// Parse a comma separated list of layer names
+    pub fn parse_list(values: &[ComponentValue]) -> Option<Vec<Self>> {
+        values
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(LayerName::parse)
+            .collect()
+    }
+}
+
+impl LayerBlockRule {
+    pub fn new(name: Option<LayerName>, css_rules: CSSRuleList) -> Self {
+        Self { name, css_rules }
+    }
+
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, RuleListError> {
+        self.css_rules.insert_rule(rule, index, false)
+    }
+
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), RuleListError> {
+        self.css_rules.delete_rule(index)
+    }
+}
+
+impl LayerStatementRule {
+    pub fn new(names: Vec<LayerName>) -> Self {
+        Self { names }
+    }
+}
+
+fn trim_whitespaces(values: &[ComponentValue]) -> &[ComponentValue] {
+    let is_whitespace =
+        |value: &ComponentValue| matches!(value, ComponentValue::PerservedToken(Token::Whitespace));
+    let start = values
+        .iter()
+        .position(|value| !is_whitespace(value))
+        .unwrap_or(values.len());
+    let end = values
+        .iter()
+        .rposition(|value| !is_whitespace(value))
+        .map_or(start, |end| end + 1);
+    &values[start..end]
+}
+
+impl Display for LayerName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names = self
+            .0
+            .iter()
+            .map(|name| serialize_identifier(name))
+            .collect::<Vec<String>>();
+        f.write_str(&names.join("."))
+    }
+}
+
+impl Display for LayerBlockRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => writeln!(f, "@layer {} {{", name)?,
+            None => writeln!(f, "@layer {{")?,
+        }
+        for rule in self.css_rules.iter() {
+            writeln!(f, "  {}", rule.to_string().replace('\n', "\n  "))?;
+        }
+        f.write_str("}")
+    }
+}
+
+impl Display for LayerStatementRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names = self
+            .names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "@layer {};", names.join(", "))
+    }
+}
@@ -0,0 +1,36 @@
+use super::css_rule_list::{CSSRuleList, RuleListError};
+use crate::media_query::structs::MediaQueryList;
+use std::fmt::{self, Display, Formatter};
+
+/// `@media` rule
+/// https://www.w3.org/TR/css-conditional-3/#at-media
+#[derive(Debug, PartialEq)]
+pub struct MediaRule {
+    pub media: MediaQueryList,
+    pub css_rules: CSSRuleList,
+}
+
+impl MediaRule {
+    pub fn new(media: MediaQueryList, css_rules: CSSRuleList) -> Self {
+        Self { media, css_rules }
+    }
+
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, RuleListError> {
+        self.css_rules.insert_rule(rule, index, false)
+    }
+
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), RuleListError> {
+        self.css_rules.delete_rule(index)
+    }
+}
+
+impl Display for MediaRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "@media {} {{", self.media)?;
+        for rule in self.css_rules.iter() {
+            // indent the nested rules
+            writeln!(f, "  {}", rule.to_string().replace('\n', "\n  "))?;
+        }
+        f.write_str("}")
+    }
+}
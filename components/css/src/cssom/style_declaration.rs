@@ -0,0 +1,229 @@
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use crate::parser::parse_declarations_str;
+use crate::parser::structs::{ComponentValue, Declaration};
+use crate::serializer::serialize_values;
+use crate::supports::structs::DeclarationSupport;
+use crate::tokenizer::token::Token;
+
+/// Declaration block of a style rule or of the `style` attribute.
+/// Shorthands are kept as declared, they are only expanded when cascading.
+/// https://www.w3.org/TR/cssom-1/#cssstyledeclaration
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CSSStyleDeclaration(pub Vec<Declaration>);
+
+impl CSSStyleDeclaration {
+    pub fn new(declarations: Vec<Declaration>) -> Self {
+        Self(declarations)
+    }
+
+    /// Parse a declaration block without its braces, e.g. the value of the
+    /// `style` attribute
+    pub fn parse(css: &str) -> Self {
+        Self(parse_declarations_str(css))
+    }
+
+    pub fn css_text(&self) -> String {
+        self.to_string()
+    }
+
+    /// Replace all of the declarations
+    pub fn set_css_text(&mut self, css: &str) {
+        self.0 = parse_declarations_str(css);
+    }
+
+    /// The serialized value of the last declaration of the property, or an
+    /// empty string if the property isn't declared
+    pub fn get_property_value(&self, name: &str) -> String {
+        self.find(name)
+            .map(|declaration| serialize_values(&declaration.value))
+            .unwrap_or_default()
+    }
+
+    /// `important` if the property is declared with `!important`
+    pub fn get_property_priority(&self, name: &str) -> String {
+        match self.find(name) {
+            Some(declaration) if declaration.important => "important".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Declare the property, replacing its previous declaration. An empty
+    /// value removes the property. The value is checked against the grammar
+    /// of the property by `is_valid`, the check of the style system.
+    /// Returns `false` if the value or the priority is invalid, in which
+    /// case the block is left unchanged.
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-setproperty
+    pub fn set_property(
+        &mut self,
+        name: &str,
+        value: &str,
+        priority: &str,
+        is_valid: &DeclarationSupport<'_>,
+    ) -> bool {
+        if value.trim().is_empty() {
+            self.remove_property(name);
+            return true;
+        }
+
+        let important = match priority.to_lowercase().as_str() {
+            "" => false,
+            "important" => true,
+            _ => return false,
+        };
+
+        let mut declarations = parse_declarations_str(&format!("{}: {}", name, value));
+        let mut declaration = match declarations.pop() {
+            Some(declaration) if declarations.is_empty() => declaration,
+            _ => return false,
+        };
+        if declaration.value.is_empty()
+            || declaration.important
+            || has_top_level_delimiters(&declaration.value)
+        {
+            return false;
+        }
+        declaration.name = normalize_name(name);
+        if !is_valid(&declaration.name, &declaration.value) {
+            return false;
+        }
+        declaration.important = important;
+
+        match self.position(name) {
+            Some(index) => {
+                // the other declarations of the property are overridden
+                self.0
+                    .retain(|d| !is_same_property(&d.name, &declaration.name));
+                self.0.insert(index.min(self.0.len()), declaration);
+            }
+            None => self.0.push(declaration),
+        }
+        true
+    }
+
+    /// Remove the declarations of the property. Returns the value of the
+    /// removed declaration.
+    pub fn remove_property(&mut self, name: &str) -> String {
+        let value = self.get_property_value(name);
+        self.0
+            .retain(|declaration| !is_same_property(&declaration.name, name));
+        value
+    }
+
+    fn find(&self, name: &str) -> Option<&Declaration> {
+        self.0
+            .iter()
+            .rev()
+            .find(|declaration| is_same_property(&declaration.name, name))
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|declaration| is_same_property(&declaration.name, name))
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-declaration-block
+impl Display for CSSStyleDeclaration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, declaration) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{};", declaration)?;
+        }
+        Ok(())
+    }
+}
+
+impl Deref for CSSStyleDeclaration {
+    type Target = Vec<Declaration>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for CSSStyleDeclaration {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Custom property names are case-sensitive, the others are ASCII
+/// case-insensitive
+fn normalize_name(name: &str) -> String {
+    let name = name.trim();
+    if name.starts_with("--") {
+        name.to_string()
+    } else {
+        name.to_lowercase()
+    }
+}
+
+fn is_same_property(a: &str, b: &str) -> bool {
+    normalize_name(a) == normalize_name(b)
+}
+
+/// `;` & `!` can't appear in a value set through the API
+fn has_top_level_delimiters(values: &[ComponentValue]) -> bool {
+    values.iter().any(|value| {
+        matches!(
+            value,
+            ComponentValue::PerservedToken(Token::Semicolon)
+                | ComponentValue::PerservedToken(Token::Delim('!'))
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Only `color: <ident>` & custom properties are valid
+    fn is_valid(name: &str, value: &[ComponentValue]) -> bool {
+        match (name, value) {
+            ("color", [ComponentValue::PerservedToken(Token::Ident(_))]) => true,
+            (name, _) => name.starts_with("--"),
+        }
+    }
+
+    #[test]
+    fn edit_declarations() {
+        let mut style = CSSStyleDeclaration::parse("color: red; margin: 0 auto !important");
+        assert_eq!(style.len(), 2);
+        assert_eq!(style.get_property_value("COLOR"), "red");
+        assert_eq!(style.get_property_value("margin"), "0 auto");
+        assert_eq!(style.get_property_priority("margin"), "important");
+
+        assert!(style.set_property("color", "blue", "", &is_valid));
+        assert!(style.set_property("--Accent", "#fff", "", &is_valid));
+        assert!(!style.set_property("--accent", "1px; color: red", "", &is_valid));
+        assert!(!style.set_property("--accent", "1px", "high", &is_valid));
+        assert_eq!(
+            style.css_text(),
+            "color: blue; margin: 0 auto !important; --Accent: #fff;"
+        );
+
+        assert_eq!(style.remove_property("margin"), "0 auto");
+        assert!(style.set_property("color", "", "", &is_valid));
+        assert_eq!(style.css_text(), "--Accent: #fff;");
+
+        style.set_css_text("width: 10px");
+        assert_eq!(style.get_property_value("width"), "10px");
+        assert_eq!(style.get_property_value("--accent"), "");
+    }
+
+    #[test]
+    fn reject_values_not_matching_the_grammar() {
+        let mut style = CSSStyleDeclaration::parse("color: red");
+        assert!(!style.set_property("color", "1px", "", &is_valid));
+        assert!(!style.set_property("COLOR", "red blue", "important", &is_valid));
+        assert!(!style.set_property("width", "10px", "", &is_valid));
+        assert_eq!(style.css_text(), "color: red;");
+
+        assert!(style.set_property("COLOR", "blue", "important", &is_valid));
+        assert_eq!(style.css_text(), "color: blue !important;");
+    }
+}
@@ -1,27 +1,37 @@
+use super::style_declaration::CSSStyleDeclaration;
 use crate::parser::structs::Declaration;
-use crate::selector::structs::{Selector, Specificity};
+use crate::selector::structs::Selector;
+use std::fmt::{self, Display, Formatter};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct StyleRule {
     pub selectors: Vec<Selector>,
-    pub declarations: Vec<Declaration>,
+    pub declarations: CSSStyleDeclaration,
 }
 
 impl StyleRule {
     pub fn new(selectors: Vec<Selector>, declarations: Vec<Declaration>) -> Self {
         Self {
             selectors,
-            declarations,
+            declarations: CSSStyleDeclaration::new(declarations),
         }
     }
 
-    pub fn specificity(&self) -> Specificity {
-        let specificities = self
-            .selectors
+    pub fn selector_text(&self) -> String {
+        self.selectors
             .iter()
-            .map(|selector| selector.specificity())
-            .collect::<Vec<Specificity>>();
+            .map(|selector| selector.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
 
-        specificities.into_iter().max().unwrap()
+impl Display for StyleRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{ ", self.selector_text())?;
+        if !self.declarations.is_empty() {
+            write!(f, "{} ", self.declarations)?;
+        }
+        f.write_str("}")
     }
 }
@@ -1,5 +1,6 @@
 use super::css_rule::CSSRule;
-use super::css_rule_list::CSSRuleList;
+use super::css_rule_list::{CSSRuleList, RuleListError};
+use std::fmt::{self, Display, Formatter};
 use std::ops::Deref;
 
 #[derive(Debug, PartialEq)]
@@ -17,6 +18,24 @@ impl StyleSheet {
     pub fn append_rule(&mut self, rule: CSSRule) {
         self.css_rules.append_rule(rule);
     }
+
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, RuleListError> {
+        self.css_rules.insert_rule(rule, index, true)
+    }
+
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), RuleListError> {
+        self.css_rules.delete_rule(index)
+    }
+
+    pub fn css_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for StyleSheet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.css_rules)
+    }
 }
 
 impl Deref for StyleSheet {
@@ -0,0 +1,42 @@
+use super::css_rule_list::{CSSRuleList, RuleListError};
+use crate::supports::structs::{DeclarationSupport, SupportsCondition};
+use std::fmt::{self, Display, Formatter};
+
+/// `@supports` rule
+/// https://www.w3.org/TR/css-conditional-3/#at-supports
+#[derive(Debug, PartialEq)]
+pub struct SupportsRule {
+    pub condition: SupportsCondition,
+    pub css_rules: CSSRuleList,
+}
+
+impl SupportsRule {
+    pub fn new(condition: SupportsCondition, css_rules: CSSRuleList) -> Self {
+        Self {
+            condition,
+            css_rules,
+        }
+    }
+
+    pub fn matches(&self, supports_declaration: &DeclarationSupport<'_>) -> bool {
+        self.condition.matches(supports_declaration)
+    }
+
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, RuleListError> {
+        self.css_rules.insert_rule(rule, index, false)
+    }
+
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), RuleListError> {
+        self.css_rules.delete_rule(index)
+    }
+}
+
+impl Display for SupportsRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "@supports {} {{", self.condition)?;
+        for rule in self.css_rules.iter() {
+            writeln!(f, "  {}", rule.to_string().replace('\n', "\n  "))?;
+        }
+        f.write_str("}")
+    }
+}
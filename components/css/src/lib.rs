@@ -1,4 +1,7 @@
 pub mod cssom;
+pub mod media_query;
 pub mod parser;
 pub mod selector;
+pub mod serializer;
+pub mod supports;
 pub mod tokenizer;
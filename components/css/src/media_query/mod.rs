@@ -0,0 +1,240 @@
+pub mod structs;
+
+use super::parser::structs::ComponentValue;
+use super::tokenizer::token::Token;
+use structs::*;
+
+/// Font size used to resolve `em` & `rem` in media queries
+const INITIAL_FONT_SIZE: f32 = 16.;
+
+/// Parse the prelude of a `@media` rule. Invalid media queries are replaced
+/// with `not all`.
+/// https://www.w3.org/TR/mediaqueries-4/#error-handling
+pub fn parse_media_query_list(values: &[ComponentValue]) -> MediaQueryList {
+    let values = values
+        .iter()
+        .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+        .collect::<Vec<&ComponentValue>>();
+
+    if values.is_empty() {
+        return MediaQueryList::new(Vec::new());
+    }
+
+    let queries = values
+        .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+        .map(|values| parse_media_query(values).unwrap_or_else(MediaQuery::not_all))
+        .collect();
+
+    MediaQueryList::new(queries)
+}
+
+/// Parse `[not | only]? <media-type> [and <media-feature>]*` or
+/// `<media-feature> [and <media-feature>]*`
+fn parse_media_query(values: &[&ComponentValue]) -> Option<MediaQuery> {
+    let mut values = values.iter().peekable();
+
+    let qualifier = match values.peek() {
+        Some(ComponentValue::PerservedToken(Token::Ident(ident))) => {
+            match ident.to_lowercase().as_str() {
+                "not" => Some(MediaQualifier::Not),
+                "only" => Some(MediaQualifier::Only),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    if qualifier.is_some() {
+        values.next();
+    }
+
+    let mut features = Vec::new();
+    let media_type = match values.next()? {
+        ComponentValue::PerservedToken(Token::Ident(ident)) => {
+            match ident.to_lowercase().as_str() {
+                "all" => MediaType::All,
+                "screen" => MediaType::Screen,
+                "print" => MediaType::Print,
+                "and" | "or" | "not" | "only" => return None,
+                media_type => MediaType::Unknown(media_type.to_string()),
+            }
+        }
+        ComponentValue::SimpleBlock(block) if block.token == Token::ParentheseOpen => {
+            // `only` requires a media type
+            if qualifier == Some(MediaQualifier::Only) {
+                return None;
+            }
+            features.push(parse_media_feature(&block.value)?);
+            MediaType::All
+        }
+        _ => return None,
+    };
+
+    while let Some(value) = values.next() {
+        match value {
+            ComponentValue::PerservedToken(Token::Ident(ident))
+                if ident.eq_ignore_ascii_case("and") => {}
+            _ => return None,
+        }
+        match values.next()? {
+            ComponentValue::SimpleBlock(block) if block.token == Token::ParentheseOpen => {
+                features.push(parse_media_feature(&block.value)?);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(MediaQuery {
+        qualifier,
+        media_type,
+        features,
+    })
+}
+
+/// Parse the content of `(<name>: <value>)`
+fn parse_media_feature(values: &[ComponentValue]) -> Option<MediaFeature> {
+    let values = values
+        .iter()
+        .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+        .collect::<Vec<&ComponentValue>>();
+
+    let (name, value) = match values.as_slice() {
+        [ComponentValue::PerservedToken(Token::Ident(name)), ComponentValue::PerservedToken(Token::Colon), value] => {
+            (name.to_lowercase(), *value)
+        }
+        _ => return None,
+    };
+
+    match name.as_str() {
+        "min-width" => Some(MediaFeature::MinWidth(parse_length(value)?)),
+        "max-width" => Some(MediaFeature::MaxWidth(parse_length(value)?)),
+        "orientation" => match parse_ident(value)?.as_str() {
+            "portrait" => Some(MediaFeature::Orientation(Orientation::Portrait)),
+            "landscape" => Some(MediaFeature::Orientation(Orientation::Landscape)),
+            _ => None,
+        },
+        "prefers-color-scheme" => match parse_ident(value)?.as_str() {
+            "light" => Some(MediaFeature::PrefersColorScheme(ColorScheme::Light)),
+            "dark" => Some(MediaFeature::PrefersColorScheme(ColorScheme::Dark)),
+            _ => None,
+        },
+        "forced-colors" => match parse_ident(value)?.as_str() {
+            "active" => Some(MediaFeature::ForcedColors(true)),
+            "none" => Some(MediaFeature::ForcedColors(false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn parse_ident(value: &ComponentValue) -> Option<String> {
+    match value {
+        ComponentValue::PerservedToken(Token::Ident(ident)) => Some(ident.to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Parse a length in px
+fn parse_length(value: &ComponentValue) -> Option<f32> {
+    match value {
+        ComponentValue::PerservedToken(Token::Dimension { value, unit, .. }) => {
+            match unit.to_lowercase().as_str() {
+                "px" => Some(*value),
+                "em" | "rem" => Some(*value * INITIAL_FONT_SIZE),
+                "pt" => Some(*value * 4. / 3.),
+                _ => None,
+            }
+        }
+        ComponentValue::PerservedToken(Token::Number { value, .. }) if *value == 0. => Some(0.),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    fn parse(css: &str) -> MediaQueryList {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parse_media_query_list(&parser.parse_a_list_of_component_values())
+    }
+
+    #[test]
+    fn parse_media_queries() {
+        assert_eq!(
+            parse("only screen and (min-width: 600px) and (orientation: landscape)"),
+            MediaQueryList::new(vec![MediaQuery {
+                qualifier: Some(MediaQualifier::Only),
+                media_type: MediaType::Screen,
+                features: vec![
+                    MediaFeature::MinWidth(600.),
+                    MediaFeature::Orientation(Orientation::Landscape),
+                ],
+            }])
+        );
+
+        assert_eq!(
+            parse("(max-width: 40em), not print"),
+            MediaQueryList::new(vec![
+                MediaQuery {
+                    qualifier: None,
+                    media_type: MediaType::All,
+                    features: vec![MediaFeature::MaxWidth(640.)],
+                },
+                MediaQuery {
+                    qualifier: Some(MediaQualifier::Not),
+                    media_type: MediaType::Print,
+                    features: vec![],
+                },
+            ])
+        );
+
+        assert_eq!(
+            parse("screen and (unknown: 1), (min-width: 1vw)"),
+            MediaQueryList::new(vec![MediaQuery::not_all(), MediaQuery::not_all()])
+        );
+    }
+
+    #[test]
+    fn evaluate_media_queries() {
+        let desktop = MediaEnvironment::new(1280., 720.);
+        let mobile = MediaEnvironment::new(375., 667.);
+        let dark = MediaEnvironment {
+            color_scheme: ColorScheme::Dark,
+            ..desktop.clone()
+        };
+
+        let cases = [
+            ("", true, true),
+            ("screen", true, true),
+            ("print", false, false),
+            ("not print", true, true),
+            ("(min-width: 768px)", true, false),
+            ("screen and (max-width: 767px)", false, true),
+            ("(orientation: portrait)", false, true),
+            ("not screen and (min-width: 768px)", false, true),
+            ("print, (max-width: 400px)", false, true),
+            ("(prefers-color-scheme: dark)", false, false),
+            ("(forced-colors: active)", false, false),
+            ("(forced-colors: none)", true, true),
+            ("tv", false, false),
+        ];
+
+        for (css, on_desktop, on_mobile) in cases {
+            let queries = parse(css);
+            assert_eq!(queries.matches(&desktop), on_desktop, "{}", css);
+            assert_eq!(queries.matches(&mobile), on_mobile, "{}", css);
+        }
+
+        assert!(parse("(prefers-color-scheme: dark)").matches(&dark));
+
+        let forced_colors = MediaEnvironment {
+            forced_colors: true,
+            ..desktop.clone()
+        };
+        assert!(parse("(forced-colors: active)").matches(&forced_colors));
+        assert!(!parse("(forced-colors: none)").matches(&forced_colors));
+    }
+}
@@ -0,0 +1,141 @@
+/// A comma separated list of media queries, e.g. `screen and (min-width: 600px), print`
+/// https://www.w3.org/TR/mediaqueries-4/#media-query-list
+#[derive(Debug, PartialEq, Clone)]
+pub struct MediaQueryList(pub Vec<MediaQuery>);
+
+/// A media query. The query matches if the media type & all of the
+/// features match, inverted by the `not` qualifier.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MediaQuery {
+    pub qualifier: Option<MediaQualifier>,
+    pub media_type: MediaType,
+    pub features: Vec<MediaFeature>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MediaQualifier {
+    Not,
+    Only,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    /// Unknown media types are valid but never match
+    Unknown(String),
+}
+
+/// A media feature with its value. Lengths are in px.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MediaFeature {
+    MinWidth(f32),
+    MaxWidth(f32),
+    Orientation(Orientation),
+    PrefersColorScheme(ColorScheme),
+    /// `forced-colors: active`, or `none` if `false`
+    ForcedColors(bool),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// The environment media queries are evaluated against
+#[derive(Debug, PartialEq, Clone)]
+pub struct MediaEnvironment {
+    pub width: f32,
+    pub height: f32,
+    pub color_scheme: ColorScheme,
+    /// Whether the browser replaces the author colors with a limited
+    /// palette, e.g. in a high contrast mode
+    pub forced_colors: bool,
+}
+
+impl MediaQueryList {
+    pub fn new(queries: Vec<MediaQuery>) -> Self {
+        Self(queries)
+    }
+
+    /// An empty list matches every environment
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        self.0.is_empty() || self.0.iter().any(|query| query.matches(environment))
+    }
+}
+
+impl MediaQuery {
+    /// The query that an invalid media query is replaced with
+    pub fn not_all() -> Self {
+        Self {
+            qualifier: Some(MediaQualifier::Not),
+            media_type: MediaType::All,
+            features: Vec::new(),
+        }
+    }
+
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        let is_match = self.media_type.matches()
+            && self
+                .features
+                .iter()
+                .all(|feature| feature.matches(environment));
+
+        match self.qualifier {
+            Some(MediaQualifier::Not) => !is_match,
+            _ => is_match,
+        }
+    }
+}
+
+impl MediaType {
+    pub fn matches(&self) -> bool {
+        matches!(self, MediaType::All | MediaType::Screen)
+    }
+}
+
+impl MediaFeature {
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        match self {
+            MediaFeature::MinWidth(width) => environment.width >= *width,
+            MediaFeature::MaxWidth(width) => environment.width <= *width,
+            MediaFeature::Orientation(orientation) => *orientation == environment.orientation(),
+            MediaFeature::PrefersColorScheme(scheme) => *scheme == environment.color_scheme,
+            MediaFeature::ForcedColors(active) => *active == environment.forced_colors,
+        }
+    }
+}
+
+impl MediaEnvironment {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            color_scheme: ColorScheme::Light,
+            forced_colors: false,
+        }
+    }
+
+    /// https://www.w3.org/TR/mediaqueries-4/#orientation
+    pub fn orientation(&self) -> Orientation {
+        if self.height >= self.width {
+            Orientation::Portrait
+        } else {
+            Orientation::Landscape
+        }
+    }
+}
+
+impl Default for MediaEnvironment {
+    fn default() -> Self {
+        Self::new(0., 0.)
+    }
+}
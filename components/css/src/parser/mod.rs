@@ -1,10 +1,19 @@
 pub mod structs;
 
 use super::cssom::css_rule::CSSRule;
+use super::cssom::css_rule_list::CSSRuleList;
+use super::cssom::font_face_rule::FontFaceRule;
+use super::cssom::import_rule::ImportRule;
+use super::cssom::layer_rule::{LayerBlockRule, LayerName, LayerStatementRule};
+use super::cssom::media_rule::MediaRule;
 use super::cssom::style_rule::StyleRule;
 use super::cssom::stylesheet::StyleSheet;
+use super::cssom::supports_rule::SupportsRule;
+use super::media_query::parse_media_query_list;
 use super::selector::parse_selectors;
+use super::supports::parse_supports_condition;
 use super::tokenizer::token::Token;
+use super::tokenizer::Tokenizer;
 use shared::data_stream::DataStream;
 use std::env;
 use structs::*;
@@ -30,8 +39,190 @@ macro_rules! emit_error {
     };
 }
 
+#[derive(Debug, PartialEq)]
 pub struct SyntaxError;
 
+/// Interpret the rules of a stylesheet or of a grouping rule (e.g. `@media`)
+/// as CSS rules. Invalid & unsupported rules are dropped.
+///
+/// `@import` rules are only valid at the top level of a stylesheet, before
+/// any other rule except `@layer` statements.
+fn interpret_rules(rules: ListOfRules, top_level: bool) -> Vec<CSSRule> {
+    let mut allow_import = top_level;
+    rules
+        .into_iter()
+        .filter_map(|rule| {
+            let rule = match rule {
+                Rule::QualifiedRule(rule) => interpret_style_rule(rule).map(CSSRule::Style),
+                Rule::AtRule(rule) => interpret_at_rule(rule),
+            }?;
+            match rule {
+                CSSRule::Import(_) if !allow_import => {
+                    emit_error!("@import is not allowed after other rules");
+                    None
+                }
+                CSSRule::Import(_) | CSSRule::LayerStatement(_) => Some(rule),
+                _ => {
+                    allow_import = false;
+                    Some(rule)
+                }
+            }
+        })
+        .collect()
+}
+
+fn interpret_style_rule(rule: QualifiedRule) -> Option<StyleRule> {
+    let selectors = parse_selectors(&rule.prelude);
+    if selectors.len() == 0 {
+        // invalid rule
+        return None;
+    }
+    let content = match rule.block {
+        Some(block) => interpret_declarations(block),
+        None => Vec::new(),
+    };
+    Some(StyleRule::new(selectors, content))
+}
+
+pub(crate) fn interpret_declarations(block: SimpleBlock) -> Vec<Declaration> {
+    let mut parser = Parser::<ComponentValue>::new(DataStream::new(block.value));
+
+    let declarations = parser.parse_a_list_of_declarations();
+
+    // take only declaration
+    declarations
+        .into_iter()
+        .filter_map(|declaration| match declaration {
+            DeclarationOrAtRule::Declaration(d) => Some(d),
+            _ => None,
+        })
+        .collect()
+}
+
+fn interpret_at_rule(rule: AtRule) -> Option<CSSRule> {
+    match rule.name.to_lowercase().as_str() {
+        "media" => {
+            let block = rule.block?;
+            let media = parse_media_query_list(&rule.prelude);
+            let mut parser = Parser::<ComponentValue>::new(DataStream::new(block.value));
+            let css_rules = interpret_rules(parser.parse_a_list_of_rules(), false);
+            Some(CSSRule::Media(MediaRule::new(
+                media,
+                CSSRuleList(css_rules),
+            )))
+        }
+        "supports" => {
+            let block = rule.block?;
+            let condition = parse_supports_condition(&rule.prelude)?;
+            let mut parser = Parser::<ComponentValue>::new(DataStream::new(block.value));
+            let css_rules = interpret_rules(parser.parse_a_list_of_rules(), false);
+            Some(CSSRule::Supports(SupportsRule::new(
+                condition,
+                CSSRuleList(css_rules),
+            )))
+        }
+        "layer" => match rule.block {
+            Some(block) => {
+                let is_anonymous = rule.prelude.iter().all(|value| {
+                    matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+                });
+                let name = if is_anonymous {
+                    None
+                } else {
+                    Some(LayerName::parse(&rule.prelude)?)
+                };
+                let mut parser = Parser::<ComponentValue>::new(DataStream::new(block.value));
+                let css_rules = interpret_rules(parser.parse_a_list_of_rules(), false);
+                Some(CSSRule::Layer(LayerBlockRule::new(
+                    name,
+                    CSSRuleList(css_rules),
+                )))
+            }
+            None => LayerName::parse_list(&rule.prelude)
+                .map(|names| CSSRule::LayerStatement(LayerStatementRule::new(names))),
+        },
+        "font-face" => {
+            let declarations = interpret_declarations(rule.block?);
+            FontFaceRule::parse(&declarations).map(CSSRule::FontFace)
+        }
+        "import" => {
+            if rule.block.is_some() {
+                return None;
+            }
+            interpret_import_rule(&rule.prelude).map(CSSRule::Import)
+        }
+        _ => {
+            emit_error!(format!("Unsupported at-rule: @{}", rule.name));
+            None
+        }
+    }
+}
+
+/// Parse the prelude of `@import`:
+/// `[<url> | <string>] [layer | layer(<layer-name>)]? <media-query-list>?`
+fn interpret_import_rule(prelude: &[ComponentValue]) -> Option<ImportRule> {
+    let mut values = prelude
+        .iter()
+        .skip_while(|value| matches!(value, ComponentValue::PerservedToken(Token::Whitespace)));
+
+    let href = match values.next()? {
+        ComponentValue::PerservedToken(Token::Url(url))
+        | ComponentValue::PerservedToken(Token::Str(url)) => url.clone(),
+        ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("url") => {
+            match function.value.as_slice() {
+                [ComponentValue::PerservedToken(Token::Str(url))] => url.clone(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let mut values = values
+        .skip_while(|value| matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+        .peekable();
+    let layer = match values.peek() {
+        Some(ComponentValue::PerservedToken(Token::Ident(ident)))
+            if ident.eq_ignore_ascii_case("layer") =>
+        {
+            Some(None)
+        }
+        Some(ComponentValue::Function(function)) if function.name.eq_ignore_ascii_case("layer") => {
+            Some(Some(LayerName::parse(&function.value)?))
+        }
+        _ => None,
+    };
+    if layer.is_some() {
+        values.next();
+    }
+
+    let media = values.cloned().collect::<Vec<ComponentValue>>();
+    let mut import = ImportRule::new(href, parse_media_query_list(&media));
+    import.layer = layer;
+    Some(import)
+}
+
+/// Parse a declaration block without its braces, e.g. the value of the
+/// `style` attribute
+pub fn parse_declarations_str(css: &str) -> Vec<Declaration> {
+    let tokenizer = Tokenizer::new(css.chars());
+    let mut parser = Parser::<Token>::new(tokenizer.run());
+    let mut block = SimpleBlock::new(Token::BraceOpen);
+    block.value = parser.parse_a_list_of_component_values();
+    interpret_declarations(block)
+}
+
+/// Parse a single rule, e.g. for `insert_rule`. `@import` rules are
+/// interpreted but their stylesheet is not loaded.
+pub fn parse_rule_str(css: &str) -> Result<CSSRule, SyntaxError> {
+    let tokenizer = Tokenizer::new(css.chars());
+    let mut parser = Parser::<Token>::new(tokenizer.run());
+    let rule = match parser.parse_a_rule()? {
+        Rule::QualifiedRule(rule) => interpret_style_rule(rule).map(CSSRule::Style),
+        Rule::AtRule(rule) => interpret_at_rule(rule),
+    };
+    rule.ok_or(SyntaxError)
+}
+
 /// CSS Parser
 /// The parser support 2 mode:
 /// 1. Token mode which used to parse tokens from the tokenizer
@@ -366,35 +557,8 @@ impl Parser<Token> {
     pub fn parse_a_css_stylesheet(&mut self) -> StyleSheet {
         let mut stylesheet = StyleSheet::new();
         let rules = self.parse_a_stylesheet();
-        for rule in rules {
-            if let Rule::QualifiedRule(rule) = rule {
-                let selectors = parse_selectors(&rule.prelude);
-                if selectors.len() == 0 {
-                    // invalid rule
-                    continue;
-                }
-                let content = if let Some(block) = rule.block {
-                    let mut parser =
-                        Parser::<ComponentValue>::new(DataStream::new(block.value.clone()));
-
-                    let declarations = parser.parse_a_list_of_declarations();
-
-                    // take only declaration
-                    declarations
-                        .into_iter()
-                        .filter_map(|declaration| match declaration {
-                            DeclarationOrAtRule::Declaration(d) => Some(d),
-                            _ => None,
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                };
-                let style_rule = StyleRule::new(selectors, content);
-                stylesheet.append_rule(CSSRule::Style(style_rule));
-            } else {
-                continue;
-            }
+        for rule in interpret_rules(rules, true) {
+            stylesheet.append_rule(rule);
         }
         stylesheet
     }
@@ -534,6 +698,56 @@ impl Parser<ComponentValue> {
         self.consume_a_list_of_declarations()
     }
 
+    /// Parse the content of a block as a list of rules (e.g. the block of
+    /// `@media`)
+    pub fn parse_a_list_of_rules(&mut self) -> ListOfRules {
+        self.consume_a_list_of_rules()
+    }
+
+    fn consume_a_list_of_rules(&mut self) -> ListOfRules {
+        let mut rules = Vec::new();
+        loop {
+            let next_token = self.consume_next_token();
+            match next_token {
+                ComponentValue::PerservedToken(Token::Whitespace)
+                | ComponentValue::PerservedToken(Token::CDO)
+                | ComponentValue::PerservedToken(Token::CDC) => continue,
+                ComponentValue::PerservedToken(Token::EOF) => return rules,
+                ComponentValue::PerservedToken(Token::AtKeyword(_)) => {
+                    self.reconsume();
+                    let at_rule = self.consume_an_at_rule();
+                    rules.push(Rule::AtRule(at_rule));
+                }
+                _ => {
+                    self.reconsume();
+                    if let Some(rule) = self.consume_a_qualified_rule() {
+                        rules.push(Rule::QualifiedRule(rule));
+                    }
+                }
+            }
+        }
+    }
+
+    fn consume_a_qualified_rule(&mut self) -> Option<QualifiedRule> {
+        let mut qualified_rule = QualifiedRule::new();
+
+        loop {
+            let next_token = self.consume_next_token();
+
+            match next_token {
+                ComponentValue::PerservedToken(Token::EOF) => {
+                    emit_error!("Unexpected EOF while consuming a qualified rule");
+                    return None;
+                }
+                ComponentValue::SimpleBlock(block) if block.token == Token::BraceOpen => {
+                    qualified_rule.set_block(block);
+                    return Some(qualified_rule);
+                }
+                value => qualified_rule.append_prelude(value),
+            }
+        }
+    }
+
     fn consume_a_component_value(&mut self) -> ComponentValue {
         self.consume_next_token()
     }
@@ -558,6 +772,10 @@ impl Parser<ComponentValue> {
                     emit_error!("Unexpected EOF while consuming an at-rule");
                     return at_rule;
                 }
+                ComponentValue::SimpleBlock(block) if block.token == Token::BraceOpen => {
+                    at_rule.set_block(block);
+                    return at_rule;
+                }
                 // TODO: How is a simple block a token?
                 _ => {
                     self.reconsume();
@@ -680,6 +898,7 @@ mod tests {
     use super::*;
     use crate::cssom::css_rule::CSSRule;
     use crate::cssom::css_rule_list::CSSRuleList;
+    use crate::cssom::font_face_rule::FontFaceSource;
     use crate::cssom::style_rule::StyleRule;
     use crate::selector::structs::*;
     use crate::tokenizer::token::HashType;
@@ -826,6 +1045,191 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_declaration_ending_with_function() {
+        let css = "p { content: \"a\" attr(title); }";
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        let rule = match &stylesheet.css_rules[0] {
+            CSSRule::Style(rule) => rule,
+            rule => panic!("Expected a style rule, got {:?}", rule),
+        };
+        assert_eq!(
+            rule.declarations[0].value,
+            vec![
+                ComponentValue::PerservedToken(Token::Str("a".to_string())),
+                ComponentValue::PerservedToken(Token::Whitespace),
+                ComponentValue::Function(Function {
+                    name: "attr".to_string(),
+                    value: vec![ComponentValue::PerservedToken(Token::Ident(
+                        "title".to_string()
+                    ))]
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_media_rule() {
+        let css = "@media screen and (min-width: 600px) { p { color: red; } @media print { a {} } } div {}";
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        assert_eq!(stylesheet.css_rules.len(), 2);
+
+        let media = match &stylesheet.css_rules[0] {
+            CSSRule::Media(media) => media,
+            rule => panic!("Expected a media rule, got {:?}", rule),
+        };
+        assert_eq!(media.media.0.len(), 1);
+        assert_eq!(media.css_rules.len(), 2);
+        assert!(matches!(media.css_rules[0], CSSRule::Style(_)));
+        match &media.css_rules[1] {
+            CSSRule::Media(nested) => assert_eq!(nested.css_rules.len(), 1),
+            rule => panic!("Expected a media rule, got {:?}", rule),
+        }
+        assert!(matches!(stylesheet.css_rules[1], CSSRule::Style(_)));
+    }
+
+    #[test]
+    fn parse_supports_rule() {
+        let css = "@supports (display: grid) and (not selector(:has(a))) { p { color: red; } } \
+                   @supports display: grid { a {} } \
+                   @supports (display: grid) { @import 'a.css'; div {} }";
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        assert_eq!(stylesheet.css_rules.len(), 2);
+
+        let supports = match &stylesheet.css_rules[0] {
+            CSSRule::Supports(supports) => supports,
+            rule => panic!("Expected a supports rule, got {:?}", rule),
+        };
+        assert_eq!(supports.css_rules.len(), 1);
+        assert_eq!(
+            stylesheet.css_text(),
+            "@supports (display: grid) and (not selector(:has(a))) {\n  p { color: red; }\n}\n\
+             @supports (display: grid) {\n  div { }\n}"
+        );
+    }
+
+    #[test]
+    fn parse_layer_rules() {
+        let css = "@layer reset, framework.base; \
+                   @import url(theme.css) layer(theme) screen; \
+                   @import 'anonymous.css' layer; \
+                   @import 'invalid.css' layer(a..b); \
+                   @layer framework { @layer base { p {} } a {} } \
+                   @layer { div {} } \
+                   @layer initial { p {} } \
+                   @layer a b;";
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        assert_eq!(stylesheet.css_rules.len(), 5);
+
+        match &stylesheet.css_rules[0] {
+            CSSRule::LayerStatement(statement) => assert_eq!(
+                statement.names,
+                vec![
+                    LayerName(vec!["reset".to_string()]),
+                    LayerName(vec!["framework".to_string(), "base".to_string()])
+                ]
+            ),
+            rule => panic!("Expected a layer statement, got {:?}", rule),
+        }
+        match &stylesheet.css_rules[3] {
+            CSSRule::Layer(layer) => {
+                assert_eq!(layer.name, Some(LayerName(vec!["framework".to_string()])));
+                assert_eq!(layer.css_rules.len(), 2);
+            }
+            rule => panic!("Expected a layer block, got {:?}", rule),
+        }
+        assert_eq!(
+            stylesheet.css_text(),
+            "@layer reset, framework.base;\n\
+             @import url(\"theme.css\") layer(theme) screen;\n\
+             @import url(\"anonymous.css\") layer;\n\
+             @layer framework {\n  @layer base {\n    p { }\n  }\n  a { }\n}\n\
+             @layer {\n  div { }\n}"
+        );
+    }
+
+    #[test]
+    fn parse_import_rules() {
+        let css = r#"
+            @import url("base.css");
+            @import "print.css" print;
+            @import url(theme.css) screen and (min-width: 600px);
+            p {}
+            @import "ignored.css";
+            @media screen { @import "ignored.css"; }
+        "#;
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        assert_eq!(stylesheet.css_rules.len(), 5);
+
+        let imports = stylesheet
+            .css_rules
+            .iter()
+            .filter_map(|rule| match rule {
+                CSSRule::Import(import) => Some(import),
+                _ => None,
+            })
+            .collect::<Vec<&ImportRule>>();
+        assert_eq!(imports.len(), 3);
+        assert_eq!(imports[0].href, "base.css");
+        assert_eq!(imports[0].media.0.len(), 0);
+        assert_eq!(imports[1].href, "print.css");
+        assert_eq!(imports[1].media.0.len(), 1);
+        assert_eq!(imports[2].href, "theme.css");
+        assert_eq!(imports[2].media.0[0].features.len(), 1);
+
+        match &stylesheet.css_rules[4] {
+            CSSRule::Media(media) => assert_eq!(media.css_rules.len(), 0),
+            rule => panic!("Expected a media rule, got {:?}", rule),
+        }
+    }
+
+    #[test]
+    fn parse_font_face_rule() {
+        let css = r#"
+            @font-face {
+                font-family: "Open Sans";
+                src: local(Open Sans), url(open-sans.woff2) format("woff2"),
+                    url("open-sans.ttf") format(truetype);
+                font-weight: 300 700;
+                font-style: italic;
+            }
+            @font-face { font-family: Missing Source; }
+        "#;
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        assert_eq!(
+            stylesheet.css_rules,
+            CSSRuleList(vec![CSSRule::FontFace(FontFaceRule {
+                family: "Open Sans".to_string(),
+                sources: vec![
+                    FontFaceSource::Local("Open Sans".to_string()),
+                    FontFaceSource::Url("open-sans.woff2".to_string(), Some("woff2".to_string())),
+                    FontFaceSource::Url("open-sans.ttf".to_string(), Some("truetype".to_string())),
+                ],
+                weight: (300., 700.),
+                style: shared::font::FontStyle::Italic,
+                stretch: (100., 100.),
+            })])
+        );
+    }
+
     #[test]
     fn parse_function() {
         let css = "#elementId { color: rgba(0 0 0 0); }";
@@ -852,21 +1256,25 @@ mod tests {
                             ComponentValue::PerservedToken(Token::Number {
                                 value: 0.0,
                                 type_: crate::tokenizer::token::NumberType::Integer,
+                                has_sign: false,
                             }),
                             ComponentValue::PerservedToken(Token::Whitespace),
                             ComponentValue::PerservedToken(Token::Number {
                                 value: 0.0,
                                 type_: crate::tokenizer::token::NumberType::Integer,
+                                has_sign: false,
                             }),
                             ComponentValue::PerservedToken(Token::Whitespace),
                             ComponentValue::PerservedToken(Token::Number {
                                 value: 0.0,
                                 type_: crate::tokenizer::token::NumberType::Integer,
+                                has_sign: false,
                             }),
                             ComponentValue::PerservedToken(Token::Whitespace),
                             ComponentValue::PerservedToken(Token::Number {
                                 value: 0.0,
                                 type_: crate::tokenizer::token::NumberType::Integer,
+                                has_sign: false,
                             })
                         ]
                     })]
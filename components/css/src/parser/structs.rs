@@ -131,13 +131,12 @@ impl Declaration {
         self.value.iter().rev().take(len).rev().collect()
     }
 
+    /// The last value of the declaration if it is a token
     pub fn last_token(&self) -> Option<&Token> {
-        for value in self.value.iter().rev() {
-            if let ComponentValue::PerservedToken(token) = value {
-                return Some(token);
-            }
+        match self.value.last() {
+            Some(ComponentValue::PerservedToken(token)) => Some(token),
+            _ => None,
         }
-        return None;
     }
 
     pub fn pop_last(&mut self, len: usize) {
@@ -2,7 +2,7 @@ pub mod structs;
 
 use super::parser::structs::ComponentValue;
 use super::parser::Parser;
-use super::tokenizer::token::Token;
+use super::tokenizer::token::{NumberType, Token};
 use super::tokenizer::Tokenizer;
 use shared::data_stream::DataStream;
 use structs::*;
@@ -83,7 +83,7 @@ pub fn parse_combinator(data_stream: &mut DataStream<ComponentValue>) -> Option<
     let next_values = data_stream.peek_next(4);
 
     if next_values.len() == 4 {
-        return match (
+        let combinator = match (
             next_values[0],
             next_values[1],
             next_values[2],
@@ -125,12 +125,17 @@ pub fn parse_combinator(data_stream: &mut DataStream<ComponentValue>) -> Option<
             }
             _ => None,
         };
+        if combinator.is_some() {
+            return combinator;
+        }
     }
 
     let next_values = data_stream.peek_next(2);
 
     if next_values.len() == 2 {
         return match (next_values[0], next_values[1]) {
+            // Space before the next selector of the list
+            (token_value!(Token::Whitespace), token_value!(Token::Comma)) => None,
             // No space between combinator
             (token_value!(Token::Whitespace), _) => {
                 data_stream.next();
@@ -213,11 +218,236 @@ pub fn parse_simple_selector(
             }
             None
         }
+        Some(ComponentValue::SimpleBlock(block)) if block.token == Token::BracketOpen => {
+            data_stream.next();
+            parse_attribute_selector(block.value)
+        }
+        Some(token_value!(Token::Colon)) => {
+            let next_values = data_stream.peek_next(2);
+            if next_values.len() != 2 {
+                return None;
+            }
+            match next_values[1].clone() {
+                token_value!(Token::Colon) => {
+                    let next_values = data_stream.peek_next(3);
+                    match next_values.get(2).map(|value| (*value).clone()) {
+                        Some(token_value!(Token::Ident(data))) => {
+                            data_stream.next();
+                            data_stream.next();
+                            data_stream.next();
+                            Some(SimpleSelector::new(
+                                SimpleSelectorType::PseudoElement,
+                                Some(data.to_lowercase()),
+                            ))
+                        }
+                        _ => None,
+                    }
+                }
+                token_value!(Token::Ident(data)) => {
+                    data_stream.next();
+                    data_stream.next();
+                    let name = data.to_lowercase();
+                    // CSS 2 pseudo-elements can be written with a single colon
+                    let type_ = match name.as_str() {
+                        "before" | "after" | "first-line" | "first-letter" => {
+                            SimpleSelectorType::PseudoElement
+                        }
+                        _ => SimpleSelectorType::Pseudo,
+                    };
+                    Some(SimpleSelector::new(type_, Some(name)))
+                }
+                ComponentValue::Function(function) => {
+                    data_stream.next();
+                    data_stream.next();
+                    parse_pseudo_class_function(&function.name, &function.value)
+                }
+                _ => None,
+            }
+        }
         // TODO: Support other selectors too
         _ => None,
     }
 }
 
+fn parse_pseudo_class_function(name: &str, values: &[ComponentValue]) -> Option<SimpleSelector> {
+    let name = name.to_lowercase();
+    let argument = match name.as_str() {
+        "nth-child" | "nth-last-child" => {
+            // An+B [of S]
+            let of_position = values.iter().position(|value| match value {
+                token_value!(Token::Ident(ident)) => ident.eq_ignore_ascii_case("of"),
+                _ => false,
+            });
+            match of_position {
+                Some(position) => {
+                    let an_plus_b = parse_an_plus_b(&values[..position])?;
+                    let selectors = parse_selectors(&values[position + 1..].to_vec());
+                    if selectors.is_empty() {
+                        return None;
+                    }
+                    PseudoClassArgument::Nth(an_plus_b, Some(selectors))
+                }
+                None => PseudoClassArgument::Nth(parse_an_plus_b(values)?, None),
+            }
+        }
+        "nth-of-type" | "nth-last-of-type" => {
+            PseudoClassArgument::Nth(parse_an_plus_b(values)?, None)
+        }
+        "not" | "is" | "where" => {
+            let selectors = parse_selectors(&values.to_vec());
+            // :is() & :where() take a forgiving selector list
+            if selectors.is_empty() && name == "not" {
+                return None;
+            }
+            PseudoClassArgument::Selectors(selectors)
+        }
+        _ => return None,
+    };
+    Some(SimpleSelector::new_pseudo_class_function(name, argument))
+}
+
+/// Parse the An+B microsyntax, e.g. `odd`, `-n+3` or `2n - 1`
+/// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+pub fn parse_an_plus_b(values: &[ComponentValue]) -> Option<AnPlusB> {
+    // An+B can be split into many different tokens (`2n-1` is a single
+    // dimension while `2n - 1` is 3 tokens), so join them back into a string.
+    let is_whitespace = |value: &ComponentValue| matches!(value, token_value!(Token::Whitespace));
+    let start = values.iter().position(|value| !is_whitespace(value));
+    let end = values.iter().rposition(|value| !is_whitespace(value));
+    let values = match (start, end) {
+        (Some(start), Some(end)) => &values[start..=end],
+        _ => return None,
+    };
+
+    let mut raw = String::new();
+    for value in values {
+        match value {
+            // the sign of A & B is attached to them, only the `+`/`-` between
+            // An & B can be surrounded by whitespace
+            token_value!(Token::Whitespace) if raw.contains('n') => {}
+            token_value!(Token::Whitespace) => return None,
+            token_value!(Token::Ident(ident)) => raw.push_str(&ident.to_lowercase()),
+            token_value!(Token::Delim(sign @ ('+' | '-'))) => raw.push(*sign),
+            token_value!(Token::Number {
+                value,
+                type_: NumberType::Integer,
+                has_sign,
+            }) => {
+                // B is either a signed integer or an unsigned one after a
+                // `+`/`-`, never both nor neither
+                let follows_sign = raw.ends_with('+') || raw.ends_with('-');
+                if !raw.is_empty() && *has_sign == follows_sign {
+                    return None;
+                }
+                if !raw.is_empty() && !follows_sign && *value >= 0. {
+                    raw.push('+');
+                }
+                raw.push_str(&(*value as i32).to_string());
+            }
+            token_value!(Token::Dimension {
+                value,
+                type_: NumberType::Integer,
+                unit,
+            }) => {
+                raw.push_str(&(*value as i32).to_string());
+                raw.push_str(&unit.to_lowercase());
+            }
+            _ => return None,
+        }
+    }
+
+    match raw.as_str() {
+        "odd" => return Some(AnPlusB::new(2, 1)),
+        "even" => return Some(AnPlusB::new(2, 0)),
+        _ => {}
+    }
+
+    let parse_b = |b: &str| -> Option<i32> {
+        match b.strip_prefix('+') {
+            Some(b) if b.starts_with(|c: char| c.is_ascii_digit()) => b.parse().ok(),
+            None if b.starts_with('-') => b.parse().ok(),
+            _ => None,
+        }
+    };
+
+    match raw.split_once('n') {
+        Some((a, b)) => {
+            let a = match a {
+                "" | "+" => 1,
+                "-" => -1,
+                a => a.parse().ok()?,
+            };
+            let b = match b {
+                "" => 0,
+                b => parse_b(b)?,
+            };
+            Some(AnPlusB::new(a, b))
+        }
+        None => Some(AnPlusB::new(0, raw.parse().ok()?)),
+    }
+}
+
+/// Parse the content of an attribute selector block, e.g. `type="text" i`
+/// https://www.w3.org/TR/selectors-4/#attribute-selectors
+fn parse_attribute_selector(values: Vec<ComponentValue>) -> Option<SimpleSelector> {
+    let mut values = values
+        .into_iter()
+        .filter(|value| !matches!(value, token_value!(Token::Whitespace)));
+
+    let name = match values.next() {
+        Some(token_value!(Token::Ident(name))) => name.to_lowercase(),
+        _ => return None,
+    };
+
+    let operator = match values.next() {
+        None => return Some(SimpleSelector::new_attribute(name, None)),
+        Some(token_value!(Token::Delim('='))) => AttributeOperator::Equals,
+        Some(token_value!(Token::Delim(delim))) => {
+            let operator = match delim {
+                '~' => AttributeOperator::Includes,
+                '|' => AttributeOperator::DashMatch,
+                '^' => AttributeOperator::Prefix,
+                '$' => AttributeOperator::Suffix,
+                '*' => AttributeOperator::Substring,
+                _ => return None,
+            };
+            match values.next() {
+                Some(token_value!(Token::Delim('='))) => operator,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let value = match values.next() {
+        Some(token_value!(Token::Ident(value))) | Some(token_value!(Token::Str(value))) => value,
+        _ => return None,
+    };
+
+    let case_flag = match values.next() {
+        None => None,
+        Some(token_value!(Token::Ident(flag))) => match flag.to_lowercase().as_str() {
+            "i" => Some(AttributeCaseFlag::AsciiCaseInsensitive),
+            "s" => Some(AttributeCaseFlag::CaseSensitive),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    if values.next().is_some() {
+        return None;
+    }
+
+    Some(SimpleSelector::new_attribute(
+        name,
+        Some(AttributeSelector {
+            operator,
+            value,
+            case_flag,
+        }),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +552,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_descendant_before_other_combinators() {
+        let selector = parse_selector_str("div p > span").unwrap();
+        let combinators = selector
+            .values()
+            .iter()
+            .map(|(_, combinator)| combinator.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            combinators,
+            vec![Some(Combinator::Descendant), Some(Combinator::Child), None]
+        );
+
+        let css = "div p , .class { color: red; }";
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let rules = parser.parse_a_stylesheet();
+        if let Some(Rule::QualifiedRule(rule)) = rules.first() {
+            let selectors = parse_selectors(&rule.prelude);
+            assert_eq!(selectors.len(), 2);
+            assert_eq!(selectors[0].values().len(), 2);
+        }
+    }
+
     #[test]
     fn parse_nested() {
         let css = "div.class > #id > #name + div { color: red; }";
@@ -422,6 +676,203 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_pseudo_class() {
+        let selector = parse_selector_str("a:hover").expect("Failed to parse selector");
+
+        let expected = Selector::new(vec![(
+            SimpleSelectorSequence::new(vec![
+                SimpleSelector::new(SimpleSelectorType::Type, Some("a".to_string())),
+                SimpleSelector::new(SimpleSelectorType::Pseudo, Some("hover".to_string())),
+            ]),
+            None,
+        )]);
+
+        assert_eq!(selector, expected);
+        assert_eq!(selector.specificity(), Specificity::new(0, 1, 1));
+        assert!(selector.has_pseudo_class("hover"));
+        assert!(!selector.has_pseudo_class("active"));
+    }
+
+    #[test]
+    fn parse_attribute() {
+        let selector =
+            parse_selector_str("input[type=text][disabled]").expect("Failed to parse selector");
+
+        let expected = Selector::new(vec![(
+            SimpleSelectorSequence::new(vec![
+                SimpleSelector::new(SimpleSelectorType::Type, Some("input".to_string())),
+                SimpleSelector::new_attribute(
+                    "type".to_string(),
+                    Some(AttributeSelector {
+                        operator: AttributeOperator::Equals,
+                        value: "text".to_string(),
+                        case_flag: None,
+                    }),
+                ),
+                SimpleSelector::new_attribute("disabled".to_string(), None),
+            ]),
+            None,
+        )]);
+
+        assert_eq!(selector, expected);
+        assert_eq!(selector.specificity(), Specificity::new(0, 2, 1));
+    }
+
+    #[test]
+    fn parse_attribute_operators() {
+        let cases = [
+            ("[a~=b]", AttributeOperator::Includes, None),
+            ("[a|=b]", AttributeOperator::DashMatch, None),
+            ("[a^=b]", AttributeOperator::Prefix, None),
+            (
+                "[a$='b' i]",
+                AttributeOperator::Suffix,
+                Some(AttributeCaseFlag::AsciiCaseInsensitive),
+            ),
+            (
+                "[ a *= \"b\" s ]",
+                AttributeOperator::Substring,
+                Some(AttributeCaseFlag::CaseSensitive),
+            ),
+        ];
+
+        for (css, operator, case_flag) in cases {
+            let selector = parse_selector_str(css).expect("Failed to parse selector");
+            let expected = Selector::new(vec![(
+                SimpleSelectorSequence::new(vec![SimpleSelector::new_attribute(
+                    "a".to_string(),
+                    Some(AttributeSelector {
+                        operator,
+                        value: "b".to_string(),
+                        case_flag,
+                    }),
+                )]),
+                None,
+            )]);
+            assert_eq!(selector, expected);
+        }
+    }
+
+    #[test]
+    fn parse_invalid_attribute() {
+        assert!(parse_selector_str("[a=]").is_none());
+        assert!(parse_selector_str("[a==b]").is_none());
+        assert!(parse_selector_str("[a=b x]").is_none());
+    }
+
+    #[test]
+    fn parse_an_plus_b_values() {
+        let cases = [
+            ("odd", AnPlusB::new(2, 1)),
+            ("EVEN", AnPlusB::new(2, 0)),
+            ("3", AnPlusB::new(0, 3)),
+            ("-2", AnPlusB::new(0, -2)),
+            ("n", AnPlusB::new(1, 0)),
+            ("+n", AnPlusB::new(1, 0)),
+            ("-n+3", AnPlusB::new(-1, 3)),
+            ("2n", AnPlusB::new(2, 0)),
+            ("2n+1", AnPlusB::new(2, 1)),
+            ("2n-1", AnPlusB::new(2, -1)),
+            ("2n - 1", AnPlusB::new(2, -1)),
+            ("2n + 1", AnPlusB::new(2, 1)),
+            (" -n+ 3 ", AnPlusB::new(-1, 3)),
+            ("2n +1", AnPlusB::new(2, 1)),
+            ("2n -1", AnPlusB::new(2, -1)),
+            ("-3n-2", AnPlusB::new(-3, -2)),
+        ];
+
+        for (css, expected) in cases {
+            let tokenizer = Tokenizer::new(css.chars());
+            let mut parser = Parser::<Token>::new(tokenizer.run());
+            let values = parser.parse_a_list_of_component_values();
+            assert_eq!(parse_an_plus_b(&values), Some(expected), "{}", css);
+        }
+
+        for css in [
+            "2n+", "n-", "x", "2.5n", "2 n", "- n", "+ 2n", "- 2", "2n 1", "2n+ +1", "2n- +1",
+        ] {
+            let tokenizer = Tokenizer::new(css.chars());
+            let mut parser = Parser::<Token>::new(tokenizer.run());
+            let values = parser.parse_a_list_of_component_values();
+            assert_eq!(parse_an_plus_b(&values), None, "{}", css);
+        }
+    }
+
+    #[test]
+    fn parse_pseudo_class_functions() {
+        let selector = parse_selector_str("li:nth-child(2n+1 of .item)").unwrap();
+        let expected = Selector::new(vec![(
+            SimpleSelectorSequence::new(vec![
+                SimpleSelector::new(SimpleSelectorType::Type, Some("li".to_string())),
+                SimpleSelector::new_pseudo_class_function(
+                    "nth-child".to_string(),
+                    PseudoClassArgument::Nth(
+                        AnPlusB::new(2, 1),
+                        Some(vec![parse_selector_str(".item").unwrap()]),
+                    ),
+                ),
+            ]),
+            None,
+        )]);
+        assert_eq!(selector, expected);
+
+        let selector = parse_selector_str(":not(p, #id)").unwrap();
+        let expected = Selector::new(vec![(
+            SimpleSelectorSequence::new(vec![SimpleSelector::new_pseudo_class_function(
+                "not".to_string(),
+                PseudoClassArgument::Selectors(vec![
+                    parse_selector_str("p").unwrap(),
+                    parse_selector_str("#id").unwrap(),
+                ]),
+            )]),
+            None,
+        )]);
+        assert_eq!(selector, expected);
+    }
+
+    #[test]
+    fn test_pseudo_class_specificity() {
+        let cases = [
+            (":first-child", Specificity::new(0, 1, 0)),
+            ("li:nth-child(odd)", Specificity::new(0, 1, 1)),
+            (":nth-child(2n of #a, .b)", Specificity::new(1, 1, 0)),
+            (":not(p, #id)", Specificity::new(1, 0, 0)),
+            (":is(p, .class)", Specificity::new(0, 1, 0)),
+            (":where(#id) p", Specificity::new(0, 0, 1)),
+        ];
+
+        for (css, expected) in cases {
+            let selector = parse_selector_str(css).expect("Failed to parse selector");
+            assert_eq!(selector.specificity(), expected, "{}", css);
+        }
+    }
+
+    #[test]
+    fn parse_pseudo_elements() {
+        let selector = parse_selector_str("p.note::before").unwrap();
+        let expected = Selector::new(vec![(
+            SimpleSelectorSequence::new(vec![
+                SimpleSelector::new(SimpleSelectorType::Type, Some("p".to_string())),
+                SimpleSelector::new(SimpleSelectorType::Class, Some("note".to_string())),
+                SimpleSelector::new(
+                    SimpleSelectorType::PseudoElement,
+                    Some("before".to_string()),
+                ),
+            ]),
+            None,
+        )]);
+        assert_eq!(selector, expected);
+        assert_eq!(selector.pseudo_element(), Some("before"));
+        assert_eq!(selector.specificity(), Specificity::new(0, 1, 2));
+
+        let selector = parse_selector_str("q:AFTER").unwrap();
+        assert_eq!(selector.pseudo_element(), Some("after"));
+
+        let selector = parse_selector_str("a:hover").unwrap();
+        assert_eq!(selector.pseudo_element(), None);
+    }
+
     #[test]
     fn test_specificity() {
         let css = "#div.class#name";
@@ -24,12 +24,101 @@ pub enum SimpleSelectorType {
     Class,
     ID,
     Pseudo,
+    PseudoElement,
+}
+
+/// Pseudo-elements that generate boxes
+/// https://www.w3.org/TR/css-pseudo-4/#generated-content
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PseudoElement {
+    Before,
+    After,
+}
+
+impl PseudoElement {
+    pub fn all() -> [PseudoElement; 2] {
+        [PseudoElement::Before, PseudoElement::After]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PseudoElement::Before => "before",
+            PseudoElement::After => "after",
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SimpleSelector {
     type_: SimpleSelectorType,
     value: Option<String>,
+    attribute: Option<AttributeSelector>,
+    argument: Option<PseudoClassArgument>,
+}
+
+/// The matching part of an attribute selector, e.g. `="text" i` in `[type="text" i]`
+/// https://www.w3.org/TR/selectors-4/#attribute-selectors
+#[derive(Debug, PartialEq, Clone)]
+pub struct AttributeSelector {
+    pub operator: AttributeOperator,
+    pub value: String,
+    pub case_flag: Option<AttributeCaseFlag>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AttributeOperator {
+    /// `[attr=value]`
+    Equals,
+    /// `[attr~=value]`
+    Includes,
+    /// `[attr|=value]`
+    DashMatch,
+    /// `[attr^=value]`
+    Prefix,
+    /// `[attr$=value]`
+    Suffix,
+    /// `[attr*=value]`
+    Substring,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AttributeCaseFlag {
+    /// `i` flag
+    AsciiCaseInsensitive,
+    /// `s` flag
+    CaseSensitive,
+}
+
+/// Argument of a functional pseudo-class
+#[derive(Debug, PartialEq, Clone)]
+pub enum PseudoClassArgument {
+    /// `An+B [of S]` of `:nth-child()`, `:nth-last-child()` & the `-of-type` variants
+    Nth(AnPlusB, Option<Vec<Selector>>),
+    /// Selector list of `:not()`, `:is()` & `:where()`
+    Selectors(Vec<Selector>),
+}
+
+/// An+B microsyntax
+/// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnPlusB {
+    pub a: i32,
+    pub b: i32,
+}
+
+impl AnPlusB {
+    pub fn new(a: i32, b: i32) -> Self {
+        Self { a, b }
+    }
+
+    /// Check if the 1-based `index` is `An+B` for some non-negative integer n
+    pub fn matches(&self, index: i32) -> bool {
+        if self.a == 0 {
+            return index == self.b;
+        }
+        let diff = index - self.b;
+        diff % self.a == 0 && diff / self.a >= 0
+    }
 }
 
 /// CSS Selector specificity
@@ -42,6 +131,23 @@ impl Specificity {
     }
 }
 
+impl std::ops::Add for Specificity {
+    type Output = Specificity;
+
+    fn add(self, other: Self) -> Self::Output {
+        Specificity(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+/// Specificity of the most specific selector in a list
+fn max_specificity(selectors: &[Selector]) -> Specificity {
+    selectors
+        .iter()
+        .map(|selector| selector.specificity())
+        .max()
+        .unwrap_or(Specificity::new(0, 0, 0))
+}
+
 impl Ord for Specificity {
     fn cmp(&self, other: &Self) -> Ordering {
         match self.0.cmp(&other.0) {
@@ -70,15 +176,44 @@ impl Selector {
     }
 
     pub fn specificity(&self) -> Specificity {
-        let (a, b, c) = self.values().iter().fold((0, 0, 0), |acc, (selector, _)| {
-            let specificity = selector.specificity();
-            (
-                acc.0 + specificity.0,
-                acc.1 + specificity.1,
-                acc.2 + specificity.2,
-            )
-        });
-        Specificity::new(a, b, c)
+        self.values()
+            .iter()
+            .fold(Specificity::new(0, 0, 0), |acc, (selector, _)| {
+                acc + selector.specificity()
+            })
+    }
+
+    /// Name of the pseudo-element the selector targets, e.g. `before` for
+    /// `p::before`. A pseudo-element can only appear in the last compound
+    /// selector.
+    pub fn pseudo_element(&self) -> Option<&str> {
+        let (sequence, _) = self.values().last()?;
+        sequence
+            .values()
+            .iter()
+            .find(|selector| matches!(selector.selector_type(), SimpleSelectorType::PseudoElement))
+            .and_then(|selector| selector.value().as_deref())
+    }
+
+    /// Check if any part of the selector depends on the given pseudo-class
+    pub fn has_pseudo_class(&self, name: &str) -> bool {
+        self.values().iter().any(|(sequence, _)| {
+            sequence.values().iter().any(|selector| {
+                if !matches!(selector.selector_type(), SimpleSelectorType::Pseudo) {
+                    return false;
+                }
+                if selector.value().as_deref() == Some(name) {
+                    return true;
+                }
+                match selector.argument() {
+                    Some(PseudoClassArgument::Nth(_, Some(selectors)))
+                    | Some(PseudoClassArgument::Selectors(selectors)) => selectors
+                        .iter()
+                        .any(|selector| selector.has_pseudo_class(name)),
+                    _ => false,
+                }
+            })
+        })
     }
 }
 
@@ -92,24 +227,71 @@ impl SimpleSelectorSequence {
     }
 
     pub fn specificity(&self) -> Specificity {
-        let (a, b, c) =
-            self.values()
-                .iter()
-                .fold((0, 0, 0), |acc, curr| match curr.selector_type() {
-                    SimpleSelectorType::ID => (acc.0 + 1, acc.1, acc.2),
-                    SimpleSelectorType::Class | SimpleSelectorType::Attribute => {
-                        (acc.0, acc.1 + 1, acc.2)
-                    }
-                    SimpleSelectorType::Type => (acc.0, acc.1, acc.2 + 1),
-                    _ => acc,
-                });
-        Specificity(a, b, c)
+        self.values()
+            .iter()
+            .fold(Specificity::new(0, 0, 0), |acc, selector| {
+                acc + selector.specificity()
+            })
     }
 }
 
 impl SimpleSelector {
     pub fn new(type_: SimpleSelectorType, value: Option<String>) -> Self {
-        Self { type_, value }
+        Self {
+            type_,
+            value,
+            attribute: None,
+            argument: None,
+        }
+    }
+
+    /// Functional pseudo-class, e.g. `:nth-child(2n+1)` or `:not(.class)`
+    pub fn new_pseudo_class_function(name: String, argument: PseudoClassArgument) -> Self {
+        Self {
+            type_: SimpleSelectorType::Pseudo,
+            value: Some(name),
+            attribute: None,
+            argument: Some(argument),
+        }
+    }
+
+    /// Attribute selector on the attribute `name`. Without `attribute`, the
+    /// selector only checks the presence of the attribute.
+    pub fn new_attribute(name: String, attribute: Option<AttributeSelector>) -> Self {
+        Self {
+            type_: SimpleSelectorType::Attribute,
+            value: Some(name),
+            attribute,
+            argument: None,
+        }
+    }
+
+    pub fn attribute(&self) -> &Option<AttributeSelector> {
+        &self.attribute
+    }
+
+    pub fn argument(&self) -> &Option<PseudoClassArgument> {
+        &self.argument
+    }
+
+    /// https://www.w3.org/TR/selectors-4/#specificity-rules
+    pub fn specificity(&self) -> Specificity {
+        match self.selector_type() {
+            SimpleSelectorType::ID => Specificity::new(1, 0, 0),
+            SimpleSelectorType::Class | SimpleSelectorType::Attribute => Specificity::new(0, 1, 0),
+            SimpleSelectorType::Type | SimpleSelectorType::PseudoElement => {
+                Specificity::new(0, 0, 1)
+            }
+            SimpleSelectorType::Universal => Specificity::new(0, 0, 0),
+            SimpleSelectorType::Pseudo => match (self.value().as_deref(), self.argument()) {
+                (Some("where"), _) => Specificity::new(0, 0, 0),
+                (_, Some(PseudoClassArgument::Selectors(selectors))) => max_specificity(selectors),
+                (_, Some(PseudoClassArgument::Nth(_, Some(selectors)))) => {
+                    Specificity::new(0, 1, 0) + max_specificity(selectors)
+                }
+                _ => Specificity::new(0, 1, 0),
+            },
+        }
     }
 
     pub fn value(&self) -> &Option<String> {
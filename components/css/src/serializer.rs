@@ -0,0 +1,380 @@
+//! Serialization of tokens, component values, selectors & media queries
+//! back to CSS text
+//! https://www.w3.org/TR/cssom-1/#serializing-css-values
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::media_query::structs::{
+    ColorScheme, MediaFeature, MediaQualifier, MediaQuery, MediaQueryList, MediaType, Orientation,
+};
+use super::parser::structs::{ComponentValue, Declaration};
+use super::selector::structs::{
+    AnPlusB, AttributeCaseFlag, AttributeOperator, Combinator, PseudoClassArgument, Selector,
+    SimpleSelector, SimpleSelectorSequence, SimpleSelectorType,
+};
+use super::supports::structs::SupportsCondition;
+use super::tokenizer::token::{HashType, Token};
+
+/// Serialize an identifier, escaping the characters that would not be
+/// tokenized as part of it
+/// https://www.w3.org/TR/cssom-1/#serialize-an-identifier
+pub fn serialize_identifier(ident: &str) -> String {
+    let mut result = String::new();
+    let chars = ident.chars().collect::<Vec<char>>();
+
+    if chars == ['-'] {
+        return "\\-".to_string();
+    }
+
+    for (index, ch) in chars.iter().enumerate() {
+        let starts_with_digit = index == 0 || (index == 1 && chars[0] == '-');
+        match ch {
+            '\0' => result.push('\u{FFFD}'),
+            '\u{1}'..='\u{1f}' | '\u{7f}' => escape_code_point(*ch, &mut result),
+            '0'..='9' if starts_with_digit => escape_code_point(*ch, &mut result),
+            ch if ch.is_ascii_alphanumeric() || *ch == '-' || *ch == '_' || !ch.is_ascii() => {
+                result.push(*ch)
+            }
+            ch => {
+                result.push('\\');
+                result.push(*ch);
+            }
+        }
+    }
+    result
+}
+
+/// Serialize a string as a double-quoted CSS string
+/// https://www.w3.org/TR/cssom-1/#serialize-a-string
+pub fn serialize_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '\0' => result.push('\u{FFFD}'),
+            '\u{1}'..='\u{1f}' | '\u{7f}' => escape_code_point(ch, &mut result),
+            '"' | '\\' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            ch => result.push(ch),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Serialize a URL as `url("...")`
+/// https://www.w3.org/TR/cssom-1/#serialize-a-url
+pub fn serialize_url(url: &str) -> String {
+    format!("url({})", serialize_string(url))
+}
+
+/// Serialize component values, e.g. the value of a declaration, without
+/// the leading & trailing whitespaces
+pub fn serialize_values(values: &[ComponentValue]) -> String {
+    let mut result = String::new();
+    for value in values {
+        let _ = write!(result, "{}", value);
+    }
+    result.trim().to_string()
+}
+
+fn escape_code_point(ch: char, result: &mut String) {
+    let _ = write!(result, "\\{:x} ", ch as u32);
+}
+
+fn write_list<T: Display>(f: &mut Formatter<'_>, items: &[T], separator: &str) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => f.write_str(&serialize_identifier(ident)),
+            Token::Function(name) => write!(f, "{}(", serialize_identifier(name)),
+            Token::AtKeyword(name) => write!(f, "@{}", serialize_identifier(name)),
+            Token::Hash(name, HashType::Id) => write!(f, "#{}", serialize_identifier(name)),
+            Token::Hash(name, HashType::Unrestricted) => write!(f, "#{}", name),
+            Token::Str(value) => f.write_str(&serialize_string(value)),
+            Token::Url(url) => f.write_str(&serialize_url(url)),
+            Token::BadStr | Token::BadUrl | Token::EOF => Ok(()),
+            Token::Delim(ch) => write!(f, "{}", ch),
+            Token::Number { value, .. } => write!(f, "{}", value),
+            Token::Percentage(value) => write!(f, "{}%", value),
+            Token::Dimension { value, unit, .. } => write!(f, "{}{}", value, unit),
+            Token::Whitespace => f.write_str(" "),
+            Token::CDO => f.write_str("<!--"),
+            Token::CDC => f.write_str("-->"),
+            Token::Colon => f.write_str(":"),
+            Token::Semicolon => f.write_str(";"),
+            Token::Comma => f.write_str(","),
+            Token::BracketOpen => f.write_str("["),
+            Token::BracketClose => f.write_str("]"),
+            Token::ParentheseOpen => f.write_str("("),
+            Token::ParentheseClose => f.write_str(")"),
+            Token::BraceOpen => f.write_str("{"),
+            Token::BraceClose => f.write_str("}"),
+        }
+    }
+}
+
+impl Display for ComponentValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentValue::PerservedToken(token) => write!(f, "{}", token),
+            ComponentValue::Function(function) => {
+                write!(f, "{}(", serialize_identifier(&function.name))?;
+                write_list(f, &function.value, "")?;
+                f.write_str(")")
+            }
+            ComponentValue::SimpleBlock(block) => {
+                let close = match block.token {
+                    Token::BracketOpen => Token::BracketClose,
+                    Token::ParentheseOpen => Token::ParentheseClose,
+                    _ => Token::BraceClose,
+                };
+                write!(f, "{}", block.token)?;
+                write_list(f, &block.value, "")?;
+                write!(f, "{}", close)
+            }
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-declaration
+impl Display for Declaration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            serialize_identifier(&self.name),
+            serialize_values(&self.value)
+        )?;
+        if self.important {
+            f.write_str(" !important")?;
+        }
+        Ok(())
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-selector
+impl Display for Selector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (sequence, combinator) in self.values() {
+            write!(f, "{}", sequence)?;
+            if let Some(combinator) = combinator {
+                write!(f, "{}", combinator)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Combinator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Combinator::Descendant => " ",
+            Combinator::Child => " > ",
+            Combinator::NextSibling => " + ",
+            Combinator::SubsequentSibling => " ~ ",
+        })
+    }
+}
+
+impl Display for SimpleSelectorSequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_list(f, self.values(), "")
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-simple-selector
+impl Display for SimpleSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value = self.value().as_deref().unwrap_or_default();
+        match self.selector_type() {
+            SimpleSelectorType::Type => f.write_str(&serialize_identifier(value)),
+            SimpleSelectorType::Universal => f.write_str("*"),
+            SimpleSelectorType::Class => write!(f, ".{}", serialize_identifier(value)),
+            SimpleSelectorType::ID => write!(f, "#{}", serialize_identifier(value)),
+            SimpleSelectorType::PseudoElement => write!(f, "::{}", serialize_identifier(value)),
+            SimpleSelectorType::Attribute => {
+                write!(f, "[{}", serialize_identifier(value))?;
+                if let Some(attribute) = self.attribute() {
+                    let operator = match attribute.operator {
+                        AttributeOperator::Equals => "=",
+                        AttributeOperator::Includes => "~=",
+                        AttributeOperator::DashMatch => "|=",
+                        AttributeOperator::Prefix => "^=",
+                        AttributeOperator::Suffix => "$=",
+                        AttributeOperator::Substring => "*=",
+                    };
+                    write!(f, "{}{}", operator, serialize_string(&attribute.value))?;
+                    match attribute.case_flag {
+                        Some(AttributeCaseFlag::AsciiCaseInsensitive) => f.write_str(" i")?,
+                        Some(AttributeCaseFlag::CaseSensitive) => f.write_str(" s")?,
+                        None => {}
+                    }
+                }
+                f.write_str("]")
+            }
+            SimpleSelectorType::Pseudo => {
+                write!(f, ":{}", serialize_identifier(value))?;
+                match self.argument() {
+                    Some(PseudoClassArgument::Nth(an_plus_b, selectors)) => {
+                        write!(f, "({}", an_plus_b)?;
+                        if let Some(selectors) = selectors {
+                            f.write_str(" of ")?;
+                            write_list(f, selectors, ", ")?;
+                        }
+                        f.write_str(")")
+                    }
+                    Some(PseudoClassArgument::Selectors(selectors)) => {
+                        f.write_str("(")?;
+                        write_list(f, selectors, ", ")?;
+                        f.write_str(")")
+                    }
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#serializing-anb
+impl Display for AnPlusB {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.a == 0 {
+            return write!(f, "{}", self.b);
+        }
+        match self.a {
+            1 => f.write_str("n")?,
+            -1 => f.write_str("-n")?,
+            a => write!(f, "{}n", a)?,
+        }
+        match self.b {
+            0 => Ok(()),
+            b if b > 0 => write!(f, "+{}", b),
+            b => write!(f, "{}", b),
+        }
+    }
+}
+
+impl Display for SupportsCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // nested conditions are parenthesized
+        fn write_in_parens(f: &mut Formatter<'_>, condition: &SupportsCondition) -> fmt::Result {
+            match condition {
+                SupportsCondition::Not(_)
+                | SupportsCondition::And(_)
+                | SupportsCondition::Or(_) => write!(f, "({})", condition),
+                _ => write!(f, "{}", condition),
+            }
+        }
+
+        fn write_conditions(
+            f: &mut Formatter<'_>,
+            conditions: &[SupportsCondition],
+            operator: &str,
+        ) -> fmt::Result {
+            for (index, condition) in conditions.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " {} ", operator)?;
+                }
+                write_in_parens(f, condition)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            SupportsCondition::Not(condition) => {
+                f.write_str("not ")?;
+                write_in_parens(f, condition)
+            }
+            SupportsCondition::And(conditions) => write_conditions(f, conditions, "and"),
+            SupportsCondition::Or(conditions) => write_conditions(f, conditions, "or"),
+            SupportsCondition::Declaration(declaration) => write!(f, "({})", declaration),
+            SupportsCondition::Selector(values) => {
+                write!(f, "selector({})", serialize_values(values))
+            }
+            SupportsCondition::GeneralEnclosed(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-media-query-list
+impl Display for MediaQueryList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_list(f, &self.0, ", ")
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-media-query
+impl Display for MediaQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.qualifier {
+            Some(MediaQualifier::Not) => f.write_str("not ")?,
+            Some(MediaQualifier::Only) => f.write_str("only ")?,
+            None => {}
+        }
+
+        // `all` is implied by the features
+        let omit_type = self.media_type == MediaType::All
+            && self.qualifier.is_none()
+            && !self.features.is_empty();
+        if !omit_type {
+            write!(f, "{}", self.media_type)?;
+        }
+
+        for (index, feature) in self.features.iter().enumerate() {
+            if index > 0 || !omit_type {
+                f.write_str(" and ")?;
+            }
+            write!(f, "{}", feature)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaType::All => f.write_str("all"),
+            MediaType::Screen => f.write_str("screen"),
+            MediaType::Print => f.write_str("print"),
+            MediaType::Unknown(media_type) => f.write_str(&serialize_identifier(media_type)),
+        }
+    }
+}
+
+impl Display for MediaFeature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaFeature::MinWidth(width) => write!(f, "(min-width: {}px)", width),
+            MediaFeature::MaxWidth(width) => write!(f, "(max-width: {}px)", width),
+            MediaFeature::Orientation(orientation) => {
+                let orientation = match orientation {
+                    Orientation::Portrait => "portrait",
+                    Orientation::Landscape => "landscape",
+                };
+                write!(f, "(orientation: {})", orientation)
+            }
+            MediaFeature::PrefersColorScheme(scheme) => {
+                let scheme = match scheme {
+                    ColorScheme::Light => "light",
+                    ColorScheme::Dark => "dark",
+                };
+                write!(f, "(prefers-color-scheme: {})", scheme)
+            }
+            MediaFeature::ForcedColors(active) => {
+                let value = if *active { "active" } else { "none" };
+                write!(f, "(forced-colors: {})", value)
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+pub mod structs;
+
+use super::parser::interpret_declarations;
+use super::parser::structs::ComponentValue;
+use super::tokenizer::token::Token;
+use structs::*;
+
+/// Parse the prelude of a `@supports` rule. Returns `None` if the condition
+/// is invalid, in which case the whole rule is ignored.
+/// https://www.w3.org/TR/css-conditional-3/#at-supports
+pub fn parse_supports_condition(values: &[ComponentValue]) -> Option<SupportsCondition> {
+    let values = values
+        .iter()
+        .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+        .collect::<Vec<&ComponentValue>>();
+
+    parse_condition(&values)
+}
+
+/// Parse `not <supports-in-parens>`, `<supports-in-parens> [and
+/// <supports-in-parens>]*` or `<supports-in-parens> [or <supports-in-parens>]*`.
+/// `and` & `or` can't be mixed without parentheses.
+fn parse_condition(values: &[&ComponentValue]) -> Option<SupportsCondition> {
+    match values {
+        [keyword, value] if is_keyword(keyword, "not") => {
+            Some(SupportsCondition::Not(Box::new(parse_in_parens(value)?)))
+        }
+        [first, rest @ ..] => {
+            let first = parse_in_parens(first)?;
+            if rest.is_empty() {
+                return Some(first);
+            }
+
+            let operator = match rest[0] {
+                ComponentValue::PerservedToken(Token::Ident(ident)) => ident.to_lowercase(),
+                _ => return None,
+            };
+            if operator != "and" && operator != "or" {
+                return None;
+            }
+
+            let mut conditions = vec![first];
+            for pair in rest.chunks(2) {
+                match pair {
+                    [keyword, value] if is_keyword(keyword, &operator) => {
+                        conditions.push(parse_in_parens(value)?)
+                    }
+                    _ => return None,
+                }
+            }
+
+            if operator == "and" {
+                Some(SupportsCondition::And(conditions))
+            } else {
+                Some(SupportsCondition::Or(conditions))
+            }
+        }
+        [] => None,
+    }
+}
+
+/// Parse `( <supports-condition> )`, `( <declaration> )`, `selector()` or
+/// `<general-enclosed>`
+fn parse_in_parens(value: &ComponentValue) -> Option<SupportsCondition> {
+    match value {
+        ComponentValue::SimpleBlock(block) if block.token == Token::ParentheseOpen => {
+            let values = block
+                .value
+                .iter()
+                .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+                .collect::<Vec<&ComponentValue>>();
+
+            if let Some(condition) = parse_condition(&values) {
+                return Some(condition);
+            }
+
+            let is_declaration = matches!(
+                values.as_slice(),
+                [
+                    ComponentValue::PerservedToken(Token::Ident(_)),
+                    ComponentValue::PerservedToken(Token::Colon),
+                    ..
+                ]
+            );
+            if is_declaration {
+                let mut declarations = interpret_declarations(block.clone());
+                if let (Some(declaration), true) = (declarations.pop(), declarations.is_empty()) {
+                    return Some(SupportsCondition::Declaration(declaration));
+                }
+            }
+            Some(SupportsCondition::GeneralEnclosed(value.clone()))
+        }
+        ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("selector") => {
+            Some(SupportsCondition::Selector(function.value.clone()))
+        }
+        ComponentValue::Function(_) => Some(SupportsCondition::GeneralEnclosed(value.clone())),
+        _ => None,
+    }
+}
+
+fn is_keyword(value: &ComponentValue, keyword: &str) -> bool {
+    matches!(value, ComponentValue::PerservedToken(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::structs::Declaration;
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    fn parse(css: &str) -> Option<SupportsCondition> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parse_supports_condition(&parser.parse_a_list_of_component_values())
+    }
+
+    fn ident(value: &str) -> ComponentValue {
+        ComponentValue::PerservedToken(Token::Ident(value.to_string()))
+    }
+
+    /// Only `display: grid` & `color: <ident>` are supported
+    fn supports_declaration(name: &str, value: &[ComponentValue]) -> bool {
+        match (name, value) {
+            ("display", [value]) => *value == ident("grid"),
+            ("color", [ComponentValue::PerservedToken(Token::Ident(_))]) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn parse_conditions() {
+        assert_eq!(
+            parse("not (display: grid)"),
+            Some(SupportsCondition::Not(Box::new(
+                SupportsCondition::Declaration(Declaration {
+                    name: "display".to_string(),
+                    value: vec![ident("grid")],
+                    important: false,
+                })
+            )))
+        );
+        assert!(matches!(
+            parse("(a: b) and ((c: d) or (e: f))"),
+            Some(SupportsCondition::And(conditions)) if conditions.len() == 2
+        ));
+        assert!(matches!(
+            parse("selector(a > b)"),
+            Some(SupportsCondition::Selector(_))
+        ));
+        assert!(matches!(
+            parse("(unknown) or font-tech(color-COLRv1)"),
+            Some(SupportsCondition::Or(_))
+        ));
+
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("(a: b) and (c: d) or (e: f)"), None);
+        assert_eq!(parse("not (a: b) and (c: d)"), None);
+        assert_eq!(parse("display: grid"), None);
+    }
+
+    #[test]
+    fn evaluate_conditions() {
+        let cases = [
+            ("(display: grid)", true),
+            ("(display: flex)", false),
+            ("not (display: flex)", true),
+            ("(display: grid) and (color: red)", true),
+            ("(display: flex) or (color: red)", true),
+            ("(display: flex) or (color: #fff)", false),
+            (
+                "((display: flex) or (color: red)) and (not (display: flex))",
+                true,
+            ),
+            ("(display: grid !important)", true),
+            ("(display: grid; color: red)", false),
+            ("selector(div > p.note)", true),
+            ("selector(div >)", false),
+            ("font-tech(color-COLRv1)", false),
+            ("not (unknown)", true),
+        ];
+
+        for (css, expected) in cases {
+            let condition = parse(css).unwrap();
+            assert_eq!(
+                condition.matches(&supports_declaration),
+                expected,
+                "{}",
+                css
+            );
+        }
+    }
+}
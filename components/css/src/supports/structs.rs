@@ -0,0 +1,61 @@
+use crate::parser::structs::{ComponentValue, Declaration};
+use crate::selector::parse_selector;
+use crate::tokenizer::token::Token;
+use shared::data_stream::DataStream;
+
+/// Checks whether a declaration is supported, i.e. the property & its value
+/// are accepted by the style system
+pub type DeclarationSupport<'a> = dyn Fn(&str, &[ComponentValue]) -> bool + 'a;
+
+/// Condition of a `@supports` rule
+/// https://www.w3.org/TR/css-conditional-3/#typedef-supports-condition
+#[derive(Debug, PartialEq, Clone)]
+pub enum SupportsCondition {
+    Not(Box<SupportsCondition>),
+    And(Vec<SupportsCondition>),
+    Or(Vec<SupportsCondition>),
+    /// `(<declaration>)`
+    Declaration(Declaration),
+    /// `selector(<complex-selector>)`
+    Selector(Vec<ComponentValue>),
+    /// A parenthesized block or a function that is not a known feature.
+    /// It's valid but never matches.
+    /// https://www.w3.org/TR/mediaqueries-4/#typedef-general-enclosed
+    GeneralEnclosed(ComponentValue),
+}
+
+impl SupportsCondition {
+    pub fn matches(&self, supports_declaration: &DeclarationSupport<'_>) -> bool {
+        match self {
+            SupportsCondition::Not(condition) => !condition.matches(supports_declaration),
+            SupportsCondition::And(conditions) => conditions
+                .iter()
+                .all(|condition| condition.matches(supports_declaration)),
+            SupportsCondition::Or(conditions) => conditions
+                .iter()
+                .any(|condition| condition.matches(supports_declaration)),
+            SupportsCondition::Declaration(declaration) => {
+                supports_declaration(&declaration.name, &declaration.value)
+            }
+            SupportsCondition::Selector(values) => is_valid_selector(values),
+            SupportsCondition::GeneralEnclosed(_) => false,
+        }
+    }
+}
+
+/// The selector is supported if it's parsed without leftovers & doesn't
+/// end with a combinator
+fn is_valid_selector(values: &[ComponentValue]) -> bool {
+    let mut data_stream = DataStream::new(values.to_vec());
+    let ends_with_combinator = match parse_selector(&mut data_stream) {
+        Some(selector) => matches!(selector.values().last(), Some((_, Some(_)))),
+        None => return false,
+    };
+    if ends_with_combinator {
+        return false;
+    }
+    while let Some(ComponentValue::PerservedToken(Token::Whitespace)) = data_stream.peek() {
+        data_stream.next();
+    }
+    data_stream.is_eos()
+}
@@ -104,8 +104,8 @@ fn is_start_identifier(value: &str) -> bool {
 
     match chars.next() {
         Some('-') => {
-            let second = chars.next().unwrap();
-            let third = chars.next().unwrap();
+            let second = chars.next().unwrap_or_default();
+            let third = chars.next().unwrap_or_default();
             if is_name_start(second) || second == '-' {
                 return true;
             }
@@ -117,9 +117,7 @@ fn is_start_identifier(value: &str) -> bool {
         Some(c) if is_name_start(c) => {
             return true;
         }
-        Some('\\') => {
-            return is_valid_escape(&format!("{}{}", '\\', chars.next().unwrap()));
-        }
+        Some('\\') => is_valid_escape(&format!("{}{}", '\\', chars.next().unwrap_or_default())),
         _ => return false,
     }
 }
@@ -398,15 +396,19 @@ where
     }
 
     fn consume_numeric(&mut self) -> Token {
+        let has_sign = matches!(self.input.peek(), Some('+' | '-'));
         let (number, type_) = self.consume_number();
-        if let Some(next_3_chars) = self.input.peek_next_as::<String>(3) {
-            if is_start_identifier(&next_3_chars) {
-                return Token::Dimension {
-                    value: number,
-                    type_,
-                    unit: self.consume_name(),
-                };
-            }
+        // the unit can be at the end of the input, e.g. in a style attribute
+        let next_3_chars = match self.input.peek_next_as::<String>(3) {
+            Some(chars) => chars,
+            None => self.input.peek_max().into_iter().collect(),
+        };
+        if is_start_identifier(&next_3_chars) {
+            return Token::Dimension {
+                value: number,
+                type_,
+                unit: self.consume_name(),
+            };
         }
         if let Some('%') = self.input.peek() {
             self.consume_next();
@@ -415,6 +417,7 @@ where
         return Token::Number {
             value: number,
             type_,
+            has_sign,
         };
     }
 
@@ -477,26 +480,14 @@ where
         let string = self.consume_name();
         if string.eq_ignore_ascii_case("url") {
             if let Some('(') = self.input.peek() {
-                self.consume_next();
-                loop {
-                    if let Some(next_2_chars) = self.input.peek_next_as::<String>(2) {
-                        let mut chars = next_2_chars.chars();
-                        let first = chars.next().unwrap();
-                        let second = chars.next().unwrap();
-                        if is_whitespace(first) && is_whitespace(second) {
-                            self.consume_next();
-                        } else {
-                            break;
-                        }
-                    }
-                }
-                if let Some(next_2_chars) = self.input.peek_next_as::<String>(2) {
-                    let re = Regex::new("^ ?('|\")$").unwrap();
-                    if re.is_match(&next_2_chars) {
-                        return Token::Function(string);
-                    }
-                    return self.consume_url();
+                // a quoted url is a normal function, the `(` is left for
+                // the parser like any other function. Any whitespace can be
+                // between the `(` & the quote.
+                if self.is_quote_after_whitespace(2) {
+                    return Token::Function(string);
                 }
+                self.consume_next();
+                return self.consume_url();
             }
         }
         if let Some('(') = self.input.peek() {
@@ -505,6 +496,19 @@ where
         return Token::Ident(string);
     }
 
+    /// Whether the first input code point from the `n`th one on that isn't
+    /// whitespace is a quote
+    fn is_quote_after_whitespace(&mut self, mut n: usize) -> bool {
+        while let Some(chars) = self.input.peek_next(n) {
+            match chars.last() {
+                Some(c) if is_whitespace(*c) => n += 1,
+                Some('\'') | Some('"') => return true,
+                _ => return false,
+            }
+        }
+        false
+    }
+
     fn consume_string(&mut self, ending: Option<char>) -> Token {
         let ending_char = if let Some(c) = ending {
             c
@@ -559,6 +563,7 @@ where
                     self.consume_while(is_whitespace);
                     if let Some(c) = self.input.peek() {
                         if c == ')' {
+                            self.consume_next();
                             return token;
                         }
                     } else {
@@ -712,6 +717,75 @@ mod tests {
         assert_eq!(tokenizer.consume_token(), Token::EOF);
     }
 
+    #[test]
+    fn tokenize_at_keyword() {
+        let mut tokenizer = Tokenizer::new("@media screen".chars());
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::AtKeyword("media".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Ident("screen".to_string())
+        );
+    }
+
+    #[test]
+    fn tokenize_dimension_at_end_of_input() {
+        let mut tokenizer = Tokenizer::new("--size 2px".chars());
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Ident("--size".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Dimension {
+                value: 2.,
+                type_: NumberType::Integer,
+                unit: "px".to_string()
+            }
+        );
+        assert_eq!(tokenizer.consume_token(), Token::EOF);
+    }
+
+    #[test]
+    fn tokenize_url() {
+        let mut tokenizer = Tokenizer::new("url( image.png ) url(\"image.png\")".chars());
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Url("image.png".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Function("url".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::ParentheseOpen);
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Str("image.png".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::ParentheseClose);
+    }
+
+    #[test]
+    fn tokenize_url_with_whitespace_before_quote() {
+        let mut tokenizer = Tokenizer::new("url(  \n 'a.css') url(  a.css  )".chars());
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Function("url".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::ParentheseOpen);
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(tokenizer.consume_token(), Token::Str("a.css".to_string()));
+        assert_eq!(tokenizer.consume_token(), Token::ParentheseClose);
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(tokenizer.consume_token(), Token::Url("a.css".to_string()));
+        assert_eq!(tokenizer.consume_token(), Token::EOF);
+    }
+
     #[test]
     fn tokenize_css_function() {
         let css = r"#id_selector .class_selector {
@@ -748,7 +822,8 @@ mod tests {
             tokenizer.consume_token(),
             Token::Number {
                 value: 0.0,
-                type_: NumberType::Integer
+                type_: NumberType::Integer,
+                has_sign: false
             }
         );
         assert_eq!(tokenizer.consume_token(), Token::Comma);
@@ -757,7 +832,8 @@ mod tests {
             tokenizer.consume_token(),
             Token::Number {
                 value: 0.0,
-                type_: NumberType::Integer
+                type_: NumberType::Integer,
+                has_sign: false
             }
         );
         assert_eq!(tokenizer.consume_token(), Token::Comma);
@@ -766,7 +842,8 @@ mod tests {
             tokenizer.consume_token(),
             Token::Number {
                 value: 0.0,
-                type_: NumberType::Integer
+                type_: NumberType::Integer,
+                has_sign: false
             }
         );
         assert_eq!(tokenizer.consume_token(), Token::Comma);
@@ -775,7 +852,8 @@ mod tests {
             tokenizer.consume_token(),
             Token::Number {
                 value: 0.0,
-                type_: NumberType::Integer
+                type_: NumberType::Integer,
+                has_sign: false
             }
         );
         assert_eq!(tokenizer.consume_token(), Token::ParentheseClose);
@@ -12,6 +12,8 @@ pub enum Token {
     Number {
         value: f32,
         type_: NumberType,
+        /// Whether the number starts with a `+` or `-`
+        has_sign: bool,
     },
     Percentage(f32),
     Dimension {
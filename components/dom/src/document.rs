@@ -1,9 +1,22 @@
+use super::element::Element;
 use super::node::{NodeHooks, NodePtr};
 use css::cssom::css_rule::CSSRule;
+use css::cssom::css_rule_list::CSSRuleList;
+use css::cssom::stylesheet::StyleSheet;
+use css::media_query::structs::MediaEnvironment;
+use css::parser::structs::ComponentValue;
+use css::supports::structs::DeclarationSupport;
 use loader::document_loader::DocumentLoader;
-use std::cell::RefCell;
+use shared::font::{FontDescription, FontFace, FontMetrics, FontMetricsProvider};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ops::Deref;
-use style_types::{ContextualRule, ContextualStyleSheet};
+use std::rc::Rc;
+use std::sync::Arc;
+use style_types::{
+    CascadeLayers, CascadeOrigin, ContextualRule, ContextualStyleSheet, LayerId, UNLAYERED,
+};
+use url::parser::URLParser;
 use url::Url;
 
 pub struct Document {
@@ -13,7 +26,21 @@ pub struct Document {
     loader: RefCell<Option<DocumentLoader>>,
     base: RefCell<Option<Url>>,
     style_elements: RefCell<Vec<NodePtr>>,
-    user_agent_stylesheet: RefCell<Option<ContextualStyleSheet>>,
+    user_agent_stylesheets: RefCell<Vec<ContextualStyleSheet>>,
+    user_stylesheets: RefCell<Vec<ContextualStyleSheet>>,
+    hovered_element: RefCell<Option<NodePtr>>,
+    active_element: RefCell<Option<NodePtr>>,
+    media_environment: RefCell<MediaEnvironment>,
+    font_faces: RefCell<Vec<FontFace>>,
+    font_cache: RefCell<HashMap<Url, Arc<Vec<u8>>>>,
+    font_metrics_provider: RefCell<Option<Rc<dyn FontMetricsProvider>>>,
+    declaration_support: RefCell<Option<Rc<DeclarationSupport<'static>>>>,
+    /// Whether the computed styles depend on the viewport size, i.e. they
+    /// have to be recomputed when the viewport is resized
+    viewport_dependent: Cell<bool>,
+    /// Whether a stylesheet has been loaded or edited since the styles were
+    /// last computed, i.e. the styles of every node have to be recomputed
+    style_invalidated: Cell<bool>,
 }
 
 pub struct DocumentType {
@@ -46,7 +73,17 @@ impl Document {
             loader: RefCell::new(None),
             base: RefCell::new(None),
             style_elements: RefCell::new(Vec::new()),
-            user_agent_stylesheet: RefCell::new(None),
+            user_agent_stylesheets: RefCell::new(Vec::new()),
+            user_stylesheets: RefCell::new(Vec::new()),
+            hovered_element: RefCell::new(None),
+            active_element: RefCell::new(None),
+            media_environment: RefCell::new(MediaEnvironment::default()),
+            font_faces: RefCell::new(Vec::new()),
+            font_cache: RefCell::new(HashMap::new()),
+            font_metrics_provider: RefCell::new(None),
+            declaration_support: RefCell::new(None),
+            viewport_dependent: Cell::new(false),
+            style_invalidated: Cell::new(false),
         }
     }
 
@@ -79,57 +116,272 @@ impl Document {
     }
 
     pub fn set_user_agent_stylesheet(&self, stylesheet: ContextualStyleSheet) {
-        self.user_agent_stylesheet.borrow_mut().replace(stylesheet);
+        self.set_user_agent_stylesheets(vec![stylesheet]);
+    }
+
+    /// Replace the user agent stylesheets, which apply in the given order
+    pub fn set_user_agent_stylesheets(&self, stylesheets: Vec<ContextualStyleSheet>) {
+        *self.user_agent_stylesheets.borrow_mut() = stylesheets;
+        self.invalidate_style();
+    }
+
+    /// Replace the stylesheets of the user, which apply in the given order
+    pub fn set_user_stylesheets(&self, stylesheets: Vec<ContextualStyleSheet>) {
+        *self.user_stylesheets.borrow_mut() = stylesheets;
+        self.invalidate_style();
     }
 
     pub fn register_style_element(&self, element: NodePtr) {
         self.style_elements.borrow_mut().push(element);
     }
 
+    /// Register a font face loaded from `@font-face`
+    pub fn register_font_face(&self, font_face: FontFace) {
+        let mut font_faces = self.font_faces.borrow_mut();
+        if !font_faces.iter().any(|face| face.is_same_face(&font_face)) {
+            font_faces.push(font_face);
+        }
+    }
+
+    pub fn font_faces(&self) -> Vec<FontFace> {
+        self.font_faces.borrow().clone()
+    }
+
+    pub fn cached_font(&self, url: &Url) -> Option<Arc<Vec<u8>>> {
+        self.font_cache.borrow().get(url).cloned()
+    }
+
+    pub fn cache_font(&self, url: Url, data: Arc<Vec<u8>>) {
+        self.font_cache.borrow_mut().insert(url, data);
+    }
+
+    pub fn set_font_metrics_provider(&self, provider: Rc<dyn FontMetricsProvider>) {
+        *self.font_metrics_provider.borrow_mut() = Some(provider);
+    }
+
+    /// Metrics of the font, or fallback metrics when no text measurement
+    /// backend provides them
+    pub fn font_metrics(&self, font: &FontDescription) -> FontMetrics {
+        match self.font_metrics_provider.borrow().as_ref() {
+            Some(provider) => provider.metrics(font),
+            None => FontMetrics::fallback(font.size),
+        }
+    }
+
+    /// Set the check of the declarations of `@supports` conditions
+    pub fn set_declaration_support(&self, declaration_support: Rc<DeclarationSupport<'static>>) {
+        *self.declaration_support.borrow_mut() = Some(declaration_support);
+    }
+
+    /// Whether the declaration of a `@supports` condition is supported.
+    /// Declarations are checked by the style system, nothing is supported
+    /// until it provides the check.
+    pub fn supports_declaration(&self, name: &str, values: &[ComponentValue]) -> bool {
+        match self.declaration_support.borrow().as_ref() {
+            Some(declaration_support) => declaration_support(name, values),
+            None => false,
+        }
+    }
+
+    pub fn set_viewport_dependent(&self, viewport_dependent: bool) {
+        self.viewport_dependent.set(viewport_dependent);
+    }
+
+    pub fn is_viewport_dependent(&self) -> bool {
+        self.viewport_dependent.get()
+    }
+
+    pub fn invalidate_style(&self) {
+        self.style_invalidated.set(true);
+    }
+
+    pub fn is_style_invalidated(&self) -> bool {
+        self.style_invalidated.get()
+    }
+
+    pub fn clear_style_invalidation(&self) {
+        self.style_invalidated.set(false);
+    }
+
+    pub fn media_environment(&self) -> MediaEnvironment {
+        self.media_environment.borrow().clone()
+    }
+
+    /// Set the environment that media queries are evaluated against.
+    /// Returns `true` if the result of any `@media` rule changed, i.e. the
+    /// style rules have to be recomputed.
+    pub fn set_media_environment(&self, environment: MediaEnvironment) -> bool {
+        fn media_changed(
+            rules: &CSSRuleList,
+            old: &MediaEnvironment,
+            new: &MediaEnvironment,
+        ) -> bool {
+            rules.iter().any(|rule| match rule {
+                CSSRule::Media(media) => {
+                    media.media.matches(old) != media.media.matches(new)
+                        || media_changed(&media.css_rules, old, new)
+                }
+                CSSRule::Supports(supports) => media_changed(&supports.css_rules, old, new),
+                CSSRule::Layer(layer) => media_changed(&layer.css_rules, old, new),
+                CSSRule::Import(import) => {
+                    import.media.matches(old) != import.media.matches(new)
+                        || import.stylesheet.as_ref().map_or(false, |stylesheet| {
+                            media_changed(&stylesheet.css_rules, old, new)
+                        })
+                }
+                _ => false,
+            })
+        }
+
+        let old = self.media_environment.replace(environment.clone());
+        let mut changed = false;
+        self.for_each_stylesheet(|stylesheet| {
+            changed |= media_changed(&stylesheet.inner.css_rules, &old, &environment);
+        });
+        changed
+    }
+
+    /// Style rules of the stylesheets that apply, in source order, with
+    /// the order of their cascade layer
     pub fn style_rules(&self) -> Vec<ContextualRule> {
-        let style_elements = self.style_elements.borrow();
-        let mut style_rules = Vec::new();
-
-        fn stylesheet_to_rules(stylesheet: &ContextualStyleSheet) -> Vec<ContextualRule> {
-            stylesheet
-                .inner
-                .iter()
-                .map(move |rule| match rule {
-                    CSSRule::Style(style) => ContextualRule {
-                        inner: style.clone(),
-                        location: stylesheet.location.clone(),
-                        origin: stylesheet.origin.clone(),
-                    },
-                })
-                .collect()
+        let environment = self.media_environment.borrow();
+        let mut collector = RuleCollector {
+            document: self,
+            environment: &environment,
+            rules: Vec::new(),
+            layers: Vec::new(),
+        };
+
+        self.for_each_stylesheet(|stylesheet| {
+            collector.collect(&stylesheet.inner.css_rules, stylesheet, CascadeLayers::ROOT);
+        });
+        collector.finish()
+    }
+
+    /// Number of author stylesheets, i.e. the loaded stylesheets of the
+    /// style & link elements
+    pub fn style_sheet_count(&self) -> usize {
+        let mut count = 0;
+        self.for_each_author_stylesheet(|_, _| count += 1);
+        count
+    }
+
+    /// Edit the author stylesheet at `index` in tree order & invalidate the
+    /// styles. `edit` gets the URL that the relative URLs of the stylesheet
+    /// resolve against. Stylesheets are edited through
+    /// `NodePtr::edit_stylesheet` outside of the crate, which loads the
+    /// resources added by the edit.
+    pub(crate) fn edit_stylesheet<R, F: FnOnce(&mut StyleSheet, Option<&Url>) -> R>(
+        &self,
+        index: usize,
+        edit: F,
+    ) -> Option<R> {
+        let mut edit = Some(edit);
+        let mut result = None;
+        let mut current = 0;
+        self.for_each_author_stylesheet(|stylesheet, base| {
+            if current == index {
+                if let Some(edit) = edit.take() {
+                    result = Some(edit(&mut stylesheet.inner, base.as_ref()));
+                }
+            }
+            current += 1;
+        });
+        if result.is_some() {
+            self.invalidate_style();
+        }
+        result
+    }
+
+    /// Serialize all of the stylesheets applied to the document, for
+    /// debugging
+    pub fn dump_stylesheets(&self) -> String {
+        let mut result = String::new();
+        self.for_each_stylesheet(|stylesheet| {
+            result.push_str(&format!(
+                "/* {:?} {:?} */\n{}\n",
+                stylesheet.origin,
+                stylesheet.location,
+                stylesheet.inner.css_text()
+            ));
+        });
+        result
+    }
+
+    fn for_each_author_stylesheet<F: FnMut(&mut ContextualStyleSheet, Option<Url>)>(
+        &self,
+        mut callback: F,
+    ) {
+        for element in self.style_elements.borrow().iter() {
+            let element = element.as_element();
+
+            match element.data() {
+                crate::elements::ElementData::Link(link) => {
+                    if let Some(stylesheet) = link.stylesheet().lock().unwrap().as_mut() {
+                        // resources of an external stylesheet are relative to it
+                        let href = element.attributes().borrow().get_str("href");
+                        callback(stylesheet, URLParser::parse(&href, self.base()));
+                    }
+                }
+                crate::elements::ElementData::Style(style) => {
+                    if let Some(stylesheet) = style.stylesheet_mut().as_mut() {
+                        callback(stylesheet, self.base());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Visit the user agent stylesheets, the user stylesheets & the
+    /// stylesheets of the style elements in tree order
+    fn for_each_stylesheet<F: FnMut(&ContextualStyleSheet)>(&self, mut callback: F) {
+        for stylesheet in self.user_agent_stylesheets.borrow().iter() {
+            callback(stylesheet);
         }
 
-        if let Some(stylesheet) = &*self.user_agent_stylesheet.borrow() {
-            style_rules.extend(stylesheet_to_rules(stylesheet));
+        for stylesheet in self.user_stylesheets.borrow().iter() {
+            callback(stylesheet);
         }
 
-        for element in style_elements.iter() {
+        for element in self.style_elements.borrow().iter() {
             let element = element.as_element();
 
-            let rules = match element.data() {
-                crate::elements::ElementData::Link(link) => link
-                    .stylesheet()
-                    .lock()
-                    .unwrap()
-                    .as_ref()
-                    .map(stylesheet_to_rules)
-                    .unwrap_or(Vec::new()),
-                crate::elements::ElementData::Style(style) => style
-                    .stylesheet()
-                    .as_ref()
-                    .map(stylesheet_to_rules)
-                    .unwrap_or(Vec::new()),
-                _ => Vec::new(),
-            };
-
-            style_rules.extend(rules);
+            match element.data() {
+                crate::elements::ElementData::Link(link) => {
+                    if let Some(stylesheet) = link.stylesheet().lock().unwrap().as_ref() {
+                        callback(stylesheet);
+                    }
+                }
+                crate::elements::ElementData::Style(style) => {
+                    if let Some(stylesheet) = style.stylesheet().as_ref() {
+                        callback(stylesheet);
+                    }
+                }
+                _ => {}
+            }
         }
-        style_rules
+    }
+
+    /// Set the element under the mouse. The element & all of its ancestors
+    /// are in hover state. Return the elements whose hover state changed.
+    pub fn set_hovered_element(&self, element: Option<NodePtr>) -> Vec<NodePtr> {
+        update_element_chain(&self.hovered_element, element, Element::set_hovered)
+    }
+
+    pub fn hovered_element(&self) -> Option<NodePtr> {
+        self.hovered_element.borrow().clone()
+    }
+
+    /// Set the element being activated (e.g. pressed by the mouse). The element
+    /// & all of its ancestors are in active state. Return the elements whose
+    /// active state changed.
+    pub fn set_active_element(&self, element: Option<NodePtr>) -> Vec<NodePtr> {
+        update_element_chain(&self.active_element, element, Element::set_active)
+    }
+
+    pub fn active_element(&self) -> Option<NodePtr> {
+        self.active_element.borrow().clone()
     }
 
     pub fn base(&self) -> Option<Url> {
@@ -141,6 +393,154 @@ impl Document {
     }
 }
 
+/// Collects the style rules of the stylesheets & declares their cascade
+/// layers. Each origin has its own layers.
+struct RuleCollector<'a> {
+    document: &'a Document,
+    environment: &'a MediaEnvironment,
+    rules: Vec<(ContextualRule, LayerId)>,
+    layers: Vec<(CascadeOrigin, CascadeLayers)>,
+}
+
+impl<'a> RuleCollector<'a> {
+    fn collect(&mut self, rules: &CSSRuleList, stylesheet: &ContextualStyleSheet, layer: LayerId) {
+        for rule in rules.iter() {
+            match rule {
+                CSSRule::Style(style) => self.rules.push((
+                    ContextualRule {
+                        inner: style.clone(),
+                        location: stylesheet.location.clone(),
+                        origin: stylesheet.origin.clone(),
+                        layer: UNLAYERED,
+                    },
+                    layer,
+                )),
+                CSSRule::Media(media) => {
+                    if media.media.matches(self.environment) {
+                        self.collect(&media.css_rules, stylesheet, layer);
+                    }
+                }
+                CSSRule::Supports(supports) => {
+                    let document = self.document;
+                    let supports_declaration = |name: &str, values: &[ComponentValue]| {
+                        document.supports_declaration(name, values)
+                    };
+                    if supports.matches(&supports_declaration) {
+                        self.collect(&supports.css_rules, stylesheet, layer);
+                    }
+                }
+                CSSRule::Layer(block) => {
+                    let layers = self.layers(&stylesheet.origin);
+                    let layer = match &block.name {
+                        Some(name) => layers.declare(layer, name),
+                        None => layers.declare_anonymous(layer),
+                    };
+                    self.collect(&block.css_rules, stylesheet, layer);
+                }
+                CSSRule::LayerStatement(statement) => {
+                    let layers = self.layers(&stylesheet.origin);
+                    for name in &statement.names {
+                        layers.declare(layer, name);
+                    }
+                }
+                CSSRule::FontFace(_) => {}
+                // the imported rules are in place of the @import rule
+                CSSRule::Import(import) => {
+                    if let Some(imported) = &import.stylesheet {
+                        if import.media.matches(self.environment) {
+                            let layers = self.layers(&stylesheet.origin);
+                            let layer = match &import.layer {
+                                Some(Some(name)) => layers.declare(layer, name),
+                                Some(None) => layers.declare_anonymous(layer),
+                                None => layer,
+                            };
+                            self.collect(&imported.css_rules, stylesheet, layer);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn layers(&mut self, origin: &CascadeOrigin) -> &mut CascadeLayers {
+        let index = match self.layers.iter().position(|(o, _)| o == origin) {
+            Some(index) => index,
+            None => {
+                self.layers.push((origin.clone(), CascadeLayers::new()));
+                self.layers.len() - 1
+            }
+        };
+        &mut self.layers[index].1
+    }
+
+    /// The layer order is only known once all of the layers are declared
+    fn finish(self) -> Vec<ContextualRule> {
+        let orders = self
+            .layers
+            .iter()
+            .map(|(origin, layers)| (origin.clone(), layers.orders()))
+            .collect::<Vec<(CascadeOrigin, Vec<u32>)>>();
+
+        self.rules
+            .into_iter()
+            .map(|(mut rule, layer)| {
+                if let Some((_, orders)) = orders.iter().find(|(origin, _)| *origin == rule.origin)
+                {
+                    rule.layer = orders[layer];
+                }
+                rule
+            })
+            .collect()
+    }
+}
+
+/// Element with all of its element ancestors
+fn element_chain(element: &NodePtr) -> Vec<NodePtr> {
+    let mut chain = vec![element.clone()];
+    let mut current = element.parent();
+    while let Some(node) = current {
+        if !node.is_element() {
+            break;
+        }
+        chain.push(NodePtr(node.clone()));
+        current = node.parent();
+    }
+    chain
+}
+
+fn update_element_chain(
+    current: &RefCell<Option<NodePtr>>,
+    new: Option<NodePtr>,
+    set_state: fn(&Element, bool),
+) -> Vec<NodePtr> {
+    let old_chain = current
+        .borrow()
+        .as_ref()
+        .map(element_chain)
+        .unwrap_or_default();
+    let new_chain = new.as_ref().map(element_chain).unwrap_or_default();
+
+    let contains =
+        |chain: &[NodePtr], node: &NodePtr| chain.iter().any(|n| Rc::ptr_eq(&n.0, &node.0));
+
+    let mut changed = Vec::new();
+    for node in old_chain.iter() {
+        if !contains(&new_chain, node) {
+            set_state(node.as_element(), false);
+            changed.push(node.clone());
+        }
+    }
+    for node in new_chain.iter() {
+        if !contains(&old_chain, node) {
+            set_state(node.as_element(), true);
+            changed.push(node.clone());
+        }
+    }
+
+    *current.borrow_mut() = new;
+    changed
+}
+
 impl core::fmt::Debug for DocumentType {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Doctype at {:#?}", self as *const DocumentType)
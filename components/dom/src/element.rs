@@ -3,7 +3,8 @@ use crate::node::{ChildrenUpdateContext, InsertContext};
 use super::dom_token_list::DOMTokenList;
 use super::elements::{ElementData, ElementMethods};
 use super::node::NodeHooks;
-use std::cell::RefCell;
+use css::cssom::style_declaration::CSSStyleDeclaration;
+use std::cell::{Ref, RefCell};
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 
@@ -14,6 +15,10 @@ pub struct Element {
     attributes: RefCell<AttributeMap>,
     id: RefCell<Option<String>>,
     class_list: RefCell<DOMTokenList>,
+    is_hovered: RefCell<bool>,
+    is_active: RefCell<bool>,
+    /// Declarations of the `style` attribute
+    inline_style: RefCell<CSSStyleDeclaration>,
     data: ElementData,
 }
 
@@ -79,6 +84,9 @@ impl Element {
             attributes: RefCell::new(AttributeMap::new()),
             id: RefCell::new(None),
             class_list: RefCell::new(DOMTokenList::new()),
+            is_hovered: RefCell::new(false),
+            is_active: RefCell::new(false),
+            inline_style: RefCell::new(CSSStyleDeclaration::default()),
             data,
         }
     }
@@ -87,14 +95,19 @@ impl Element {
         self.data.tag_name()
     }
 
-    pub fn set_attribute(&self, name: &str, value: &str) {
+    /// Set an attribute, without marking the node style dirty. Attributes
+    /// are set through `NodePtr::set_attribute` outside of the crate.
+    pub(crate) fn set_attribute(&self, name: &str, value: &str) {
         if name == "id" {
             *self.id.borrow_mut() = Some(value.to_string());
             return;
         }
+        // the attribute is kept as is, even if it has no classes
         if name == "class" {
             *self.class_list.borrow_mut() = DOMTokenList::from(value);
-            return;
+        }
+        if name == "style" {
+            *self.inline_style.borrow_mut() = CSSStyleDeclaration::parse(value);
         }
         self.attributes
             .borrow_mut()
@@ -110,6 +123,15 @@ impl Element {
         self.attributes.borrow().contains_key(name)
     }
 
+    /// Value of an attribute, including `id` which is not kept in the
+    /// attribute map.
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        match name {
+            "id" => self.id(),
+            _ => self.attributes.borrow().get(name).cloned(),
+        }
+    }
+
     pub fn class_list(&self) -> RefCell<DOMTokenList> {
         self.class_list.clone()
     }
@@ -118,6 +140,40 @@ impl Element {
         self.id.borrow().clone()
     }
 
+    /// Declarations of the `style` attribute
+    pub fn style(&self) -> Ref<'_, CSSStyleDeclaration> {
+        self.inline_style.borrow()
+    }
+
+    /// Edit the declarations of the `style` attribute. The attribute is
+    /// updated with the serialized declarations.
+    pub(crate) fn edit_style<R, F: FnOnce(&mut CSSStyleDeclaration) -> R>(&self, edit: F) -> R {
+        let mut style = self.inline_style.borrow_mut();
+        let result = edit(&mut style);
+        self.attributes
+            .borrow_mut()
+            .insert("style".to_string(), style.css_text());
+        result
+    }
+
+    /// Whether the element matches `:hover`
+    pub fn is_hovered(&self) -> bool {
+        *self.is_hovered.borrow()
+    }
+
+    pub fn set_hovered(&self, value: bool) {
+        *self.is_hovered.borrow_mut() = value;
+    }
+
+    /// Whether the element matches `:active`
+    pub fn is_active(&self) -> bool {
+        *self.is_active.borrow()
+    }
+
+    pub fn set_active(&self, value: bool) {
+        *self.is_active.borrow_mut() = value;
+    }
+
     pub fn handle_on_inserted(&self, context: InsertContext) {
         self.data.handle_on_inserted(context);
     }
@@ -6,41 +6,10 @@ use super::ElementMethods;
 use crate::node::InsertContext;
 use crate::node::NodeHooks;
 use crate::node::NodePtr;
-use flume::bounded;
-use flume::Sender;
-use loader::resource_loop::request::FetchListener;
-use shared::byte_string::ByteString;
+use crate::stylesheet_loader::load_stylesheet;
 use style_types::ContextualStyleSheet;
-use url::Url;
-
-use css::parser::Parser;
-use css::tokenizer::{token::Token, Tokenizer};
 use url::parser::URLParser;
-
-struct StyleLoaderContext {
-    stylesheet_tx: Sender<ContextualStyleSheet>,
-}
-
-impl FetchListener for StyleLoaderContext {
-    fn on_finished(&self, bytes: loader::resource_loop::request::Bytes) {
-        let css = ByteString::new(&bytes);
-        let tokenizer = Tokenizer::new(css.chars());
-        let mut parser = Parser::<Token>::new(tokenizer.run());
-        let stylesheet = parser.parse_a_css_stylesheet();
-
-        let stylesheet = ContextualStyleSheet::new(
-            stylesheet,
-            style_types::CascadeOrigin::Author,
-            style_types::CSSLocation::External,
-        );
-
-        self.stylesheet_tx.send(stylesheet).unwrap();
-    }
-
-    fn on_errored(&self, error: loader::resource_loop::error::LoadError) {
-        log::error!("Unable to load CSS: {}", error);
-    }
-}
+use url::Url;
 
 #[derive(Debug)]
 pub struct HTMLLinkElement {
@@ -57,19 +26,14 @@ impl HTMLLinkElement {
     pub fn load_stylesheet(&self, url: &Url, document: NodePtr) {
         log::info!("Loading stylesheet from: {}", url);
 
-        let stylesheet = self.stylesheet.clone();
-        let (tx, rx) = bounded(1);
-
-        let loader = document.as_document().loader();
-        loader.fetch(url.clone(), StyleLoaderContext { stylesheet_tx: tx });
-
-        // This is blocking the main thread manually. In the future, this receiving should run on a separate thread
-        // and the main thread should wait for that thread to finish, while working on other things.
-        match rx.recv() {
-            Ok(sheet) => {
-                stylesheet.lock().unwrap().replace(sheet);
-            }
-            _ => {}
+        if let Some(stylesheet) = load_stylesheet(url, &document) {
+            let stylesheet = ContextualStyleSheet::new(
+                stylesheet,
+                style_types::CascadeOrigin::Author,
+                style_types::CSSLocation::External,
+            );
+            self.stylesheet.lock().unwrap().replace(stylesheet);
+            document.as_document().invalidate_style();
         }
     }
 
@@ -1,15 +1,14 @@
 use std::cell::Ref;
 use std::cell::RefCell;
+use std::cell::RefMut;
 
-use css::parser::Parser;
-use css::tokenizer::token::Token;
-use css::tokenizer::Tokenizer;
 use style_types::ContextualStyleSheet;
 
 use super::ElementHooks;
 use super::ElementMethods;
 use crate::node::ChildrenUpdateContext;
 use crate::node::NodeHooks;
+use crate::stylesheet_loader::{has_subresources, load_subresources, parse_stylesheet};
 
 #[derive(Debug)]
 pub struct HTMLStyleElement {
@@ -23,9 +22,13 @@ impl HTMLStyleElement {
         }
     }
 
-    pub fn stylesheet(&self) -> Ref<Option<ContextualStyleSheet>> {
+    pub fn stylesheet(&self) -> Ref<'_, Option<ContextualStyleSheet>> {
         self.stylesheet.borrow()
     }
+
+    pub fn stylesheet_mut(&self) -> RefMut<'_, Option<ContextualStyleSheet>> {
+        self.stylesheet.borrow_mut()
+    }
 }
 
 impl ElementHooks for HTMLStyleElement {}
@@ -40,9 +43,18 @@ impl NodeHooks for HTMLStyleElement {
 
     fn on_children_updated(&self, context: ChildrenUpdateContext) {
         let css = context.current_node.descendant_text_content();
-        let tokenizer = Tokenizer::new(css.chars());
-        let mut parser = Parser::<Token>::new(tokenizer.run());
-        let stylesheet = parser.parse_a_css_stylesheet();
+        let mut stylesheet = parse_stylesheet(&css);
+
+        // resources of an embedded stylesheet are relative to the document
+        if has_subresources(&stylesheet) {
+            let base = context.document.as_document().base();
+            load_subresources(
+                &mut stylesheet,
+                base.as_ref(),
+                &context.document,
+                &mut Vec::new(),
+            );
+        }
 
         let stylesheet = ContextualStyleSheet::new(
             stylesheet,
@@ -51,6 +63,7 @@ impl NodeHooks for HTMLStyleElement {
         );
 
         self.stylesheet.replace(Some(stylesheet));
+        context.document.as_document().invalidate_style();
     }
 }
 
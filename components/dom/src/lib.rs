@@ -12,5 +12,6 @@ pub mod text;
 pub mod conversion;
 
 mod element_factory;
+mod stylesheet_loader;
 
 pub use element_factory::create_element;
@@ -1,23 +1,53 @@
 use crate::node_list::NodeList;
+use crate::stylesheet_loader::{has_subresources, load_subresources};
 
 use super::comment::Comment;
 use super::document::Document;
 use super::element::Element;
 use super::elements::ElementData;
 use super::text::Text;
+use css::cssom::style_declaration::CSSStyleDeclaration;
+use css::cssom::stylesheet::StyleSheet;
+use css::selector::structs::PseudoElement;
 use enum_dispatch::enum_dispatch;
 use shared::tree_node::{TreeNode, TreeNodeHooks, WeakTreeNode};
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
 use std::ops::Deref;
-use style_types::{Property, Value};
+use std::rc::Rc;
+use style_types::{ComputedStyle, CustomProperties, Property, Value};
 
 pub struct NodePtr(pub TreeNode<Node>);
 
 pub struct Node {
     owner_document: RefCell<Option<WeakTreeNode<Node>>>,
     data: Option<NodeData>,
-    computed_styles: RefCell<HashMap<Property, Value>>,
+    computed_style: RefCell<ComputedStyle>,
+    pseudo_element_styles: RefCell<HashMap<PseudoElement, ComputedStyle>>,
+    custom_properties: RefCell<Rc<CustomProperties>>,
+    /// The styles of the node & of its descendants have to be recomputed
+    style_dirty: Cell<bool>,
+    /// Only the style of the node has to be recomputed. Its descendants are
+    /// restyled if its style changes.
+    self_style_dirty: Cell<bool>,
+    /// A descendant of the node is style dirty
+    has_style_dirty_descendants: Cell<bool>,
+    layout_dirty: Cell<LayoutDirty>,
+    /// A descendant of the node is layout dirty
+    has_layout_dirty_descendants: Cell<bool>,
+}
+
+/// How the layout boxes of a node have to be updated, from the cheapest to
+/// the most expensive update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LayoutDirty {
+    Clean,
+    /// The boxes of the node have to be laid out again, e.g. its margins
+    /// changed
+    Reflow,
+    /// The boxes of the children of the node have to be built again, e.g.
+    /// a child was inserted or its `display` changed
+    RebuildChildren,
 }
 
 #[enum_dispatch(NodeHooks)]
@@ -71,6 +101,20 @@ impl Clone for NodePtr {
 
 impl TreeNodeHooks<Node> for Node {
     fn on_inserted(&self, current: TreeNode<Node>, parent: TreeNode<Node>) {
+        let current_node = NodePtr(current.clone());
+        let parent_node = NodePtr(parent.clone());
+        current_node.mark_style_dirty();
+        // `:empty` matches the parent differently
+        parent_node.mark_self_style_dirty();
+        parent_node.mark_siblings_style_dirty();
+        parent_node.mark_layout_dirty(LayoutDirty::RebuildChildren);
+        // the flags set before the insertion are found from the new ancestors
+        if current_node.layout_dirty() != LayoutDirty::Clean
+            || current_node.has_layout_dirty_descendants()
+        {
+            current_node.mark_layout_dirty(current_node.layout_dirty());
+        }
+
         if let Some(data) = &self.data {
             if let Some(document) = self.owner_document() {
                 let context = InsertContext {
@@ -94,6 +138,13 @@ impl TreeNodeHooks<Node> for Node {
             }
         }
     }
+
+    fn on_removed(&self, _current: TreeNode<Node>, parent: TreeNode<Node>) {
+        let parent = NodePtr(parent);
+        parent.mark_self_style_dirty();
+        parent.mark_siblings_style_dirty();
+        parent.mark_layout_dirty(LayoutDirty::RebuildChildren);
+    }
 }
 
 impl NodeData {
@@ -150,6 +201,96 @@ impl NodePtr {
         result
     }
 
+    /// Edit the declarations of the element's `style` attribute & mark the
+    /// element style dirty. Returns `None` if the node isn't an element.
+    pub fn edit_inline_style<R, F: FnOnce(&mut CSSStyleDeclaration) -> R>(
+        &self,
+        edit: F,
+    ) -> Option<R> {
+        let result = self.as_element_opt()?.edit_style(edit);
+        self.mark_style_dirty();
+        Some(result)
+    }
+
+    /// Edit the author stylesheet at `index` of the document in tree order
+    /// & invalidate the styles. The imported stylesheets & the fonts that
+    /// the edit adds are loaded. Returns `None` if there's no stylesheet at
+    /// the index.
+    pub fn edit_stylesheet<R, F: FnOnce(&mut StyleSheet) -> R>(
+        &self,
+        index: usize,
+        edit: F,
+    ) -> Option<R> {
+        self.as_document()
+            .edit_stylesheet(index, |stylesheet, base| {
+                let result = edit(stylesheet);
+                if has_subresources(stylesheet) {
+                    load_subresources(stylesheet, base, self, &mut Vec::new());
+                }
+                result
+            })
+    }
+
+    /// Set an attribute of the element & mark the element style dirty
+    pub fn set_attribute(&self, name: &str, value: &str) {
+        self.as_element().set_attribute(name, value);
+        self.mark_style_dirty();
+        // `attr()` in the content of pseudo-elements is resolved when their
+        // boxes are built
+        if !self.pseudo_element_styles().is_empty() {
+            self.mark_layout_dirty(LayoutDirty::RebuildChildren);
+        }
+    }
+
+    /// Mark the styles of the node & of its descendants to be recomputed
+    pub fn mark_style_dirty(&self) {
+        self.style_dirty.set(true);
+        self.mark_ancestors_style_dirty();
+    }
+
+    /// Mark the style of the node itself to be recomputed, its descendants
+    /// are only restyled if its style changes
+    pub fn mark_self_style_dirty(&self) {
+        self.self_style_dirty.set(true);
+        self.mark_ancestors_style_dirty();
+    }
+
+    fn mark_ancestors_style_dirty(&self) {
+        let mut parent = self.parent();
+        while let Some(node) = parent {
+            // the ancestors of a node with the flag have it too
+            if node.has_style_dirty_descendants.replace(true) {
+                break;
+            }
+            parent = node.parent();
+        }
+    }
+
+    /// Mark the children of the node to be restyled, as structural
+    /// pseudo-classes (e.g. `:last-child`) & sibling combinators match
+    /// differently after one of them is inserted or removed. Restyling the
+    /// first child restyles its later siblings too.
+    fn mark_siblings_style_dirty(&self) {
+        if let Some(first_child) = self.first_child() {
+            NodePtr(first_child).mark_self_style_dirty();
+        }
+    }
+
+    /// Mark the layout boxes of the node to be updated. A node keeps the
+    /// most expensive update it's marked with.
+    pub fn mark_layout_dirty(&self, dirty: LayoutDirty) {
+        if dirty > self.layout_dirty.get() {
+            self.layout_dirty.set(dirty);
+        }
+        let mut parent = self.parent();
+        while let Some(node) = parent {
+            if node.has_layout_dirty_descendants.replace(true) {
+                break;
+            }
+            parent = node.parent();
+        }
+    }
+
     pub fn child_nodes(&self) -> NodeList {
         NodeList::new(self.first_child())
     }
@@ -185,7 +326,15 @@ impl Node {
         Self {
             owner_document: RefCell::new(None),
             data: None,
-            computed_styles: RefCell::new(HashMap::new()),
+            computed_style: RefCell::new(ComputedStyle::initial()),
+            pseudo_element_styles: RefCell::new(HashMap::new()),
+            custom_properties: RefCell::new(Rc::new(HashMap::new())),
+            // a new node has never been styled
+            style_dirty: Cell::new(true),
+            self_style_dirty: Cell::new(false),
+            has_style_dirty_descendants: Cell::new(false),
+            layout_dirty: Cell::new(LayoutDirty::Clean),
+            has_layout_dirty_descendants: Cell::new(false),
         }
     }
 
@@ -266,18 +415,83 @@ impl Node {
         &self.data
     }
 
-    pub fn set_computed_styles(&self, computed_styles: HashMap<Property, Value>) {
-        *self.computed_styles.borrow_mut() = computed_styles;
+    pub fn set_computed_style(&self, computed_style: ComputedStyle) {
+        *self.computed_style.borrow_mut() = computed_style;
+    }
+
+    /// The computed style of the node, the initial style until it's computed
+    pub fn computed_style(&self) -> Ref<'_, ComputedStyle> {
+        self.computed_style.borrow()
+    }
+
+    /// Set the computed styles of the pseudo-elements of the node. Only the
+    /// pseudo-elements matched by a style rule have styles.
+    pub fn set_pseudo_element_styles(&self, styles: HashMap<PseudoElement, ComputedStyle>) {
+        *self.pseudo_element_styles.borrow_mut() = styles;
+    }
+
+    pub fn pseudo_element_styles(&self) -> Ref<'_, HashMap<PseudoElement, ComputedStyle>> {
+        self.pseudo_element_styles.borrow()
+    }
+
+    /// Set the computed custom properties of the node. They are shared with
+    /// the descendants that don't declare any custom property.
+    pub fn set_custom_properties(&self, custom_properties: Rc<CustomProperties>) {
+        *self.custom_properties.borrow_mut() = custom_properties;
+    }
+
+    pub fn custom_properties(&self) -> Rc<CustomProperties> {
+        self.custom_properties.borrow().clone()
+    }
+
+    pub fn is_style_dirty(&self) -> bool {
+        self.style_dirty.get()
+    }
+
+    pub fn is_self_style_dirty(&self) -> bool {
+        self.self_style_dirty.get()
+    }
+
+    pub fn has_style_dirty_descendants(&self) -> bool {
+        self.has_style_dirty_descendants.get()
+    }
+
+    /// Clear the style dirty flags of the node, once the node & its dirty
+    /// descendants are restyled
+    pub fn clear_style_dirty(&self) {
+        self.style_dirty.set(false);
+        self.self_style_dirty.set(false);
+        self.has_style_dirty_descendants.set(false);
+    }
+
+    pub fn layout_dirty(&self) -> LayoutDirty {
+        self.layout_dirty.get()
+    }
+
+    pub fn has_layout_dirty_descendants(&self) -> bool {
+        self.has_layout_dirty_descendants.get()
+    }
+
+    /// Clear the layout dirty flags of the node, once the boxes of the node
+    /// & of its dirty descendants are updated
+    pub fn clear_layout_dirty(&self) {
+        self.layout_dirty.set(LayoutDirty::Clean);
+        self.has_layout_dirty_descendants.set(false);
     }
 
-    pub fn computed_styles(&self) -> Ref<HashMap<Property, Value>> {
-        self.computed_styles.borrow()
+    /// Whether the node or one of its descendants has to be restyled or
+    /// laid out again
+    pub fn needs_update(&self) -> bool {
+        self.is_style_dirty()
+            || self.is_self_style_dirty()
+            || self.has_style_dirty_descendants()
+            || self.layout_dirty() != LayoutDirty::Clean
+            || self.has_layout_dirty_descendants()
     }
 
+    /// The computed value of a single property. Prefer the typed accessors
+    /// of `computed_style` when the property is known.
     pub fn get_style(&self, property: &Property) -> Value {
-        self.computed_styles()
-            .get(property)
-            .expect(&format!("Unavailable style for :{:?}", property))
-            .clone()
+        self.computed_style().get(property)
     }
 }
@@ -0,0 +1,201 @@
+use css::cssom::css_rule::CSSRule;
+use css::cssom::font_face_rule::{FontFaceRule, FontFaceSource};
+use css::cssom::stylesheet::StyleSheet;
+use css::parser::Parser;
+use css::tokenizer::{token::Token, Tokenizer};
+use flume::{bounded, Sender};
+use loader::resource_loop::request::{Bytes, FetchListener};
+use shared::byte_string::ByteString;
+use shared::font::FontFace;
+use std::sync::Arc;
+use url::parser::URLParser;
+use url::Url;
+
+use crate::node::NodePtr;
+
+struct ResourceLoaderContext {
+    bytes_tx: Sender<Bytes>,
+}
+
+impl FetchListener for ResourceLoaderContext {
+    fn on_finished(&self, bytes: Bytes) {
+        // the document may be gone by the time the resource is loaded
+        let _ = self.bytes_tx.send(bytes);
+    }
+
+    fn on_errored(&self, error: loader::resource_loop::error::LoadError) {
+        log::error!("Unable to load resource: {}", error);
+    }
+}
+
+/// Font formats that can be rendered
+const SUPPORTED_FONT_FORMATS: [&str; 3] = ["truetype", "opentype", "collection"];
+
+/// The tags that the data of the supported font formats start with
+const FONT_SIGNATURES: [&[u8]; 4] = [b"\x00\x01\x00\x00", b"true", b"OTTO", b"ttcf"];
+
+pub fn parse_stylesheet(css: &str) -> StyleSheet {
+    let tokenizer = Tokenizer::new(css.chars());
+    let mut parser = Parser::<Token>::new(tokenizer.run());
+    parser.parse_a_css_stylesheet()
+}
+
+/// Fetch & parse the stylesheet at the URL, including its imported
+/// stylesheets.
+pub fn load_stylesheet(url: &Url, document: &NodePtr) -> Option<StyleSheet> {
+    let mut stylesheet = fetch_stylesheet(url, document)?;
+    load_subresources(&mut stylesheet, Some(url), document, &mut vec![url.clone()]);
+    Some(stylesheet)
+}
+
+/// Check if the stylesheet has resources to load, i.e. imported
+/// stylesheets or fonts
+pub fn has_subresources(stylesheet: &StyleSheet) -> bool {
+    stylesheet
+        .iter()
+        .any(|rule| matches!(rule, CSSRule::Import(_) | CSSRule::FontFace(_)))
+}
+
+/// Load the imported stylesheets & the fonts of the stylesheet. Relative
+/// URLs are resolved against `base`, the URL of the stylesheet.
+///
+/// `loading` is the chain of stylesheets that led to this stylesheet.
+pub fn load_subresources(
+    stylesheet: &mut StyleSheet,
+    base: Option<&Url>,
+    document: &NodePtr,
+    loading: &mut Vec<Url>,
+) {
+    load_imports(stylesheet, base, document, loading);
+    load_font_faces(stylesheet, base, document);
+}
+
+/// Load the stylesheets imported by `@import` rules of the stylesheet that
+/// aren't loaded yet. An import of a stylesheet in the `loading` chain
+/// would be a cycle, so it's ignored.
+/// https://www.w3.org/TR/css-cascade-4/#at-import
+fn load_imports(
+    stylesheet: &mut StyleSheet,
+    base: Option<&Url>,
+    document: &NodePtr,
+    loading: &mut Vec<Url>,
+) {
+    for rule in stylesheet.css_rules.iter_mut() {
+        let import = match rule {
+            CSSRule::Import(import) if import.stylesheet.is_none() => import,
+            _ => continue,
+        };
+
+        let url = match URLParser::parse(&import.href, base.cloned()) {
+            Some(url) => url,
+            None => {
+                log::warn!("Invalid @import URL: {}", import.href);
+                continue;
+            }
+        };
+
+        if loading.contains(&url) {
+            log::warn!("Ignoring cyclic @import of: {}", url);
+            continue;
+        }
+
+        log::info!("Loading imported stylesheet from: {}", url);
+
+        if let Some(mut imported) = fetch_stylesheet(&url, document) {
+            loading.push(url.clone());
+            load_subresources(&mut imported, Some(&url), document, loading);
+            loading.pop();
+            import.set_stylesheet(imported);
+        }
+    }
+}
+
+/// Load the fonts of the `@font-face` rules of the stylesheet & register
+/// them to the document. The first source that can be loaded as a
+/// supported font is used.
+/// https://www.w3.org/TR/css-fonts-4/#src-desc
+fn load_font_faces(stylesheet: &StyleSheet, base: Option<&Url>, document: &NodePtr) {
+    for rule in stylesheet.iter() {
+        let font_face = match rule {
+            CSSRule::FontFace(font_face) => font_face,
+            _ => continue,
+        };
+
+        let data = font_face
+            .sources
+            .iter()
+            .find_map(|source| load_font_source(source, base, document));
+
+        match data {
+            Some(data) => document
+                .as_document()
+                .register_font_face(create_font_face(font_face, data)),
+            None => log::warn!("Unable to load font face: {}", font_face.family),
+        }
+    }
+}
+
+fn load_font_source(
+    source: &FontFaceSource,
+    base: Option<&Url>,
+    document: &NodePtr,
+) -> Option<Arc<Vec<u8>>> {
+    match source {
+        FontFaceSource::Url(href, format) => {
+            if let Some(format) = format {
+                if !SUPPORTED_FONT_FORMATS.contains(&format.as_str()) {
+                    return None;
+                }
+            }
+
+            let url = URLParser::parse(href, base.cloned())?;
+            if let Some(data) = document.as_document().cached_font(&url) {
+                return Some(data);
+            }
+
+            log::info!("Loading font from: {}", url);
+            let data = fetch(&url, document)?;
+            // e.g. the error page of a missing font
+            if !FONT_SIGNATURES
+                .iter()
+                .any(|signature| data.starts_with(signature))
+            {
+                log::warn!("Unsupported font data from: {}", url);
+                return None;
+            }
+
+            let data = Arc::new(data);
+            document.as_document().cache_font(url, data.clone());
+            Some(data)
+        }
+        // there are no system fonts
+        FontFaceSource::Local(_) => None,
+    }
+}
+
+fn create_font_face(rule: &FontFaceRule, data: Arc<Vec<u8>>) -> FontFace {
+    FontFace {
+        family: rule.family.clone(),
+        weight: rule.weight,
+        style: rule.style,
+        stretch: rule.stretch,
+        data,
+    }
+}
+
+fn fetch_stylesheet(url: &Url, document: &NodePtr) -> Option<StyleSheet> {
+    let bytes = fetch(url, document)?;
+    let css = ByteString::new(&bytes);
+    Some(parse_stylesheet(&css.to_string()))
+}
+
+fn fetch(url: &Url, document: &NodePtr) -> Option<Bytes> {
+    let (tx, rx) = bounded(1);
+
+    let loader = document.as_document().loader();
+    loader.fetch(url.clone(), ResourceLoaderContext { bytes_tx: tx });
+
+    // This is blocking the main thread manually. In the future, this receiving should run on a separate thread
+    // and the main thread should wait for that thread to finish, while working on other things.
+    rx.recv().ok()
+}
@@ -1,3 +1,4 @@
+use super::font_database::FontDatabase;
 use super::text;
 use super::triangle;
 use lyon_tessellation::VertexBuffers;
@@ -13,10 +14,14 @@ pub struct DrawRequest<'a> {
 }
 
 impl Backend {
-    pub fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+    pub fn new(
+        device: &wgpu::Device,
+        texture_format: wgpu::TextureFormat,
+        font_database: FontDatabase,
+    ) -> Self {
         Self {
             triangle_pipeline: triangle::Pipeline::new(device, texture_format),
-            text_pipeline: text::Pipeline::new(device, texture_format, None),
+            text_pipeline: text::Pipeline::new(device, texture_format, font_database),
         }
     }
 
@@ -42,6 +47,7 @@ impl Backend {
 
         if !request.texts.is_empty() {
             for text in request.texts {
+                let font_id = self.text_pipeline.font_id(text.face);
                 let section = wgpu_glyph::Section {
                     screen_position: (text.bounds.x, text.bounds.y),
                     // TODO: Re-enable this once we have proper text bounds computation
@@ -52,7 +58,7 @@ impl Backend {
                             x: text.size,
                             y: text.size,
                         },
-                        font_id: wgpu_glyph::FontId(if text.bold { 1 } else { 0 }),
+                        font_id,
                         extra: wgpu_glyph::Extra {
                             color: text.color.clone().into(),
                             z: 0.0,
@@ -1,5 +1,7 @@
 use super::backend::{Backend, DrawRequest};
 use super::Bitmap;
+use crate::decoration::text_decoration_polygons;
+use crate::font_database::FontDatabase;
 use crate::painters::polygon::PolygonPainter;
 use crate::painters::rect::RectPainter;
 use crate::painters::text::TextPainter;
@@ -7,6 +9,7 @@ use crate::tessellator::Tessellator;
 use crate::Graphics;
 use async_trait::async_trait;
 use shared::color::Color;
+use shared::font::FontDescription;
 use shared::primitive::*;
 
 pub struct Canvas<'a> {
@@ -24,6 +27,7 @@ pub struct Canvas<'a> {
     frame_texture_view: wgpu::TextureView,
     output_buffer: wgpu::Buffer,
     output_buffer_desc: wgpu::BufferDescriptor<'a>,
+    font_database: FontDatabase,
 }
 
 pub const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
@@ -31,7 +35,7 @@ pub const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
 impl<'a> Canvas<'a> {
     const CHUNK_SIZE: u64 = 10 * 1024;
 
-    pub async fn new() -> Canvas<'a> {
+    pub async fn new(font_database: FontDatabase) -> Canvas<'a> {
         let instance = wgpu::Instance::new(wgpu::Backends::PRIMARY);
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
@@ -76,7 +80,7 @@ impl<'a> Canvas<'a> {
         let output_buffer = device.create_buffer(&output_buffer_desc);
 
         Self {
-            backend: Backend::new(&device, TEXTURE_FORMAT),
+            backend: Backend::new(&device, TEXTURE_FORMAT, font_database.clone()),
             tessellator: Tessellator::new(),
             polygon_painter: PolygonPainter::new(),
             rect_painter: RectPainter::new(),
@@ -90,6 +94,7 @@ impl<'a> Canvas<'a> {
             frame_texture_view,
             output_buffer,
             output_buffer_desc,
+            font_database,
         }
     }
 
@@ -217,9 +222,10 @@ impl<'a> Graphics for Canvas<'a> {
             .draw_solid_rrect(&mut self.tessellator, &rect, &color);
     }
 
-    fn fill_text(&mut self, content: String, bounds: Rect, color: Color, size: f32, bold: bool) {
+    fn fill_text(&mut self, content: String, bounds: Rect, color: Color, font: FontDescription) {
+        let face = self.font_database.find(&font);
         self.text_painter
-            .fill_text(content, bounds, color, size, bold);
+            .fill_text(content, bounds, color, font.size, face);
     }
 
     fn fill_polygon(&mut self, points: Vec<Point>, color: Color) {
@@ -227,6 +233,13 @@ impl<'a> Graphics for Canvas<'a> {
             .fill_polygon(&mut self.tessellator, &points, &color);
     }
 
+    fn fill_text_decoration(&mut self, rect: Rect, style: LineStyle, color: Color) {
+        for points in text_decoration_polygons(&rect, style) {
+            self.polygon_painter
+                .fill_polygon(&mut self.tessellator, &points, &color);
+        }
+    }
+
     fn resize(&mut self, size: Size) {
         self.resize((size.width as u32, size.height as u32));
     }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use fontdue::{
     layout::{HorizontalAlign, Layout, LayoutSettings, TextStyle, VerticalAlign},
@@ -6,23 +8,25 @@ use fontdue::{
 use raqote::{DrawOptions, DrawTarget, PathBuilder, SolidSource};
 use shared::{
     color::Color,
-    primitive::{Point, RRect, Rect, Size},
+    font::FontDescription,
+    primitive::{LineStyle, Point, RRect, Rect, Size},
 };
 
 use crate::{
-    fonts::{FALLBACK, FALLBACK_BOLD},
+    decoration::text_decoration_polygons,
+    font_database::{FaceId, FontDatabase},
     Graphics,
 };
 
 pub struct CanvasCPU {
     target: DrawTarget,
     text_layout: Layout,
-    default_font: Font,
-    default_font_bold: Font,
+    font_database: FontDatabase,
+    fonts: HashMap<FaceId, Font>,
 }
 
 impl CanvasCPU {
-    pub fn new() -> Self {
+    pub fn new(font_database: FontDatabase) -> Self {
         let mut target = DrawTarget::new(0, 0);
         target.clear(raqote::SolidSource::from_unpremultiplied_argb(
             255, 255, 255, 255,
@@ -31,15 +35,23 @@ impl CanvasCPU {
         Self {
             target,
             text_layout: Layout::new(fontdue::layout::CoordinateSystem::PositiveYDown),
-            default_font: fontdue::Font::from_bytes(FALLBACK, fontdue::FontSettings::default())
-                .unwrap(),
-            default_font_bold: fontdue::Font::from_bytes(
-                FALLBACK_BOLD,
-                fontdue::FontSettings::default(),
-            )
-            .unwrap(),
+            font_database,
+            fonts: HashMap::new(),
         }
     }
+
+    /// Font of the face, parsed the first time it's used
+    fn font(&mut self, face: FaceId) -> Font {
+        let font_database = &self.font_database;
+        self.fonts
+            .entry(face)
+            .or_insert_with(|| {
+                let data = font_database.face_data(face);
+                Font::from_bytes(data.as_slice(), fontdue::FontSettings::default())
+                    .expect("Faces are validated when added")
+            })
+            .clone()
+    }
 }
 
 #[async_trait(?Send)]
@@ -112,7 +124,10 @@ impl Graphics for CanvasCPU {
         self.target.fill(&path, &src, &options);
     }
 
-    fn fill_text(&mut self, content: String, bounds: Rect, color: Color, size: f32, bold: bool) {
+    fn fill_text(&mut self, content: String, bounds: Rect, color: Color, font: FontDescription) {
+        let size = font.size;
+        let face = self.font_database.find(&font);
+        let font = self.font(face);
         let options = DrawOptions::new();
         self.text_layout.reset(&LayoutSettings {
             x: bounds.x,
@@ -121,11 +136,6 @@ impl Graphics for CanvasCPU {
             vertical_align: VerticalAlign::Top,
             ..LayoutSettings::default()
         });
-        let font = if bold {
-            self.default_font_bold.clone()
-        } else {
-            self.default_font.clone()
-        };
         self.text_layout.append(
             &[font.clone()],
             &TextStyle::new(&content, size * (75. / 96.), 0),
@@ -189,6 +199,12 @@ impl Graphics for CanvasCPU {
         self.target.fill(&path, &src, &options);
     }
 
+    fn fill_text_decoration(&mut self, rect: Rect, style: LineStyle, color: Color) {
+        for points in text_decoration_polygons(&rect, style) {
+            self.fill_polygon(points, color.clone());
+        }
+    }
+
     fn resize(&mut self, size: Size) {
         self.target = DrawTarget::new(size.width as i32, size.height as i32);
         self.target
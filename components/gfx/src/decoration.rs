@@ -0,0 +1,186 @@
+use std::f32::consts::PI;
+
+use shared::primitive::{LineStyle, Point, Rect};
+
+/// The polygons that a text decoration line is painted with. The height
+/// of `rect` is the thickness of the line.
+/// https://www.w3.org/TR/css-text-decor-3/#text-decoration-style-property
+pub fn text_decoration_polygons(rect: &Rect, style: LineStyle) -> Vec<Vec<Point>> {
+    let thickness = rect.height;
+    if thickness <= 0. || rect.width <= 0. {
+        return Vec::new();
+    }
+
+    match style {
+        LineStyle::Solid => vec![rect_points(rect.x, rect.y, rect.width, thickness)],
+        LineStyle::Double => vec![
+            rect_points(rect.x, rect.y, rect.width, thickness),
+            rect_points(rect.x, rect.y + thickness * 2., rect.width, thickness),
+        ],
+        LineStyle::Dashed => segments(rect, thickness * 3., thickness * 3.)
+            .map(|(x, width)| rect_points(x, rect.y, width, thickness))
+            .collect(),
+        LineStyle::Dotted => segments(rect, thickness, thickness)
+            // a dot that would be cut at the end of the line is left out
+            .filter(|(_, width)| *width >= thickness)
+            .map(|(x, _)| {
+                let radius = thickness / 2.;
+                dot(Point::new(x + radius, rect.y + radius), radius)
+            })
+            .collect(),
+        LineStyle::Wavy => vec![wave(rect)],
+    }
+}
+
+fn rect_points(x: f32, y: f32, width: f32, height: f32) -> Vec<Point> {
+    vec![
+        Point::new(x, y),
+        Point::new(x + width, y),
+        Point::new(x + width, y + height),
+        Point::new(x, y + height),
+    ]
+}
+
+/// The start & width of segments of `length` separated by `gap` along the
+/// line. The last segment is cut at the end of the line.
+fn segments(rect: &Rect, length: f32, gap: f32) -> impl Iterator<Item = (f32, f32)> {
+    let end = rect.x + rect.width;
+    std::iter::successors(Some(rect.x), move |x| Some(x + length + gap))
+        .take_while(move |x| *x < end)
+        .map(move |x| (x, length.min(end - x)))
+}
+
+/// A dot approximated by an octagon
+fn dot(center: Point, radius: f32) -> Vec<Point> {
+    (0..8)
+        .map(|i| {
+            let angle = i as f32 * PI / 4.;
+            Point::new(
+                center.x + radius * angle.cos(),
+                center.y + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// A band of the thickness of the line that follows a sine wave. The
+/// wave goes up & down by the thickness around the line.
+fn wave(rect: &Rect) -> Vec<Point> {
+    let thickness = rect.height;
+    let wavelength = thickness * 4.;
+    let step = wavelength / 8.;
+    let steps = (rect.width / step).ceil() as usize;
+
+    let centers = (0..=steps)
+        .map(|i| {
+            let x = (i as f32 * step).min(rect.width);
+            let y = rect.y + thickness / 2. + thickness * (2. * PI * x / wavelength).sin();
+            Point::new(rect.x + x, y)
+        })
+        .collect::<Vec<Point>>();
+
+    let mut points = centers
+        .iter()
+        .map(|center| Point::new(center.x, center.y - thickness / 2.))
+        .collect::<Vec<Point>>();
+    points.extend(
+        centers
+            .iter()
+            .rev()
+            .map(|center| Point::new(center.x, center.y + thickness / 2.)),
+    );
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(polygon: &[Point]) -> Rect {
+        let min_x = polygon.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+        let max_x = polygon
+            .iter()
+            .map(|p| p.x)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let min_y = polygon.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_y = polygon
+            .iter()
+            .map(|p| p.y)
+            .fold(f32::NEG_INFINITY, f32::max);
+        Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    #[test]
+    fn solid_and_double_lines() {
+        let rect = Rect::new(10., 20., 100., 2.);
+        assert_eq!(
+            text_decoration_polygons(&rect, LineStyle::Solid),
+            vec![rect_points(10., 20., 100., 2.)]
+        );
+        // the lines are separated by the thickness
+        assert_eq!(
+            text_decoration_polygons(&rect, LineStyle::Double),
+            vec![
+                rect_points(10., 20., 100., 2.),
+                rect_points(10., 24., 100., 2.)
+            ]
+        );
+    }
+
+    #[test]
+    fn dashed_line() {
+        let rect = Rect::new(0., 0., 15., 2.);
+        let dashes = text_decoration_polygons(&rect, LineStyle::Dashed);
+        // the last dash is cut at the end of the line
+        assert_eq!(
+            dashes
+                .iter()
+                .map(|dash| bounds(dash))
+                .collect::<Vec<Rect>>(),
+            vec![Rect::new(0., 0., 6., 2.), Rect::new(12., 0., 3., 2.)]
+        );
+    }
+
+    #[test]
+    fn dotted_line() {
+        let rect = Rect::new(5., 10., 9., 2.);
+        let dots = text_decoration_polygons(&rect, LineStyle::Dotted);
+        // the dot that would be cut at the end is left out
+        assert_eq!(dots.len(), 2);
+
+        for (dot, center_x) in dots.iter().zip([6., 10.]) {
+            assert_eq!(dot.len(), 8);
+            assert!(dot.iter().all(|point| {
+                let distance = (point.x - center_x).hypot(point.y - 11.);
+                (distance - 1.).abs() < 1e-4
+            }));
+        }
+    }
+
+    #[test]
+    fn wavy_line() {
+        let rect = Rect::new(0., 10., 20., 2.);
+        let waves = text_decoration_polygons(&rect, LineStyle::Wavy);
+        assert_eq!(waves.len(), 1);
+
+        // the band goes along the line & back, with a point per eighth of
+        // a wavelength
+        let wave = &waves[0];
+        assert_eq!(wave.len(), 2 * 21);
+        assert_eq!(wave.first().map(|point| point.x), Some(0.));
+        assert_eq!(wave.last().map(|point| point.x), Some(0.));
+        // the wave goes up & down by the thickness around the line
+        let bounds = bounds(wave);
+        assert_eq!((bounds.x, bounds.width), (0., 20.));
+        assert!((bounds.y - 8.).abs() < 1e-4);
+        assert!((bounds.height - 6.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn empty_lines() {
+        for style in [LineStyle::Solid, LineStyle::Dotted, LineStyle::Wavy] {
+            assert!(text_decoration_polygons(&Rect::new(0., 0., 10., 0.), style).is_empty());
+            assert!(text_decoration_polygons(&Rect::new(0., 0., 0., 2.), style).is_empty());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use glyph_brush::ab_glyph::{Font, FontArc, FontRef};
+use shared::font::{
+    FontDescription, FontFace, FontFaceSet, FontMetrics, FontMetricsProvider, FontStyle,
+};
+
+use crate::fonts::{FALLBACK, FALLBACK_BOLD};
+
+/// Family of the bundled fonts. Generic families & unavailable families
+/// are resolved to it.
+const FALLBACK_FAMILY: &str = "Lato";
+
+/// Identifier of a face in the font database
+pub type FaceId = usize;
+
+/// Font faces available for rendering. The database is shared by text
+/// measurement & the canvases, so that text is painted with the same font
+/// that it was measured with.
+#[derive(Clone)]
+pub struct FontDatabase {
+    faces: Arc<RwLock<FontFaceSet>>,
+    /// Metrics of the faces for a font size of 1px
+    metrics: Arc<RwLock<HashMap<FaceId, FontMetrics>>>,
+}
+
+impl FontDatabase {
+    pub fn new() -> Self {
+        let mut faces = FontFaceSet::new(FALLBACK_FAMILY);
+        faces.add(bundled_face(FALLBACK, 400.));
+        faces.add(bundled_face(FALLBACK_BOLD, 700.));
+
+        Self {
+            faces: Arc::new(RwLock::new(faces)),
+            metrics: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the web fonts, e.g. loaded from `@font-face`, with the ones
+    /// of the current document. Faces with data that can't be parsed as a
+    /// font are rejected.
+    pub fn set_web_faces(&self, faces: Vec<FontFace>) {
+        let faces = faces
+            .into_iter()
+            .filter(|face| {
+                let is_valid = FontRef::try_from_slice(&face.data).is_ok();
+                if !is_valid {
+                    log::warn!("Unable to parse font face: {}", face.family);
+                }
+                is_valid
+            })
+            .collect();
+        self.faces.write().unwrap().set_web_faces(faces);
+    }
+
+    /// Find the face that best matches the description
+    pub fn find(&self, description: &FontDescription) -> FaceId {
+        // the fallback family is always available
+        self.faces.read().unwrap().find(description).unwrap_or(0)
+    }
+
+    pub fn face_data(&self, id: FaceId) -> Arc<Vec<u8>> {
+        let faces = self.faces.read().unwrap();
+        let face = faces
+            .get(id)
+            .or_else(|| faces.get(0))
+            .expect("The fallback font should be in the database");
+        face.data.clone()
+    }
+
+    pub fn font_arc(&self, id: FaceId) -> FontArc {
+        let data = self.face_data(id);
+        FontArc::try_from_vec(data.to_vec()).expect("Faces are validated when added")
+    }
+}
+
+impl FontMetricsProvider for FontDatabase {
+    fn metrics(&self, font: &FontDescription) -> FontMetrics {
+        let id = self.find(font);
+        let cached = self.metrics.read().unwrap().get(&id).copied();
+        let metrics = match cached {
+            Some(metrics) => metrics,
+            None => {
+                let metrics = unit_metrics(&self.face_data(id));
+                self.metrics.write().unwrap().insert(id, metrics);
+                metrics
+            }
+        };
+
+        FontMetrics {
+            x_height: metrics.x_height * font.size,
+            zero_advance: metrics.zero_advance * font.size,
+            line_height: metrics.line_height * font.size,
+            ascent: metrics.ascent * font.size,
+        }
+    }
+}
+
+impl Default for FontDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn bundled_face(data: &[u8], weight: f32) -> FontFace {
+    FontFace {
+        family: FALLBACK_FAMILY.to_string(),
+        weight: (weight, weight),
+        style: FontStyle::Normal,
+        stretch: (100., 100.),
+        data: Arc::new(data.to_vec()),
+    }
+}
+
+/// Metrics of a face for a font size of 1px
+fn unit_metrics(data: &[u8]) -> FontMetrics {
+    let font = match FontRef::try_from_slice(data) {
+        Ok(font) => font,
+        Err(_) => return FontMetrics::fallback(1.),
+    };
+    let units_per_em = match font.units_per_em() {
+        Some(units_per_em) => units_per_em,
+        None => return FontMetrics::fallback(1.),
+    };
+    let fallback = FontMetrics::fallback(units_per_em);
+
+    let x_height = font
+        .outline(font.glyph_id('x'))
+        .map(|outline| outline.bounds.max.y - outline.bounds.min.y)
+        .unwrap_or(fallback.x_height);
+    let zero = font.glyph_id('0');
+    let zero_advance = if zero.0 == 0 {
+        fallback.zero_advance
+    } else {
+        font.h_advance_unscaled(zero)
+    };
+    let line_height = font.ascent_unscaled() - font.descent_unscaled() + font.line_gap_unscaled();
+    // text is drawn at a scale where the ascent minus the descent is the
+    // font size
+    let ascent = font.ascent_unscaled() / (font.ascent_unscaled() - font.descent_unscaled());
+
+    FontMetrics {
+        x_height: x_height / units_per_em,
+        zero_advance: zero_advance / units_per_em,
+        line_height: line_height / units_per_em,
+        ascent,
+    }
+}
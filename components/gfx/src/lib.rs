@@ -1,6 +1,8 @@
 mod backend;
 mod canvas;
 mod canvas_cpu;
+mod decoration;
+mod font_database;
 mod fonts;
 mod graphics;
 mod painters;
@@ -13,5 +15,6 @@ pub type Bitmap = Vec<u8>;
 
 pub use canvas::Canvas;
 pub use canvas_cpu::CanvasCPU;
+pub use font_database::{FaceId, FontDatabase};
 pub use graphics::Graphics;
 pub use text_measure::TextMeasure;
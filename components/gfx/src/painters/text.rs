@@ -1,6 +1,6 @@
 use shared::{color::Color, primitive::Rect};
 
-use crate::text::Text;
+use crate::{font_database::FaceId, text::Text};
 
 pub struct TextPainter {
     texts: Vec<Text>,
@@ -17,14 +17,14 @@ impl TextPainter {
         bounds: Rect,
         color: Color,
         size: f32,
-        bold: bool,
+        face: FaceId,
     ) {
         self.texts.push(Text {
             content,
             bounds,
             color,
             size,
-            bold,
+            face,
         })
     }
 
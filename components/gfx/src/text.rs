@@ -1,48 +1,51 @@
-use crate::fonts::{FALLBACK, FALLBACK_BOLD};
+use std::collections::HashMap;
+
+use crate::font_database::{FaceId, FontDatabase};
 use shared::{color::Color, primitive::rect::Rect};
-use wgpu_glyph::ab_glyph;
 
 pub struct Text {
     pub content: String,
     pub bounds: Rect,
     pub size: f32,
     pub color: Color,
-    pub bold: bool,
+    pub face: FaceId,
 }
 
 pub struct Pipeline {
     draw_brush: wgpu_glyph::GlyphBrush<()>,
+    font_database: FontDatabase,
+    font_ids: HashMap<FaceId, wgpu_glyph::FontId>,
 }
 
 impl Pipeline {
     pub fn new(
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
-        default_font: Option<&[u8]>,
+        font_database: FontDatabase,
     ) -> Self {
-        let default_font = default_font.map(|slice| slice.to_vec());
-        let default_font = default_font.unwrap_or_else(|| FALLBACK.to_vec());
-
-        let font = ab_glyph::FontArc::try_from_vec(default_font).unwrap_or_else(|_| {
-            log::warn!(
-                "System font failed to load. Falling back to \
-                    embedded font..."
-            );
+        let font = font_database.font_arc(0);
 
-            ab_glyph::FontArc::try_from_slice(FALLBACK).expect("Load fallback font")
-        });
-
-        let mut draw_brush = wgpu_glyph::GlyphBrushBuilder::using_font(font)
+        let draw_brush = wgpu_glyph::GlyphBrushBuilder::using_font(font)
             .initial_cache_size((2048, 2048))
             .draw_cache_multithread(true)
             .build(device, format);
 
-        draw_brush.add_font(
-            ab_glyph::FontArc::try_from_vec(FALLBACK_BOLD.to_vec())
-                .expect("Unable to load bold fallback font"),
-        );
+        Self {
+            draw_brush,
+            font_database,
+            font_ids: HashMap::from([(0, wgpu_glyph::FontId(0))]),
+        }
+    }
 
-        Self { draw_brush }
+    /// Font of the face in the brush. Faces are added to the brush the first
+    /// time they are used.
+    pub fn font_id(&mut self, face: FaceId) -> wgpu_glyph::FontId {
+        if let Some(font_id) = self.font_ids.get(&face) {
+            return *font_id;
+        }
+        let font_id = self.draw_brush.add_font(self.font_database.font_arc(face));
+        self.font_ids.insert(face, font_id);
+        font_id
     }
 
     pub fn queue(&mut self, section: wgpu_glyph::Section<'_>) {
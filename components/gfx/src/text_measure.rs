@@ -1,24 +1,33 @@
-use crate::fonts;
-use glyph_brush::{ab_glyph::FontArc, Extra, FontId, GlyphCruncher, Section, Text};
-use shared::primitive::Size;
+use std::collections::HashMap;
+
+use crate::font_database::{FaceId, FontDatabase};
+use glyph_brush::{Extra, FontId, GlyphCruncher, Section, Text};
+use shared::{font::FontDescription, primitive::Size};
 
 pub struct TextMeasure {
+    font_database: FontDatabase,
     brush: glyph_brush::GlyphBrush<()>,
+    font_ids: HashMap<FaceId, FontId>,
 }
 
 impl TextMeasure {
-    pub fn new() -> Self {
-        let font = FontArc::try_from_slice(fonts::FALLBACK).expect("Unable to load default font");
+    pub fn new(font_database: FontDatabase) -> Self {
+        let font = font_database.font_arc(0);
         let brush = glyph_brush::GlyphBrushBuilder::using_font(font).build();
-        Self { brush }
+        Self {
+            font_database,
+            brush,
+            font_ids: HashMap::from([(0, FontId(0))]),
+        }
     }
 
-    pub fn measure(&mut self, content: &str, font_size: f32) -> Size {
+    pub fn measure(&mut self, content: &str, font: &FontDescription) -> Size {
+        let face = self.font_database.find(font);
         let section = Section {
             text: vec![Text {
                 text: content,
-                scale: font_size.into(),
-                font_id: FontId(0),
+                scale: font.size.into(),
+                font_id: self.font_id(face),
                 extra: Extra::default(),
             }],
             bounds: (f32::MAX, f32::MAX),
@@ -30,4 +39,15 @@ impl TextMeasure {
             Size::new(0., 0.)
         }
     }
+
+    /// Font of the face in the brush. Faces are added to the brush the first
+    /// time they are used.
+    fn font_id(&mut self, face: FaceId) -> FontId {
+        if let Some(font_id) = self.font_ids.get(&face) {
+            return *font_id;
+        }
+        let font_id = self.brush.add_font(self.font_database.font_arc(face));
+        self.font_ids.insert(face, font_id);
+        font_id
+    }
 }
@@ -360,9 +360,7 @@ impl<T: Tokenizing> TreeBuilder<T> {
         };
         let element_ref = dom::create_element(WeakTreeNode::from(&self.document.0), &tag_name);
         for attribute in attributes {
-            element_ref
-                .as_element()
-                .set_attribute(&attribute.name, &attribute.value);
+            element_ref.set_attribute(&attribute.name, &attribute.value);
         }
         element_ref
     }
@@ -1495,7 +1493,7 @@ impl<T: Tokenizing> TreeBuilder<T> {
                 if current_element.has_attribute(&attribute.name) {
                     continue;
                 }
-                current_element.set_attribute(&attribute.name, &attribute.value);
+                current_node.set_attribute(&attribute.name, &attribute.value);
             }
             return;
         }
@@ -1540,9 +1538,8 @@ impl<T: Tokenizing> TreeBuilder<T> {
 
             self.frameset_ok = false;
             let body = self.open_elements.get(1);
-            let body = body.as_element();
             for attribute in token.attributes() {
-                if body.has_attribute(&attribute.name) {
+                if body.as_element().has_attribute(&attribute.name) {
                     continue;
                 }
                 body.set_attribute(&attribute.name, &attribute.value);
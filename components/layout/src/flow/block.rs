@@ -8,7 +8,7 @@ use crate::{
     layout_context::LayoutContext,
 };
 use shared::primitive::edge::Edge;
-use style_types::{values::prelude::Position, Property};
+use style_types::values::prelude::Position;
 
 #[derive(Debug)]
 pub struct BlockFormattingContext {
@@ -149,21 +149,22 @@ impl BlockFormattingContext {
             }
         };
 
-        let computed_width = node.get_style(&Property::Width);
-        let computed_margin_left = node.get_style(&Property::MarginLeft);
-        let computed_margin_right = node.get_style(&Property::MarginRight);
-        let computed_border_left = node.get_style(&Property::BorderLeftWidth);
-        let computed_border_right = node.get_style(&Property::BorderRightWidth);
-        let computed_padding_left = node.get_style(&Property::PaddingLeft);
-        let computed_padding_right = node.get_style(&Property::PaddingRight);
+        let style = node.computed_style();
+        let computed_width = style.width();
+        let computed_margin_left = style.margin_left();
+        let computed_margin_right = style.margin_right();
+        let computed_border_left = style.border_left_width();
+        let computed_border_right = style.border_right_width();
+        let computed_padding_left = style.padding_left();
+        let computed_padding_right = style.padding_right();
         let containing_width = containing_block.width;
 
         let box_width = computed_margin_left.to_px(containing_width)
-            + computed_border_left.to_px(containing_width)
+            + computed_border_left.to_px()
             + computed_padding_left.to_px(containing_width)
             + computed_width.to_px(containing_width)
             + computed_padding_right.to_px(containing_width)
-            + computed_border_right.to_px(containing_width)
+            + computed_border_right.to_px()
             + computed_margin_right.to_px(containing_width);
 
         let mut used_width = computed_width.to_px(containing_width);
@@ -269,12 +270,12 @@ impl BlockFormattingContext {
         box_model.set(
             BoxComponent::Border,
             Edge::Left,
-            computed_border_left.to_px(containing_width),
+            computed_border_left.to_px(),
         );
         box_model.set(
             BoxComponent::Border,
             Edge::Right,
-            computed_border_right.to_px(containing_width),
+            computed_border_right.to_px(),
         );
     }
 
@@ -284,27 +285,16 @@ impl BlockFormattingContext {
         }
 
         let node = layout_node.node().unwrap();
+        let style = node.computed_style();
         let containing_block = layout_node.containing_block().unwrap().content_size();
-        let margin_top = node
-            .get_style(&Property::MarginTop)
-            .to_px(containing_block.width);
-        let margin_bottom = node
-            .get_style(&Property::MarginBottom)
-            .to_px(containing_block.width);
-
-        let padding_top = node
-            .get_style(&Property::PaddingTop)
-            .to_px(containing_block.width);
-        let padding_bottom = node
-            .get_style(&Property::PaddingBottom)
-            .to_px(containing_block.width);
-
-        let border_top = node
-            .get_style(&Property::BorderTopWidth)
-            .to_px(containing_block.width);
-        let border_bottom = node
-            .get_style(&Property::BorderBottomWidth)
-            .to_px(containing_block.width);
+        let margin_top = style.margin_top().to_px(containing_block.width);
+        let margin_bottom = style.margin_bottom().to_px(containing_block.width);
+
+        let padding_top = style.padding_top().to_px(containing_block.width);
+        let padding_bottom = style.padding_bottom().to_px(containing_block.width);
+
+        let border_top = style.border_top_width().to_px();
+        let border_bottom = style.border_bottom_width().to_px();
 
         let mut box_model = layout_node.box_model.borrow_mut();
         box_model.set(BoxComponent::Margin, Edge::Top, margin_top);
@@ -326,7 +316,12 @@ impl BlockFormattingContext {
         }
 
         let containing_block = layout_node.containing_block().unwrap().content_size();
-        let computed_height = layout_node.node().unwrap().get_style(&Property::Height);
+        let computed_height = layout_node
+            .node()
+            .unwrap()
+            .computed_style()
+            .height()
+            .clone();
 
         if computed_height.is_auto() {
             self.compute_auto_height(layout_node)
@@ -427,4 +422,68 @@ mod tests {
         assert_eq!(root.content_size().height, 40.);
         assert_eq!(root.content_size().width, layout_context.viewport.width);
     }
+
+    #[test]
+    fn test_block_layout_math_functions() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![
+                element("div.calc", document.clone(), vec![]),
+                element("div.min", document.clone(), vec![]),
+            ],
+        );
+
+        let css = format!(
+            "
+        {}
+        .calc {{
+            width: calc(100% - 240px);
+            height: clamp(10px, 2em, 20px);
+            margin-left: calc((1em + 10%) / 2);
+        }}
+        .min {{
+            width: min(50%, 100px);
+            height: max(1px * 3, 2px);
+        }}
+        ",
+            SHARED_CSS
+        );
+
+        let root = build_tree(dom, &css);
+
+        let mut layout_context = LayoutContext {
+            viewport: Rect {
+                x: 0.,
+                y: 0.,
+                width: 500.,
+                height: 300.,
+            },
+            measure_text_fn: Box::new(|_, _| Size::new(0., 0.)),
+        };
+
+        let initial_block_box = LayoutBoxPtr(TreeNode::new(LayoutBox::new_anonymous(
+            BoxData::block_box(),
+        )));
+        establish_context(
+            FormattingContextType::BlockFormattingContext,
+            initial_block_box.clone(),
+        );
+        initial_block_box.append_child(root.0.clone());
+
+        initial_block_box
+            .formatting_context()
+            .run(&mut layout_context, initial_block_box.clone());
+
+        let calc = root.first_child().map(LayoutBoxPtr).unwrap();
+        let min = root.last_child().map(LayoutBoxPtr).unwrap();
+
+        assert_eq!(calc.content_size().width, 260.);
+        assert_eq!(calc.content_size().height, 20.);
+        assert_eq!(calc.box_model().borrow().margin.left, 33.);
+        assert_eq!(min.content_size().width, 100.);
+        assert_eq!(min.content_size().height, 3.);
+        assert_eq!(root.content_size().height, 23.);
+    }
 }
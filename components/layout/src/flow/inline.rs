@@ -7,11 +7,13 @@ use crate::{
     layout_context::LayoutContext,
 };
 use dom::node::NodeData;
-use regex::Regex;
 use shared::primitive::edge::Edge;
-use style_types::Property;
 
-use super::line_box::LineBoxBuilder;
+use super::{
+    line_box::LineBoxBuilder,
+    line_break::BreakOpportunities,
+    white_space::{TextItem, WhiteSpaceCollapser},
+};
 
 pub struct InlineBoxIterator {
     stack: Vec<LayoutBoxPtr>,
@@ -59,6 +61,33 @@ impl Iterator for InlineBoxIterator {
     }
 }
 
+/// A text item or an inline-level box of an inline formatting context
+enum InlineItem {
+    Text(LayoutBoxPtr, TextItem),
+    Box(LayoutBoxPtr),
+}
+
+impl InlineItem {
+    /// The text of the item for the line breaking algorithm. Atomic inlines
+    /// are object replacement characters, the boundaries of inline boxes
+    /// aren't part of the text.
+    fn text(&self) -> &str {
+        match self {
+            InlineItem::Text(_, TextItem::Word(text)) => text,
+            InlineItem::Text(_, TextItem::PreservedSpaces(spaces)) => spaces,
+            InlineItem::Text(_, TextItem::CollapsibleSpace) => " ",
+            InlineItem::Text(_, TextItem::Tab) => "\t",
+            InlineItem::Text(_, TextItem::LineBreak) => "\n",
+            InlineItem::Box(layout_box) if layout_box.is_anonymous() => "\u{fffc}",
+            InlineItem::Box(layout_box) => match layout_box.node().unwrap().as_element_opt() {
+                Some(element) if element.tag_name() == "br" => "\n",
+                _ if layout_box.is_atomic_inline() => "\u{fffc}",
+                _ => "",
+            },
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct InlineFormattingContext {
     base: BaseFormattingContext,
@@ -97,47 +126,94 @@ impl InlineFormattingContext {
         let mut line_box_builder = LineBoxBuilder::new(layout_node.clone());
         layout_node.lines().borrow_mut().clear();
 
-        let inline_child_iter = InlineBoxIterator::new(layout_node.clone());
+        let items = self.collect_inline_items(context, layout_node.clone());
+        let opportunities = BreakOpportunities::new(items.iter().map(InlineItem::text).collect());
+
+        let mut offset = 0;
+        for item in items.iter() {
+            match item {
+                InlineItem::Text(layout_box, TextItem::Word(word)) => {
+                    let node = layout_box.node().unwrap();
+                    let style = node.computed_style();
+                    let segments = opportunities.segment_word(
+                        offset,
+                        word,
+                        style.word_break(),
+                        style.hyphens(),
+                    );
+                    for segment in segments {
+                        line_box_builder.add_text_fragment(
+                            context,
+                            layout_box.clone(),
+                            segment.text,
+                            segment.break_before,
+                        );
+                    }
+                }
+                InlineItem::Text(layout_box, TextItem::CollapsibleSpace) => {
+                    line_box_builder.add_collapsible_space(context, layout_box.clone())
+                }
+                InlineItem::Text(layout_box, TextItem::PreservedSpaces(spaces)) => line_box_builder
+                    .add_preserved_spaces(context, layout_box.clone(), spaces.clone()),
+                InlineItem::Text(layout_box, TextItem::Tab) => {
+                    line_box_builder.add_tab(context, layout_box.clone())
+                }
+                InlineItem::Text(_, TextItem::LineBreak) => line_box_builder.force_line_break(),
+                InlineItem::Box(layout_box) => {
+                    line_box_builder.add_box_fragment(layout_box.clone())
+                }
+            }
+            offset += item.text().len();
+        }
+        *layout_node.lines().borrow_mut() = line_box_builder.finish();
+    }
 
-        for child in inline_child_iter {
+    /// Lay out the inline-level boxes & process the white space of the
+    /// text of the formatting context, in order
+    fn collect_inline_items(
+        &self,
+        context: &mut LayoutContext,
+        layout_node: LayoutBoxPtr,
+    ) -> Vec<InlineItem> {
+        let mut items = Vec::new();
+        let mut collapser = WhiteSpaceCollapser::new();
+        // whether the next text run starts a word, for `text-transform`
+        let mut word_start = true;
+
+        for child in InlineBoxIterator::new(layout_node) {
             match child.node() {
                 Some(node) => match node.data() {
                     Some(NodeData::Text(content)) => {
-                        let text_content = content.get_data();
-                        if text_content.trim().is_empty() {
-                            continue;
-                        }
-                        // TODO: Support different line break types
-                        let regex = Regex::new(r"\s|\t|\n").unwrap();
-                        for word in regex.split(text_content.trim()) {
-                            if word.is_empty() {
-                                continue;
-                            }
-                            line_box_builder.add_text_fragment(
-                                context,
-                                child.clone(),
-                                word.to_string(),
-                            );
-                            line_box_builder.add_text_fragment(
-                                context,
-                                child.clone(),
-                                ' '.to_string(),
-                            );
-                        }
+                        let style = node.computed_style();
+                        let text_content = style
+                            .text_transform()
+                            .apply(&content.get_data(), &mut word_start);
+                        items.extend(
+                            collapser
+                                .process(&text_content, style.white_space())
+                                .into_iter()
+                                .map(|item| InlineItem::Text(child.clone(), item)),
+                        );
                     }
-                    Some(NodeData::Element(_)) => {
+                    Some(NodeData::Element(element)) => {
+                        if element.tag_name() == "br" {
+                            collapser.line_break();
+                            word_start = true;
+                        } else if child.is_atomic_inline() {
+                            collapser.atomic_inline();
+                        }
                         self.layout_dimension_box(context, child.clone());
-                        line_box_builder.add_box_fragment(context, child.clone());
+                        items.push(InlineItem::Box(child.clone()));
                     }
                     _ => {}
                 },
                 _ => {
                     self.layout_dimension_box(context, child.clone());
-                    line_box_builder.add_box_fragment(context, child.clone());
+                    items.push(InlineItem::Box(child.clone()));
                 }
             }
         }
-        *layout_node.lines().borrow_mut() = line_box_builder.finish(context);
+        items
     }
 
     fn layout_dimension_box(&self, context: &mut LayoutContext, layout_node: LayoutBoxPtr) {
@@ -157,9 +233,10 @@ impl InlineFormattingContext {
             _ => return,
         };
 
-        let computed_width = node.get_style(&Property::Width);
-        let computed_margin_left = node.get_style(&Property::MarginLeft);
-        let computed_margin_right = node.get_style(&Property::MarginRight);
+        let style = node.computed_style();
+        let computed_width = style.width();
+        let computed_margin_left = style.margin_left();
+        let computed_margin_right = style.margin_right();
         let containing_width = containing_block.width;
 
         let mut used_width = computed_width.to_px(containing_width);
@@ -198,26 +275,15 @@ impl InlineFormattingContext {
         let mut box_model = layout_node.box_model.borrow_mut();
 
         if let Some(node) = node {
-            let margin_top = node
-                .get_style(&Property::MarginTop)
-                .to_px(containing_block.width);
-            let margin_bottom = node
-                .get_style(&Property::MarginBottom)
-                .to_px(containing_block.width);
-
-            let border_top = node
-                .get_style(&Property::BorderTopWidth)
-                .to_px(containing_block.width);
-            let border_bottom = node
-                .get_style(&Property::BorderBottomWidth)
-                .to_px(containing_block.width);
-
-            let padding_top = node
-                .get_style(&Property::PaddingTop)
-                .to_px(containing_block.width);
-            let padding_bottom = node
-                .get_style(&Property::PaddingBottom)
-                .to_px(containing_block.width);
+            let style = node.computed_style();
+            let margin_top = style.margin_top().to_px(containing_block.width);
+            let margin_bottom = style.margin_bottom().to_px(containing_block.width);
+
+            let border_top = style.border_top_width().to_px();
+            let border_bottom = style.border_bottom_width().to_px();
+
+            let padding_top = style.padding_top().to_px(containing_block.width);
+            let padding_bottom = style.padding_bottom().to_px(containing_block.width);
 
             box_model.set(BoxComponent::Margin, Edge::Top, margin_top);
             box_model.set(BoxComponent::Margin, Edge::Bottom, margin_bottom);
@@ -234,9 +300,10 @@ impl InlineFormattingContext {
 #[cfg(test)]
 mod tests {
     use shared::primitive::{Rect, Size};
-    use test_utils::dom_creator::{document, element};
+    use test_utils::dom_creator::{document, element, text};
 
     use crate::{
+        flow::line_box::LineFragmentData,
         formatting_context::{establish_context, FormattingContextType},
         layout_box::LayoutBoxPtr,
         layout_context::LayoutContext,
@@ -364,4 +431,510 @@ mod tests {
             Some(3)
         );
     }
+
+    #[test]
+    fn test_text_properties() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![
+                element(
+                    "span#a",
+                    document.clone(),
+                    vec![text("ab cd", document.clone())],
+                ),
+                element(
+                    "span#b",
+                    document.clone(),
+                    vec![text("ef", document.clone())],
+                ),
+                element(
+                    "span#c",
+                    document.clone(),
+                    vec![text("gh ij", document.clone())],
+                ),
+            ],
+        );
+        let css = format!(
+            "{} div {{ line-height: 30px; text-transform: capitalize; }} \
+             #a {{ word-spacing: 5px; }} #b {{ letter-spacing: 2px; }} \
+             #c {{ text-transform: uppercase; }}",
+            SHARED_CSS
+        );
+
+        let root = build_tree(dom, &css);
+        root.set_content_width(500.);
+        let mut layout_context = LayoutContext {
+            viewport: Rect::new(0., 0., 500., 300.),
+            measure_text_fn: Box::new(|content, _| {
+                Size::new(10. * content.chars().count() as f32, 10.)
+            }),
+        };
+        establish_context(FormattingContextType::InlineFormattingContext, root.clone());
+        root.formatting_context()
+            .run(&mut layout_context, root.clone());
+
+        let lines = root.lines().borrow();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].size.height, 30.);
+        assert_eq!(root.content_size().height, 30.);
+
+        let text_fragments = lines[0]
+            .fragments
+            .iter()
+            .filter(|fragment| matches!(fragment.data, LineFragmentData::Text(..)))
+            .collect::<Vec<_>>();
+        let fragments = text_fragments
+            .iter()
+            .map(|fragment| match &fragment.data {
+                LineFragmentData::Text(_, content) => {
+                    (content.as_str(), fragment.offset.x, fragment.size.width)
+                }
+                LineFragmentData::Box(_) => unreachable!(),
+            })
+            .collect::<Vec<(&str, f32, f32)>>();
+        assert_eq!(
+            fragments,
+            vec![
+                // the words are positioned one by one with word spacing
+                ("Ab", 0., 20.),
+                (" ", 20., 15.),
+                ("Cd", 35., 20.),
+                // and the letters with letter spacing, the word continuing
+                // across the spans
+                ("e", 55., 12.),
+                ("f", 67., 12.),
+                // no space is added between adjacent spans nor at the end
+                ("GH IJ", 79., 50.),
+            ]
+        );
+        // the text is centered in the line height
+        assert!(text_fragments
+            .iter()
+            .all(|fragment| fragment.offset.y == 10.));
+    }
+
+    /// Lay out the inline content of the root in a line of `width` with
+    /// characters of 10px, & return the text fragments of every line
+    fn layout_text(root: LayoutBoxPtr, width: f32) -> Vec<Vec<(String, f32, f32)>> {
+        root.set_content_width(width);
+        let mut layout_context = LayoutContext {
+            viewport: Rect::new(0., 0., 500., 300.),
+            measure_text_fn: Box::new(|content, _| {
+                Size::new(10. * content.chars().count() as f32, 10.)
+            }),
+        };
+        establish_context(FormattingContextType::InlineFormattingContext, root.clone());
+        root.formatting_context()
+            .run(&mut layout_context, root.clone());
+
+        let lines = root.lines().borrow();
+        lines
+            .iter()
+            .map(|line| {
+                line.fragments
+                    .iter()
+                    .filter_map(|fragment| match &fragment.data {
+                        LineFragmentData::Text(_, content) => {
+                            Some((content.clone(), fragment.offset.x, fragment.size.width))
+                        }
+                        LineFragmentData::Box(_) => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_white_space() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![
+                text(" one\n  two ", document.clone()),
+                element(
+                    "span#pre",
+                    document.clone(),
+                    vec![text("a\tb\nc  ", document.clone())],
+                ),
+                text("  end\n", document.clone()),
+            ],
+        );
+        let css = format!("{} #pre {{ white-space: pre; tab-size: 4; }}", SHARED_CSS);
+        let root = build_tree(dom, &css);
+
+        assert_eq!(
+            layout_text(root.clone(), 500.),
+            vec![
+                vec![
+                    // spaces collapse & segment breaks are transformed to spaces
+                    fragment("one two", 0., 70.),
+                    fragment(" ", 70., 10.),
+                    fragment("a", 80., 10.),
+                    // up to the next tab stop
+                    fragment("\t", 90., 30.),
+                    fragment("b", 120., 10.),
+                ],
+                // preserved segment breaks are forced line breaks
+                vec![fragment("c  ", 0., 30.), fragment(" end", 30., 40.)],
+            ]
+        );
+        assert_eq!(root.content_size().height, 2. * 19.2);
+    }
+
+    #[test]
+    fn test_white_space_wrapping() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![
+                text("aaa bbb   ccc ", document.clone()),
+                element(
+                    "span#nowrap",
+                    document.clone(),
+                    vec![text("ddd eee", document.clone())],
+                ),
+                element(
+                    "span#pre-wrap",
+                    document.clone(),
+                    vec![text(" fff   ", document.clone())],
+                ),
+            ],
+        );
+        let css = format!(
+            "{} #nowrap {{ white-space: nowrap; }} #pre-wrap {{ white-space: pre-wrap; }}",
+            SHARED_CSS
+        );
+        let root = build_tree(dom, &css);
+
+        assert_eq!(
+            layout_text(root, 50.),
+            vec![
+                // the spaces at the end of the lines are removed
+                vec![fragment("aaa", 0., 30.)],
+                vec![fragment("bbb", 0., 30.)],
+                vec![fragment("ccc", 0., 30.)],
+                // text that doesn't wrap overflows the line, & preserved
+                // spaces hang at the end of the line
+                vec![fragment("ddd eee", 0., 70.), fragment(" ", 70., 10.)],
+                vec![fragment("fff   ", 0., 60.)],
+            ]
+        );
+    }
+
+    /// A text fragment of a line, at `x` & `width` wide
+    fn fragment(content: &str, x: f32, width: f32) -> (String, f32, f32) {
+        (content.to_string(), x, width)
+    }
+
+    /// Lay out a div with the text & the white space processing given by
+    /// `css`
+    fn layout_div_text(content: &str, css: &str, width: f32) -> Vec<Vec<(String, f32, f32)>> {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![text(content, document.clone())],
+        );
+        let css = format!("{} div {{ {} }}", SHARED_CSS, css);
+        layout_text(build_tree(dom, &css), width)
+    }
+
+    #[test]
+    fn test_white_space_values() {
+        let content = "aaa   bbb\n  ccc";
+
+        // spaces collapse & lines wrap
+        assert_eq!(
+            layout_div_text(content, "white-space: normal;", 70.),
+            vec![
+                vec![fragment("aaa bbb", 0., 70.)],
+                vec![fragment("ccc", 0., 30.)]
+            ]
+        );
+        assert_eq!(
+            layout_div_text(content, "white-space: nowrap;", 50.),
+            vec![vec![fragment("aaa bbb ccc", 0., 110.)]]
+        );
+        // segment breaks are kept
+        assert_eq!(
+            layout_div_text(content, "white-space: pre-line;", 70.),
+            vec![
+                vec![fragment("aaa bbb", 0., 70.)],
+                vec![fragment("ccc", 0., 30.)]
+            ]
+        );
+        // spaces are kept & lines only break at segment breaks
+        assert_eq!(
+            layout_div_text(content, "white-space: pre;", 50.),
+            vec![
+                vec![fragment("aaa   bbb", 0., 90.)],
+                vec![fragment("  ccc", 0., 50.)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_white_space_wrapping_preserved_spaces() {
+        // preserved spaces hang at the end of the line
+        assert_eq!(
+            layout_div_text("aaa    bbb", "white-space: pre-wrap;", 50.),
+            vec![
+                vec![fragment("aaa    ", 0., 70.)],
+                vec![fragment("bbb", 0., 30.)]
+            ]
+        );
+        // the line can break after every space
+        assert_eq!(
+            layout_div_text("aaa    bbb", "white-space: break-spaces;", 50.),
+            vec![
+                vec![fragment("aaa  ", 0., 50.)],
+                vec![fragment("  bbb", 0., 50.)]
+            ]
+        );
+        assert_eq!(
+            layout_div_text("aa bb", "white-space: break-spaces;", 30.),
+            vec![
+                vec![fragment("aa ", 0., 30.)],
+                vec![fragment("bb", 0., 20.)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tab_size() {
+        let tab = |content: &str, tab_size: &str| {
+            let css = format!("white-space: pre; tab-size: {};", tab_size);
+            layout_div_text(content, &css, 500.)
+        };
+
+        // a number of spaces, 8 by default
+        assert_eq!(
+            tab("\t\tb", "initial"),
+            vec![vec![
+                fragment("\t", 0., 80.),
+                fragment("\t", 80., 80.),
+                fragment("b", 160., 10.)
+            ]]
+        );
+        assert_eq!(
+            tab("aaaaa\tb", "3"),
+            vec![vec![
+                fragment("aaaaa", 0., 50.),
+                fragment("\t", 50., 10.),
+                fragment("b", 60., 10.)
+            ]]
+        );
+        // tab stops are relative to the start of the line
+        assert_eq!(
+            tab("aa\tb\tc", "25px"),
+            vec![vec![
+                fragment("aa", 0., 20.),
+                fragment("\t", 20., 5.),
+                fragment("b", 25., 10.),
+                fragment("\t", 35., 15.),
+                fragment("c", 50., 10.)
+            ]]
+        );
+        // a tab stop closer than half of a `ch` is skipped
+        assert_eq!(
+            tab("aa\tb", "21px"),
+            vec![vec![
+                fragment("aa", 0., 20.),
+                fragment("\t", 20., 22.),
+                fragment("b", 42., 10.)
+            ]]
+        );
+        assert_eq!(
+            tab("a\tb", "0"),
+            vec![vec![
+                fragment("a", 0., 10.),
+                fragment("\t", 10., 0.),
+                fragment("b", 10., 10.)
+            ]]
+        );
+        // tabs collapse into spaces when spaces collapse
+        assert_eq!(
+            layout_div_text("a\t\tb", "tab-size: 4;", 500.),
+            vec![vec![fragment("a b", 0., 30.)]]
+        );
+    }
+
+    #[test]
+    fn test_line_breaking() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![
+                text("a/bbb/cc 漢字漢字 ", document.clone()),
+                element(
+                    "span#anywhere",
+                    document.clone(),
+                    vec![text("xxxxxxx", document.clone())],
+                ),
+                text(" hy\u{ad}phen", document.clone()),
+            ],
+        );
+        let css = format!("{} #anywhere {{ overflow-wrap: anywhere; }}", SHARED_CSS);
+        let root = build_tree(dom, &css);
+
+        assert_eq!(
+            layout_text(root, 50.),
+            vec![
+                // after the slashes of URLs & between ideographs
+                vec![fragment("a/", 0., 20.)],
+                vec![fragment("bbb/", 0., 40.)],
+                vec![fragment("cc 漢字", 0., 50.)],
+                vec![fragment("漢字", 0., 20.)],
+                // a word that overflows the line breaks anywhere
+                vec![fragment("xxxxx", 0., 50.)],
+                // a hyphen is shown where the line breaks at a soft hyphen
+                vec![fragment("xx", 0., 20.)],
+                vec![fragment("hy-", 0., 30.)],
+                vec![fragment("phen", 0., 40.)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overflow_wrap() {
+        let content = "aa bbbbbbb c";
+
+        // a word that overflows the line only breaks if `overflow-wrap` allows it
+        assert_eq!(
+            layout_div_text(content, "overflow-wrap: normal;", 50.),
+            vec![
+                vec![fragment("aa", 0., 20.)],
+                vec![fragment("bbbbbbb", 0., 70.)],
+                vec![fragment("c", 0., 10.)]
+            ]
+        );
+        let broken = vec![
+            vec![fragment("aa", 0., 20.)],
+            vec![fragment("bbbbb", 0., 50.)],
+            vec![fragment("bb c", 0., 40.)],
+        ];
+        assert_eq!(
+            layout_div_text(content, "overflow-wrap: break-word;", 50.),
+            broken
+        );
+        assert_eq!(
+            layout_div_text(content, "overflow-wrap: anywhere;", 50.),
+            broken
+        );
+        assert_eq!(
+            layout_div_text(content, "word-break: break-word;", 50.),
+            broken
+        );
+        // unless the text doesn't wrap
+        assert_eq!(
+            layout_div_text(
+                content,
+                "overflow-wrap: anywhere; white-space: nowrap;",
+                50.
+            ),
+            vec![vec![fragment("aa bbbbbbb c", 0., 120.)]]
+        );
+    }
+
+    #[test]
+    fn test_word_break() {
+        // between any two letters
+        assert_eq!(
+            layout_div_text("aa bbbbbbb", "word-break: break-all;", 50.),
+            vec![
+                vec![fragment("aa bb", 0., 50.)],
+                vec![fragment("bbbbb", 0., 50.)]
+            ]
+        );
+        // not between ideographs, but still at spaces & punctuation
+        assert_eq!(
+            layout_div_text("漢字漢字 漢字、漢字", "word-break: keep-all;", 40.),
+            vec![
+                vec![fragment("漢字漢字", 0., 40.)],
+                vec![fragment("漢字、", 0., 30.)],
+                vec![fragment("漢字", 0., 20.)]
+            ]
+        );
+        assert_eq!(
+            layout_div_text("漢字漢字 漢字、漢字", "word-break: normal;", 40.),
+            vec![
+                vec![fragment("漢字漢字", 0., 40.)],
+                vec![fragment("漢字、漢", 0., 40.)],
+                vec![fragment("字", 0., 10.)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hyphens() {
+        let content = "aa hy\u{ad}phen";
+
+        // a soft hyphen isn't shown where the line doesn't break
+        assert_eq!(
+            layout_div_text(content, "hyphens: manual;", 500.),
+            vec![vec![fragment("aa hyphen", 0., 90.)]]
+        );
+        assert_eq!(
+            layout_div_text(content, "hyphens: manual;", 60.),
+            vec![
+                vec![fragment("aa hy-", 0., 60.)],
+                vec![fragment("phen", 0., 40.)]
+            ]
+        );
+        // the line breaks before a segment that fits without its hyphen only
+        assert_eq!(
+            layout_div_text(content, "hyphens: manual;", 50.),
+            vec![
+                vec![fragment("aa", 0., 20.)],
+                vec![fragment("hy-", 0., 30.)],
+                vec![fragment("phen", 0., 40.)]
+            ]
+        );
+        // the line doesn't break at soft hyphens
+        assert_eq!(
+            layout_div_text(content, "hyphens: none;", 60.),
+            vec![
+                vec![fragment("aa", 0., 20.)],
+                vec![fragment("hyphen", 0., 60.)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_capitalize_across_text_runs() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![
+                text("ab", document.clone()),
+                element(
+                    "span",
+                    document.clone(),
+                    vec![text("cd ef", document.clone())],
+                ),
+                element("br", document.clone(), vec![]),
+                text("gh", document.clone()),
+            ],
+        );
+        let css = format!(
+            "{} div {{ text-transform: capitalize; }} br {{ display: inline; }}",
+            SHARED_CSS
+        );
+
+        assert_eq!(
+            layout_text(build_tree(dom, &css), 500.),
+            vec![
+                // the word continues in the span
+                vec![fragment("Ab", 0., 20.), fragment("cd Ef", 20., 50.)],
+                // a line break starts a word
+                vec![fragment("Gh", 0., 20.)]
+            ]
+        );
+    }
 }
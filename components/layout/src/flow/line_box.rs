@@ -1,9 +1,14 @@
 use std::rc::Rc;
 
 use shared::primitive::{Point, Size};
-use style_types::{values::prelude::TextAlign, Property, Value};
+use style_types::values::prelude::{OverflowWrap, TextAlign, WhiteSpace, WordBreak};
 
-use crate::{layout_box::LayoutBoxPtr, layout_context::LayoutContext};
+use super::line_break::SOFT_HYPHEN;
+use crate::{
+    font::{font_description, font_metrics, line_height},
+    layout_box::LayoutBoxPtr,
+    layout_context::LayoutContext,
+};
 
 #[derive(Debug)]
 pub struct LineFragment {
@@ -22,6 +27,26 @@ pub struct LineBoxBuilder {
     line_boxes: Vec<LineBox>,
     parent: LayoutBoxPtr,
     current_offset_y: f32,
+    /// A collapsible space that is only placed once the next fragment is
+    /// placed on the same line
+    pending_space: Option<TextRuns>,
+    /// The hyphen that is shown if the line breaks after the last placed
+    /// text, that ends with a soft hyphen
+    pending_hyphen: Option<TextRuns>,
+}
+
+/// The runs that a text is laid out in, with their sizes
+struct TextRuns {
+    layout_box: LayoutBoxPtr,
+    runs: Vec<(String, Size)>,
+    /// Whether the runs can be merged with the text before them
+    merge: bool,
+}
+
+impl TextRuns {
+    fn width(&self) -> f32 {
+        self.runs.iter().map(|(_, size)| size.width).sum()
+    }
 }
 
 #[derive(Debug)]
@@ -57,35 +82,42 @@ impl LineBox {
         self.size.height = f32::max(self.size.height, fragment_height);
     }
 
+    /// Add a run of text. The text is centered vertically in the line
+    /// height of its box, the space left being the half-leading above &
+    /// below it. Consecutive runs of a box are merged if `merge` is set.
     pub fn add_text_fragment(
         &mut self,
-        fragment_width: f32,
-        fragment_height: f32,
+        fragment_size: Size,
+        line_height: f32,
         layout_box: LayoutBoxPtr,
         text: String,
+        merge: bool,
     ) {
-        if !self.fragments.is_empty() {
+        let fragment_width = fragment_size.width;
+        self.size.height = f32::max(self.size.height, line_height);
+
+        if merge && !self.fragments.is_empty() {
             let last_fragment = self.fragments.last_mut().unwrap();
 
             if let LineFragmentData::Text(last_box, ref mut content) = &mut last_fragment.data {
-                if Rc::ptr_eq(last_box, &layout_box) {
+                // the text after a tab starts at the tab stop
+                if Rc::ptr_eq(last_box, &layout_box) && !content.ends_with('\t') {
                     content.push_str(&text);
                     last_fragment.size.width += fragment_width;
                     self.size.width += fragment_width;
-                    self.size.height = f32::max(self.size.height, fragment_height);
                     return;
                 }
             }
         }
+        let half_leading = (line_height - fragment_size.height) / 2.;
         let fragment = LineFragment::new_text(
             layout_box,
             text,
-            Point::new(self.size.width, 0.),
-            Size::new(fragment_width, fragment_height),
+            Point::new(self.size.width, half_leading),
+            fragment_size,
         );
         self.fragments.push(fragment);
         self.size.width += fragment_width;
-        self.size.height = f32::max(self.size.height, fragment_height);
     }
 
     pub fn dump(&self, level: usize) -> String {
@@ -150,20 +182,28 @@ impl LineBoxBuilder {
             line_boxes: Vec::new(),
             parent,
             current_offset_y: 0.,
+            pending_space: None,
+            pending_hyphen: None,
         }
     }
 
-    pub fn finish(mut self, context: &mut LayoutContext) -> Vec<LineBox> {
-        self.update_last_line(context);
+    pub fn finish(mut self) -> Vec<LineBox> {
+        self.pending_space = None;
+
+        // a forced line break at the end doesn't start another line
+        if self.line_boxes.len() > 1 && self.at_line_start() {
+            self.line_boxes.pop();
+        } else {
+            self.update_last_line();
+        }
         self.line_boxes
     }
 
-    pub fn add_box_fragment(&mut self, context: &mut LayoutContext, layout_box: LayoutBoxPtr) {
+    pub fn add_box_fragment(&mut self, layout_box: LayoutBoxPtr) {
         if let Some(node) = layout_box.node() {
             if let Some(element) = node.as_element_opt() {
                 if element.tag_name() == "br" {
-                    self.break_line(context);
-                    self.update_last_line(context);
+                    self.force_line_break();
                     return;
                 }
             }
@@ -171,44 +211,257 @@ impl LineBoxBuilder {
 
         let fragment_width = layout_box.content_size().width;
         let fragment_height = layout_box.content_size().height;
-        self.break_line_if_needed(context, layout_box.margin_box_width());
+        // the boundary of an inline box isn't a place where the line breaks
+        let is_inline_box =
+            !layout_box.is_anonymous() && layout_box.is_inline() && !layout_box.is_atomic_inline();
+        if !is_inline_box {
+            self.place_pending_space(layout_box.margin_box_width(), true, true);
+            self.pending_hyphen = None;
+        }
 
         self.current_line()
             .add_box_fragment(fragment_width, fragment_height, layout_box);
     }
 
+    /// Add a segment of a word. The line can only break before it at a
+    /// soft wrap opportunity of text that wraps, or anywhere in the segment
+    /// if it overflows the line & `overflow-wrap` allows it.
+    /// https://www.w3.org/TR/css-text-3/#overflow-wrap-property
     pub fn add_text_fragment(
         &mut self,
         context: &mut LayoutContext,
         layout_box: LayoutBoxPtr,
         text: String,
+        break_before: bool,
     ) {
         let node = layout_box.node().unwrap();
-        let font_size = node.get_style(&Property::FontSize).to_absolute_px();
-        let text_size = context.measure_text(&text, font_size);
-        let fragment_width = text_size.width;
-        let fragment_height = text_size.height;
-        self.break_line_if_needed(context, fragment_width);
-        self.current_line()
-            .add_text_fragment(fragment_width, fragment_height, layout_box, text);
+        let style = node.computed_style();
+        let wraps = style.white_space().wraps();
+        let breaks_anywhere = *style.overflow_wrap() != OverflowWrap::Normal
+            || *style.word_break() == WordBreak::BreakWord;
+
+        // soft hyphens are only shown as a hyphen at the end of a line
+        let hyphenated = text.ends_with(SOFT_HYPHEN);
+        let text = text.replace(SOFT_HYPHEN, "");
+
+        let runs = self.measure_text(context, layout_box.clone(), text.clone());
+        let hyphen =
+            hyphenated.then(|| self.measure_text(context, layout_box.clone(), '-'.to_string()));
+        // the hyphen must fit too if the line breaks after the segment
+        let fit_width = runs.width() + hyphen.as_ref().map_or(0., TextRuns::width);
+        self.place_pending_space(fit_width, break_before, wraps);
+        self.pending_hyphen = None;
+
+        if wraps && breaks_anywhere && !self.fits(fit_width) {
+            for c in text.chars() {
+                let runs = self.measure_text(context, layout_box.clone(), c.to_string());
+                self.break_line_if_needed(runs.width());
+                self.place_runs(runs);
+            }
+        } else {
+            self.place_runs(runs);
+        }
+
+        self.pending_hyphen = hyphen;
     }
 
-    fn break_line_if_needed(&mut self, context: &mut LayoutContext, next_fragment_width: f32) {
-        if self.line_boxes.is_empty() {
+    /// Add a collapsible space. It's removed if it ends up at the start or
+    /// at the end of a line.
+    /// https://www.w3.org/TR/css-text-3/#white-space-phase-2
+    pub fn add_collapsible_space(&mut self, context: &mut LayoutContext, layout_box: LayoutBoxPtr) {
+        self.pending_hyphen = None;
+        self.pending_space = Some(self.measure_text(context, layout_box, ' '.to_string()));
+    }
+
+    /// Add preserved spaces. They hang at the end of the line instead of
+    /// breaking it, unless the text breaks spaces, then the line can break
+    /// after every space.
+    pub fn add_preserved_spaces(
+        &mut self,
+        context: &mut LayoutContext,
+        layout_box: LayoutBoxPtr,
+        spaces: String,
+    ) {
+        let breaks_spaces =
+            *layout_box.node().unwrap().computed_style().white_space() == WhiteSpace::BreakSpaces;
+        let spaces = if breaks_spaces {
+            spaces.chars().map(String::from).collect()
+        } else {
+            vec![spaces]
+        };
+
+        for spaces in spaces {
+            let runs = self.measure_text(context, layout_box.clone(), spaces);
+            self.place_pending_space(runs.width(), breaks_spaces, true);
+            self.pending_hyphen = None;
+            self.place_runs(runs);
+        }
+    }
+
+    /// Add a preserved tab, that spans up to the next tab stop. Tab stops
+    /// are every `tab-size` from the start of the line, the next one being
+    /// skipped if it's closer than half of a `ch`.
+    /// https://www.w3.org/TR/css-text-3/#tab-size-property
+    pub fn add_tab(&mut self, context: &mut LayoutContext, layout_box: LayoutBoxPtr) {
+        self.place_pending_space(0., false, false);
+        self.pending_hyphen = None;
+
+        let node = layout_box.node().unwrap();
+        let style = node.computed_style();
+        let space_size = context.measure_text(" ", &font_description(&node));
+        let space_advance =
+            space_size.width + style.letter_spacing().to_px() + style.word_spacing().to_px();
+        let interval = style.tab_size().to_px(space_advance);
+
+        let line_width = self.current_line().size.width;
+        let width = if interval > 0. {
+            let mut tab_stop = ((line_width / interval).floor() + 1.) * interval;
+            if tab_stop - line_width < font_metrics(&node).zero_advance / 2. {
+                tab_stop += interval;
+            }
+            tab_stop - line_width
+        } else {
+            0.
+        };
+
+        let line_height = line_height(&node);
+        self.current_line().add_text_fragment(
+            Size::new(width, space_size.height),
+            line_height,
+            layout_box,
+            '\t'.to_string(),
+            false,
+        );
+    }
+
+    /// End the line, for a `<br>` or a preserved segment break
+    pub fn force_line_break(&mut self) {
+        self.pending_space = None;
+        self.pending_hyphen = None;
+        // the line is empty if the break is at its start
+        self.current_line();
+        self.break_line();
+    }
+
+    /// Measure the runs of a text. Spaced text is split in runs that are
+    /// painted where they are laid out: every letter with letter spacing,
+    /// otherwise every word.
+    fn measure_text(
+        &self,
+        context: &mut LayoutContext,
+        layout_box: LayoutBoxPtr,
+        text: String,
+    ) -> TextRuns {
+        let node = layout_box.node().unwrap();
+        let style = node.computed_style();
+        let font = font_description(&node);
+        let letter_spacing = style.letter_spacing().to_px();
+        let word_spacing = style.word_spacing().to_px();
+
+        if letter_spacing == 0. && word_spacing == 0. {
+            let text_size = context.measure_text(&text, &font);
+            return TextRuns {
+                layout_box: layout_box.clone(),
+                runs: vec![(text, text_size)],
+                merge: true,
+            };
+        }
+
+        let runs = if letter_spacing != 0. {
+            text.chars().map(String::from).collect()
+        } else {
+            vec![text]
+        };
+        let runs = runs
+            .into_iter()
+            .map(|run| {
+                let mut size = context.measure_text(&run, &font);
+                size.width += letter_spacing * run.chars().count() as f32
+                    + word_spacing * run.matches(' ').count() as f32;
+                (run, size)
+            })
+            .collect::<Vec<(String, Size)>>();
+
+        TextRuns {
+            layout_box: layout_box.clone(),
+            runs,
+            merge: false,
+        }
+    }
+
+    fn place_runs(&mut self, text_runs: TextRuns) {
+        let line_height = line_height(&text_runs.layout_box.node().unwrap());
+        for (run, size) in text_runs.runs {
+            self.current_line().add_text_fragment(
+                size,
+                line_height,
+                text_runs.layout_box.clone(),
+                run,
+                text_runs.merge,
+            );
+        }
+    }
+
+    /// Place the pending collapsible space before the next fragment. If
+    /// both don't fit & the line can break before the fragment, it breaks
+    /// after the space, that is removed, as long as the text of the space
+    /// wraps. Without a pending space, the line breaks before the fragment
+    /// if it can & `wraps` is set.
+    fn place_pending_space(&mut self, next_fragment_width: f32, break_before: bool, wraps: bool) {
+        let pending_space = match self.pending_space.take() {
+            Some(pending_space) => pending_space,
+            None => {
+                if break_before && wraps {
+                    self.break_line_if_needed(next_fragment_width);
+                }
+                return;
+            }
+        };
+
+        if self.at_line_start() {
             return;
         }
-        let parent_width = self.parent.content_size().width;
-        let new_line_box_width = self.current_line().size.width + next_fragment_width;
 
-        let should_break = new_line_box_width > parent_width;
+        let space_wraps = pending_space
+            .layout_box
+            .node()
+            .unwrap()
+            .computed_style()
+            .white_space()
+            .wraps();
+        if break_before && space_wraps && !self.fits(pending_space.width() + next_fragment_width) {
+            self.wrap_line();
+            return;
+        }
+        self.place_runs(pending_space);
+    }
+
+    /// Whether a fragment fits in the space left on the current line
+    fn fits(&mut self, fragment_width: f32) -> bool {
+        self.current_line().size.width + fragment_width <= self.parent.content_size().width
+    }
 
-        if should_break {
-            self.break_line(context);
+    fn break_line_if_needed(&mut self, next_fragment_width: f32) {
+        // a fragment that doesn't fit on an empty line overflows it
+        if self.at_line_start() {
+            return;
         }
+        if !self.fits(next_fragment_width) {
+            self.wrap_line();
+        }
+    }
+
+    /// Break the line at a soft wrap opportunity. A hyphen is shown at the
+    /// end of the line if it breaks after a soft hyphen.
+    fn wrap_line(&mut self) {
+        if let Some(hyphen) = self.pending_hyphen.take() {
+            self.place_runs(hyphen);
+        }
+        self.break_line();
     }
 
-    fn break_line(&mut self, context: &mut LayoutContext) {
-        self.update_last_line(context);
+    fn break_line(&mut self) {
+        self.update_last_line();
 
         if let Some(last_line) = self.line_boxes.last() {
             self.current_offset_y += last_line.size.height;
@@ -217,7 +470,7 @@ impl LineBoxBuilder {
         self.line_boxes.push(LineBox::new());
     }
 
-    fn update_last_line(&mut self, context: &mut LayoutContext) {
+    fn update_last_line(&mut self) {
         if self.line_boxes.is_empty() {
             return;
         }
@@ -226,14 +479,7 @@ impl LineBoxBuilder {
 
         if last_line.fragments.is_empty() {
             let parent = self.parent.get_non_anonymous_parent();
-            let font_size = parent
-                .node()
-                .unwrap()
-                .get_style(&Property::FontSize)
-                .to_absolute_px();
-            let text_size = context.measure_text("H", font_size);
-
-            last_line.size.height = text_size.height;
+            last_line.size.height = line_height(&parent.node().unwrap());
         }
 
         let mut x_offset = last_line
@@ -245,21 +491,27 @@ impl LineBoxBuilder {
         let remaining_space = self.parent.content_size().width - last_line.size.width;
 
         if let Some(node) = self.parent.node() {
-            match node.get_style(&Property::TextAlign) {
-                Value::TextAlign(TextAlign::Center) => {
-                    x_offset += remaining_space / 2.;
-                }
-                _ => {}
+            if *node.computed_style().text_align() == TextAlign::Center {
+                x_offset += remaining_space / 2.;
             }
         }
 
         for fragment in &mut last_line.fragments {
-            let mut used_offset = Point::new(fragment.offset.x, self.current_offset_y);
+            let mut used_offset =
+                Point::new(fragment.offset.x, self.current_offset_y + fragment.offset.y);
             used_offset.translate(x_offset, 0.);
             fragment.set_offset(used_offset);
         }
     }
 
+    /// Whether nothing is placed on the current line yet
+    fn at_line_start(&self) -> bool {
+        match self.line_boxes.last() {
+            Some(line) => line.fragments.is_empty(),
+            None => true,
+        }
+    }
+
     fn current_line(&mut self) -> &mut LineBox {
         if self.line_boxes.is_empty() {
             self.line_boxes.push(LineBox::new());
@@ -0,0 +1,169 @@
+use style_types::values::prelude::{Hyphens, WordBreak};
+use unicode_linebreak::linebreaks;
+
+/// A hyphen that is only shown if the line breaks after it
+pub const SOFT_HYPHEN: char = '\u{ad}';
+
+/// A part of a word between two of its soft wrap opportunities
+#[derive(Debug, PartialEq)]
+pub struct WordSegment {
+    pub text: String,
+    /// Whether the line can break before the segment
+    pub break_before: bool,
+}
+
+/// The soft wrap opportunities of the text of an inline formatting context,
+/// found with the Unicode line breaking algorithm. The text is processed
+/// as a whole as whether a line can break between two characters depends
+/// on the characters around them, even across inline boxes.
+/// https://www.unicode.org/reports/tr14/
+pub struct BreakOpportunities {
+    text: String,
+    /// Whether a line can break before the byte at every index of the text
+    breaks: Vec<bool>,
+}
+
+impl BreakOpportunities {
+    pub fn new(text: String) -> Self {
+        let mut breaks = vec![false; text.len() + 1];
+        for (index, _) in linebreaks(&text) {
+            breaks[index] = true;
+        }
+        Self { text, breaks }
+    }
+
+    /// Split the word that starts at `start` in the text at its soft wrap
+    /// opportunities, adjusted by the `word-break` & `hyphens` of its text
+    /// https://www.w3.org/TR/css-text-3/#word-break-property
+    pub fn segment_word(
+        &self,
+        start: usize,
+        word: &str,
+        word_break: &WordBreak,
+        hyphens: &Hyphens,
+    ) -> Vec<WordSegment> {
+        let mut segments = Vec::new();
+        let mut segment = WordSegment {
+            text: String::new(),
+            break_before: self.can_break_at(start, word_break, hyphens),
+        };
+
+        for (index, c) in word.char_indices() {
+            if index > 0 && self.can_break_at(start + index, word_break, hyphens) {
+                let next = WordSegment {
+                    text: String::new(),
+                    break_before: true,
+                };
+                segments.push(std::mem::replace(&mut segment, next));
+            }
+            segment.text.push(c);
+        }
+
+        segments.push(segment);
+        segments
+    }
+
+    fn can_break_at(&self, index: usize, word_break: &WordBreak, hyphens: &Hyphens) -> bool {
+        let before = self.text[..index].chars().next_back();
+        let after = self.text[index..].chars().next();
+        let (before, after) = match (before, after) {
+            (Some(before), Some(after)) => (before, after),
+            _ => return self.breaks[index],
+        };
+
+        if before == SOFT_HYPHEN && *hyphens == Hyphens::None {
+            return false;
+        }
+
+        // letters & numbers are what `word-break` considers a word
+        let within_word = before.is_alphanumeric() && after.is_alphanumeric();
+        match word_break {
+            WordBreak::BreakAll if within_word => true,
+            WordBreak::KeepAll if within_word => false,
+            _ => self.breaks[index],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(text: &str, word_break: WordBreak, hyphens: Hyphens) -> Vec<(String, bool)> {
+        let opportunities = BreakOpportunities::new(text.to_string());
+        opportunities
+            .segment_word(0, text, &word_break, &hyphens)
+            .into_iter()
+            .map(|segment| (segment.text, segment.break_before))
+            .collect()
+    }
+
+    fn segment(text: &str, break_before: bool) -> (String, bool) {
+        (text.to_string(), break_before)
+    }
+
+    #[test]
+    fn find_break_opportunities() {
+        // after hyphens & slashes, but not before closing punctuation
+        assert_eq!(
+            segments("well-known/path.", WordBreak::Normal, Hyphens::Manual),
+            vec![
+                segment("well-", false),
+                segment("known/", true),
+                segment("path.", true)
+            ]
+        );
+        // between ideographs & after zero width spaces
+        assert_eq!(
+            segments("漢字。ab\u{200b}cd", WordBreak::Normal, Hyphens::Manual),
+            vec![
+                segment("漢", false),
+                segment("字。", true),
+                segment("ab\u{200b}", true),
+                segment("cd", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn adjust_break_opportunities() {
+        assert_eq!(
+            segments("ab-c", WordBreak::BreakAll, Hyphens::Manual),
+            vec![segment("a", false), segment("b-", true), segment("c", true)]
+        );
+        assert_eq!(
+            segments("漢字", WordBreak::KeepAll, Hyphens::Manual),
+            vec![segment("漢字", false)]
+        );
+        assert_eq!(
+            segments("hy\u{ad}phen", WordBreak::Normal, Hyphens::Manual),
+            vec![segment("hy\u{ad}", false), segment("phen", true)]
+        );
+        assert_eq!(
+            segments("hy\u{ad}phen", WordBreak::Normal, Hyphens::None),
+            vec![segment("hy\u{ad}phen", false)]
+        );
+    }
+
+    #[test]
+    fn segment_words_of_the_text() {
+        let text = "see https://a.b/c?d=e";
+        let opportunities = BreakOpportunities::new(text.to_string());
+        let segments: Vec<_> = opportunities
+            .segment_word(4, &text[4..], &WordBreak::Normal, &Hyphens::Manual)
+            .into_iter()
+            .map(|segment| (segment.text, segment.break_before))
+            .collect();
+
+        // after the space before the word
+        assert_eq!(
+            segments,
+            vec![
+                segment("https://", true),
+                segment("a.b/", true),
+                segment("c?", true),
+                segment("d=e", true)
+            ]
+        );
+    }
+}
@@ -1,3 +1,5 @@
 pub mod block;
 pub mod inline;
 pub mod line_box;
+pub mod line_break;
+pub mod white_space;
@@ -0,0 +1,280 @@
+use style_types::values::prelude::WhiteSpace;
+
+/// A piece of the text of an inline formatting context once its white
+/// space is processed
+#[derive(Debug, PartialEq)]
+pub enum TextItem {
+    /// Text without spaces
+    Word(String),
+    /// A space that remains of a sequence of collapsible white space. It's
+    /// removed at the start & at the end of a line.
+    CollapsibleSpace,
+    /// Spaces that are kept as they are
+    PreservedSpaces(String),
+    /// A preserved tab, that moves the next text to the next tab stop
+    Tab,
+    /// A preserved segment break, that forces a line break
+    LineBreak,
+}
+
+/// Process the white space of the text runs of an inline formatting
+/// context. White space collapses across the boundaries of inline boxes,
+/// so the runs are processed in order with the same collapser.
+/// https://www.w3.org/TR/css-text-3/#white-space-phase-1
+pub struct WhiteSpaceCollapser {
+    /// Whether the last processed character is a collapsible space or the
+    /// start of a line, after which collapsible spaces are removed
+    after_collapsible_space: bool,
+}
+
+impl WhiteSpaceCollapser {
+    pub fn new() -> Self {
+        Self {
+            after_collapsible_space: true,
+        }
+    }
+
+    /// An atomic inline is placed after the processed text, spaces after
+    /// it aren't collapsed with the ones before it
+    pub fn atomic_inline(&mut self) {
+        self.after_collapsible_space = false;
+    }
+
+    /// A line break is forced after the processed text
+    pub fn line_break(&mut self) {
+        self.after_collapsible_space = true;
+    }
+
+    pub fn process(&mut self, text: &str, white_space: &WhiteSpace) -> Vec<TextItem> {
+        if white_space.collapses_spaces() {
+            self.process_collapsible(text, white_space.preserves_segment_breaks())
+        } else {
+            let items = process_preserved(text);
+            self.after_collapsible_space = items.last() == Some(&TextItem::LineBreak);
+            items
+        }
+    }
+
+    fn process_collapsible(&mut self, text: &str, preserve_segment_breaks: bool) -> Vec<TextItem> {
+        let mut items = Vec::new();
+        let mut word = String::new();
+
+        for c in text.chars() {
+            match c {
+                '\n' if preserve_segment_breaks => {
+                    flush_word(&mut word, &mut items);
+                    // the spaces before a segment break are removed
+                    if items.last() == Some(&TextItem::CollapsibleSpace) {
+                        items.pop();
+                    }
+                    items.push(TextItem::LineBreak);
+                    self.after_collapsible_space = true;
+                }
+                // segment breaks & tabs are transformed into spaces
+                ' ' | '\t' | '\n' | '\r' => {
+                    if !self.after_collapsible_space {
+                        flush_word(&mut word, &mut items);
+                        items.push(TextItem::CollapsibleSpace);
+                        self.after_collapsible_space = true;
+                    }
+                }
+                c => {
+                    word.push(c);
+                    self.after_collapsible_space = false;
+                }
+            }
+        }
+
+        flush_word(&mut word, &mut items);
+        items
+    }
+}
+
+impl Default for WhiteSpaceCollapser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn process_preserved(text: &str) -> Vec<TextItem> {
+    let mut items = Vec::new();
+    let mut word = String::new();
+    let mut spaces = String::new();
+
+    for c in text.chars() {
+        match c {
+            '\n' | '\t' => {
+                flush_word(&mut word, &mut items);
+                flush_spaces(&mut spaces, &mut items);
+                items.push(match c {
+                    '\n' => TextItem::LineBreak,
+                    _ => TextItem::Tab,
+                });
+            }
+            ' ' => {
+                flush_word(&mut word, &mut items);
+                spaces.push(c);
+            }
+            // a carriage return is rendered as a space
+            '\r' => {
+                flush_word(&mut word, &mut items);
+                spaces.push(' ');
+            }
+            c => {
+                flush_spaces(&mut spaces, &mut items);
+                word.push(c);
+            }
+        }
+    }
+
+    flush_word(&mut word, &mut items);
+    flush_spaces(&mut spaces, &mut items);
+    items
+}
+
+fn flush_word(word: &mut String, items: &mut Vec<TextItem>) {
+    if !word.is_empty() {
+        items.push(TextItem::Word(std::mem::take(word)));
+    }
+}
+
+fn flush_spaces(spaces: &mut String, items: &mut Vec<TextItem>) {
+    if !spaces.is_empty() {
+        items.push(TextItem::PreservedSpaces(std::mem::take(spaces)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str) -> TextItem {
+        TextItem::Word(text.to_string())
+    }
+
+    #[test]
+    fn collapse_white_space() {
+        let mut collapser = WhiteSpaceCollapser::new();
+        assert_eq!(
+            collapser.process("  a \t b\n\nc ", &WhiteSpace::Normal),
+            vec![
+                word("a"),
+                TextItem::CollapsibleSpace,
+                word("b"),
+                TextItem::CollapsibleSpace,
+                word("c"),
+                TextItem::CollapsibleSpace,
+            ]
+        );
+        // across the boundaries of inline boxes
+        assert_eq!(
+            collapser.process(" d", &WhiteSpace::Nowrap),
+            vec![word("d")]
+        );
+        collapser.atomic_inline();
+        assert_eq!(
+            collapser.process(" e", &WhiteSpace::Normal),
+            vec![TextItem::CollapsibleSpace, word("e")]
+        );
+    }
+
+    #[test]
+    fn preserve_segment_breaks() {
+        let mut collapser = WhiteSpaceCollapser::new();
+        assert_eq!(
+            collapser.process("a  \n  b\n", &WhiteSpace::PreLine),
+            vec![
+                word("a"),
+                TextItem::LineBreak,
+                word("b"),
+                TextItem::LineBreak
+            ]
+        );
+        assert_eq!(
+            collapser.process("\tc  d\n", &WhiteSpace::Pre),
+            vec![
+                TextItem::Tab,
+                word("c"),
+                TextItem::PreservedSpaces("  ".to_string()),
+                word("d"),
+                TextItem::LineBreak,
+            ]
+        );
+        assert_eq!(
+            collapser.process(" e", &WhiteSpace::Normal),
+            vec![word("e")]
+        );
+        // preserved spaces don't collapse with the next ones
+        assert_eq!(
+            collapser.process("f ", &WhiteSpace::PreWrap),
+            vec![word("f"), TextItem::PreservedSpaces(" ".to_string())]
+        );
+        assert_eq!(
+            collapser.process(" g", &WhiteSpace::Normal),
+            vec![TextItem::CollapsibleSpace, word("g")]
+        );
+    }
+
+    #[test]
+    fn process_white_space_values() {
+        let process = |white_space: WhiteSpace| {
+            WhiteSpaceCollapser::new().process(" a \t b\r\n c ", &white_space)
+        };
+        let spaces = |spaces: &str| TextItem::PreservedSpaces(spaces.to_string());
+
+        let collapsed = vec![
+            word("a"),
+            TextItem::CollapsibleSpace,
+            word("b"),
+            TextItem::CollapsibleSpace,
+            word("c"),
+            TextItem::CollapsibleSpace,
+        ];
+        assert_eq!(process(WhiteSpace::Normal), collapsed);
+        assert_eq!(process(WhiteSpace::Nowrap), collapsed);
+        assert_eq!(
+            process(WhiteSpace::PreLine),
+            vec![
+                word("a"),
+                TextItem::CollapsibleSpace,
+                word("b"),
+                TextItem::LineBreak,
+                word("c"),
+                TextItem::CollapsibleSpace,
+            ]
+        );
+
+        // a carriage return is kept as a space
+        let preserved = vec![
+            spaces(" "),
+            word("a"),
+            spaces(" "),
+            TextItem::Tab,
+            spaces(" "),
+            word("b"),
+            spaces(" "),
+            TextItem::LineBreak,
+            spaces(" "),
+            word("c"),
+            spaces(" "),
+        ];
+        assert_eq!(process(WhiteSpace::Pre), preserved);
+        assert_eq!(process(WhiteSpace::PreWrap), preserved);
+        assert_eq!(process(WhiteSpace::BreakSpaces), preserved);
+    }
+
+    #[test]
+    fn remove_spaces_after_line_breaks() {
+        let mut collapser = WhiteSpaceCollapser::new();
+        assert_eq!(
+            collapser.process("a ", &WhiteSpace::Normal),
+            vec![word("a"), TextItem::CollapsibleSpace]
+        );
+        // e.g. after a `<br>`
+        collapser.line_break();
+        assert_eq!(
+            collapser.process(" b", &WhiteSpace::Normal),
+            vec![word("b")]
+        );
+    }
+}
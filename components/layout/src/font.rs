@@ -0,0 +1,30 @@
+use dom::node::NodePtr;
+use shared::font::{FontDescription, FontMetrics};
+
+/// The font that the text of the node is rendered with
+pub fn font_description(node: &NodePtr) -> FontDescription {
+    style::font::font_description(&node.computed_style())
+}
+
+/// The metrics of the font that the text of the node is rendered with
+pub fn font_metrics(node: &NodePtr) -> FontMetrics {
+    let font = font_description(node);
+    let document = node.owner_document().map(NodePtr);
+    match document
+        .as_ref()
+        .and_then(|document| document.as_document_opt())
+    {
+        Some(document) => document.font_metrics(&font),
+        None => FontMetrics::fallback(font.size),
+    }
+}
+
+/// The used height of the lines of the node. `line-height: normal` is the
+/// line height of the font.
+pub fn line_height(node: &NodePtr) -> f32 {
+    let font = font_description(node);
+    node.computed_style()
+        .line_height()
+        .to_px(font.size)
+        .unwrap_or_else(|| font_metrics(node).line_height)
+}
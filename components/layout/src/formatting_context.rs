@@ -1,10 +1,7 @@
 use std::{cell::RefCell, fmt::Debug, rc::Rc};
 
 use shared::tree_node::WeakTreeNode;
-use style_types::{
-    values::{display::InnerDisplayType, prelude::Display},
-    Property, Value,
-};
+use style_types::values::{display::InnerDisplayType, prelude::Display};
 
 use crate::{
     flow::{block::BlockFormattingContext, inline::InlineFormattingContext},
@@ -83,9 +80,10 @@ fn get_formatting_context_type(layout_node: LayoutBoxPtr) -> FormattingContextTy
 
     let node = layout_node.node().unwrap();
 
-    let display = node.get_style(&Property::Display);
+    let style = node.computed_style();
+    let display = style.display();
     let inner_display = match display {
-        Value::Display(Display::Full(_, ref inner)) => inner,
+        Display::Full(_, inner) => inner,
         _ => unreachable!(),
     };
 
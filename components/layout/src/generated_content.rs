@@ -0,0 +1,178 @@
+use dom::{
+    element::Element,
+    node::{Node, NodeData, NodePtr},
+    text::Text,
+};
+use shared::tree_node::TreeNode;
+use style_types::{
+    values::prelude::{ContentItem, CounterIncrement, CounterReset},
+    ComputedStyle,
+};
+
+/// Default value of the `quotes` property: curly double quotes for the
+/// outer level & curly single quotes for the nested levels.
+const QUOTES: [(&str, &str); 2] = [("\u{201c}", "\u{201d}"), ("\u{2018}", "\u{2019}")];
+
+/// State of the counters & quotes used to resolve generated content. It is
+/// updated while the layout tree is built in document order.
+///
+/// https://www.w3.org/TR/css-lists-3/#auto-numbering
+pub struct GeneratedContent {
+    /// Counters instantiated by the boxes of each level of the tree. A
+    /// counter is visible to the box that instantiated it, its descendants
+    /// & its following siblings.
+    counter_scopes: Vec<Vec<(String, i32)>>,
+    quote_depth: usize,
+    /// Whether a counter or a quote was used, so the generated content
+    /// depends on the boxes before it in document order
+    depends_on_document_order: bool,
+}
+
+impl GeneratedContent {
+    pub fn new() -> Self {
+        Self {
+            counter_scopes: vec![Vec::new()],
+            quote_depth: 0,
+            depends_on_document_order: false,
+        }
+    }
+
+    /// Start the children of the current box
+    pub fn enter_scope(&mut self) {
+        self.counter_scopes.push(Vec::new());
+    }
+
+    /// Finish the children of the current box, dropping their counters
+    pub fn leave_scope(&mut self) {
+        self.counter_scopes.pop();
+    }
+
+    pub fn depends_on_document_order(&self) -> bool {
+        self.depends_on_document_order
+    }
+
+    /// Apply `counter-reset` & `counter-increment` of a box
+    pub fn update_counters(&mut self, style: &ComputedStyle) {
+        let CounterReset(counters) = style.counter_reset();
+        for (name, value) in counters {
+            self.reset_counter(name, *value);
+        }
+
+        let CounterIncrement(counters) = style.counter_increment();
+        for (name, value) in counters {
+            self.increment_counter(name, *value);
+        }
+    }
+
+    /// Resolve the `content` of a pseudo-element of `element` into text
+    pub fn resolve(&mut self, element: &Element, content: &[ContentItem]) -> String {
+        let mut result = String::new();
+        for item in content {
+            if !matches!(item, ContentItem::String(_) | ContentItem::Attr(_)) {
+                self.depends_on_document_order = true;
+            }
+            match item {
+                ContentItem::String(value) => result.push_str(value),
+                ContentItem::Attr(name) => {
+                    result.push_str(&element.get_attribute(name).unwrap_or_default())
+                }
+                ContentItem::Counter(name, style) => {
+                    let value = self.counter_values(name).last().copied().unwrap_or(0);
+                    result.push_str(&style.format(value));
+                }
+                ContentItem::Counters(name, separator, style) => {
+                    let mut values = self.counter_values(name);
+                    if values.is_empty() {
+                        values.push(0);
+                    }
+                    let values = values
+                        .into_iter()
+                        .map(|value| style.format(value))
+                        .collect::<Vec<String>>();
+                    result.push_str(&values.join(separator));
+                }
+                ContentItem::OpenQuote => {
+                    result.push_str(self.quotes().0);
+                    self.quote_depth += 1;
+                }
+                ContentItem::CloseQuote => {
+                    // a close quote without an open quote produces nothing
+                    if self.quote_depth > 0 {
+                        self.quote_depth -= 1;
+                        result.push_str(self.quotes().1);
+                    }
+                }
+                ContentItem::NoOpenQuote => self.quote_depth += 1,
+                ContentItem::NoCloseQuote => {
+                    self.quote_depth = self.quote_depth.saturating_sub(1);
+                }
+            }
+        }
+        result
+    }
+
+    fn quotes(&self) -> (&'static str, &'static str) {
+        QUOTES[self.quote_depth.min(QUOTES.len() - 1)]
+    }
+
+    fn reset_counter(&mut self, name: &str, value: i32) {
+        self.depends_on_document_order = true;
+        let scope = self
+            .counter_scopes
+            .last_mut()
+            .expect("There should be at least one counter scope");
+
+        // a counter reset by a previous sibling is replaced
+        match scope.iter_mut().find(|(counter, _)| counter == name) {
+            Some((_, counter_value)) => *counter_value = value,
+            None => scope.push((name.to_string(), value)),
+        }
+    }
+
+    fn increment_counter(&mut self, name: &str, value: i32) {
+        let counter = self
+            .counter_scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut())
+            .find(|(counter, _)| counter == name);
+
+        match counter {
+            Some((_, counter_value)) => *counter_value += value,
+            // incrementing a counter that doesn't exist instantiates it
+            None => self.reset_counter(name, value),
+        }
+    }
+
+    /// Values of the counters with the given name from the outermost to the
+    /// innermost
+    fn counter_values(&self, name: &str) -> Vec<i32> {
+        self.counter_scopes
+            .iter()
+            .flat_map(|scope| scope.iter())
+            .filter(|(counter, _)| counter == name)
+            .map(|(_, value)| *value)
+            .collect()
+    }
+}
+
+impl Default for GeneratedContent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create the node of a pseudo-element with its generated text. The node is
+/// not part of the document, it only holds the styles for the layout boxes.
+pub fn create_pseudo_element_node(style: ComputedStyle, text: String) -> NodePtr {
+    let node = NodePtr(TreeNode::new(Node::empty()));
+
+    if !text.is_empty() {
+        let text_node = NodePtr(TreeNode::new(Node::new(NodeData::Text(Text::new(text)))));
+        node.append_child(text_node.0.clone());
+        text_node.set_computed_style(ComputedStyle::inherit_from(&style));
+    }
+    node.set_computed_style(style);
+
+    node
+}
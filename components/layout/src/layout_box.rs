@@ -5,13 +5,10 @@ use shared::{
     primitive::{EdgeSizes, Point, Rect, Size},
     tree_node::{TreeNode, TreeNodeHooks},
 };
-use style_types::{
-    values::{
-        display::Display,
-        display::{InnerDisplayType, OuterDisplayType},
-        prelude::{Overflow, Position},
-    },
-    Property, Value,
+use style_types::values::{
+    display::Display,
+    display::{InnerDisplayType, OuterDisplayType},
+    prelude::{Float, Overflow, Position},
 };
 
 use crate::{
@@ -85,21 +82,18 @@ impl LayoutBox {
             if node.is_text() {
                 BoxData::InlineContents(InlineContents::TextRun)
             } else {
-                match node.get_style(&Property::Display) {
-                    Value::Display(d) => match d {
-                        Display::Full(ref outer, ref inner) => match (outer, inner) {
-                            (OuterDisplayType::Block, InnerDisplayType::Flow) => {
-                                BoxData::block_box()
-                            }
-                            (OuterDisplayType::Inline, InnerDisplayType::Flow)
-                            | (OuterDisplayType::Inline, InnerDisplayType::FlowRoot) => {
-                                BoxData::inline_box()
-                            }
-                            _ => unimplemented!("Unsupport display type: {:#?}", d),
-                        },
+                let style = node.computed_style();
+                let d = style.display();
+                match d {
+                    Display::Full(ref outer, ref inner) => match (outer, inner) {
+                        (OuterDisplayType::Block, InnerDisplayType::Flow) => BoxData::block_box(),
+                        (OuterDisplayType::Inline, InnerDisplayType::Flow)
+                        | (OuterDisplayType::Inline, InnerDisplayType::FlowRoot) => {
+                            BoxData::inline_box()
+                        }
                         _ => unimplemented!("Unsupport display type: {:#?}", d),
                     },
-                    _ => unreachable!(),
+                    _ => unimplemented!("Unsupport display type: {:#?}", d),
                 }
             }
         };
@@ -230,20 +224,30 @@ impl LayoutBoxPtr {
 
     pub fn is_inline_block(&self) -> bool {
         match self.node() {
-            Some(node) => match node.get_style(&Property::Display) {
-                Value::Display(Display::Full(_, InnerDisplayType::FlowRoot)) => self.is_inline(),
+            Some(node) => match node.computed_style().display() {
+                Display::Full(_, InnerDisplayType::FlowRoot) => self.is_inline(),
                 _ => false,
             },
             _ => false,
         }
     }
 
+    /// Whether the box is an inline-level box that is laid out as a whole,
+    /// an inline-block or a replaced element
+    pub fn is_atomic_inline(&self) -> bool {
+        self.is_inline_block() || (!self.is_anonymous() && !self.is_non_replaced())
+    }
+
     pub fn is_positioned(&self, position: Position) -> bool {
         match self.node() {
-            Some(node) => match node.get_style(&Property::Position) {
-                Value::Position(pos) => pos == position,
-                _ => false,
-            },
+            Some(node) => *node.computed_style().position() == position,
+            _ => false,
+        }
+    }
+
+    pub fn is_floated(&self) -> bool {
+        match self.node() {
+            Some(node) => *node.computed_style().float() != Float::None,
             _ => false,
         }
     }
@@ -343,11 +347,29 @@ impl LayoutBoxPtr {
             self.set_mouse_over(true);
             self.for_each_child(|child| LayoutBoxPtr(child).handle_mouse_move(mouse_coord));
         } else {
-            self.set_mouse_over(false);
-            self.for_each_child(|child| LayoutBoxPtr(child).set_mouse_over(false));
+            self.clear_mouse_over();
         }
     }
 
+    fn clear_mouse_over(&self) {
+        self.set_mouse_over(false);
+        self.for_each_child(|child| LayoutBoxPtr(child).clear_mouse_over());
+    }
+
+    /// The deepest DOM node whose box is under the mouse. Boxes of generated
+    /// content are skipped as their nodes are not part of the document.
+    pub fn hovered_node(&self) -> Option<NodePtr> {
+        self.find_first_deepest_decendant(|node| {
+            let node = LayoutBoxPtr(node);
+            node.is_mouse_over()
+                && node
+                    .node()
+                    .map(|node| node.owner_document().is_some())
+                    .unwrap_or(false)
+        })
+        .and_then(|node| LayoutBoxPtr(node).node())
+    }
+
     pub fn is_mouse_over(&self) -> bool {
         *self.is_mouse_over.borrow()
     }
@@ -358,8 +380,10 @@ impl LayoutBoxPtr {
         let is_overflow_scrollable = self
             .node()
             .map(|node| {
-                let overflow_value = node.get_style(&Property::OverflowY);
-                overflow_value.is_auto() || overflow_value == Value::Overflow(Overflow::Scroll)
+                matches!(
+                    node.computed_style().overflow_y(),
+                    Overflow::Auto | Overflow::Scroll
+                )
             })
             .unwrap_or_else(|| self.parent().is_none());
 
@@ -368,10 +392,7 @@ impl LayoutBoxPtr {
 
     pub fn is_overflow_visible(&self) -> bool {
         self.node()
-            .map(|node| {
-                let overflow_value = node.get_style(&Property::OverflowY);
-                overflow_value == Value::Overflow(Overflow::Visible)
-            })
+            .map(|node| *node.computed_style().overflow_y() == Overflow::Visible)
             .unwrap_or(true)
     }
 
@@ -447,8 +468,9 @@ impl LayoutBoxPtr {
         }
 
         if let Some(node) = self.node() {
-            let computed_width = node.get_style(&Property::Width);
-            let computed_height = node.get_style(&Property::Height);
+            let style = node.computed_style();
+            let computed_width = style.width();
+            let computed_height = style.height();
 
             if !computed_width.is_auto() {
                 let used_width = computed_width.to_px(containing_block.width);
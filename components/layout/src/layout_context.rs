@@ -1,12 +1,18 @@
-use shared::primitive::{Rect, Size};
+use shared::{
+    font::FontDescription,
+    primitive::{Rect, Size},
+};
+
+/// Measure the size of a text rendered with a font
+pub type MeasureTextFn = Box<dyn FnMut(&str, &FontDescription) -> Size>;
 
 pub struct LayoutContext {
     pub viewport: Rect,
-    pub measure_text_fn: Box<dyn FnMut(&str, f32) -> Size>,
+    pub measure_text_fn: MeasureTextFn,
 }
 
 impl LayoutContext {
-    pub fn measure_text(&mut self, content: &str, font_size: f32) -> Size {
-        (self.measure_text_fn)(content, font_size)
+    pub fn measure_text(&mut self, content: &str, font: &FontDescription) -> Size {
+        (self.measure_text_fn)(content, font)
     }
 }
@@ -1,6 +1,8 @@
 pub mod box_model;
 pub mod flow;
+pub mod font;
 pub mod formatting_context;
+pub mod generated_content;
 pub mod layout_box;
 pub mod layout_context;
 pub mod tree_builder;
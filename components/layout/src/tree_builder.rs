@@ -1,24 +1,33 @@
-use dom::node::NodePtr;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use css::selector::structs::PseudoElement;
+use dom::node::{LayoutDirty, NodePtr};
 use shared::tree_node::TreeNode;
-use style_types::{
-    values::{display::DisplayBox, prelude::Display},
-    Value,
+use style_types::values::{
+    display::DisplayBox,
+    prelude::{Content, Display},
 };
 
-use crate::layout_box::{BoxData, LayoutBox, LayoutBoxPtr};
+use crate::{
+    generated_content::{create_pseudo_element_node, GeneratedContent},
+    layout_box::{BoxData, LayoutBox, LayoutBoxPtr},
+};
 
 pub struct TreeBuilder {
     parent_stack: Vec<LayoutBoxPtr>,
+    generated_content: GeneratedContent,
 }
 
 impl TreeBuilder {
     pub fn new() -> Self {
         Self {
             parent_stack: Vec::new(),
+            generated_content: GeneratedContent::new(),
         }
     }
 
-    pub fn build(mut self, root: NodePtr) -> Option<LayoutBoxPtr> {
+    pub fn build(&mut self, root: NodePtr) -> Option<LayoutBoxPtr> {
         let root_node = if root.is_document() {
             // the first child is HTML tag
             root.first_child().map(|n| NodePtr(n))
@@ -27,62 +36,122 @@ impl TreeBuilder {
         };
 
         if let Some(root_node) = root_node {
-            if let Value::Display(Display::Box(DisplayBox::None)) =
-                root_node.get_style(&style_types::Property::Display)
-            {
+            if *root_node.computed_style().display() == Display::Box(DisplayBox::None) {
                 return None;
             }
             let root_box = LayoutBoxPtr(TreeNode::new(LayoutBox::new(root_node.clone())));
 
-            self.parent_stack.push(root_box.clone());
-            root_node.for_each_child(|child| {
-                self.build_layout_tree(NodePtr(child));
-            });
-            self.parent_stack.pop();
+            self.build_children(&root_node, root_box.clone());
 
             return Some(root_box);
         }
         None
     }
 
+    /// Whether the generated content of the built boxes uses counters or
+    /// quotes, i.e. it depends on the boxes before it in document order
+    pub fn depends_on_document_order(&self) -> bool {
+        self.generated_content.depends_on_document_order()
+    }
+
+    /// Build the boxes of the children of a node again, in place of the
+    /// current children of its block box
+    pub fn rebuild_children(&mut self, node: &NodePtr, layout_box: &LayoutBoxPtr) {
+        layout_box.set_children(&[]);
+        layout_box.lines().borrow_mut().clear();
+        self.build_children(node, layout_box.clone());
+    }
+
     fn build_layout_tree(&mut self, node: NodePtr) {
-        if let Value::Display(Display::Box(DisplayBox::None)) =
-            node.get_style(&style_types::Property::Display)
-        {
+        if *node.computed_style().display() == Display::Box(DisplayBox::None) {
             return;
         }
-        let layout_box = TreeNode::new(LayoutBox::new(node.clone()));
-
-        let parent = if LayoutBoxPtr(layout_box.clone()).is_inline() {
-            self.get_parent_for_inline()
-        } else {
-            self.get_parent_for_block()
-        };
-
-        if parent.is_some() {
-            parent.unwrap().append_child(layout_box.clone());
 
+        if let Some(layout_box) = self.insert_box(&node) {
             if !node.is_element() && !node.is_document() {
                 return;
             }
+            self.build_children(&node, layout_box);
+        }
+    }
+
+    /// Build the boxes of the children of an element, including the boxes of
+    /// its `::before` & `::after` pseudo-elements.
+    fn build_children(&mut self, node: &NodePtr, layout_box: LayoutBoxPtr) {
+        self.generated_content
+            .update_counters(&node.computed_style());
+        self.generated_content.enter_scope();
+        self.parent_stack.push(layout_box);
+
+        self.build_pseudo_element(node, PseudoElement::Before);
+        node.for_each_child(|child| {
+            self.build_layout_tree(NodePtr(child));
+        });
+        self.build_pseudo_element(node, PseudoElement::After);
+
+        self.parent_stack.pop();
+        self.generated_content.leave_scope();
+    }
+
+    /// Build the box of a pseudo-element of the node. A pseudo-element only
+    /// generates a box if its `content` is not `none` or `normal`.
+    fn build_pseudo_element(&mut self, node: &NodePtr, pseudo_element: PseudoElement) {
+        let style = match node.pseudo_element_styles().get(&pseudo_element) {
+            Some(style) => style.clone(),
+            None => return,
+        };
 
-            self.parent_stack.push(LayoutBoxPtr(layout_box));
-            node.for_each_child(|child| {
+        let content = match style.content() {
+            Content::Items(items) => items.clone(),
+            _ => return,
+        };
+
+        if *style.display() == Display::Box(DisplayBox::None) {
+            return;
+        }
+
+        self.generated_content.update_counters(&style);
+        let text = self.generated_content.resolve(node.as_element(), &content);
+        let pseudo_node = create_pseudo_element_node(style, text);
+
+        if let Some(layout_box) = self.insert_box(&pseudo_node) {
+            self.parent_stack.push(layout_box);
+            pseudo_node.for_each_child(|child| {
                 self.build_layout_tree(NodePtr(child));
             });
             self.parent_stack.pop();
+        }
+    }
+
+    /// Create the box of the node & insert it into the correct parent
+    fn insert_box(&mut self, node: &NodePtr) -> Option<LayoutBoxPtr> {
+        let layout_box = LayoutBoxPtr(TreeNode::new(LayoutBox::new(node.clone())));
+
+        let parent = if layout_box.is_inline() {
+            self.get_parent_for_inline()
         } else {
-            let parent_stack = self
-                .parent_stack
-                .iter()
-                .map(|parent| format!("{} {:?}", parent.friendly_name(), parent.node()))
-                .collect::<Vec<String>>();
-            log::error!(
-                "Couldn't find the correct parent for node: {} {:?}",
-                LayoutBoxPtr(layout_box).friendly_name(),
-                node
-            );
-            log::error!("Parent stack: {:#?}", parent_stack);
+            self.get_parent_for_block()
+        };
+
+        match parent {
+            Some(parent) => {
+                parent.append_child(layout_box.0.clone());
+                Some(layout_box)
+            }
+            None => {
+                let parent_stack = self
+                    .parent_stack
+                    .iter()
+                    .map(|parent| format!("{} {:?}", parent.friendly_name(), parent.node()))
+                    .collect::<Vec<String>>();
+                log::error!(
+                    "Couldn't find the correct parent for node: {} {:?}",
+                    layout_box.friendly_name(),
+                    node
+                );
+                log::error!("Parent stack: {:#?}", parent_stack);
+                None
+            }
         }
     }
 
@@ -151,9 +220,123 @@ impl TreeBuilder {
     }
 }
 
+/// How the layout tree was updated for the layout dirty nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutTreeUpdate {
+    /// No box changed, the tree keeps its layout
+    Clean,
+    /// Boxes were rebuilt in place or have to be laid out again. The
+    /// formatting contexts don't reuse the layout of the boxes that didn't
+    /// change, so the whole tree is laid out again.
+    Relayout,
+    /// The tree can't be updated in place & has to be built again
+    Rebuild,
+}
+
+/// Update the boxes of the layout dirty nodes of the document in the layout
+/// tree & clear the flags of the nodes. The children of a node are rebuilt
+/// under the block box of the node, or of its nearest ancestor with one,
+/// as block-level descendants of inline boxes are placed in it.
+///
+/// The tree has to be built again if the root element has to be rebuilt or
+/// if generated content depends on the document order.
+pub fn update_layout_tree(
+    layout_tree: &LayoutBoxPtr,
+    document: &NodePtr,
+    depends_on_document_order: bool,
+) -> LayoutTreeUpdate {
+    let mut rebuild = Vec::new();
+    let mut reflow = Vec::new();
+    collect_layout_dirty_nodes(document, &mut rebuild, &mut reflow);
+    if rebuild.is_empty() && reflow.is_empty() {
+        return LayoutTreeUpdate::Clean;
+    }
+    if !rebuild.is_empty() && depends_on_document_order {
+        return LayoutTreeUpdate::Rebuild;
+    }
+
+    let mut boxes = HashMap::new();
+    collect_boxes(layout_tree, &mut boxes);
+    let find_box = |node: &NodePtr| boxes.get(&Rc::as_ptr(&node.0)).cloned();
+
+    // the nearest node with a block box contains the boxes of all of the
+    // descendants of the dirty node
+    let mut roots = Vec::new();
+    for node in rebuild {
+        let mut current = node;
+        loop {
+            match find_box(&current) {
+                Some(layout_box) if layout_box.is_block() => break,
+                _ => match current.parent() {
+                    Some(parent) => current = NodePtr(parent),
+                    None => return LayoutTreeUpdate::Rebuild,
+                },
+            }
+        }
+        roots.push(current);
+    }
+
+    let root_keys = roots
+        .iter()
+        .map(|node| Rc::as_ptr(&node.0))
+        .collect::<HashSet<_>>();
+    for node in &roots {
+        let is_nested = node
+            .find_first_ancestor(|parent| root_keys.contains(&Rc::as_ptr(&parent)))
+            .is_some();
+        if is_nested {
+            continue;
+        }
+        if let Some(layout_box) = find_box(node) {
+            TreeBuilder::new().rebuild_children(node, &layout_box);
+        }
+    }
+    LayoutTreeUpdate::Relayout
+}
+
+/// Clear the layout dirty flags of the document, e.g. once the whole layout
+/// tree is built
+pub fn clear_layout_dirty(document: &NodePtr) {
+    collect_layout_dirty_nodes(document, &mut Vec::new(), &mut Vec::new());
+}
+
+/// Collect the nodes whose children have to be rebuilt & the nodes that
+/// have to be laid out again, then clear their flags
+fn collect_layout_dirty_nodes(
+    node: &NodePtr,
+    rebuild: &mut Vec<NodePtr>,
+    reflow: &mut Vec<NodePtr>,
+) {
+    match node.layout_dirty() {
+        LayoutDirty::Clean => {}
+        LayoutDirty::Reflow => reflow.push(node.clone()),
+        LayoutDirty::RebuildChildren => rebuild.push(node.clone()),
+    }
+    if node.has_layout_dirty_descendants() {
+        node.for_each_child(|child| collect_layout_dirty_nodes(&NodePtr(child), rebuild, reflow));
+    }
+    node.clear_layout_dirty();
+}
+
+/// Map the nodes of the document to their boxes
+fn collect_boxes(
+    layout_box: &LayoutBoxPtr,
+    boxes: &mut HashMap<*const shared::tree_node::Node<dom::node::Node>, LayoutBoxPtr>,
+) {
+    if let Some(node) = layout_box.node() {
+        boxes
+            .entry(Rc::as_ptr(&node.0))
+            .or_insert_with(|| layout_box.clone());
+    }
+    layout_box.for_each_child(|child| collect_boxes(&LayoutBoxPtr(child), boxes));
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{update_layout_tree, LayoutTreeUpdate};
     use crate::{layout_box::LayoutBoxPtr, utils::*};
+    use dom::node::LayoutDirty;
+    use std::rc::Rc;
     use test_utils::dom_creator::*;
 
     #[test]
@@ -233,4 +416,125 @@ mod tests {
         assert!(LayoutBoxPtr(root.nth_child(2).unwrap()).is_block());
         assert!(LayoutBoxPtr(root.nth_child(2).unwrap()).is_anonymous());
     }
+
+    #[test]
+    fn test_generated_content() {
+        let document = document();
+        let first = element("p.item", document.clone(), vec![]);
+        let second = element("p.item", document.clone(), vec![]);
+        second.set_attribute("title", "Second");
+        let dom = element("div", document.clone(), vec![first, second]);
+
+        let css = format!(
+            "{}{}",
+            r#"
+            div { counter-reset: item; }
+            p.item { counter-increment: item; }
+            p.item::before { content: counter(item) ". " attr(title); }
+            div::after { content: open-quote "end" close-quote; display: block; }
+            "#,
+            SHARED_CSS
+        );
+        let root = build_tree(dom, &css);
+
+        // The result box tree should look like this
+        // [Block] - Div
+        //   |- [Block] - P
+        //        |- [Inline] - ::before
+        //             |- [Inline] - Text("1. ")
+        //   |- [Block] - P
+        //        |- [Inline] - ::before
+        //             |- [Inline] - Text("2. Second")
+        //   |- [Block] - ::after
+        //        |- [Inline] - Text("“end”")
+
+        let generated_text = |layout_box: LayoutBoxPtr| {
+            let pseudo_element = LayoutBoxPtr(layout_box.first_child().unwrap());
+            assert!(pseudo_element.node().unwrap().owner_document().is_none());
+            let text = LayoutBoxPtr(pseudo_element.first_child().unwrap());
+            text.node().unwrap().as_text().get_data()
+        };
+
+        assert_eq!(root.children_count(), 3);
+        assert_eq!(
+            generated_text(LayoutBoxPtr(root.first_child().unwrap())),
+            "1. "
+        );
+        assert_eq!(
+            generated_text(LayoutBoxPtr(root.nth_child(1).unwrap())),
+            "2. Second"
+        );
+
+        let after = LayoutBoxPtr(root.nth_child(2).unwrap());
+        assert!(after.is_block());
+        let text = LayoutBoxPtr(after.first_child().unwrap());
+        assert_eq!(
+            text.node().unwrap().as_text().get_data(),
+            "\u{201c}end\u{201d}"
+        );
+    }
+
+    #[test]
+    fn test_update_dirty_boxes() {
+        let document = document();
+        let span = element("span", document.clone(), vec![]);
+        let first = element("p", document.clone(), vec![span.clone()]);
+        let second = element("p", document.clone(), vec![]);
+        let dom = element("div", document.clone(), vec![first.clone(), second.clone()]);
+
+        let root = build_tree(dom, SHARED_CSS);
+        // the root element was inserted, the whole tree has to be built
+        assert_eq!(
+            update_layout_tree(&root, &document, false),
+            LayoutTreeUpdate::Rebuild
+        );
+        assert_eq!(
+            update_layout_tree(&root, &document, false),
+            LayoutTreeUpdate::Clean
+        );
+
+        let first_box = LayoutBoxPtr(root.first_child().unwrap());
+        let second_box = LayoutBoxPtr(root.nth_child(1).unwrap());
+        let span_box = LayoutBoxPtr(first_box.first_child().unwrap());
+
+        let inserted = element("span", document.clone(), vec![]);
+        inserted.set_computed_style(span.computed_style().clone());
+        second.append_child(inserted.0.clone());
+        assert_eq!(second.layout_dirty(), LayoutDirty::RebuildChildren);
+
+        assert_eq!(
+            update_layout_tree(&root, &document, false),
+            LayoutTreeUpdate::Relayout
+        );
+        assert_eq!(second.layout_dirty(), LayoutDirty::Clean);
+        assert!(!document.has_layout_dirty_descendants());
+        // only the boxes of the children of the second paragraph are built
+        assert!(Rc::ptr_eq(&root.first_child().unwrap(), &first_box));
+        assert!(Rc::ptr_eq(&root.nth_child(1).unwrap(), &second_box));
+        assert_eq!(second_box.children_count(), 1);
+
+        // the children of an inline box are built in its block container
+        span.mark_layout_dirty(LayoutDirty::RebuildChildren);
+        assert_eq!(
+            update_layout_tree(&root, &document, false),
+            LayoutTreeUpdate::Relayout
+        );
+        assert!(!Rc::ptr_eq(&first_box.first_child().unwrap(), &span_box));
+
+        first.mark_layout_dirty(LayoutDirty::Reflow);
+        assert_eq!(
+            update_layout_tree(&root, &document, false),
+            LayoutTreeUpdate::Relayout
+        );
+        // the boxes are only laid out again
+        assert!(Rc::ptr_eq(&root.first_child().unwrap(), &first_box));
+        assert_eq!(first.layout_dirty(), LayoutDirty::Clean);
+
+        // counters & quotes depend on the boxes before them
+        second.mark_layout_dirty(LayoutDirty::RebuildChildren);
+        assert_eq!(
+            update_layout_tree(&root, &document, true),
+            LayoutTreeUpdate::Rebuild
+        );
+    }
 }
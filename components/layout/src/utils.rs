@@ -1,6 +1,7 @@
 use css::cssom::css_rule::CSSRule;
 use dom::node::NodePtr;
-use style_types::{CSSLocation, CascadeOrigin, ContextualRule};
+use style::rule_map::RuleMap;
+use style_types::{CSSLocation, CascadeOrigin, ContextualRule, UNLAYERED};
 use test_utils::css::parse_stylesheet;
 
 use crate::layout_box::LayoutBoxPtr;
@@ -27,22 +28,27 @@ pub fn build_tree(dom: NodePtr, css: &str) -> LayoutBoxPtr {
 
     let rules = stylesheet
         .iter()
-        .map(|rule| match rule {
-            CSSRule::Style(style) => ContextualRule {
+        .filter_map(|rule| match rule {
+            CSSRule::Style(style) => Some(ContextualRule {
                 inner: style.clone(),
                 location: CSSLocation::Embedded,
                 origin: CascadeOrigin::User,
-            },
+                layer: UNLAYERED,
+            }),
+            _ => None,
         })
         .collect::<Vec<ContextualRule>>();
 
-    fn compute_styles(element: NodePtr, style_rules: &[ContextualRule]) {
-        let computed_styles = style::compute::compute_styles(element.clone(), &style_rules);
-        element.set_computed_styles(computed_styles);
+    fn compute_styles(element: NodePtr, style_rules: &RuleMap) {
+        let computed_style = style::compute::compute_styles(element.clone(), style_rules, None);
+        element.set_computed_style(computed_style);
+        let pseudo_element_styles =
+            style::compute::compute_pseudo_element_styles(element.clone(), style_rules, None);
+        element.set_pseudo_element_styles(pseudo_element_styles);
 
         element.for_each_child(|child| compute_styles(NodePtr(child), style_rules))
     }
 
-    compute_styles(NodePtr(document), &rules);
+    compute_styles(NodePtr(document), &RuleMap::new(rules));
     crate::tree_builder::TreeBuilder::new().build(dom).unwrap()
 }
@@ -1,14 +1,19 @@
-use layout::{flow::line_box::LineFragmentData, layout_box::LayoutBoxPtr};
+use layout::{
+    flow::line_box::LineFragmentData,
+    font::{font_description, font_metrics},
+    layout_box::LayoutBoxPtr,
+};
 use shared::{
     color::Color,
-    primitive::{Corners, RRect, Rect, Size},
+    font::FontDescription,
+    primitive::{Corners, LineStyle, RRect, Rect, Size},
 };
-use style_types::{
-    values::{color::Color as CSSColor, prelude::BorderStyle},
-    Property, Value,
+use style_types::values::{
+    color::Color as CSSColor,
+    prelude::{BorderStyle, Position, TextDecorationLine},
 };
 
-use crate::utils::{color_from_value, is_zero, to_radii};
+use crate::utils::{is_zero, to_radii};
 
 pub struct DisplayList(Vec<Command>);
 
@@ -16,7 +21,8 @@ pub enum Command {
     FillRect(Rect, Color),
     FillRRect(RRect, Color),
     FillBorder(Rect, Rect, Borders),
-    FillText(String, Rect, Color, f32, bool),
+    FillText(String, Rect, Color, FontDescription),
+    FillTextDecoration(Rect, LineStyle, Color),
     ClipRect(Rect),
     EndClipRect,
 }
@@ -33,6 +39,15 @@ pub struct Border {
     pub color: Color,
 }
 
+/// A text decoration of a decorating box, that is painted across the text
+/// of its in-flow descendants
+pub struct TextDecoration {
+    pub line: TextDecorationLine,
+    pub style: LineStyle,
+    pub color: Color,
+    pub thickness: f32,
+}
+
 pub struct OverflowData {
     pub visible: bool,
     pub visible_region: Rect,
@@ -143,21 +158,58 @@ impl<'a> DisplayListBuilder<'a> {
         overflow_data: &OverflowData,
     ) {
         let node = layout_box.node().unwrap();
-        let color = color_from_value(&node.get_style(&Property::Color));
-        let font_size = node.get_style(&Property::FontSize).to_absolute_px();
+        let color = node.computed_style().color().into();
 
         if !text_rect.is_overlap_rect(&overflow_data.visible_region) && !overflow_data.visible {
             return;
         }
 
-        let bold = if let Value::FontWeight(weight) = node.get_style(&Property::FontWeight) {
-            weight.value() >= 700.
-        } else {
-            false
-        };
+        let decorations = text_decorations(layout_box);
+        let metrics = font_metrics(&node);
+        let baseline = text_rect.y + metrics.ascent;
+        let line_rect =
+            |y: f32, thickness: f32| Rect::new(text_rect.x, y, text_rect.width, thickness);
+
+        // underlines & overlines are painted below the text
+        for decoration in decorations.iter() {
+            if decoration.line.underline {
+                self.display_list.fill_text_decoration(
+                    line_rect(baseline + decoration.thickness, decoration.thickness),
+                    decoration.style,
+                    decoration.color.clone(),
+                );
+            }
+            if decoration.line.overline {
+                self.display_list.fill_text_decoration(
+                    line_rect(text_rect.y, decoration.thickness),
+                    decoration.style,
+                    decoration.color.clone(),
+                );
+            }
+        }
 
-        self.display_list
-            .fill_text(content.to_string(), text_rect, color, font_size, bold);
+        // spaced text is laid out in runs, the runs of spaces are only
+        // there to move the next runs
+        if !content.trim().is_empty() {
+            self.display_list.fill_text(
+                content.to_string(),
+                text_rect.clone(),
+                color,
+                font_description(&node),
+            );
+        }
+
+        // line-throughs are painted over the text
+        for decoration in decorations.iter() {
+            if decoration.line.line_through {
+                let y = baseline - metrics.x_height / 2. - decoration.thickness / 2.;
+                self.display_list.fill_text_decoration(
+                    line_rect(y, decoration.thickness),
+                    decoration.style,
+                    decoration.color.clone(),
+                );
+            }
+        }
     }
 
     fn build_paint_boxes(
@@ -172,18 +224,12 @@ impl<'a> DisplayListBuilder<'a> {
 
         let node = layout_box.node().unwrap();
         let mut rect = override_rect.unwrap_or(layout_box.padding_box_absolute());
-        let background_color = color_from_value(&node.get_style(&Property::BackgroundColor));
+        let style = node.computed_style();
+        let background_color = style.background_color().into();
 
         if layout_box.is_root_element() {
-            self.root_element_use_body_background = {
-                if let Value::Color(CSSColor::Transparent) =
-                    node.get_style(&Property::BackgroundColor)
-                {
-                    true
-                } else {
-                    false
-                }
-            };
+            self.root_element_use_body_background =
+                *style.background_color() == CSSColor::Transparent;
 
             if self.root_element_use_body_background {
                 // Delegate the rendering to the body element
@@ -228,24 +274,20 @@ impl<'a> DisplayListBuilder<'a> {
             };
         }
         let node = layout_box.node().unwrap();
-        macro_rules! compute_border {
-            ($style:ident, $color:ident) => {
-                match node.get_style(&Property::$style) {
-                    Value::BorderStyle(BorderStyle::None) => None,
-                    Value::BorderStyle(style) => Some(Border {
-                        color: color_from_value(&node.get_style(&Property::$color)),
-                        style,
-                    }),
-                    _ => None,
-                }
-            };
-        }
+        let style = node.computed_style();
+        let border = |border_style: &BorderStyle, color: &CSSColor| match border_style {
+            BorderStyle::None => None,
+            style => Some(Border {
+                style: style.clone(),
+                color: color.into(),
+            }),
+        };
 
         Borders {
-            top: compute_border!(BorderTopStyle, BorderTopColor),
-            right: compute_border!(BorderRightStyle, BorderRightColor),
-            bottom: compute_border!(BorderBottomStyle, BorderBottomColor),
-            left: compute_border!(BorderLeftStyle, BorderLeftColor),
+            top: border(style.border_top_style(), style.border_top_color()),
+            right: border(style.border_right_style(), style.border_right_color()),
+            bottom: border(style.border_bottom_style(), style.border_bottom_color()),
+            left: border(style.border_left_style(), style.border_left_color()),
         }
     }
 
@@ -254,15 +296,16 @@ impl<'a> DisplayListBuilder<'a> {
             return None;
         }
         let node = layout_box.node().unwrap();
-        let border_top_left_radius = node.get_style(&Property::BorderTopLeftRadius);
-        let border_bottom_left_radius = node.get_style(&Property::BorderBottomLeftRadius);
-        let border_top_right_radius = node.get_style(&Property::BorderTopRightRadius);
-        let border_bottom_right_radius = node.get_style(&Property::BorderBottomRightRadius);
+        let style = node.computed_style();
+        let border_top_left_radius = style.border_top_left_radius();
+        let border_bottom_left_radius = style.border_bottom_left_radius();
+        let border_top_right_radius = style.border_top_right_radius();
+        let border_bottom_right_radius = style.border_bottom_right_radius();
 
-        let has_no_border_radius = is_zero(&border_top_left_radius)
-            && is_zero(&border_bottom_left_radius)
-            && is_zero(&border_top_right_radius)
-            && is_zero(&border_bottom_right_radius);
+        let has_no_border_radius = is_zero(border_top_left_radius)
+            && is_zero(border_bottom_left_radius)
+            && is_zero(border_top_right_radius)
+            && is_zero(border_bottom_right_radius);
 
         if has_no_border_radius {
             return None;
@@ -270,12 +313,10 @@ impl<'a> DisplayListBuilder<'a> {
 
         let border_box = layout_box.border_box_absolute();
 
-        let font_size = node.get_style(&Property::FontSize).to_absolute_px();
-
-        let tl = to_radii(&border_top_left_radius, border_box.width, font_size);
-        let tr = to_radii(&border_top_right_radius, border_box.width, font_size);
-        let bl = to_radii(&border_bottom_left_radius, border_box.width, font_size);
-        let br = to_radii(&border_bottom_right_radius, border_box.width, font_size);
+        let tl = to_radii(border_top_left_radius, border_box.width);
+        let tr = to_radii(border_top_right_radius, border_box.width);
+        let bl = to_radii(border_bottom_left_radius, border_box.width);
+        let br = to_radii(border_bottom_right_radius, border_box.width);
 
         Some(Corners::new(tl, tr, bl, br))
     }
@@ -334,6 +375,51 @@ impl<'a> DisplayListBuilder<'a> {
     }
 }
 
+/// The decorations that the text of a box is painted with. Decorations are
+/// propagated from the decorating boxes to their in-flow descendants, but
+/// not into inline-blocks, floats or absolutely positioned boxes.
+/// https://www.w3.org/TR/css-text-decor-3/#line-decoration
+fn text_decorations(layout_box: &LayoutBoxPtr) -> Vec<TextDecoration> {
+    let mut decorations = Vec::new();
+    let mut current = Some(layout_box.clone());
+
+    while let Some(decorating_box) = current {
+        if let Some(node) = decorating_box.node() {
+            let style = node.computed_style();
+            let line = style.text_decoration_line();
+            if !line.is_none() {
+                let font_size = font_description(&node).size;
+                let color = match style.text_decoration_color() {
+                    CSSColor::CurrentColor => style.color(),
+                    color => color,
+                };
+                let thickness = style
+                    .text_decoration_thickness()
+                    .to_px(font_size)
+                    .unwrap_or_else(|| (font_size / 16.).max(1.));
+                decorations.push(TextDecoration {
+                    line: line.clone(),
+                    style: style.text_decoration_style().into(),
+                    color: color.into(),
+                    thickness,
+                });
+            }
+        }
+
+        let propagates = !decorating_box.is_inline_block()
+            && !decorating_box.is_floated()
+            && !decorating_box.is_positioned(Position::Absolute)
+            && !decorating_box.is_positioned(Position::Fixed);
+        current = if propagates {
+            decorating_box.parent().map(LayoutBoxPtr)
+        } else {
+            None
+        };
+    }
+
+    decorations
+}
+
 impl DisplayList {
     pub fn new() -> Self {
         Self(Vec::new())
@@ -354,15 +440,13 @@ impl DisplayList {
         self.0.push(command);
     }
 
-    pub fn fill_text(
-        &mut self,
-        content: String,
-        rect: Rect,
-        color: Color,
-        font_size: f32,
-        bold: bool,
-    ) {
-        let command = Command::FillText(content, rect, color, font_size, bold);
+    pub fn fill_text(&mut self, content: String, rect: Rect, color: Color, font: FontDescription) {
+        let command = Command::FillText(content, rect, color, font);
+        self.0.push(command);
+    }
+
+    pub fn fill_text_decoration(&mut self, rect: Rect, style: LineStyle, color: Color) {
+        let command = Command::FillTextDecoration(rect, style, color);
         self.0.push(command);
     }
 
@@ -380,3 +464,68 @@ impl DisplayList {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layout::tree_builder::TreeBuilder;
+    use style::{rule_map::RuleMap, traversal::restyle_document};
+    use test_utils::dom_creator::{document, element, text};
+
+    /// The decoration lines of the text of a span in an underlined div,
+    /// the span being overlined & styled with `span_css`
+    fn span_text_decorations(span_css: &str) -> Vec<TextDecorationLine> {
+        let doc = document();
+        let css = format!(
+            "div {{ display: block; text-decoration: underline; }} \
+             span {{ display: inline; text-decoration: overline; {} }}",
+            span_css
+        );
+        let style = element("style", doc.clone(), vec![text(&css, doc.clone())]);
+        let span = element("span", doc.clone(), vec![text("text", doc.clone())]);
+        let div = element("div", doc.clone(), vec![span]);
+        doc.append_child(style.0.clone());
+        doc.append_child(div.0.clone());
+        restyle_document(&doc, &RuleMap::new(doc.as_document().style_rules()));
+
+        let root = TreeBuilder::new().build(div).unwrap();
+        let text_box = root
+            .find_first_deepest_decendant(
+                |node| matches!(LayoutBoxPtr(node).node(), Some(node) if node.is_text()),
+            )
+            .map(LayoutBoxPtr)
+            .unwrap();
+        text_decorations(&text_box)
+            .into_iter()
+            .map(|decoration| decoration.line)
+            .collect()
+    }
+
+    #[test]
+    fn propagate_text_decorations() {
+        let underline = TextDecorationLine {
+            underline: true,
+            ..Default::default()
+        };
+        let overline = TextDecorationLine {
+            overline: true,
+            ..Default::default()
+        };
+
+        assert_eq!(span_text_decorations(""), vec![overline.clone(), underline]);
+        // not into boxes that aren't in the inline formatting context
+        for css in [
+            "display: inline-block;",
+            "float: left;",
+            "position: absolute;",
+            "position: fixed;",
+        ] {
+            assert_eq!(
+                span_text_decorations(css),
+                vec![overline.clone()],
+                "{}",
+                css
+            );
+        }
+    }
+}
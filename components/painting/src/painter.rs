@@ -39,9 +39,13 @@ impl Painter {
                 Command::FillBorder(rect, border_rect, borders) => {
                     self.paint_borders(rect, border_rect, borders)
                 }
-                Command::FillText(content, rect, color, font_size, bold) => {
+                Command::FillText(content, rect, color, font) => {
                     self.gfx
-                        .fill_text(content, self.clip_rect(rect), color, font_size, bold)
+                        .fill_text(content, self.clip_rect(rect), color, font)
+                }
+                Command::FillTextDecoration(rect, style, color) => {
+                    self.gfx
+                        .fill_text_decoration(self.clip_rect(rect), style, color)
                 }
                 Command::ClipRect(rect) => self.clip_rects.push(rect),
                 Command::EndClipRect => {
@@ -0,0 +1,396 @@
+use std::sync::Arc;
+
+/// Generic font families. They are resolved to the default family of a
+/// font face set.
+/// https://www.w3.org/TR/css-fonts-4/#generic-font-families
+pub const GENERIC_FAMILIES: [&str; 6] = [
+    "serif",
+    "sans-serif",
+    "monospace",
+    "cursive",
+    "fantasy",
+    "system-ui",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// The font properties of a run of text, used to select a font face
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontDescription {
+    /// Family names in order of preference
+    pub families: Vec<String>,
+    pub size: f32,
+    pub weight: f32,
+    pub style: FontStyle,
+    /// Width of the font in percentage of the normal width
+    pub stretch: f32,
+}
+
+/// Metrics of a font at a size, used to resolve font-relative lengths
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    /// Height of lowercase letters, for `ex`
+    pub x_height: f32,
+    /// Advance of the "0" glyph, for `ch`
+    pub zero_advance: f32,
+    /// Height of a line with `line-height: normal`, for `lh`
+    pub line_height: f32,
+    /// Distance from the top of the text to its baseline
+    pub ascent: f32,
+}
+
+/// Provide the metrics of the fonts that text is rendered with
+pub trait FontMetricsProvider {
+    fn metrics(&self, font: &FontDescription) -> FontMetrics;
+}
+
+/// A font face of a family with the range of properties it supports,
+/// e.g. a face loaded from `@font-face`
+#[derive(Debug, Clone)]
+pub struct FontFace {
+    pub family: String,
+    pub weight: (f32, f32),
+    pub style: FontStyle,
+    pub stretch: (f32, f32),
+    pub data: Arc<Vec<u8>>,
+}
+
+/// A set of font faces that text can be rendered with. The index of a face
+/// is never reused, even once the face is removed, so that it can be used
+/// as an identifier.
+#[derive(Debug, Clone)]
+pub struct FontFaceSet {
+    faces: Vec<Option<FontFace>>,
+    /// Indices of the faces of the current document, e.g. its web fonts
+    web_faces: Vec<usize>,
+    default_family: String,
+}
+
+impl Default for FontDescription {
+    fn default() -> Self {
+        Self {
+            families: vec!["sans-serif".to_string()],
+            size: 16.,
+            weight: 400.,
+            style: FontStyle::Normal,
+            stretch: 100.,
+        }
+    }
+}
+
+impl FontMetrics {
+    /// Metrics to use when they can't be read from the font
+    /// https://www.w3.org/TR/css-values-4/#ex
+    pub fn fallback(font_size: f32) -> Self {
+        Self {
+            x_height: font_size * 0.5,
+            zero_advance: font_size * 0.5,
+            line_height: font_size * 1.2,
+            ascent: font_size * 0.8,
+        }
+    }
+}
+
+impl FontFace {
+    pub fn is_same_face(&self, other: &FontFace) -> bool {
+        self.family.eq_ignore_ascii_case(&other.family)
+            && self.weight == other.weight
+            && self.style == other.style
+            && self.stretch == other.stretch
+            && Arc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl FontFaceSet {
+    /// Create a set where generic families & unavailable families are
+    /// resolved to `default_family`
+    pub fn new(default_family: &str) -> Self {
+        Self {
+            faces: Vec::new(),
+            web_faces: Vec::new(),
+            default_family: default_family.to_string(),
+        }
+    }
+
+    /// Add a face to the set & return its index. Adding a face that is
+    /// already in the set returns the index of the existing face.
+    pub fn add(&mut self, face: FontFace) -> usize {
+        let existing = self.faces.iter().position(|f| match f {
+            Some(f) => f.is_same_face(&face),
+            None => false,
+        });
+        if let Some(index) = existing {
+            return index;
+        }
+        self.faces.push(Some(face));
+        self.faces.len() - 1
+    }
+
+    /// Replace the faces of the current document with the faces of a new
+    /// one. The faces of the previous document that the new one doesn't
+    /// have are removed, so that they can't be matched by its text.
+    pub fn set_web_faces(&mut self, faces: Vec<FontFace>) -> Vec<usize> {
+        let web_faces = faces
+            .into_iter()
+            .map(|face| self.add(face))
+            .collect::<Vec<usize>>();
+        for index in &self.web_faces {
+            if !web_faces.contains(index) {
+                self.faces[*index] = None;
+            }
+        }
+        self.web_faces = web_faces.clone();
+        web_faces
+    }
+
+    pub fn get(&self, index: usize) -> Option<&FontFace> {
+        self.faces.get(index).and_then(Option::as_ref)
+    }
+
+    pub fn len(&self) -> usize {
+        self.faces.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find the face that best matches the description. The families are
+    /// tried in order, falling back to the default family.
+    /// https://www.w3.org/TR/css-fonts-4/#font-style-matching
+    pub fn find(&self, description: &FontDescription) -> Option<usize> {
+        description
+            .families
+            .iter()
+            .map(|family| {
+                if GENERIC_FAMILIES.contains(&family.to_lowercase().as_str()) {
+                    &self.default_family
+                } else {
+                    family
+                }
+            })
+            .chain(std::iter::once(&self.default_family))
+            .find_map(|family| self.find_in_family(family, description))
+    }
+
+    fn find_in_family(&self, family: &str, description: &FontDescription) -> Option<usize> {
+        let candidates = self
+            .faces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, face)| face.as_ref().map(|face| (index, face)))
+            .filter(|(_, face)| face.family.eq_ignore_ascii_case(family))
+            .collect::<Vec<(usize, &FontFace)>>();
+
+        // narrow down the candidates by stretch, then style, then weight
+        let candidates = closest(candidates, |face| {
+            stretch_distance(face.stretch, description.stretch)
+        });
+        let candidates = closest(candidates, |face| {
+            style_distance(face.style, description.style)
+        });
+        let candidates = closest(candidates, |face| {
+            weight_distance(face.weight, description.weight)
+        });
+
+        candidates.first().map(|(index, _)| *index)
+    }
+}
+
+/// Keep only the candidates with the smallest distance
+fn closest<F: Fn(&FontFace) -> f32>(
+    candidates: Vec<(usize, &FontFace)>,
+    distance: F,
+) -> Vec<(usize, &FontFace)> {
+    let min_distance = candidates
+        .iter()
+        .map(|(_, face)| distance(face))
+        .fold(f32::INFINITY, f32::min);
+
+    candidates
+        .into_iter()
+        .filter(|(_, face)| distance(face) == min_distance)
+        .collect()
+}
+
+/// Narrower faces are preferred for condensed values, wider faces for
+/// expanded values.
+fn stretch_distance((min, max): (f32, f32), desired: f32) -> f32 {
+    if min <= desired && desired <= max {
+        0.
+    } else if desired <= 100. {
+        if max < desired {
+            desired - max
+        } else {
+            1000. + min - desired
+        }
+    } else if min > desired {
+        min - desired
+    } else {
+        1000. + desired - max
+    }
+}
+
+fn style_distance(style: FontStyle, desired: FontStyle) -> f32 {
+    let preferences = match desired {
+        FontStyle::Normal => [FontStyle::Normal, FontStyle::Oblique, FontStyle::Italic],
+        FontStyle::Italic => [FontStyle::Italic, FontStyle::Oblique, FontStyle::Normal],
+        FontStyle::Oblique => [FontStyle::Oblique, FontStyle::Italic, FontStyle::Normal],
+    };
+    preferences.iter().position(|s| *s == style).unwrap() as f32
+}
+
+/// For weights between 400 & 500, heavier weights up to 500 are preferred,
+/// then lighter weights, then heavier weights. Lighter weights are
+/// preferred below 400 & heavier weights above 500.
+fn weight_distance((min, max): (f32, f32), desired: f32) -> f32 {
+    if min <= desired && desired <= max {
+        0.
+    } else if (400. ..=500.).contains(&desired) {
+        if min > desired && min <= 500. {
+            min - desired
+        } else if max < desired {
+            1000. + desired - max
+        } else {
+            2000. + min - desired
+        }
+    } else if desired < 400. {
+        if max < desired {
+            desired - max
+        } else {
+            1000. + min - desired
+        }
+    } else if min > desired {
+        min - desired
+    } else {
+        1000. + desired - max
+    }
+}
+
+/// Percentage of the normal width of a `font-stretch` keyword
+/// https://www.w3.org/TR/css-fonts-4/#font-stretch-prop
+pub fn stretch_keyword(keyword: &str) -> Option<f32> {
+    match keyword.to_lowercase().as_str() {
+        "ultra-condensed" => Some(50.),
+        "extra-condensed" => Some(62.5),
+        "condensed" => Some(75.),
+        "semi-condensed" => Some(87.5),
+        "normal" => Some(100.),
+        "semi-expanded" => Some(112.5),
+        "expanded" => Some(125.),
+        "extra-expanded" => Some(150.),
+        "ultra-expanded" => Some(200.),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(family: &str, weight: f32, style: FontStyle) -> FontFace {
+        FontFace {
+            family: family.to_string(),
+            weight: (weight, weight),
+            style,
+            stretch: (100., 100.),
+            data: Arc::new(Vec::new()),
+        }
+    }
+
+    fn description(families: &[&str], weight: f32, style: FontStyle) -> FontDescription {
+        FontDescription {
+            families: families.iter().map(|family| family.to_string()).collect(),
+            weight,
+            style,
+            ..FontDescription::default()
+        }
+    }
+
+    #[test]
+    fn match_font_faces() {
+        let mut set = FontFaceSet::new("Lato");
+        let lato = set.add(face("Lato", 400., FontStyle::Normal));
+        let lato_bold = set.add(face("Lato", 700., FontStyle::Normal));
+        let light = set.add(face("Open Sans", 300., FontStyle::Normal));
+        let regular = set.add(face("Open Sans", 400., FontStyle::Normal));
+        let semibold = set.add(face("Open Sans", 600., FontStyle::Normal));
+        let italic = set.add(face("Open Sans", 400., FontStyle::Italic));
+
+        let find = |families: &[&str], weight: f32, style: FontStyle| {
+            set.find(&description(families, weight, style))
+        };
+
+        assert_eq!(find(&["open sans"], 400., FontStyle::Normal), Some(regular));
+        assert_eq!(find(&["Open Sans"], 500., FontStyle::Normal), Some(regular));
+        assert_eq!(
+            find(&["Open Sans"], 700., FontStyle::Normal),
+            Some(semibold)
+        );
+        assert_eq!(find(&["Open Sans"], 200., FontStyle::Normal), Some(light));
+        assert_eq!(find(&["Open Sans"], 350., FontStyle::Normal), Some(light));
+        assert_eq!(find(&["Open Sans"], 700., FontStyle::Italic), Some(italic));
+        assert_eq!(find(&["Open Sans"], 400., FontStyle::Oblique), Some(italic));
+
+        // unavailable & generic families fall back to the default family
+        assert_eq!(
+            find(&["Unknown", "serif"], 400., FontStyle::Normal),
+            Some(lato)
+        );
+        assert_eq!(find(&["Unknown"], 800., FontStyle::Normal), Some(lato_bold));
+        assert_eq!(
+            find(&["Unknown", "Open Sans"], 400., FontStyle::Normal),
+            Some(regular)
+        );
+    }
+
+    #[test]
+    fn add_same_face_once() {
+        let mut set = FontFaceSet::new("Lato");
+        let lato = face("Lato", 400., FontStyle::Normal);
+        assert_eq!(set.add(lato.clone()), 0);
+        assert_eq!(set.add(face("Lato", 400., FontStyle::Normal)), 1);
+        assert_eq!(set.add(lato), 0);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn replace_web_faces() {
+        let mut set = FontFaceSet::new("Lato");
+        let lato = set.add(face("Lato", 400., FontStyle::Normal));
+        let open_sans = face("Open Sans", 400., FontStyle::Normal);
+        let roboto = face("Roboto", 400., FontStyle::Normal);
+        let find = |set: &FontFaceSet, family: &str| {
+            set.find(&description(&[family], 400., FontStyle::Normal))
+        };
+
+        assert_eq!(
+            set.set_web_faces(vec![open_sans.clone(), roboto.clone()]),
+            vec![1, 2]
+        );
+        assert_eq!(find(&set, "Roboto"), Some(2));
+        // the faces of a document are added once
+        assert_eq!(
+            set.set_web_faces(vec![open_sans, roboto.clone()]),
+            vec![1, 2]
+        );
+
+        // the faces of the previous document are removed, without reusing
+        // their index
+        let noto = face("Noto", 400., FontStyle::Normal);
+        assert_eq!(set.set_web_faces(vec![noto, roboto]), vec![3, 2]);
+        assert_eq!(find(&set, "Open Sans"), Some(lato));
+        assert!(set.get(1).is_none());
+        assert_eq!(find(&set, "Noto"), Some(3));
+        assert_eq!(set.len(), 3);
+
+        set.set_web_faces(Vec::new());
+        assert_eq!(find(&set, "Noto"), Some(lato));
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -35,6 +35,16 @@ where
         }
     }
 
+    /// Buffer the source until there are at least `n` items in the buffer
+    fn consume_source_to_buffer_until(&mut self, n: usize) {
+        while self.buffer.len() < n {
+            match self.source.next() {
+                Some(item) => self.buffer.push_back(item),
+                None => return,
+            }
+        }
+    }
+
     pub fn next(&mut self) -> Option<I> {
         let is_reconsume = self.is_reconsume;
         self.is_reconsume = false;
@@ -61,14 +71,14 @@ where
     }
 
     pub fn peek_next(&mut self, n: usize) -> Option<Vec<I>> {
-        self.consume_source_to_buffer();
+        let n = if self.is_reconsume { n - 1 } else { n };
+
+        self.consume_source_to_buffer_until(n);
 
         if self.buffer.len() < n {
             return None;
         }
 
-        let n = if self.is_reconsume { n - 1 } else { n };
-
         let mut result = self
             .buffer
             .iter()
@@ -86,14 +96,14 @@ where
     }
 
     pub fn peek_next_as<S: FromIterator<I>>(&mut self, n: usize) -> Option<S> {
-        self.consume_source_to_buffer();
+        let n = if self.is_reconsume { n - 1 } else { n };
+
+        self.consume_source_to_buffer_until(n);
 
         if self.buffer.len() < n {
             return None;
         }
 
-        let n = if self.is_reconsume { n - 1 } else { n };
-
         let mut result = self
             .buffer
             .iter()
@@ -132,3 +142,33 @@ where
         self.is_reconsume = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peek_past_buffered_items() {
+        let mut stream = CharInputStream::new("abcdef".chars());
+
+        // `a` is buffered by the peek, the others are read from the source
+        assert_eq!(stream.peek(), Some('a'));
+        assert_eq!(stream.peek_next(3), Some(vec!['a', 'b', 'c']));
+        assert_eq!(stream.next(), Some('a'));
+        assert_eq!(stream.next(), Some('b'));
+        assert_eq!(stream.peek_next_as::<String>(2), Some("cd".to_string()));
+        assert_eq!(stream.next(), Some('c'));
+
+        // the reconsumed item comes first
+        stream.reconsume();
+        assert_eq!(stream.peek_next(3), Some(vec!['c', 'd', 'e']));
+        assert_eq!(stream.next(), Some('c'));
+        assert_eq!(stream.next(), Some('d'));
+
+        assert_eq!(stream.peek_next(3), None);
+        assert_eq!(stream.peek_max(), vec!['e', 'f']);
+        assert_eq!(stream.next(), Some('e'));
+        assert_eq!(stream.next(), Some('f'));
+        assert_eq!(stream.next(), None);
+    }
+}
@@ -1,6 +1,7 @@
 pub mod byte_string;
 pub mod color;
 pub mod data_stream;
+pub mod font;
 pub mod input_stream;
 pub mod primitive;
 pub mod tree_node;
@@ -0,0 +1,10 @@
+/// How a line is painted, e.g. a text decoration line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    /// Two parallel lines
+    Double,
+    Dotted,
+    Dashed,
+    Wavy,
+}
@@ -1,10 +1,12 @@
 pub mod edge;
+pub mod line_style;
 pub mod point;
 pub mod rect;
 pub mod rrect;
 pub mod size;
 
 pub use edge::*;
+pub use line_style::*;
 pub use point::*;
 pub use rect::*;
 pub use rrect::*;
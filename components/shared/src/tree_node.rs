@@ -15,6 +15,7 @@ pub type NullableNode<T> = Option<TreeNode<T>>;
 pub trait TreeNodeHooks<T: TreeNodeHooks<T> + Debug> {
     fn on_inserted(&self, current: TreeNode<T>, parent: TreeNode<T>) {}
     fn on_children_updated(&self, current: TreeNode<T>) {}
+    fn on_removed(&self, current: TreeNode<T>, parent: TreeNode<T>) {}
 }
 
 pub struct Node<T: TreeNodeHooks<T> + Debug> {
@@ -77,6 +78,7 @@ impl<T: TreeNodeHooks<T> + Debug> TreeNode<T> {
 
     /// Detach node from the parent
     pub fn detach(&self) {
+        let parent = self.parent();
         if let Some(previous_sibling) = self.prev_sibling() {
             previous_sibling.next_sibling.replace(self.next_sibling());
         }
@@ -85,7 +87,7 @@ impl<T: TreeNodeHooks<T> + Debug> TreeNode<T> {
                 .prev_sibling
                 .replace(self.prev_sibling.borrow().clone());
         }
-        if let Some(parent) = self.parent() {
+        if let Some(parent) = &parent {
             let first_child = parent.first_child().unwrap();
             let last_child = parent.last_child().unwrap();
 
@@ -99,6 +101,11 @@ impl<T: TreeNodeHooks<T> + Debug> TreeNode<T> {
         self.parent_node.replace(None);
         self.prev_sibling.replace(None);
         self.next_sibling.replace(None);
+
+        if let Some(parent) = parent {
+            self.data.on_removed(self.clone(), parent.clone());
+            parent.data.on_children_updated(parent.clone());
+        }
     }
 
     pub fn for_each_child<F>(&self, mut callback: F)
@@ -181,6 +188,27 @@ impl<T: TreeNodeHooks<T> + Debug> TreeNode<T> {
         ChildrenIterator::new(self.clone())
     }
 
+    /// Previous siblings of the node, from the closest one to the first child
+    pub fn iterate_prev_siblings(&self) -> SiblingsIterator<T> {
+        SiblingsIterator {
+            current_node: self.prev_sibling(),
+            forward: false,
+        }
+    }
+
+    /// Next siblings of the node, from the closest one to the last child
+    pub fn iterate_next_siblings(&self) -> SiblingsIterator<T> {
+        SiblingsIterator {
+            current_node: self.next_sibling(),
+            forward: true,
+        }
+    }
+
+    /// Index of the node among its siblings
+    pub fn sibling_index(&self) -> usize {
+        self.iterate_prev_siblings().count()
+    }
+
     pub fn has_no_child(&self) -> bool {
         self.first_child().is_none()
     }
@@ -289,6 +317,10 @@ impl<T: TreeNodeHooks<T> + Debug> TreeNode<T> {
                 .prev_sibling
                 .replace(Some(WeakTreeNode::from(child.clone())));
             child.next_sibling.replace(Some(ref_child));
+
+            // trigger hook callback
+            child.data.on_inserted(child.clone(), self.clone());
+            self.data.on_children_updated(self.clone());
         } else {
             self.append_child(child);
         }
@@ -412,6 +444,27 @@ impl<T: TreeNodeHooks<T> + Debug> DoubleEndedIterator for ChildrenIterator<T> {
     }
 }
 
+pub struct SiblingsIterator<T: TreeNodeHooks<T> + Debug> {
+    current_node: Option<TreeNode<T>>,
+    forward: bool,
+}
+
+impl<T: TreeNodeHooks<T> + Debug> Iterator for SiblingsIterator<T> {
+    type Item = TreeNode<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current_node.take();
+        self.current_node = current.as_ref().and_then(|node| {
+            if self.forward {
+                node.next_sibling()
+            } else {
+                node.prev_sibling()
+            }
+        });
+        current
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -641,4 +694,30 @@ mod test {
         let deepest_node = parent.find_first_deepest_decendant(|node| node.data.data == 2);
         assert_node_eq(deepest_node, Some(level2_node1));
     }
+
+    #[test]
+    fn iterate_siblings() {
+        let parent = TreeNode::new(TestNodeWithData { data: 0 });
+        let child1 = TreeNode::new(TestNodeWithData { data: 1 });
+        let child2 = TreeNode::new(TestNodeWithData { data: 2 });
+        let child3 = TreeNode::new(TestNodeWithData { data: 3 });
+
+        parent.append_child(child1.clone());
+        parent.append_child(child2.clone());
+        parent.append_child(child3.clone());
+
+        let prev: Vec<u8> = child3
+            .iterate_prev_siblings()
+            .map(|n| n.data.data)
+            .collect();
+        let next: Vec<u8> = child1
+            .iterate_next_siblings()
+            .map(|n| n.data.data)
+            .collect();
+
+        assert_eq!(prev, vec![2, 1]);
+        assert_eq!(next, vec![2, 3]);
+        assert_eq!(child1.sibling_index(), 0);
+        assert_eq!(child3.sibling_index(), 2);
+    }
 }
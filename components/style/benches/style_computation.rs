@@ -0,0 +1,120 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use css::cssom::css_rule::CSSRule;
+use dom::node::NodePtr;
+use html::tokenizer::Tokenizer;
+use html::tree_builder::TreeBuilder;
+use style::bloom_filter::AncestorBloomFilter;
+use style::compute::compute_styles;
+use style::rule_map::RuleMap;
+use style::style_sharing::StyleSharingCache;
+use style_types::{CSSLocation, CascadeOrigin, ContextualRule, UNLAYERED};
+use test_utils::css::parse_stylesheet;
+
+/// A stylesheet in the shape of a large CSS framework: mostly utility
+/// classes, with some type, ID, descendant & pseudo-class selectors
+fn framework_css() -> String {
+    let mut css = String::new();
+    for i in 0..1000 {
+        css.push_str(&format!(".m-{i} {{ margin: {i}px; }}\n", i = i));
+        css.push_str(&format!(".col-{i} {{ width: {i}px; }}\n", i = i));
+        css.push_str(&format!(".strand.s{i} {{ border-width: {i}px; }}\n", i = i));
+        css.push_str(&format!("div > .card-{i} span {{ color: red; }}\n", i = i));
+        css.push_str(&format!("#section-{i} {{ padding: {i}px; }}\n", i = i));
+    }
+    css.push_str("div { display: block; } span, a { display: inline; }\n");
+    css.push_str("a:hover { color: blue; } * { box-sizing: border-box; }\n");
+    css
+}
+
+fn style_rules(css: &str) -> Vec<ContextualRule> {
+    parse_stylesheet(css)
+        .iter()
+        .filter_map(|rule| match rule {
+            CSSRule::Style(style) => Some(ContextualRule {
+                inner: style.clone(),
+                location: CSSLocation::Embedded,
+                origin: CascadeOrigin::Author,
+                layer: UNLAYERED,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn compute_tree(node: NodePtr, rule_map: &RuleMap, bloom_filter: &mut AncestorBloomFilter) {
+    let computed_style = compute_styles(node.clone(), rule_map, Some(bloom_filter));
+    node.set_computed_style(computed_style);
+
+    match node.as_element_opt() {
+        Some(element) => {
+            bloom_filter.push_element(element);
+            node.for_each_child(|child| compute_tree(NodePtr(child), rule_map, bloom_filter));
+            bloom_filter.pop_element(element);
+        }
+        None => node.for_each_child(|child| compute_tree(NodePtr(child), rule_map, bloom_filter)),
+    }
+}
+
+/// Same as `compute_tree`, with the styles of siblings shared when possible
+fn compute_tree_with_sharing(
+    node: NodePtr,
+    rule_map: &RuleMap,
+    bloom_filter: &mut AncestorBloomFilter,
+    sharing_cache: &mut StyleSharingCache,
+) {
+    if !sharing_cache.share_style(&node, rule_map, Some(bloom_filter)) {
+        let computed_style = compute_styles(node.clone(), rule_map, Some(bloom_filter));
+        node.set_computed_style(computed_style);
+        sharing_cache.insert(node.clone());
+    }
+
+    let mut children_cache = StyleSharingCache::new();
+    let mut compute_children = |bloom_filter: &mut AncestorBloomFilter| {
+        node.for_each_child(|child| {
+            compute_tree_with_sharing(NodePtr(child), rule_map, bloom_filter, &mut children_cache)
+        })
+    };
+    match node.as_element_opt() {
+        Some(element) => {
+            bloom_filter.push_element(element);
+            compute_children(bloom_filter);
+            bloom_filter.pop_element(element);
+        }
+        None => compute_children(bloom_filter),
+    }
+}
+
+fn style_computation_benchmark(c: &mut Criterion) {
+    let html = include_str!("../../html/benches/purecss_gaze.html");
+    let document = TreeBuilder::default(Tokenizer::new(html.chars())).run();
+    let rules = style_rules(&framework_css());
+
+    c.bench_function("build_rule_map", |b| {
+        b.iter(|| RuleMap::new(black_box(rules.clone())))
+    });
+
+    let rule_map = RuleMap::new(rules);
+    c.bench_function("compute_styles_purecss_gaze", |b| {
+        b.iter(|| {
+            compute_tree(
+                black_box(document.clone()),
+                &rule_map,
+                &mut AncestorBloomFilter::new(),
+            )
+        })
+    });
+
+    c.bench_function("compute_styles_purecss_gaze_with_sharing", |b| {
+        b.iter(|| {
+            compute_tree_with_sharing(
+                black_box(document.clone()),
+                &rule_map,
+                &mut AncestorBloomFilter::new(),
+                &mut StyleSharingCache::new(),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, style_computation_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,186 @@
+use css::selector::structs::{Combinator, Selector, SimpleSelectorType};
+use dom::{element::Element, node::NodePtr};
+
+const FILTER_BITS: u32 = 12;
+const FILTER_SIZE: usize = 1 << FILTER_BITS;
+const FILTER_MASK: u32 = FILTER_SIZE as u32 - 1;
+
+const TAG_SALT: u8 = b't';
+const ID_SALT: u8 = b'#';
+const CLASS_SALT: u8 = b'.';
+
+/// A counting bloom filter of the tags, ids & classes of the ancestors of the
+/// element being styled. It is used to quickly reject selectors that require
+/// an ancestor that does not exist, without walking up the tree.
+///
+/// The filter is maintained during the tree walk of style computation: an
+/// element is pushed before its children are styled & popped after.
+pub struct AncestorBloomFilter {
+    counters: Box<[u8; FILTER_SIZE]>,
+}
+
+impl AncestorBloomFilter {
+    pub fn new() -> Self {
+        Self {
+            counters: Box::new([0; FILTER_SIZE]),
+        }
+    }
+
+    /// Create a filter for styling the children of `node`, which contains
+    /// `node` & all of its ancestors.
+    pub fn for_children_of(node: &NodePtr) -> Self {
+        let mut filter = Self::new();
+        let mut current = Some(node.0.clone());
+        while let Some(node) = current {
+            if let Some(element) = node.as_element_opt() {
+                filter.push_element(element);
+            }
+            current = node.parent();
+        }
+        filter
+    }
+
+    pub fn push_element(&mut self, element: &Element) {
+        for hash in element_hashes(element) {
+            self.insert_hash(hash);
+        }
+    }
+
+    pub fn pop_element(&mut self, element: &Element) {
+        for hash in element_hashes(element) {
+            self.remove_hash(hash);
+        }
+    }
+
+    /// Return `false` if the selector can't match because one of the
+    /// ancestors it requires is definitely missing.
+    pub fn might_match(&self, selector: &Selector) -> bool {
+        let values = selector.values();
+        values.iter().all(|(sequence, combinator)| {
+            // only compound selectors followed by a child or descendant
+            // combinator are guaranteed to match an ancestor of the element
+            if !matches!(
+                combinator,
+                Some(Combinator::Child) | Some(Combinator::Descendant)
+            ) {
+                return true;
+            }
+
+            sequence.values().iter().all(|simple_selector| {
+                let value = match simple_selector.value() {
+                    Some(value) => value,
+                    None => return true,
+                };
+                let hash = match simple_selector.selector_type() {
+                    SimpleSelectorType::Type => hash(TAG_SALT, &value.to_lowercase()),
+                    SimpleSelectorType::ID => hash(ID_SALT, value),
+                    SimpleSelectorType::Class => hash(CLASS_SALT, value),
+                    _ => return true,
+                };
+                self.might_contain_hash(hash)
+            })
+        })
+    }
+
+    fn insert_hash(&mut self, hash: u32) {
+        for index in indexes(hash) {
+            let counter = &mut self.counters[index];
+            *counter = counter.saturating_add(1);
+        }
+    }
+
+    fn remove_hash(&mut self, hash: u32) {
+        for index in indexes(hash) {
+            let counter = &mut self.counters[index];
+            // a saturated counter can't tell how many elements it holds,
+            // so it stays saturated to avoid false negatives.
+            if *counter != u8::MAX {
+                *counter = counter.saturating_sub(1);
+            }
+        }
+    }
+
+    fn might_contain_hash(&self, hash: u32) -> bool {
+        indexes(hash).iter().all(|index| self.counters[*index] != 0)
+    }
+}
+
+impl Default for AncestorBloomFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn indexes(hash: u32) -> [usize; 2] {
+    [
+        (hash & FILTER_MASK) as usize,
+        ((hash >> FILTER_BITS) & FILTER_MASK) as usize,
+    ]
+}
+
+fn element_hashes(element: &Element) -> Vec<u32> {
+    let mut hashes = vec![hash(TAG_SALT, &element.tag_name().to_lowercase())];
+    if let Some(id) = element.id() {
+        hashes.push(hash(ID_SALT, &id));
+    }
+    let class_list = element.class_list();
+    let class_list = class_list.borrow();
+    for index in 0..class_list.length() {
+        if let Some(class) = class_list.item(index) {
+            hashes.push(hash(CLASS_SALT, &class));
+        }
+    }
+    hashes
+}
+
+/// 32-bit FNV-1a
+fn hash(salt: u8, value: &str) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in std::iter::once(salt).chain(value.bytes()) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::selector::parse_selector_str;
+    use test_utils::dom_creator::{document, element};
+
+    #[test]
+    fn reject_missing_ancestors() {
+        let doc = document();
+        let child = element("span", doc.clone(), vec![]);
+        let parent = element("div#main.content", doc.clone(), vec![child.clone()]);
+
+        let mut filter = AncestorBloomFilter::new();
+        filter.push_element(parent.as_element());
+
+        let might_match = |css: &str| filter.might_match(&parse_selector_str(css).unwrap());
+
+        assert!(might_match("div span"));
+        assert!(might_match("#main > span"));
+        assert!(might_match(".content *"));
+        assert!(might_match("p + span"));
+        assert!(!might_match("p span"));
+        assert!(!might_match("#other > span"));
+        assert!(!might_match("div.other span"));
+
+        filter.pop_element(parent.as_element());
+        assert!(!filter.might_match(&parse_selector_str("div span").unwrap()));
+    }
+
+    #[test]
+    fn filter_for_children() {
+        let doc = document();
+        let child = element("span", doc.clone(), vec![]);
+        let parent = element("p", doc.clone(), vec![child.clone()]);
+        let _root = element("section.wrapper", doc.clone(), vec![parent.clone()]);
+
+        let filter = AncestorBloomFilter::for_children_of(&parent);
+        assert!(filter.might_match(&parse_selector_str(".wrapper p > span").unwrap()));
+        assert!(!filter.might_match(&parse_selector_str("article span").unwrap()));
+    }
+}
@@ -1,7 +1,10 @@
-use super::selector_matching::is_match_selectors;
+use super::bloom_filter::AncestorBloomFilter;
+use crate::custom_properties::has_var_references;
+use crate::rule_map::{MatchedRule, RuleMap};
+use css::cssom::style_rule::StyleRule;
 use css::parser::structs::ComponentValue;
 use css::parser::structs::Declaration;
-use css::selector::structs::Specificity;
+use css::selector::structs::{PseudoElement, Specificity};
 use css::tokenizer::token::Token;
 use dom::node::NodePtr;
 use std::cmp::{Ord, Ordering};
@@ -11,42 +14,181 @@ use style_types::CascadeOrigin;
 use style_types::ContextualRule;
 use style_types::Property;
 use style_types::Value;
+use style_types::UNLAYERED;
 
 use super::expand::prelude::*;
 
-type DeclaredValuesMap = HashMap<Property, Vec<PropertyDeclaration>>;
+type DeclaredValuesMap = HashMap<Property, Vec<PropertyDeclaration<DeclaredValue>>>;
+
+type DeclaredCustomValuesMap = HashMap<String, Vec<PropertyDeclaration<Vec<ComponentValue>>>>;
 
 pub type Properties = HashMap<Property, Value>;
 
 /// CSS property declaration for cascading
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct PropertyDeclaration {
-    pub value: Value,
+pub struct PropertyDeclaration<T = Value> {
+    pub value: T,
     pub important: bool,
     pub origin: CascadeOrigin,
     pub location: CSSLocation,
     pub specificity: Specificity,
+    /// Order of the cascade layer of the declaration within its origin
+    pub layer: u32,
+    /// Position of the declaration's rule among the style rules
+    pub source_order: usize,
+}
+
+/// Declared value of a property
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclaredValue {
+    Value(Value),
+    /// A value with `var()` references. It can only be parsed after the
+    /// references are substituted at computed-value time.
+    /// https://www.w3.org/TR/css-variables-1/#pending-substitution-value
+    PendingSubstitution {
+        values: Vec<ComponentValue>,
+        /// The shorthand that the property was declared with
+        shorthand: Option<String>,
+    },
+}
+
+/// The cascaded values of a node
+#[derive(Debug, Default)]
+pub struct CascadedValues {
+    pub properties: HashMap<Property, DeclaredValue>,
+    /// Unparsed values of the custom properties (`--*`)
+    pub custom_properties: HashMap<String, Vec<ComponentValue>>,
+}
+
+impl CascadedValues {
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty() && self.custom_properties.is_empty()
+    }
 }
 
-pub fn collect_cascaded_values(node: &NodePtr, rules: &[ContextualRule]) -> Properties {
+/// Collect the cascaded values of the node, or of its pseudo-element if
+/// `pseudo_element` is given.
+pub fn collect_cascaded_values(
+    node: &NodePtr,
+    rules: &RuleMap,
+    pseudo_element: Option<&PseudoElement>,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> CascadedValues {
     // https://www.w3.org/TR/css3-cascade/#value-stages
     // Step 1
-    let mut declared_values = collect_declared_values(&node, rules);
+    let (mut declared_values, mut declared_custom_values) =
+        collect_declared_values(node, rules, pseudo_element, bloom_filter);
 
     // Step 2
-    let cascade_values = declared_values
+    let properties = declared_values
         .iter_mut()
-        .map(|(property, values)| (property.clone(), cascade(values)))
-        .collect::<Properties>();
+        .filter_map(|(property, values)| Some((property.clone(), cascade(values)?)))
+        .collect();
+
+    let custom_properties = declared_custom_values
+        .iter_mut()
+        .filter_map(|(name, values)| Some((name.clone(), cascade(values)?)))
+        .collect();
+
+    CascadedValues {
+        properties,
+        custom_properties,
+    }
+}
+
+/// Rollback of the cascade requested by a declared value
+/// https://www.w3.org/TR/css-cascade-5/#rollback-origin
+enum Rollback {
+    /// `revert`, to the previous origin
+    Origin,
+    /// `revert-layer`, to the previous cascade layer
+    Layer,
+}
+
+trait CascadeValue {
+    fn rollback(&self) -> Option<Rollback>;
+}
+
+impl CascadeValue for Value {
+    fn rollback(&self) -> Option<Rollback> {
+        match self {
+            Value::Revert => Some(Rollback::Origin),
+            Value::RevertLayer => Some(Rollback::Layer),
+            _ => None,
+        }
+    }
+}
+
+impl CascadeValue for DeclaredValue {
+    fn rollback(&self) -> Option<Rollback> {
+        match self {
+            DeclaredValue::Value(value) => value.rollback(),
+            DeclaredValue::PendingSubstitution { .. } => None,
+        }
+    }
+}
+
+/// Unparsed values of custom properties
+impl CascadeValue for Vec<ComponentValue> {
+    fn rollback(&self) -> Option<Rollback> {
+        let mut values = self
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)));
+        match (values.next(), values.next()) {
+            (Some(ComponentValue::PerservedToken(Token::Ident(ident))), None) => {
+                match ident.to_lowercase().as_str() {
+                    "revert" => Some(Rollback::Origin),
+                    "revert-layer" => Some(Rollback::Layer),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
 
-    cascade_values
+/// Cascade sort the property declarations for a property and get the
+/// winning value. Returns `None` if all of the declarations are reverted,
+/// in which case the property has no cascaded value.
+fn cascade<T: Clone + CascadeValue>(declared_values: &mut [PropertyDeclaration<T>]) -> Option<T> {
+    declared_values.sort_by(cmp_declarations);
+
+    // `revert` rolls back the origin of the declaration & the origins that
+    // win over it without importance, whether the declaration is important
+    // or not. Only the precedence of the lowest rolled back origin is kept.
+    let mut reverted_origin: Option<u8> = None;
+    // the layers that are rolled back by `revert-layer`
+    let mut reverted_layers: Vec<(CascadeOrigin, u32)> = Vec::new();
+    for declaration in declared_values.iter().rev() {
+        let is_reverted = reverted_origin
+            .is_some_and(|reverted| origin_precedence(&declaration.origin) >= reverted)
+            || reverted_layers.iter().any(|(origin, layer)| {
+                *origin == declaration.origin && *layer == declaration.layer
+            });
+        if is_reverted {
+            continue;
+        }
+
+        match declaration.value.rollback() {
+            Some(Rollback::Origin) => {
+                reverted_origin = Some(origin_precedence(&declaration.origin))
+            }
+            Some(Rollback::Layer) => {
+                reverted_layers.push((declaration.origin.clone(), declaration.layer))
+            }
+            None => return Some(declaration.value.clone()),
+        }
+    }
+    None
 }
 
-/// Cascade sort the property declarations
-/// for a property and get the wining value
-fn cascade(declared_values: &mut Vec<PropertyDeclaration>) -> Value {
-    declared_values.sort();
-    declared_values.last().unwrap().value.clone()
+/// Precedence of the normal declarations of an origin
+fn origin_precedence(origin: &CascadeOrigin) -> u8 {
+    match origin {
+        CascadeOrigin::UserAgent => 0,
+        CascadeOrigin::User => 1,
+        CascadeOrigin::Author => 2,
+    }
 }
 
 /// Get a short-hand property expander
@@ -65,56 +207,194 @@ fn get_expander_shorthand_property(
         "border-right" => Some(&expand_border_right),
         "border-bottom" => Some(&expand_border_bottom),
         "border-left" => Some(&expand_border_left),
+        "text-decoration" => Some(&expand_text_decoration),
+        _ => None,
+    }
+}
+
+/// Get the longhands that a short-hand property sets
+pub(crate) fn get_shorthand_longhands(property: &str) -> Option<&'static [Property]> {
+    match property {
+        "margin" => Some(&[
+            Property::MarginTop,
+            Property::MarginRight,
+            Property::MarginBottom,
+            Property::MarginLeft,
+        ]),
+        "padding" => Some(&[
+            Property::PaddingTop,
+            Property::PaddingRight,
+            Property::PaddingBottom,
+            Property::PaddingLeft,
+        ]),
+        "border" => Some(&[
+            Property::BorderTopStyle,
+            Property::BorderRightStyle,
+            Property::BorderBottomStyle,
+            Property::BorderLeftStyle,
+            Property::BorderTopWidth,
+            Property::BorderRightWidth,
+            Property::BorderBottomWidth,
+            Property::BorderLeftWidth,
+            Property::BorderTopColor,
+            Property::BorderRightColor,
+            Property::BorderBottomColor,
+            Property::BorderLeftColor,
+        ]),
+        "border-style" => Some(&[
+            Property::BorderTopStyle,
+            Property::BorderRightStyle,
+            Property::BorderBottomStyle,
+            Property::BorderLeftStyle,
+        ]),
+        "border-width" => Some(&[
+            Property::BorderTopWidth,
+            Property::BorderRightWidth,
+            Property::BorderBottomWidth,
+            Property::BorderLeftWidth,
+        ]),
+        "border-color" => Some(&[
+            Property::BorderTopColor,
+            Property::BorderRightColor,
+            Property::BorderBottomColor,
+            Property::BorderLeftColor,
+        ]),
+        "border-radius" => Some(&[
+            Property::BorderTopLeftRadius,
+            Property::BorderTopRightRadius,
+            Property::BorderBottomRightRadius,
+            Property::BorderBottomLeftRadius,
+        ]),
+        "border-top" => Some(&[
+            Property::BorderTopStyle,
+            Property::BorderTopWidth,
+            Property::BorderTopColor,
+        ]),
+        "border-right" => Some(&[
+            Property::BorderRightStyle,
+            Property::BorderRightWidth,
+            Property::BorderRightColor,
+        ]),
+        "border-bottom" => Some(&[
+            Property::BorderBottomStyle,
+            Property::BorderBottomWidth,
+            Property::BorderBottomColor,
+        ]),
+        "border-left" => Some(&[
+            Property::BorderLeftStyle,
+            Property::BorderLeftWidth,
+            Property::BorderLeftColor,
+        ]),
+        "text-decoration" => Some(&[
+            Property::TextDecorationLine,
+            Property::TextDecorationStyle,
+            Property::TextDecorationColor,
+            Property::TextDecorationThickness,
+        ]),
         _ => None,
     }
 }
 
+/// Expand a short-hand property into its longhands. Return `None` if the
+/// property is not a short-hand.
+pub(crate) fn expand_shorthand(property: &str, values: &[ComponentValue]) -> Option<ExpandOutput> {
+    let expand = get_expander_shorthand_property(property)?;
+    let tokens = values
+        .split(|val| matches!(val, ComponentValue::PerservedToken(Token::Whitespace)))
+        .collect::<Vec<&[ComponentValue]>>();
+
+    Some(expand(&tokens))
+}
+
+fn new_declaration<T>(
+    value: T,
+    matched: &MatchedRule,
+    declaration: &Declaration,
+) -> PropertyDeclaration<T> {
+    PropertyDeclaration {
+        value,
+        important: declaration.important,
+        origin: matched.rule.origin.clone(),
+        location: matched.rule.location.clone(),
+        specificity: matched.specificity.clone(),
+        layer: matched.rule.layer,
+        source_order: matched.source_order,
+    }
+}
+
 /// Collect declared values for each property
 /// found in each style rule
-fn collect_declared_values(node: &NodePtr, rules: &[ContextualRule]) -> DeclaredValuesMap {
+fn collect_declared_values(
+    node: &NodePtr,
+    rules: &RuleMap,
+    pseudo_element: Option<&PseudoElement>,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> (DeclaredValuesMap, DeclaredCustomValuesMap) {
     let mut result: DeclaredValuesMap = HashMap::new();
+    let mut custom_result: DeclaredCustomValuesMap = HashMap::new();
 
     if !node.is_element() {
-        return result;
+        return (result, custom_result);
     }
 
-    let matched_rules = rules
-        .iter()
-        .filter(|rule| is_match_selectors(node, &rule.inner.selectors))
-        .collect::<Vec<&ContextualRule>>();
-
-    let mut insert_declaration =
-        |value: Value, property: Property, rule: &ContextualRule, declaration: &Declaration| {
-            let declaration = PropertyDeclaration {
-                value,
-                important: declaration.important,
-                origin: rule.origin.clone(),
-                location: rule.location.clone(),
-                specificity: rule.inner.specificity(),
-            };
-            if result.contains_key(&property) {
-                result.get_mut(&property).unwrap().push(declaration);
-            } else {
-                result.insert(property, vec![declaration]);
-            }
-        };
+    let mut matched_rules = rules.matched_rules(node, pseudo_element, bloom_filter);
+
+    // declarations of the `style` attribute apply to the element itself,
+    // not to its pseudo-elements
+    let inline_rule = ContextualRule {
+        inner: StyleRule::new(Vec::new(), node.as_element().style().to_vec()),
+        origin: CascadeOrigin::Author,
+        location: CSSLocation::Inline,
+        layer: UNLAYERED,
+    };
+    if pseudo_element.is_none() && !inline_rule.inner.declarations.is_empty() {
+        matched_rules.push(MatchedRule {
+            rule: &inline_rule,
+            specificity: Specificity::new(0, 0, 0),
+            source_order: rules.len(),
+        });
+    }
 
-    for rule in matched_rules {
-        for declaration in &rule.inner.declarations {
-            if let Some(expand) = get_expander_shorthand_property(&declaration.name) {
+    let mut insert_declaration = |value: DeclaredValue,
+                                  property: Property,
+                                  matched: &MatchedRule,
+                                  declaration: &Declaration| {
+        let declaration = new_declaration(value, matched, declaration);
+        result.entry(property).or_default().push(declaration);
+    };
+
+    for matched in &matched_rules {
+        for declaration in matched.rule.inner.declarations.iter() {
+            if declaration.name.starts_with("--") {
+                // custom property names are case-sensitive & their values
+                // are kept unparsed
+                let custom_declaration =
+                    new_declaration(declaration.value.clone(), matched, declaration);
+                custom_result
+                    .entry(declaration.name.clone())
+                    .or_default()
+                    .push(custom_declaration);
+            } else if has_var_references(&declaration.value) {
+                // the value is parsed after `var()` substitution
+                let pending = |shorthand: Option<String>| DeclaredValue::PendingSubstitution {
+                    values: declaration.value.clone(),
+                    shorthand,
+                };
+                if let Some(longhands) = get_shorthand_longhands(&declaration.name) {
+                    for property in longhands {
+                        let value = pending(Some(declaration.name.clone()));
+                        insert_declaration(value, property.clone(), matched, declaration);
+                    }
+                } else if let Some(property) = Property::parse(&declaration.name) {
+                    insert_declaration(pending(None), property, matched, declaration);
+                }
+            } else if let Some(values) = expand_shorthand(&declaration.name, &declaration.value) {
                 // process short hand property
-                let tokens = declaration
-                    .value
-                    .split(|val| match val {
-                        ComponentValue::PerservedToken(Token::Whitespace) => true,
-                        _ => false,
-                    })
-                    .collect::<Vec<&[ComponentValue]>>();
-
-                if let Some(values) = expand(&tokens) {
+                if let Some(values) = values {
                     for (property, value) in values {
                         if let Some(v) = value {
-                            insert_declaration(v, property, rule, declaration);
+                            let v = DeclaredValue::Value(v);
+                            insert_declaration(v, property, matched, declaration);
                         }
                     }
                 }
@@ -126,51 +406,66 @@ fn collect_declared_values(node: &NodePtr, rules: &[ContextualRule]) -> Declared
                     let value = Value::parse(&property, values);
 
                     if let Some(value) = value {
-                        insert_declaration(value, property, rule, declaration);
+                        let value = DeclaredValue::Value(value);
+                        insert_declaration(value, property, matched, declaration);
                     }
                 }
             }
         }
     }
 
-    result
+    (result, custom_result)
 }
 
 /// The implementation for ordering for cascade sort
+/// https://www.w3.org/TR/css-cascade-5/#cascade-sort
 ///
 /// These are the steps to compare the order:
-/// 1. Comparing the location of the property declaration (Inline, Embedded, etc.)
-/// 2. If step 1 result in equal ordering compare the cascade origin
-/// 3. If step 2 result in equal ordering compare the specificity
-impl Ord for PropertyDeclaration {
+/// 1. Comparing the cascade origin & importance
+/// 2. If step 1 result in equal ordering, declarations of the `style`
+///    attribute win
+/// 3. If step 2 result in equal ordering compare the cascade layers
+/// 4. If step 3 result in equal ordering compare the specificity
+/// 5. If step 4 result in equal ordering the last declared wins
+impl<T: Eq> Ord for PropertyDeclaration<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        match cmp_location(self, other) {
-            Ordering::Greater => Ordering::Greater,
-            Ordering::Less => Ordering::Less,
-            Ordering::Equal => match cmp_cascade_origin(self, other) {
-                Ordering::Greater => Ordering::Greater,
-                Ordering::Less => Ordering::Less,
-                Ordering::Equal => self.specificity.cmp(&other.specificity),
-            },
-        }
+        cmp_declarations(self, other)
     }
 }
 
-impl PartialOrd for PropertyDeclaration {
-    fn partial_cmp(&self, other: &PropertyDeclaration) -> Option<Ordering> {
+impl<T: Eq> PartialOrd for PropertyDeclaration<T> {
+    fn partial_cmp(&self, other: &PropertyDeclaration<T>) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-fn cmp_location(a: &PropertyDeclaration, b: &PropertyDeclaration) -> Ordering {
-    match (&a.location, &b.location) {
-        (CSSLocation::Inline, CSSLocation::Embedded)
-        | (CSSLocation::Inline, CSSLocation::External)
-        | (CSSLocation::Embedded, CSSLocation::External) => Ordering::Greater,
-        (CSSLocation::Inline, CSSLocation::Inline)
-        | (CSSLocation::Embedded, CSSLocation::Embedded)
-        | (CSSLocation::External, CSSLocation::External) => Ordering::Equal,
-        _ => Ordering::Less,
+/// Cascade sort order of declarations. Unlike `Ord`, it is available for
+/// values that can't be compared, e.g. unparsed values.
+fn cmp_declarations<T>(a: &PropertyDeclaration<T>, b: &PropertyDeclaration<T>) -> Ordering {
+    cmp_cascade_origin(a, b)
+        .then_with(|| cmp_location(a, b))
+        .then_with(|| cmp_layer(a, b))
+        .then_with(|| a.specificity.cmp(&b.specificity))
+        .then_with(|| a.source_order.cmp(&b.source_order))
+}
+
+/// Declarations of the `style` attribute win over the ones of stylesheets.
+/// Embedded & external stylesheets are ordered by their position in the
+/// document.
+fn cmp_location<T>(a: &PropertyDeclaration<T>, b: &PropertyDeclaration<T>) -> Ordering {
+    let is_inline =
+        |declaration: &PropertyDeclaration<T>| matches!(declaration.location, CSSLocation::Inline);
+    is_inline(a).cmp(&is_inline(b))
+}
+
+/// Later layers win for normal declarations, earlier layers win for
+/// important declarations. Both have the same importance at this point.
+/// https://www.w3.org/TR/css-cascade-5/#cascade-layering
+fn cmp_layer<T>(a: &PropertyDeclaration<T>, b: &PropertyDeclaration<T>) -> Ordering {
+    if a.important {
+        b.layer.cmp(&a.layer)
+    } else {
+        a.layer.cmp(&b.layer)
     }
 }
 
@@ -184,7 +479,7 @@ fn cmp_location(a: &PropertyDeclaration, b: &PropertyDeclaration) -> Ordering {
 /// 6. Normal author declarations
 /// 7. Normal user declarations
 /// 8. Normal user agent declarations
-fn cmp_cascade_origin(a: &PropertyDeclaration, b: &PropertyDeclaration) -> Ordering {
+fn cmp_cascade_origin<T>(a: &PropertyDeclaration<T>, b: &PropertyDeclaration<T>) -> Ordering {
     // -----------------
     // Rule #2 #3 #4 #5 #6 #7 #8
     match (a.important, b.important) {
@@ -233,6 +528,8 @@ mod tests {
             important: false,
             value: Value::Color(Color::black()),
             specificity: Specificity::new(1, 0, 1),
+            layer: UNLAYERED,
+            source_order: 1,
         };
 
         let b = PropertyDeclaration {
@@ -241,6 +538,8 @@ mod tests {
             important: false,
             value: Value::Color(Color::black()),
             specificity: Specificity::new(1, 0, 1),
+            layer: UNLAYERED,
+            source_order: 2,
         };
 
         let c = PropertyDeclaration {
@@ -249,12 +548,14 @@ mod tests {
             important: true,
             value: Value::Color(Color::black()),
             specificity: Specificity::new(1, 0, 1),
+            layer: UNLAYERED,
+            source_order: 3,
         };
 
         let mut declared = vec![a.clone(), b.clone(), c.clone()];
 
         let win = cascade(&mut declared);
-        assert_eq!(win, c.value);
+        assert_eq!(win, Some(c.value));
     }
 
     #[test]
@@ -288,6 +589,8 @@ mod tests {
             important: false,
             value: Value::Color(Color::black()),
             specificity: Specificity::new(0, 0, 0),
+            layer: UNLAYERED,
+            source_order: 4,
         };
 
         let b = PropertyDeclaration {
@@ -296,11 +599,13 @@ mod tests {
             important: false,
             value: Value::Color(Color::transparent()),
             specificity: Specificity::new(0, 0, 1),
+            layer: UNLAYERED,
+            source_order: 5,
         };
 
         let mut declared = vec![b.clone(), a.clone()];
 
         let win = cascade(&mut declared);
-        assert_eq!(win, b.value);
+        assert_eq!(win, Some(b.value));
     }
 }
@@ -1,152 +1,898 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
+use css::selector::structs::PseudoElement;
 use dom::node::NodePtr;
+use shared::font::{FontDescription, FontMetrics};
 use style_types::{
     values::{
         length::LengthUnit,
-        prelude::{BorderStyle, Color, Length, Percentage},
+        prelude::{
+            BorderRadius, Calc, Color, Length, LengthPercentage, LineHeight, Spacing, TabSize,
+            TextDecorationThickness,
+        },
     },
-    ContextualRule, Property, Value,
+    ComputedStyle, CustomProperties, Property, Value,
 };
 
-use crate::cascade::collect_cascaded_values;
+use crate::bloom_filter::AncestorBloomFilter;
+use crate::cascade::{collect_cascaded_values, expand_shorthand, DeclaredValue};
+use crate::custom_properties::{
+    compute_custom_properties, substitute_var_references, trim_whitespaces,
+};
+use crate::font::font_description;
+use crate::rule_map::RuleMap;
+
+/// Compute the style of a node. The bloom filter, if any, must hold the
+/// ancestors of the node. The computed custom properties are stored on the
+/// node for its descendants to inherit.
+pub fn compute_styles(
+    node: NodePtr,
+    rules: &RuleMap,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> ComputedStyle {
+    let cascaded = collect_cascaded_values(&node, rules, None, bloom_filter);
+    let parent = node.parent().map(NodePtr);
 
-pub fn compute_styles(node: NodePtr, rules: &[ContextualRule]) -> HashMap<Property, Value> {
-    let mut styles = collect_cascaded_values(&node, rules);
+    let inherited_custom_properties = parent
+        .as_ref()
+        .map(|parent| parent.custom_properties())
+        .unwrap_or_default();
+    let custom_properties =
+        compute_custom_properties(&cascaded.custom_properties, &inherited_custom_properties);
+    let declared = substitute_declared_values(cascaded.properties, &custom_properties);
+    node.set_custom_properties(custom_properties);
 
-    compute_default_values(&node, &mut styles);
-    compute_absolute_values(&node, &mut styles);
-    styles
+    let parent_style = parent.map(|parent| parent.computed_style().clone());
+    let root_font_size = match document_element(&node) {
+        Some(root) if Rc::ptr_eq(&root.0, &node.0) => None,
+        root => Some(font_size_of(root.as_ref())),
+    };
+    compute_style(&node, parent_style.as_ref(), declared, root_font_size)
 }
 
-fn compute_absolute_values(node: &NodePtr, styles: &mut HashMap<Property, Value>) {
-    let base_font_size = 16.;
-    let parent_font_size = node
-        .parent()
-        .map(|parent| {
-            NodePtr(parent)
-                .get_style(&Property::FontSize)
-                .to_absolute_px()
+/// Compute the styles of the pseudo-elements of an element. The style of
+/// the element must be computed first as pseudo-elements inherit from it.
+/// Pseudo-elements that no rule applies to are left out.
+pub fn compute_pseudo_element_styles(
+    node: NodePtr,
+    rules: &RuleMap,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> HashMap<PseudoElement, ComputedStyle> {
+    if !node.is_element() {
+        return HashMap::new();
+    }
+
+    let element_style = node.computed_style().clone();
+    // the style of the root element is computed before its pseudo-elements
+    let root_font_size = font_size_of(document_element(&node).as_ref());
+    PseudoElement::all()
+        .iter()
+        .filter_map(|pseudo_element| {
+            let cascaded =
+                collect_cascaded_values(&node, rules, Some(pseudo_element), bloom_filter);
+            if cascaded.is_empty() {
+                return None;
+            }
+            let custom_properties =
+                compute_custom_properties(&cascaded.custom_properties, &node.custom_properties());
+            let declared = substitute_declared_values(cascaded.properties, &custom_properties);
+            let style = compute_style(&node, Some(&element_style), declared, Some(root_font_size));
+            Some((pseudo_element.clone(), style))
         })
-        .unwrap_or(base_font_size);
-
-    let root_font_size = node
-        .owner_document()
-        .map(|root| {
-            NodePtr(root)
-                .get_style(&Property::FontSize)
-                .to_absolute_px()
+        .collect()
+}
+
+/// Compute a style from the declared values. The style starts from the
+/// inherited properties of `parent`, the style the node inherits from, and
+/// the initial non-inherited properties. Both stay shared as long as the
+/// declared values don't change them. `rem` is relative to `root_font_size`,
+/// which is `None` for the root element itself.
+fn compute_style(
+    node: &NodePtr,
+    parent: Option<&ComputedStyle>,
+    declared: HashMap<Property, Value>,
+    root_font_size: Option<f32>,
+) -> ComputedStyle {
+    let mut style = match parent {
+        Some(parent) => ComputedStyle::inherit_from(parent),
+        None => ComputedStyle::initial(),
+    };
+    if declared.is_empty() {
+        return style;
+    }
+
+    let specified = compute_specified_values(parent, declared);
+    compute_absolute_values(node, parent, specified, &mut style, root_font_size);
+    style
+}
+
+/// The root element of the document of the node, whose font size `rem` is
+/// relative to
+fn document_element(node: &NodePtr) -> Option<NodePtr> {
+    let document = node.owner_document().map(NodePtr)?;
+    document
+        .iterate_children()
+        .find(|child| child.is_element())
+        .map(NodePtr)
+}
+
+/// The computed font size of the element in pixels, or the initial font
+/// size if there's no element
+fn font_size_of(element: Option<&NodePtr>) -> f32 {
+    match element {
+        Some(element) => element.computed_style().font_size().to_px(),
+        None => ComputedStyle::initial().font_size().to_px(),
+    }
+}
+
+/// Substitute the `var()` references of the declared values & parse them.
+/// A value that is invalid after substitution behaves as `unset`.
+/// https://www.w3.org/TR/css-variables-1/#invalid-at-computed-value-time
+fn substitute_declared_values(
+    declared_values: HashMap<Property, DeclaredValue>,
+    custom_properties: &CustomProperties,
+) -> HashMap<Property, Value> {
+    declared_values
+        .into_iter()
+        .map(|(property, value)| {
+            let value = match value {
+                DeclaredValue::Value(value) => value,
+                DeclaredValue::PendingSubstitution { values, shorthand } => {
+                    substitute_var_references(&values, custom_properties)
+                        .and_then(|values| {
+                            let values = trim_whitespaces(&values);
+                            match shorthand {
+                                Some(shorthand) => expand_shorthand(&shorthand, values)
+                                    .flatten()
+                                    .and_then(|longhands| {
+                                        longhands
+                                            .into_iter()
+                                            .find(|(longhand, _)| *longhand == property)
+                                            .and_then(|(_, value)| value)
+                                    }),
+                                None => Value::parse(&property, values),
+                            }
+                        })
+                        .unwrap_or(Value::Unset)
+                }
+            };
+            (property, value)
         })
-        .unwrap_or(base_font_size);
+        .collect()
+}
+
+/// What relative lengths are resolved against at computed-value time
+struct LengthContext<'a> {
+    document: Option<&'a NodePtr>,
+    font: FontDescription,
+    /// The computed line height that `lh` is relative to
+    line_height: LineHeight,
+    root_font_size: f32,
+    font_metrics: RefCell<Option<FontMetrics>>,
+}
+
+impl<'a> LengthContext<'a> {
+    fn new(
+        document: Option<&'a NodePtr>,
+        font: FontDescription,
+        line_height: LineHeight,
+        root_font_size: f32,
+    ) -> Self {
+        Self {
+            document,
+            font,
+            line_height,
+            root_font_size,
+            font_metrics: RefCell::new(None),
+        }
+    }
+
+    /// The metrics are only read from the font when they are used
+    fn font_metrics(&self) -> FontMetrics {
+        *self.font_metrics.borrow_mut().get_or_insert_with(|| {
+            match self
+                .document
+                .and_then(|document| document.as_document_opt())
+            {
+                Some(document) => document.font_metrics(&self.font),
+                None => FontMetrics::fallback(self.font.size),
+            }
+        })
+    }
+
+    /// Resolve a length to pixels
+    /// https://www.w3.org/TR/css-values-4/#lengths
+    fn resolve(&self, length: &Length) -> Length {
+        if length.unit.is_viewport_relative() {
+            return Length::new_px(self.resolve_viewport_relative(length));
+        }
+
+        let value = length.value.0;
+        let px = match length.unit {
+            LengthUnit::Em => value * self.font.size,
+            LengthUnit::Rem => value * self.root_font_size,
+            LengthUnit::Ex => value * self.font_metrics().x_height,
+            LengthUnit::Ch => value * self.font_metrics().zero_advance,
+            LengthUnit::Lh => value * self.line_height(),
+            _ => length.to_px(),
+        };
+        Length::new_px(px)
+    }
+
+    /// The height of a line in pixels
+    fn line_height(&self) -> f32 {
+        self.line_height
+            .to_px(self.font.size)
+            .unwrap_or_else(|| self.font_metrics().line_height)
+    }
 
-    let mut updates = Vec::new();
-    for (property, value) in styles.iter() {
+    fn resolve_viewport_relative(&self, length: &Length) -> f32 {
+        let document = self
+            .document
+            .and_then(|document| document.as_document_opt());
+        let (width, height) = match document {
+            Some(document) => {
+                document.set_viewport_dependent(true);
+                let environment = document.media_environment();
+                (environment.width, environment.height)
+            }
+            None => (0., 0.),
+        };
+
+        let size = match length.unit {
+            LengthUnit::Vw | LengthUnit::Svw | LengthUnit::Lvw | LengthUnit::Dvw => width,
+            LengthUnit::Vh | LengthUnit::Svh | LengthUnit::Lvh | LengthUnit::Dvh => height,
+            LengthUnit::Vmin | LengthUnit::Svmin | LengthUnit::Lvmin | LengthUnit::Dvmin => {
+                width.min(height)
+            }
+            _ => width.max(height),
+        };
+        length.value.0 * size / 100.
+    }
+
+    fn resolve_calc(&self, calc: &Calc) -> Calc {
+        calc.map_lengths(&|length: &Length| self.resolve(length))
+    }
+
+    fn resolve_length_percentage(&self, value: &LengthPercentage) -> LengthPercentage {
         match value {
-            Value::Length(length) => match length {
-                Length {
-                    value,
-                    unit: LengthUnit::Em,
-                } => {
-                    let abs_length = Length::new_px(value.0 * parent_font_size);
-                    updates.push((property.clone(), Value::Length(abs_length)));
-                }
-                Length {
-                    value,
-                    unit: LengthUnit::Rem,
-                } => {
-                    let abs_length = Length::new_px(value.0 * root_font_size);
-                    updates.push((property.clone(), Value::Length(abs_length)));
-                }
-                _ => {}
-            },
-            Value::Percentage(percentage) => match percentage {
-                Percentage(value) if matches!(property, Property::FontSize) => {
-                    let abs_length = Length::new_px(value.0 * parent_font_size / 100.);
-                    updates.push((property.clone(), Value::Length(abs_length)));
-                }
-                _ => {}
-            },
-            Value::Color(color) => match color {
-                Color::CurrentColor => {
-                    let color = node
-                        .parent()
-                        .map(|p| p.get_style(&Property::Color))
-                        .unwrap_or(Value::initial(&Property::Color));
-                    updates.push((property.clone(), color));
+            LengthPercentage::Length(length) => LengthPercentage::Length(self.resolve(length)),
+            LengthPercentage::Percentage(_) => value.clone(),
+            LengthPercentage::Calc(calc) => LengthPercentage::Calc(self.resolve_calc(calc)),
+        }
+    }
+}
+
+/// Resolve relative values against `parent`, the style the node inherits
+/// from, and set them on the style. The `font-size` of the root element,
+/// whose `root_font_size` is `None`, is relative to the initial font size &
+/// its other properties to its own font size.
+fn compute_absolute_values(
+    node: &NodePtr,
+    parent: Option<&ComputedStyle>,
+    specified: HashMap<Property, Value>,
+    style: &mut ComputedStyle,
+    root_font_size: Option<f32>,
+) {
+    let document = node.owner_document().map(NodePtr);
+    let parent_font = parent.map(font_description).unwrap_or_default();
+
+    // the font size is resolved first as the other font-relative lengths
+    // are relative to the font of the element. Font-relative lengths in
+    // `font-size` itself are relative to the font of the parent.
+    let parent_font_size = parent_font.size;
+    let parent_line_height = parent
+        .map(|parent| parent.line_height().clone())
+        .unwrap_or(LineHeight::Normal);
+    let parent_context = LengthContext::new(
+        document.as_ref(),
+        parent_font,
+        parent_line_height.clone(),
+        root_font_size.unwrap_or_else(|| font_size_of(None)),
+    );
+    if let Some(value) = specified.get(&Property::FontSize) {
+        let font_size = match value {
+            Value::Length(length) => parent_context.resolve(length),
+            Value::Percentage(percentage) => Length::new_px(percentage.to_px(parent_font_size)),
+            Value::Calc(calc) => {
+                Length::new_px(parent_context.resolve_calc(calc).to_px(parent_font_size))
+            }
+            _ => Length::new_px(parent_font_size),
+        };
+        style.set(&Property::FontSize, Value::Length(font_size));
+    }
+
+    // then the line height, for `lh`, which is relative to the line height
+    // of the parent in `line-height` itself
+    let mut context = LengthContext::new(
+        document.as_ref(),
+        font_description(style),
+        parent_line_height,
+        root_font_size.unwrap_or_else(|| style.font_size().to_px()),
+    );
+    if let Some(Value::LineHeight(line_height)) = specified.get(&Property::LineHeight) {
+        let line_height = match line_height {
+            LineHeight::Length(length) => LineHeight::Length(context.resolve(length)),
+            LineHeight::Percentage(percentage) => {
+                LineHeight::Length(Length::new_px(percentage.to_px(context.font.size)))
+            }
+            line_height => line_height.clone(),
+        };
+        style.set(&Property::LineHeight, Value::LineHeight(line_height));
+    }
+    context.line_height = style.line_height().clone();
+
+    for (property, value) in specified.iter() {
+        let value = match value {
+            _ if *property == Property::FontSize => continue,
+            _ if *property == Property::LineHeight => continue,
+            Value::Length(length) => Value::Length(context.resolve(length)),
+            Value::Calc(calc) => {
+                let calc = context.resolve_calc(calc);
+                if calc.has_percentage() {
+                    // finished against the containing block during layout
+                    Value::Calc(calc)
+                } else {
+                    Value::Length(Length::new_px(calc.to_px(0.)))
                 }
-                _ => {}
-            },
-            Value::BorderWidth(_) => {
-                let border_style = match &property {
-                    Property::BorderTopWidth => Property::BorderTopStyle,
-                    Property::BorderLeftWidth => Property::BorderLeftStyle,
-                    Property::BorderBottomWidth => Property::BorderBottomStyle,
-                    Property::BorderRightWidth => Property::BorderRightStyle,
-                    _ => unreachable!(),
-                };
-
-                let border_style = styles.get(&border_style).unwrap();
-                match border_style {
-                    Value::BorderStyle(BorderStyle::None)
-                    | Value::BorderStyle(BorderStyle::Hidden) => {
-                        let value = Value::Length(Length::zero());
-                        updates.push((property.clone(), value));
+            }
+            Value::Spacing(Spacing::Length(length)) => {
+                Value::Spacing(Spacing::Length(context.resolve(length)))
+            }
+            Value::TabSize(TabSize::Length(length)) => {
+                Value::TabSize(TabSize::Length(context.resolve(length)))
+            }
+            Value::BorderRadius(BorderRadius(horizontal, vertical)) => {
+                Value::BorderRadius(BorderRadius(
+                    context.resolve_length_percentage(horizontal),
+                    context.resolve_length_percentage(vertical),
+                ))
+            }
+            Value::TextDecorationThickness(thickness) => {
+                Value::TextDecorationThickness(match thickness {
+                    TextDecorationThickness::Length(length) => {
+                        TextDecorationThickness::Length(context.resolve(length))
                     }
-                    _ => {}
-                }
+                    TextDecorationThickness::Percentage(percentage) => {
+                        TextDecorationThickness::Length(Length::new_px(
+                            percentage.to_px(context.font.size),
+                        ))
+                    }
+                    thickness => thickness.clone(),
+                })
             }
-            _ => {}
+            Value::Color(Color::CurrentColor) if *property == Property::Color => parent
+                .map(|parent| parent.get(&Property::Color))
+                .unwrap_or_else(|| Value::initial(&Property::Color)),
+            // the other colors are the color of the element, which might
+            // not be computed yet
+            Value::Color(Color::CurrentColor) => continue,
+            value => value.clone(),
+        };
+        style.set(property, value);
+    }
+    for (property, value) in specified.iter() {
+        if *property != Property::Color && *value == Value::Color(Color::CurrentColor) {
+            let color = style.get(&Property::Color);
+            style.set(property, color);
         }
     }
 
-    for (property, value) in updates {
-        styles.insert(property, value);
+    // a border that gets a style without a width has the initial width,
+    // which isn't the width of the initial style since it has no border
+    let borders = [
+        (Property::BorderTopStyle, Property::BorderTopWidth),
+        (Property::BorderRightStyle, Property::BorderRightWidth),
+        (Property::BorderBottomStyle, Property::BorderBottomWidth),
+        (Property::BorderLeftStyle, Property::BorderLeftWidth),
+    ];
+    for (border_style, border_width) in borders.iter() {
+        if specified.contains_key(border_style) && !specified.contains_key(border_width) {
+            style.set(border_width, Value::initial(border_width));
+        }
     }
+    style.fix_border_widths();
 }
 
-fn compute_default_values(node: &NodePtr, styles: &mut HashMap<Property, Value>) {
+/// Resolve the defaulting keywords of the declared values
+/// https://www.w3.org/TR/css3-cascade/#defaulting-keywords
+fn compute_specified_values(
+    parent: Option<&ComputedStyle>,
+    declared: HashMap<Property, Value>,
+) -> HashMap<Property, Value> {
     // get inherit value for a property
-    let inherit = |property: Property| {
-        if let Some(parent) = &node.parent() {
-            return (property.clone(), parent.get_style(&property));
-        }
+    let inherit = |property: &Property| match parent {
+        Some(parent) => parent.get(property),
         // if there's no parent
         // we will use the initial value for that property
-        return (property.clone(), Value::initial(&property));
+        None => Value::initial(property),
     };
 
-    // Step 3
-    let specified_values = Property::all()
-        .map(|property| {
-            if let Some(value) = styles.get(&property) {
-                // Explicit defaulting
-                // https://www.w3.org/TR/css3-cascade/#defaulting-keywords
-                if let Value::Initial = value {
-                    return (property.clone(), Value::initial(&property));
-                }
-                if let Value::Inherit = value {
-                    return inherit(property);
-                }
-                if let Value::Unset = value {
+    declared
+        .into_iter()
+        .map(|(property, value)| {
+            let value = match value {
+                Value::Initial => Value::initial(&property),
+                Value::Inherit => inherit(&property),
+                // `revert` & `revert-layer` are rolled back while cascading,
+                // they only remain if they come from a `var()` substitution
+                Value::Unset | Value::Revert | Value::RevertLayer => {
                     if property.inheritable() {
-                        return inherit(property);
+                        inherit(&property)
+                    } else {
+                        Value::initial(&property)
                     }
-                    return (property.clone(), Value::initial(&property));
                 }
-                return (property, value.clone());
+                value => value,
+            };
+            (property, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supports::supports_declaration;
+    use crate::utils::rule_map;
+    use css::media_query::structs::MediaEnvironment;
+    use loader::document_loader::DocumentLoader;
+    use loader::resource_loop::error::LoadError;
+    use loader::resource_loop::request::LoadRequest;
+    use style_types::values::prelude::{
+        OverflowWrap, TextDecorationLine, TextDecorationStyle, WhiteSpace, WordBreak,
+    };
+    use style_types::{CSSLocation, CascadeOrigin, ContextualStyleSheet};
+    use test_utils::css::parse_stylesheet;
+    use test_utils::dom_creator::{document, element, text};
+
+    fn compute_tree(doc: &NodePtr, css: &str) {
+        compute_rules(doc.clone(), &rule_map(css));
+    }
+
+    fn compute_rules(node: NodePtr, rules: &RuleMap) {
+        let style = compute_styles(node.clone(), rules, None);
+        node.set_computed_style(style);
+        node.for_each_child(|child| compute_rules(NodePtr(child), rules));
+    }
+
+    fn px(node: &NodePtr, property: Property) -> f32 {
+        node.get_style(&property).to_absolute_px()
+    }
+
+    /// A loader that responds to every request with the CSS
+    fn css_loader(css: &'static str) -> DocumentLoader {
+        let (tx, rx) = flume::unbounded::<LoadRequest>();
+        std::thread::spawn(move || {
+            for request in rx.iter() {
+                request.listener().on_finished(css.as_bytes().to_vec());
+            }
+        });
+        DocumentLoader::new(tx)
+    }
+
+    /// A loader that responds with a TrueType font to the requests of
+    /// `/font.ttf`, fails the requests of `/broken.ttf` & responds with an
+    /// error page to the other requests
+    fn font_loader() -> DocumentLoader {
+        let (tx, rx) = flume::unbounded::<LoadRequest>();
+        std::thread::spawn(move || {
+            for request in rx.iter() {
+                let listener = request.listener();
+                let url = request.url().as_str();
+                if url.ends_with("/font.ttf") {
+                    listener.on_finished(b"\x00\x01\x00\x00font".to_vec());
+                } else if url.ends_with("/broken.ttf") {
+                    listener.on_errored(LoadError::IOError("broken".to_string()));
+                } else {
+                    listener.on_finished(b"<html>Not Found</html>".to_vec());
+                }
             }
-            // if there's no specified value in properties
-            // we will try to inherit it
-            if property.inheritable() {
-                return inherit(property);
+        });
+        DocumentLoader::new(tx)
+    }
+
+    #[test]
+    fn resolve_viewport_relative_lengths() {
+        let doc = document();
+        let div = element("div", doc.clone(), vec![]);
+        doc.append_child(div.0.clone());
+        doc.as_document()
+            .set_media_environment(MediaEnvironment::new(800., 600.));
+
+        compute_tree(
+            &doc,
+            "div { width: 50vw; height: 10vh; margin-top: 10vmin; margin-left: 10vmax; \
+             padding-top: 100dvh; padding-left: calc(10svw + 10px); }",
+        );
+
+        assert_eq!(px(&div, Property::Width), 400.);
+        assert_eq!(px(&div, Property::Height), 60.);
+        assert_eq!(px(&div, Property::MarginTop), 60.);
+        assert_eq!(px(&div, Property::MarginLeft), 80.);
+        assert_eq!(px(&div, Property::PaddingTop), 600.);
+        assert_eq!(px(&div, Property::PaddingLeft), 90.);
+        assert!(doc.as_document().is_viewport_dependent());
+    }
+
+    #[test]
+    fn resolve_absolute_lengths() {
+        let doc = document();
+        let div = element("div", doc.clone(), vec![]);
+        doc.append_child(div.0.clone());
+
+        compute_tree(
+            &doc,
+            "div { width: 1in; height: 2.54cm; margin-top: 72pt; margin-left: 1pc; \
+             padding-top: 25.4mm; padding-left: 4Q; }",
+        );
+
+        assert_eq!(px(&div, Property::Width), 96.);
+        assert!((px(&div, Property::Height) - 96.).abs() < 0.001);
+        assert_eq!(px(&div, Property::MarginTop), 96.);
+        assert_eq!(px(&div, Property::MarginLeft), 16.);
+        assert!((px(&div, Property::PaddingTop) - 96.).abs() < 0.001);
+        assert!((px(&div, Property::PaddingLeft) - 3.78).abs() < 0.01);
+        assert!(!doc.as_document().is_viewport_dependent());
+    }
+
+    #[test]
+    fn resolve_font_relative_lengths() {
+        let doc = document();
+        let p = element("p", doc.clone(), vec![]);
+        let div = element("div", doc.clone(), vec![p.clone()]);
+        doc.append_child(div.0.clone());
+
+        compute_tree(
+            &doc,
+            "div { font-size: 2em; } \
+             p { font-size: 1.5em; width: 2em; height: 2ex; margin-top: 2ch; \
+             margin-left: 1lh; padding-top: 1rem; }",
+        );
+
+        assert_eq!(px(&div, Property::FontSize), 32.);
+        assert_eq!(px(&p, Property::FontSize), 48.);
+        // relative to the font size of the element itself
+        assert_eq!(px(&p, Property::Width), 96.);
+        // without a font metrics provider, the fallback metrics are used
+        assert_eq!(px(&p, Property::Height), 48.);
+        assert_eq!(px(&p, Property::MarginTop), 48.);
+        assert!((px(&p, Property::MarginLeft) - 57.6).abs() < 0.001);
+        // relative to the font size of the root element
+        assert_eq!(px(&p, Property::PaddingTop), 32.);
+    }
+
+    #[test]
+    fn resolve_rem_against_root_element() {
+        let doc = document();
+        let p = element("p", doc.clone(), vec![]);
+        let html = element("html", doc.clone(), vec![p.clone()]);
+        doc.append_child(html.0.clone());
+
+        let rules = rule_map(
+            "html { font-size: 2rem; width: 1rem; }              html::before { content: ''; width: 1rem; }              p { font-size: 1rem; width: 2rem; }",
+        );
+        compute_rules(doc.clone(), &rules);
+        let pseudo_element_styles = compute_pseudo_element_styles(html.clone(), &rules, None);
+
+        // the font size of the root element is relative to the initial one
+        assert_eq!(px(&html, Property::FontSize), 32.);
+        assert_eq!(px(&html, Property::Width), 32.);
+        assert_eq!(
+            pseudo_element_styles[&PseudoElement::Before]
+                .get(&Property::Width)
+                .to_absolute_px(),
+            32.
+        );
+        assert_eq!(px(&p, Property::FontSize), 32.);
+        assert_eq!(px(&p, Property::Width), 64.);
+    }
+
+    #[test]
+    fn compute_text_properties() {
+        let doc = document();
+        let span = element("span", doc.clone(), vec![]);
+        let p = element("p", doc.clone(), vec![span.clone()]);
+        let div = element("div", doc.clone(), vec![p.clone()]);
+        doc.append_child(div.0.clone());
+
+        compute_tree(
+            &doc,
+            "div { font-size: 10px; line-height: 1.5; letter-spacing: 0.1em; \
+             text-transform: uppercase; white-space: pre-wrap; tab-size: 2em; \
+             word-wrap: break-word; word-break: keep-all; } \
+             p { font-size: 20px; word-spacing: 1em; margin-top: 2lh; } \
+             span { line-height: 150%; font-size: 40px; letter-spacing: normal; }",
+        );
+
+        // a number is inherited as a number
+        assert_eq!(
+            *p.computed_style().line_height(),
+            LineHeight::Number(1.5.into())
+        );
+        assert_eq!(px(&p, Property::MarginTop), 60.);
+        // lengths are inherited once they are absolute
+        assert_eq!(
+            p.get_style(&Property::LetterSpacing),
+            Value::Spacing(Spacing::Length(Length::new_px(1.)))
+        );
+        assert_eq!(p.computed_style().word_spacing().to_px(), 20.);
+        assert_eq!(*p.computed_style().white_space(), WhiteSpace::PreWrap);
+        assert_eq!(
+            *p.computed_style().tab_size(),
+            TabSize::Length(Length::new_px(20.))
+        );
+        // `word-wrap` is an alias of `overflow-wrap`
+        assert_eq!(*p.computed_style().overflow_wrap(), OverflowWrap::BreakWord);
+        assert_eq!(*p.computed_style().word_break(), WordBreak::KeepAll);
+        assert_eq!(
+            *span.computed_style().line_height(),
+            LineHeight::Length(Length::new_px(60.))
+        );
+        assert_eq!(*span.computed_style().letter_spacing(), Spacing::Normal);
+        assert_eq!(
+            span.get_style(&Property::TextTransform),
+            div.get_style(&Property::TextTransform)
+        );
+    }
+
+    #[test]
+    fn compute_text_decoration() {
+        let doc = document();
+        let em = element("em", doc.clone(), vec![]);
+        let a = element("a", doc.clone(), vec![em.clone()]);
+        doc.append_child(a.0.clone());
+
+        compute_tree(
+            &doc,
+            "a { color: blue; font-size: 20px; \
+             text-decoration: underline overline dotted currentcolor 10%; } \
+             em { color: red; text-decoration-line: line-through; }",
+        );
+
+        let style = a.computed_style();
+        assert_eq!(
+            *style.text_decoration_line(),
+            TextDecorationLine {
+                underline: true,
+                overline: true,
+                line_through: false,
             }
-            // if the property is not inheritable
-            // we will use the initial value for that property
-            return (property.clone(), Value::initial(&property));
-        })
-        .collect::<HashMap<Property, Value>>();
+        );
+        assert_eq!(*style.text_decoration_style(), TextDecorationStyle::Dotted);
+        // currentcolor is the color of the element itself
+        assert_eq!(style.text_decoration_color(), style.color());
+        assert_eq!(style.text_decoration_thickness().to_px(20.), Some(2.));
+
+        // the decoration is propagated when painting, it's not inherited
+        let style = em.computed_style();
+        assert!(!style.text_decoration_line().underline);
+        assert_eq!(*style.text_decoration_style(), TextDecorationStyle::Solid);
+        assert_eq!(
+            *style.text_decoration_thickness(),
+            TextDecorationThickness::Auto
+        );
+    }
+
+    #[test]
+    fn cascade_inline_style() {
+        let doc = document();
+        let div = element("div#main", doc.clone(), vec![]);
+        doc.append_child(div.0.clone());
+        div.set_attribute("style", "width: 10px; height: 20px");
+
+        compute_tree(
+            &doc,
+            "#main { width: 100px; height: 200px !important; margin-top: 5px; }",
+        );
+        assert_eq!(px(&div, Property::Width), 10.);
+        assert_eq!(px(&div, Property::Height), 200.);
+        assert_eq!(px(&div, Property::MarginTop), 5.);
+
+        let set_property = |name: &str, value: &str| {
+            div.edit_inline_style(|style| {
+                style.set_property(name, value, "", &supports_declaration)
+            })
+            .unwrap()
+        };
+        // values are checked with the grammar of the property
+        assert!(!set_property("margin-top", "red"));
+        assert!(!set_property("margin", "1px 2px 3px 4px 5px"));
+        assert!(set_property("margin-top", "1em"));
+        assert_eq!(
+            div.as_element().get_attribute("style").unwrap(),
+            "width: 10px; height: 20px; margin-top: 1em;"
+        );
+        assert!(div.is_style_dirty());
+
+        compute_tree(&doc, "");
+        assert_eq!(px(&div, Property::MarginTop), 16.);
+        // only elements have a `style` attribute
+        let text = text("text", doc.clone());
+        assert!(text.edit_inline_style(|_| ()).is_none());
+    }
+
+    #[test]
+    fn load_import_inserted_into_stylesheet() {
+        let doc = document();
+        doc.as_document()
+            .set_loader(css_loader("p { width: 10px; }"));
+        let style = element(
+            "style",
+            doc.clone(),
+            vec![text("p { height: 5px; }", doc.clone())],
+        );
+        let p = element("p", doc.clone(), vec![]);
+        doc.append_child(style.0.clone());
+        doc.append_child(p.0.clone());
+
+        let inserted = doc.edit_stylesheet(0, |stylesheet| {
+            stylesheet.insert_rule("@import url(https://example.com/imported.css);", 0)
+        });
+        assert!(matches!(inserted, Some(Ok(0))));
+
+        compute_rules(doc.clone(), &RuleMap::new(doc.as_document().style_rules()));
+        assert_eq!(px(&p, Property::Width), 10.);
+        assert_eq!(px(&p, Property::Height), 5.);
+        assert!(doc.edit_stylesheet(1, |_| ()).is_none());
+    }
+
+    #[test]
+    fn load_font_face_from_first_valid_source() {
+        let doc = document();
+        doc.as_document().set_loader(font_loader());
+        let style = element("style", doc.clone(), vec![text("", doc.clone())]);
+        doc.append_child(style.0.clone());
+
+        let font_face = |family: &str, src: &str| {
+            let rule = format!("@font-face {{ font-family: {}; src: {}; }}", family, src);
+            doc.edit_stylesheet(0, |stylesheet| stylesheet.insert_rule(&rule, 0))
+        };
+        // the sources that fail to load, that aren't fonts or that are
+        // invalid, e.g. relative to a document without a base URL, are
+        // skipped
+        font_face(
+            "A",
+            "url(https://example.com/broken.ttf), url(https://example.com/missing.ttf), \
+             url(https://example.com/font.ttf) format(\"svg\"), local(Arial), \
+             url(font.ttf), url(https://example.com/font.ttf)",
+        );
+        font_face("B", "url(https://example.com/missing.ttf)");
+
+        let font_faces = doc.as_document().font_faces();
+        assert_eq!(font_faces.len(), 1);
+        assert_eq!(font_faces[0].family, "A");
+        assert_eq!(font_faces[0].data.as_slice(), b"\x00\x01\x00\x00font");
+    }
+
+    #[test]
+    fn cascade_layers_and_revert() {
+        let doc = document();
+        let p = element("p", doc.clone(), vec![]);
+        let css = "@layer base, theme; \
+                   @layer theme { p { width: 30px; height: 30px !important; margin-top: 3px; } } \
+                   @layer base { p { width: 10px !important; height: 10px !important; margin-top: 1px; } } \
+                   p { width: 20px; margin-top: revert-layer; margin-left: revert; } \
+                   p { padding-top: 5px; margin-right: revert; } \
+                   p { padding-top: 7px; }";
+        let style = element("style", doc.clone(), vec![text(css, doc.clone())]);
+        doc.append_child(style.0.clone());
+        doc.append_child(p.0.clone());
+        doc.as_document()
+            .set_user_agent_stylesheet(ContextualStyleSheet::new(
+                parse_stylesheet("p { margin-left: 4px; } style { display: none; }"),
+                CascadeOrigin::UserAgent,
+                CSSLocation::External,
+            ));
+
+        compute_rules(doc.clone(), &doc.as_document().style_rules().into());
+
+        // important declarations of earlier layers win
+        assert_eq!(px(&p, Property::Width), 10.);
+        assert_eq!(px(&p, Property::Height), 10.);
+        // rolled back to the previous layer
+        assert_eq!(px(&p, Property::MarginTop), 3.);
+        // rolled back to the user agent origin
+        assert_eq!(px(&p, Property::MarginLeft), 4.);
+        assert_eq!(px(&p, Property::MarginRight), 0.);
+        // the last declared wins
+        assert_eq!(px(&p, Property::PaddingTop), 7.);
+    }
+
+    #[test]
+    fn cascade_user_stylesheet() {
+        let doc = document();
+        let p = element("p", doc.clone(), vec![]);
+        let css = "p { margin-top: 3px; height: 3px !important; margin-left: revert; \
+                   padding-top: 3px !important; padding-left: revert !important; }";
+        let style = element("style", doc.clone(), vec![text(css, doc.clone())]);
+        doc.append_child(style.0.clone());
+        doc.append_child(p.0.clone());
+
+        let stylesheet = |css: &str, origin: CascadeOrigin| {
+            ContextualStyleSheet::new(parse_stylesheet(css), origin, CSSLocation::External)
+        };
+        doc.as_document().set_user_agent_stylesheets(vec![
+            stylesheet(
+                "p { width: 1px !important; padding-top: 1px; }",
+                CascadeOrigin::UserAgent,
+            ),
+            stylesheet("style { display: none; }", CascadeOrigin::UserAgent),
+        ]);
+        doc.as_document().set_user_stylesheets(vec![stylesheet(
+            "p { margin-top: 2px; margin-left: 2px; height: 2px !important; width: 2px; \
+             padding-top: revert !important; padding-left: 2px; }",
+            CascadeOrigin::User,
+        )]);
+
+        compute_rules(doc.clone(), &doc.as_document().style_rules().into());
+
+        // normal author declarations win over the user
+        assert_eq!(px(&p, Property::MarginTop), 3.);
+        // important user declarations win over the author
+        assert_eq!(px(&p, Property::Height), 2.);
+        // important user agent declarations win over everything
+        assert_eq!(px(&p, Property::Width), 1.);
+        // rolled back to the user origin
+        assert_eq!(px(&p, Property::MarginLeft), 2.);
+        assert_eq!(px(&p, Property::PaddingLeft), 2.);
+        // an important user declaration rolls back the author origin too
+        assert_eq!(px(&p, Property::PaddingTop), 1.);
+    }
+
+    #[test]
+    fn share_style_groups() {
+        let doc = document();
+        let span = element("span", doc.clone(), vec![text("text", doc.clone())]);
+        let p = element("p", doc.clone(), vec![]);
+        let div = element("div", doc.clone(), vec![p.clone(), span.clone()]);
+        doc.append_child(div.0.clone());
+
+        compute_tree(
+            &doc,
+            "div { color: red; margin-top: 1px; } p { width: 10px; }",
+        );
+
+        let div_style = div.computed_style();
+        let p_style = p.computed_style();
+        let span_style = span.computed_style();
+        let initial_style = ComputedStyle::initial();
+        // only non-inherited properties are declared on the paragraph
+        assert!(p_style.shares_inherited_with(&div_style));
+        assert!(!p_style.shares_non_inherited_with(&initial_style));
+        // nothing is declared on the span
+        assert!(span_style.shares_inherited_with(&div_style));
+        assert!(span_style.shares_non_inherited_with(&initial_style));
+        assert!(!div_style.shares_inherited_with(&initial_style));
+        let text_node = NodePtr(span.first_child().unwrap());
+        assert!(text_node.computed_style().shares_inherited_with(&div_style));
+    }
+
+    #[test]
+    fn compute_border_widths() {
+        let doc = document();
+        let p = element("p", doc.clone(), vec![]);
+        let div = element("div", doc.clone(), vec![p.clone()]);
+        doc.append_child(div.0.clone());
+
+        compute_tree(
+            &doc,
+            "div { border-style: solid none none hidden; border-right: thick solid; } \
+             p { border-width: 4px; border-top: dotted; }",
+        );
+
+        let style = div.computed_style();
+        // the initial width is `medium`
+        assert_eq!(style.border_top_width().to_px(), 3.);
+        assert_eq!(style.border_right_width().to_px(), 5.);
+        // without a style, there is no border
+        assert_eq!(style.border_bottom_width().to_px(), 0.);
+        assert_eq!(style.border_left_width().to_px(), 0.);
 
-    for (property, value) in specified_values {
-        styles.insert(property, value);
+        let style = p.computed_style();
+        assert_eq!(style.border_top_width().to_px(), 4.);
+        assert_eq!(style.border_left_width().to_px(), 0.);
     }
 }
@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use css::parser::structs::{ComponentValue, Function, SimpleBlock};
+use css::tokenizer::token::Token;
+use style_types::CustomProperties;
+
+/// Compute the custom properties of an element from its cascaded custom
+/// properties & the custom properties inherited from its parent. Custom
+/// properties are always inherited.
+/// https://www.w3.org/TR/css-variables-1/#defining-variables
+pub fn compute_custom_properties(
+    cascaded: &HashMap<String, Vec<ComponentValue>>,
+    inherited: &Rc<CustomProperties>,
+) -> Rc<CustomProperties> {
+    if cascaded.is_empty() {
+        return inherited.clone();
+    }
+
+    let mut computed = (**inherited).clone();
+    let mut specified = HashMap::new();
+
+    for (name, values) in cascaded {
+        match css_wide_keyword(values) {
+            // the initial value is the guaranteed-invalid value
+            Some("initial") => {
+                computed.remove(name);
+            }
+            // the inherited value is already in the computed values
+            Some("inherit") | Some("unset") => {}
+            _ => {
+                computed.remove(name);
+                specified.insert(name.as_str(), values.as_slice());
+            }
+        }
+    }
+
+    let mut resolver = Resolver {
+        specified,
+        computed,
+        stack: Vec::new(),
+        in_cycle: HashSet::new(),
+        resolved: HashSet::new(),
+    };
+    let names = resolver.specified.keys().copied().collect::<Vec<&str>>();
+    for name in names {
+        resolver.resolve(name);
+    }
+
+    Rc::new(resolver.computed)
+}
+
+/// Resolve the `var()` references in custom properties. Custom properties
+/// that reference each other in a cycle are invalid at computed-value time.
+/// https://www.w3.org/TR/css-variables-1/#cycles
+struct Resolver<'a> {
+    specified: HashMap<&'a str, &'a [ComponentValue]>,
+    computed: CustomProperties,
+    /// Custom properties being resolved
+    stack: Vec<&'a str>,
+    in_cycle: HashSet<&'a str>,
+    resolved: HashSet<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&mut self, name: &str) {
+        let (name, values) = match self.specified.get_key_value(name) {
+            Some((name, values)) => (*name, *values),
+            // not declared on the element, the computed value is inherited
+            None => return,
+        };
+
+        if self.resolved.contains(name) {
+            return;
+        }
+
+        if let Some(index) = self.stack.iter().position(|n| *n == name) {
+            self.in_cycle.extend(self.stack[index..].iter().copied());
+            return;
+        }
+
+        // resolve the referenced custom properties first
+        self.stack.push(name);
+        for reference in var_references(values) {
+            self.resolve(&reference);
+        }
+        self.stack.pop();
+        self.resolved.insert(name);
+
+        if self.in_cycle.contains(name) {
+            return;
+        }
+
+        if let Some(values) = substitute_var_references(values, &self.computed) {
+            self.computed.insert(name.to_string(), values);
+        }
+    }
+}
+
+/// Check if the values contain a `var()` reference
+pub fn has_var_references(values: &[ComponentValue]) -> bool {
+    values.iter().any(|value| match value {
+        ComponentValue::Function(function) => {
+            function.name.eq_ignore_ascii_case("var") || has_var_references(&function.value)
+        }
+        ComponentValue::SimpleBlock(block) => has_var_references(&block.value),
+        _ => false,
+    })
+}
+
+/// Replace the `var()` references in the values with the values of the
+/// custom properties. Return `None` if a reference is to an invalid custom
+/// property without a fallback, which makes the declaration invalid at
+/// computed-value time.
+/// https://www.w3.org/TR/css-variables-1/#substitute-a-var
+pub fn substitute_var_references(
+    values: &[ComponentValue],
+    custom_properties: &CustomProperties,
+) -> Option<Vec<ComponentValue>> {
+    let mut result = Vec::new();
+
+    for value in values {
+        match value {
+            ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("var") => {
+                let (name, fallback) = parse_var_function(&function.value)?;
+                match (custom_properties.get(name), fallback) {
+                    (Some(values), _) => result.extend(values.iter().cloned()),
+                    (None, Some(fallback)) => {
+                        result.extend(substitute_var_references(fallback, custom_properties)?)
+                    }
+                    (None, None) => return None,
+                }
+            }
+            ComponentValue::Function(function) => {
+                result.push(ComponentValue::Function(Function {
+                    name: function.name.clone(),
+                    value: substitute_var_references(&function.value, custom_properties)?,
+                }));
+            }
+            ComponentValue::SimpleBlock(block) => {
+                result.push(ComponentValue::SimpleBlock(SimpleBlock {
+                    token: block.token.clone(),
+                    value: substitute_var_references(&block.value, custom_properties)?,
+                }));
+            }
+            value => result.push(value.clone()),
+        }
+    }
+
+    Some(result)
+}
+
+/// Remove the leading & trailing whitespaces, which substitution can leave
+/// around a value
+pub fn trim_whitespaces(values: &[ComponentValue]) -> &[ComponentValue] {
+    let is_whitespace =
+        |value: &ComponentValue| matches!(value, ComponentValue::PerservedToken(Token::Whitespace));
+    let start = values
+        .iter()
+        .position(|value| !is_whitespace(value))
+        .unwrap_or(values.len());
+    let end = values
+        .iter()
+        .rposition(|value| !is_whitespace(value))
+        .map(|index| index + 1)
+        .unwrap_or(start);
+    &values[start..end]
+}
+
+/// Parse the arguments of `var(<custom-property-name>, <fallback>?)`
+fn parse_var_function(values: &[ComponentValue]) -> Option<(&str, Option<&[ComponentValue]>)> {
+    let values = trim_whitespaces(values);
+    let (name, remain) = match values.split_first() {
+        Some((ComponentValue::PerservedToken(Token::Ident(name)), remain))
+            if name.starts_with("--") =>
+        {
+            (name.as_str(), trim_whitespaces(remain))
+        }
+        _ => return None,
+    };
+
+    match remain.split_first() {
+        None => Some((name, None)),
+        Some((ComponentValue::PerservedToken(Token::Comma), fallback)) => {
+            Some((name, Some(trim_whitespaces(fallback))))
+        }
+        _ => None,
+    }
+}
+
+/// Names of the custom properties referenced by the values, including the
+/// references in fallbacks
+fn var_references(values: &[ComponentValue]) -> Vec<String> {
+    let mut references = Vec::new();
+    for value in values {
+        match value {
+            ComponentValue::Function(function) => {
+                if function.name.eq_ignore_ascii_case("var") {
+                    if let Some((name, _)) = parse_var_function(&function.value) {
+                        references.push(name.to_string());
+                    }
+                }
+                references.extend(var_references(&function.value));
+            }
+            ComponentValue::SimpleBlock(block) => references.extend(var_references(&block.value)),
+            _ => {}
+        }
+    }
+    references
+}
+
+fn css_wide_keyword(values: &[ComponentValue]) -> Option<&'static str> {
+    match trim_whitespaces(values) {
+        [ComponentValue::PerservedToken(Token::Ident(keyword))] => ["initial", "inherit", "unset"]
+            .iter()
+            .find(|k| keyword.eq_ignore_ascii_case(k))
+            .copied(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::compute_styles;
+    use crate::rule_map::RuleMap;
+    use crate::utils::rule_map;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+    use dom::node::NodePtr;
+    use style_types::values::prelude::{Color, Length};
+    use style_types::{Property, Value};
+    use test_utils::dom_creator::{document, element};
+
+    fn values(css: &str) -> Vec<ComponentValue> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parser.parse_a_list_of_component_values()
+    }
+
+    fn declared(properties: &[(&str, &str)]) -> HashMap<String, Vec<ComponentValue>> {
+        properties
+            .iter()
+            .map(|(name, value)| (name.to_string(), values(value)))
+            .collect()
+    }
+
+    #[test]
+    fn substitute_with_fallback() {
+        let custom_properties = declared(&[("--color", "red")]);
+
+        assert_eq!(
+            substitute_var_references(&values("1px solid var(--color)"), &custom_properties),
+            Some(values("1px solid red"))
+        );
+        assert_eq!(
+            substitute_var_references(&values("var(--missing, var(--color))"), &custom_properties),
+            Some(values("red"))
+        );
+        assert_eq!(
+            substitute_var_references(&values("var(--missing)"), &custom_properties),
+            None
+        );
+        assert!(has_var_references(&values("rgb(var(--r), 0, 0)")));
+        assert!(!has_var_references(&values("rgb(0, 0, 0)")));
+    }
+
+    #[test]
+    fn compute_and_inherit_custom_properties() {
+        let inherited = Rc::new(declared(&[("--size", "10px"), ("--color", "red")]));
+        let cascaded = declared(&[
+            ("--color", "inherit"),
+            ("--size", "initial"),
+            ("--border", "var(--width) solid var(--color)"),
+            ("--width", "var(--missing, 2px)"),
+        ]);
+
+        let computed = compute_custom_properties(&cascaded, &inherited);
+        assert_eq!(computed.get("--color"), Some(&values("red")));
+        assert_eq!(computed.get("--size"), None);
+        assert_eq!(computed.get("--width"), Some(&values("2px")));
+        assert_eq!(computed.get("--border"), Some(&values("2px solid red")));
+
+        // elements without custom properties share the inherited ones
+        let shared = compute_custom_properties(&HashMap::new(), &computed);
+        assert!(Rc::ptr_eq(&shared, &computed));
+    }
+
+    #[test]
+    fn custom_properties_in_cycle_are_invalid() {
+        let inherited = Rc::new(declared(&[("--a", "1px")]));
+        let cascaded = declared(&[
+            ("--a", "var(--b)"),
+            ("--b", "var(--a)"),
+            ("--c", "var(--a, 3px)"),
+            ("--d", "var(--d)"),
+        ]);
+
+        let computed = compute_custom_properties(&cascaded, &inherited);
+        assert_eq!(computed.get("--a"), None);
+        assert_eq!(computed.get("--b"), None);
+        assert_eq!(computed.get("--c"), Some(&values("3px")));
+        assert_eq!(computed.get("--d"), None);
+    }
+
+    #[test]
+    fn substitute_var_in_declarations() {
+        let rules = rule_map(
+            r#"
+            div { --space: 4px 8px; --accent: red; }
+            p { margin: var(--space); color: var(--accent); --accent: var(--missing); }
+            p { background-color: var(--accent, blue); padding-top: var(--missing); }
+            "#,
+        );
+
+        let doc = document();
+        let p = element("p", doc.clone(), vec![]);
+        let div = element("div", doc.clone(), vec![p.clone()]);
+        doc.append_child(div.0.clone());
+
+        fn compute(node: NodePtr, rules: &RuleMap) {
+            let style = compute_styles(node.clone(), rules, None);
+            node.set_computed_style(style);
+            node.for_each_child(|child| compute(NodePtr(child), rules));
+        }
+        compute(doc, &rules);
+
+        assert_eq!(
+            p.get_style(&Property::MarginTop),
+            Value::Length(Length::new_px(4.))
+        );
+        assert_eq!(
+            p.get_style(&Property::MarginLeft),
+            Value::Length(Length::new_px(8.))
+        );
+        // `--accent` is invalid on the paragraph
+        assert_eq!(p.get_style(&Property::Color), Value::Color(Color::black()));
+        assert_eq!(
+            p.get_style(&Property::BackgroundColor),
+            Value::parse(&Property::BackgroundColor, &values("blue")).unwrap()
+        );
+        // invalid at computed-value time
+        assert_eq!(
+            p.get_style(&Property::PaddingTop),
+            Value::Length(Length::zero())
+        );
+    }
+}
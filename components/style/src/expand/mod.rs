@@ -9,6 +9,7 @@ mod border_style;
 mod border_width;
 mod margin;
 mod padding;
+mod text_decoration;
 
 pub(crate) mod prelude {
     pub use super::border::*;
@@ -18,5 +19,6 @@ pub(crate) mod prelude {
     pub use super::border_width::expand_border_width;
     pub use super::margin::expand_margin;
     pub use super::padding::expand_padding;
+    pub use super::text_decoration::expand_text_decoration;
     pub use super::ExpandOutput;
 }
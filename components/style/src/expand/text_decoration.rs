@@ -0,0 +1,45 @@
+use super::ExpandOutput;
+use css::parser::structs::ComponentValue;
+use style_types::{Property, Value};
+
+/// `text-decoration: <line> || <style> || <color> || <thickness>`. The
+/// longhands that are left out are reset to their initial value.
+/// https://www.w3.org/TR/css-text-decor-4/#text-decoration-property
+pub fn expand_text_decoration(values: &[&[ComponentValue]]) -> ExpandOutput {
+    let mut style = None;
+    let mut color = None;
+    let mut thickness = None;
+    let mut line_keywords: Vec<ComponentValue> = Vec::new();
+
+    for tokens in values {
+        if let Some(value) = Value::parse(&Property::TextDecorationStyle, tokens) {
+            if style.replace(value).is_some() {
+                return None;
+            }
+        } else if let Some(value) = Value::parse(&Property::TextDecorationThickness, tokens) {
+            if thickness.replace(value).is_some() {
+                return None;
+            }
+        } else if let Some(value) = Value::parse(&Property::TextDecorationColor, tokens) {
+            if color.replace(value).is_some() {
+                return None;
+            }
+        } else {
+            line_keywords.extend_from_slice(tokens);
+        }
+    }
+
+    let line = if line_keywords.is_empty() {
+        None
+    } else {
+        Some(Value::parse(&Property::TextDecorationLine, &line_keywords)?)
+    };
+
+    let or_initial = |value: Option<Value>| Some(value.unwrap_or(Value::Initial));
+    Some(vec![
+        (Property::TextDecorationLine, or_initial(line)),
+        (Property::TextDecorationStyle, or_initial(style)),
+        (Property::TextDecorationColor, or_initial(color)),
+        (Property::TextDecorationThickness, or_initial(thickness)),
+    ])
+}
@@ -0,0 +1,17 @@
+use shared::font::{FontDescription, FontStyle};
+use style_types::{values::prelude, ComputedStyle};
+
+/// The font described by a computed style
+pub fn font_description(style: &ComputedStyle) -> FontDescription {
+    FontDescription {
+        families: style.font_family().names(),
+        size: style.font_size().to_px(),
+        weight: style.font_weight().value(),
+        style: match style.font_style() {
+            prelude::FontStyle::Normal => FontStyle::Normal,
+            prelude::FontStyle::Italic => FontStyle::Italic,
+            prelude::FontStyle::Oblique => FontStyle::Oblique,
+        },
+        stretch: style.font_stretch().value(),
+    }
+}
@@ -1,4 +1,14 @@
+pub mod bloom_filter;
 pub mod cascade;
 pub mod compute;
+pub mod custom_properties;
 pub mod expand;
+pub mod font;
+pub mod rule_map;
 pub mod selector_matching;
+pub mod style_sharing;
+pub mod supports;
+pub mod traversal;
+
+#[cfg(test)]
+pub mod utils;
@@ -0,0 +1,394 @@
+use std::collections::{HashMap, HashSet};
+
+use css::selector::structs::{
+    Combinator, PseudoClassArgument, PseudoElement, Selector, SimpleSelectorSequence,
+    SimpleSelectorType, Specificity,
+};
+use dom::node::NodePtr;
+use style_types::ContextualRule;
+
+use crate::bloom_filter::AncestorBloomFilter;
+use crate::selector_matching::{is_match_originating_element, is_match_selector_of};
+
+/// A selector of a style rule, by their indexes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SelectorEntry {
+    rule: usize,
+    selector: usize,
+    /// Whether the selector can match only one of two sibling elements
+    /// that have the same type, classes & attributes, see `needs_revalidation`
+    revalidate: bool,
+}
+
+/// The style rules indexed by the rightmost compound selector of their
+/// selectors, so that only the selectors that might match an element are
+/// tested against it.
+///
+/// A selector is put in a single bucket, the most specific one of its
+/// rightmost compound selector: its ID, its first class, its type or the
+/// universal bucket if it has none of them. E.g. `div.item > a#link` is
+/// found through the `link` ID.
+#[derive(Debug, Default)]
+pub struct RuleMap {
+    rules: Vec<ContextualRule>,
+    by_id: HashMap<String, Vec<SelectorEntry>>,
+    by_class: HashMap<String, Vec<SelectorEntry>>,
+    /// Type selectors, lowercased
+    by_type: HashMap<String, Vec<SelectorEntry>>,
+    universal: Vec<SelectorEntry>,
+    /// Names of the attributes used by attribute selectors
+    attribute_names: HashSet<String>,
+    /// Whether a selector depends on the position of an ancestor among its
+    /// siblings, see `depends_on_ancestor_position`
+    ancestor_position_dependent: bool,
+}
+
+/// A style rule that matches the element
+pub(crate) struct MatchedRule<'a> {
+    pub rule: &'a ContextualRule,
+    pub specificity: Specificity,
+    pub source_order: usize,
+}
+
+enum BucketKey<'a> {
+    ID(&'a str),
+    Class(&'a str),
+    Type(String),
+    Universal,
+}
+
+impl RuleMap {
+    /// Index the style rules, which are in source order
+    pub fn new(rules: Vec<ContextualRule>) -> Self {
+        let mut map = Self {
+            rules: Vec::new(),
+            by_id: HashMap::new(),
+            by_class: HashMap::new(),
+            by_type: HashMap::new(),
+            universal: Vec::new(),
+            attribute_names: HashSet::new(),
+            ancestor_position_dependent: false,
+        };
+
+        for (rule_index, rule) in rules.iter().enumerate() {
+            for (selector_index, selector) in rule.inner.selectors.iter().enumerate() {
+                collect_attribute_names(selector, &mut map.attribute_names);
+                map.ancestor_position_dependent |= depends_on_ancestor_position(selector);
+                let entry = SelectorEntry {
+                    rule: rule_index,
+                    selector: selector_index,
+                    revalidate: needs_revalidation(selector),
+                };
+                let bucket = match bucket_key(selector) {
+                    BucketKey::ID(id) => map.by_id.entry(id.to_string()).or_default(),
+                    BucketKey::Class(class) => map.by_class.entry(class.to_string()).or_default(),
+                    BucketKey::Type(name) => map.by_type.entry(name).or_default(),
+                    BucketKey::Universal => &mut map.universal,
+                };
+                bucket.push(entry);
+            }
+        }
+
+        map.rules = rules;
+        map
+    }
+
+    /// The style rules in source order
+    pub fn rules(&self) -> &[ContextualRule] {
+        &self.rules
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules that match the element, or its pseudo-element if
+    /// `pseudo_element` is given, in source order. A rule applies with the
+    /// specificity of its most specific selector that matches.
+    pub(crate) fn matched_rules(
+        &self,
+        element: &NodePtr,
+        pseudo_element: Option<&PseudoElement>,
+        bloom_filter: Option<&AncestorBloomFilter>,
+    ) -> Vec<MatchedRule<'_>> {
+        let mut candidates = self.candidates(element);
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut result: Vec<MatchedRule> = Vec::new();
+        for entry in candidates {
+            let rule = &self.rules[entry.rule];
+            let selector = &rule.inner.selectors[entry.selector];
+            if !is_match_selector_of(element, selector, pseudo_element, bloom_filter) {
+                continue;
+            }
+
+            let specificity = selector.specificity();
+            match result.last_mut() {
+                // the candidates of a rule are next to each other
+                Some(matched) if matched.source_order == entry.rule => {
+                    if specificity > matched.specificity {
+                        matched.specificity = specificity;
+                    }
+                }
+                _ => result.push(MatchedRule {
+                    rule,
+                    specificity,
+                    source_order: entry.rule,
+                }),
+            }
+        }
+        result
+    }
+
+    /// Lowercase names of the attributes that selectors depend on
+    pub fn attribute_names(&self) -> &HashSet<String> {
+        &self.attribute_names
+    }
+
+    /// Whether the descendants of an element can match differently when
+    /// its siblings change, e.g. with `li:last-child a`. Otherwise only the
+    /// element itself is restyled when a sibling is inserted or removed.
+    pub fn is_ancestor_position_dependent(&self) -> bool {
+        self.ancestor_position_dependent
+    }
+
+    /// Check if the selectors that can tell apart two siblings with the same
+    /// type, classes & attributes, e.g. `:nth-child()` or `+`, match both
+    /// elements or neither of them. The elements must share their parent so
+    /// that the bloom filter holds the ancestors of both.
+    pub(crate) fn matches_same_revalidation_selectors(
+        &self,
+        element: &NodePtr,
+        other: &NodePtr,
+        bloom_filter: Option<&AncestorBloomFilter>,
+    ) -> bool {
+        self.candidates(element)
+            .into_iter()
+            .filter(|entry| entry.revalidate)
+            .all(|entry| {
+                let selector = &self.rules[entry.rule].inner.selectors[entry.selector];
+                if let Some(filter) = bloom_filter {
+                    if !filter.might_match(selector) {
+                        return true;
+                    }
+                }
+                is_match_originating_element(element, selector)
+                    == is_match_originating_element(other, selector)
+            })
+    }
+
+    /// The selectors in the buckets of the element's ID, classes & type
+    fn candidates(&self, element: &NodePtr) -> Vec<SelectorEntry> {
+        let mut candidates = self.universal.clone();
+        let element = match element.as_element_opt() {
+            Some(element) => element,
+            None => return Vec::new(),
+        };
+
+        let mut add_bucket = |bucket: Option<&Vec<SelectorEntry>>| {
+            if let Some(bucket) = bucket {
+                candidates.extend_from_slice(bucket);
+            }
+        };
+
+        if let Some(id) = element.id() {
+            add_bucket(self.by_id.get(&id));
+        }
+        {
+            let class_list = element.class_list();
+            let class_list = class_list.borrow();
+            for index in 0..class_list.length() {
+                if let Some(class) = class_list.item(index) {
+                    add_bucket(self.by_class.get(&class));
+                }
+            }
+        }
+        add_bucket(self.by_type.get(&element.tag_name().to_lowercase()));
+
+        candidates
+    }
+}
+
+impl From<Vec<ContextualRule>> for RuleMap {
+    fn from(rules: Vec<ContextualRule>) -> Self {
+        Self::new(rules)
+    }
+}
+
+/// Check if the selector depends on more than the type, ID, classes &
+/// attributes of the element and on its ancestors, i.e. if it has a
+/// pseudo-class, like `:nth-child()` or `:hover`, or a sibling combinator
+fn needs_revalidation(selector: &Selector) -> bool {
+    selector.values().iter().any(|(compound, combinator)| {
+        matches!(
+            combinator,
+            Some(Combinator::NextSibling) | Some(Combinator::SubsequentSibling)
+        ) || compound
+            .values()
+            .iter()
+            .any(|simple_selector| *simple_selector.selector_type() == SimpleSelectorType::Pseudo)
+    })
+}
+
+/// Whether the selector can match an element differently depending on the
+/// siblings of one of its ancestors, e.g. `li:first-child a` or `h1 + p a`.
+/// Like `needs_revalidation`, any pseudo-class is assumed to depend on them.
+fn depends_on_ancestor_position(selector: &Selector) -> bool {
+    let values = selector.values();
+    let (rightmost, ancestors) = match values.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+
+    let has_pseudo_class = |compound: &SimpleSelectorSequence| {
+        compound
+            .values()
+            .iter()
+            .any(|simple_selector| *simple_selector.selector_type() == SimpleSelectorType::Pseudo)
+    };
+    // the last combinator only leads to the siblings of the element itself
+    let has_sibling_combinator = ancestors.iter().rev().skip(1).any(|(_, combinator)| {
+        matches!(
+            combinator,
+            Some(Combinator::NextSibling) | Some(Combinator::SubsequentSibling)
+        )
+    });
+    // e.g. `a:is(li:last-child *)` looks at the ancestors of the element
+    let has_complex_argument =
+        rightmost
+            .0
+            .values()
+            .iter()
+            .any(|simple_selector| match simple_selector.argument() {
+                Some(PseudoClassArgument::Nth(_, Some(selectors)))
+                | Some(PseudoClassArgument::Selectors(selectors)) => {
+                    selectors.iter().any(|selector| selector.values().len() > 1)
+                }
+                _ => false,
+            });
+
+    has_sibling_combinator
+        || has_complex_argument
+        || ancestors
+            .iter()
+            .any(|(compound, _)| has_pseudo_class(compound))
+}
+
+fn collect_attribute_names(selector: &Selector, names: &mut HashSet<String>) {
+    for (compound, _) in selector.values() {
+        for simple_selector in compound.values() {
+            match simple_selector.selector_type() {
+                SimpleSelectorType::Attribute => {
+                    if let Some(name) = simple_selector.value() {
+                        names.insert(name.to_ascii_lowercase());
+                    }
+                }
+                SimpleSelectorType::Pseudo => match simple_selector.argument() {
+                    Some(PseudoClassArgument::Nth(_, Some(selectors)))
+                    | Some(PseudoClassArgument::Selectors(selectors)) => {
+                        for selector in selectors {
+                            collect_attribute_names(selector, names);
+                        }
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The bucket of the selector, from its rightmost compound selector
+fn bucket_key(selector: &Selector) -> BucketKey<'_> {
+    let compound = match selector.values().last() {
+        Some((compound, _)) => compound,
+        None => return BucketKey::Universal,
+    };
+
+    let find = |selector_type: SimpleSelectorType| {
+        compound
+            .values()
+            .iter()
+            .find(|simple_selector| *simple_selector.selector_type() == selector_type)
+            .and_then(|simple_selector| simple_selector.value().as_deref())
+    };
+
+    if let Some(id) = find(SimpleSelectorType::ID) {
+        BucketKey::ID(id)
+    } else if let Some(class) = find(SimpleSelectorType::Class) {
+        BucketKey::Class(class)
+    } else if let Some(name) = find(SimpleSelectorType::Type) {
+        BucketKey::Type(name.to_lowercase())
+    } else {
+        BucketKey::Universal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rule_map;
+    use test_utils::dom_creator::{document, element};
+
+    #[test]
+    fn index_selectors_by_rightmost_compound() {
+        let map = rule_map(
+            "div.item > a#link { } .item span { } P { } * { } :hover { } .a, #b { } p::before { }",
+        );
+        assert_eq!(map.by_id.get("link").unwrap().len(), 1);
+        assert_eq!(map.by_id.get("b").unwrap().len(), 1);
+        assert_eq!(map.by_class.get("a").unwrap().len(), 1);
+        assert!(!map.by_class.contains_key("item"));
+        assert_eq!(map.by_type.get("span").unwrap().len(), 1);
+        assert_eq!(map.by_type.get("p").unwrap().len(), 2);
+        assert_eq!(map.universal.len(), 2);
+    }
+
+    #[test]
+    fn detect_ancestor_position_dependent_selectors() {
+        let cases = [
+            ("li a, .a > .b, h1 + p, li:last-child { }", false),
+            ("li:last-child a { }", true),
+            ("h1 + p a { }", true),
+            ("h1 ~ p > a { }", true),
+            ("a:is(li:first-child *) { }", true),
+        ];
+        for (css, expected) in cases {
+            assert_eq!(
+                rule_map(css).is_ancestor_position_dependent(),
+                expected,
+                "{}",
+                css
+            );
+        }
+    }
+
+    #[test]
+    fn match_candidate_rules() {
+        let map = rule_map(
+            "p { } .a { } #b { } div p { } span { } .a, p#b { } .c { } p::before { } * { }",
+        );
+        let doc = document();
+        let p = element("p#b.a.c.a", doc.clone(), vec![]);
+        let div = element("div", doc.clone(), vec![p.clone()]);
+        doc.append_child(div.0.clone());
+
+        let matched = map.matched_rules(&p, None, None);
+        let source_orders = matched
+            .iter()
+            .map(|matched| matched.source_order)
+            .collect::<Vec<usize>>();
+        assert_eq!(source_orders, vec![0, 1, 2, 3, 5, 6, 8]);
+        // the most specific selector of the list
+        assert_eq!(matched[4].specificity, Specificity::new(1, 0, 1));
+
+        let matched = map.matched_rules(&p, Some(&PseudoElement::Before), None);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].source_order, 7);
+    }
+}
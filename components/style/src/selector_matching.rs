@@ -1,6 +1,8 @@
 use css::selector::structs::*;
 use dom::{element::Element, node::NodePtr};
 
+use crate::bloom_filter::AncestorBloomFilter;
+
 fn get_parent(el: &NodePtr) -> Option<NodePtr> {
     let parent = el.parent();
     if let Some(p) = parent {
@@ -11,74 +13,102 @@ fn get_parent(el: &NodePtr) -> Option<NodePtr> {
     None
 }
 
-fn get_prev_sibling(el: &NodePtr) -> Option<NodePtr> {
-    el.prev_sibling().map(|node| NodePtr(node))
-}
-
 pub fn is_match_selectors(element: &NodePtr, selectors: &Vec<Selector>) -> bool {
     selectors
         .iter()
         .any(|selector| is_match_selector(element.clone(), selector))
 }
 
+/// Find the most specific selector of the list that matches the element,
+/// or its pseudo-element if `pseudo_element` is given. The bloom filter
+/// holds the ancestors of the element & is used to skip selectors that
+/// can't match.
+pub fn find_matched_selector<'a>(
+    element: &NodePtr,
+    selectors: &'a [Selector],
+    pseudo_element: Option<&PseudoElement>,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> Option<&'a Selector> {
+    selectors
+        .iter()
+        .filter(|selector| is_match_selector_of(element, selector, pseudo_element, bloom_filter))
+        .max_by_key(|selector| selector.specificity())
+}
+
+/// Check if the selector matches the element, or its pseudo-element if
+/// `pseudo_element` is given. The bloom filter holds the ancestors of the
+/// element.
+pub fn is_match_selector_of(
+    element: &NodePtr,
+    selector: &Selector,
+    pseudo_element: Option<&PseudoElement>,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> bool {
+    selector.pseudo_element() == pseudo_element.map(|p| p.name())
+        && bloom_filter
+            .map(|filter| filter.might_match(selector))
+            .unwrap_or(true)
+        && is_match_originating_element(element, selector)
+}
+
+/// Check if the selector matches the element. Selectors of pseudo-elements
+/// never match an element.
 pub fn is_match_selector(element: NodePtr, selector: &Selector) -> bool {
-    let mut current_element = Some(element);
-    for (selector_seq, combinator) in selector.values().iter().rev() {
-        if let Some(el) = current_element.clone() {
-            match combinator {
-                Some(Combinator::Child) => {
-                    let parent = get_parent(&el);
-                    if let Some(p) = &parent {
-                        if !is_match_simple_selector_seq(p, selector_seq) {
-                            return false;
-                        }
-                    }
-                    current_element = parent;
-                }
-                Some(Combinator::Descendant) => loop {
-                    let parent = get_parent(&el);
-                    if let Some(p) = &parent {
-                        if is_match_simple_selector_seq(p, selector_seq) {
-                            current_element = parent;
-                            break;
-                        }
-                    }
-                    return false;
-                },
-                Some(Combinator::NextSibling) => {
-                    let sibling = get_prev_sibling(&el);
-                    if let Some(sibling) = &sibling {
-                        if !is_match_simple_selector_seq(sibling, selector_seq) {
-                            return false;
-                        }
-                    }
-                    current_element = sibling;
-                }
-                Some(Combinator::SubsequentSibling) => loop {
-                    let sibling = get_prev_sibling(&el);
-                    if let Some(s) = &sibling {
-                        if is_match_simple_selector_seq(s, selector_seq) {
-                            current_element = sibling;
-                            break;
-                        }
-                    }
-                    return false;
-                },
-                None => {
-                    if !is_match_simple_selector_seq(&el, selector_seq) {
-                        return false;
-                    }
+    selector.pseudo_element().is_none() && is_match_originating_element(&element, selector)
+}
+
+/// Match the selector while ignoring its pseudo-element, if any
+pub(crate) fn is_match_originating_element(element: &NodePtr, selector: &Selector) -> bool {
+    let values = selector.values();
+    match values.len() {
+        0 => false,
+        len => is_match_compound_selectors(element, values, len - 1),
+    }
+}
+
+/// Match the compound selector at `index` against the element, then match
+/// the rest of the selector (on the left) against the elements reached
+/// through the combinators. Descendant & subsequent sibling combinators try
+/// every candidate until one of them matches.
+fn is_match_compound_selectors(element: &NodePtr, values: &SelectorData, index: usize) -> bool {
+    let (selector_seq, _) = &values[index];
+    if !is_match_simple_selector_seq(element, selector_seq) {
+        return false;
+    }
+
+    if index == 0 {
+        return true;
+    }
+
+    let next_index = index - 1;
+    let (_, combinator) = &values[next_index];
+    match combinator {
+        Some(Combinator::Child) => get_parent(element)
+            .map(|parent| is_match_compound_selectors(&parent, values, next_index))
+            .unwrap_or(false),
+        Some(Combinator::Descendant) => {
+            let mut ancestor = get_parent(element);
+            while let Some(el) = ancestor {
+                if is_match_compound_selectors(&el, values, next_index) {
+                    return true;
                 }
+                ancestor = get_parent(&el);
             }
-        } else {
-            return false;
+            false
         }
+        Some(Combinator::NextSibling) => prev_element_siblings(element)
+            .next()
+            .map(|sibling| is_match_compound_selectors(&sibling, values, next_index))
+            .unwrap_or(false),
+        Some(Combinator::SubsequentSibling) => prev_element_siblings(element)
+            .any(|sibling| is_match_compound_selectors(&sibling, values, next_index)),
+        // compound selectors are always separated by a combinator
+        None => false,
     }
-    true
 }
 
 fn is_match_simple_selector_seq(element: &NodePtr, sequence: &SimpleSelectorSequence) -> bool {
-    if let Some(element) = element.as_element_opt() {
+    if element.is_element() {
         return sequence
             .values()
             .iter()
@@ -89,7 +119,8 @@ fn is_match_simple_selector_seq(element: &NodePtr, sequence: &SimpleSelectorSequ
     false
 }
 
-fn is_match_simple_selector(element: &Element, selector: &SimpleSelector) -> bool {
+fn is_match_simple_selector(node: &NodePtr, selector: &SimpleSelector) -> bool {
+    let element = node.as_element();
     match selector.selector_type() {
         SimpleSelectorType::Universal => true,
         SimpleSelectorType::Type => {
@@ -110,10 +141,211 @@ fn is_match_simple_selector(element: &Element, selector: &SimpleSelector) -> boo
             }
             false
         }
+        SimpleSelectorType::Attribute => {
+            if let Some(name) = selector.value() {
+                return is_match_attribute_selector(element, name, selector.attribute());
+            }
+            false
+        }
+        // checked against the requested pseudo-element when matching the
+        // whole selector
+        SimpleSelectorType::PseudoElement => true,
+        SimpleSelectorType::Pseudo => match selector.argument() {
+            Some(argument) => is_match_pseudo_class_function(node, selector.value(), argument),
+            None => is_match_pseudo_class(node, selector.value()),
+        },
+    }
+}
+
+fn is_match_pseudo_class(node: &NodePtr, name: &Option<String>) -> bool {
+    let element = node.as_element();
+    let is_same_type = |sibling: &NodePtr| sibling.as_element().tag_name() == element.tag_name();
+
+    match name.as_deref() {
+        Some("hover") => element.is_hovered(),
+        Some("active") => element.is_active(),
+        Some("root") => node
+            .parent()
+            .map(|parent| parent.is_document())
+            .unwrap_or(false),
+        Some("empty") => !node.iterate_children().any(|child| {
+            child.is_element()
+                || child
+                    .as_text_opt()
+                    .map(|text| !text.get_data().is_empty())
+                    .unwrap_or(false)
+        }),
+        Some("first-child") => prev_element_siblings(node).next().is_none(),
+        Some("last-child") => next_element_siblings(node).next().is_none(),
+        Some("only-child") => {
+            prev_element_siblings(node).next().is_none()
+                && next_element_siblings(node).next().is_none()
+        }
+        Some("first-of-type") => !prev_element_siblings(node).any(|s| is_same_type(&s)),
+        Some("last-of-type") => !next_element_siblings(node).any(|s| is_same_type(&s)),
+        Some("only-of-type") => {
+            !prev_element_siblings(node).any(|s| is_same_type(&s))
+                && !next_element_siblings(node).any(|s| is_same_type(&s))
+        }
+        _ => false,
+    }
+}
+
+fn is_match_pseudo_class_function(
+    node: &NodePtr,
+    name: &Option<String>,
+    argument: &PseudoClassArgument,
+) -> bool {
+    match (name.as_deref(), argument) {
+        (Some("not"), PseudoClassArgument::Selectors(selectors)) => {
+            !is_match_selectors(node, selectors)
+        }
+        (Some("is"), PseudoClassArgument::Selectors(selectors))
+        | (Some("where"), PseudoClassArgument::Selectors(selectors)) => {
+            is_match_selectors(node, selectors)
+        }
+        (Some(name), PseudoClassArgument::Nth(an_plus_b, selectors)) => {
+            let element = node.as_element();
+            let is_counted = |sibling: &NodePtr| match name {
+                "nth-of-type" | "nth-last-of-type" => {
+                    sibling.as_element().tag_name() == element.tag_name()
+                }
+                _ => selectors
+                    .as_ref()
+                    .map(|selectors| is_match_selectors(sibling, selectors))
+                    .unwrap_or(true),
+            };
+
+            // with `of S`, the element itself must match S
+            if !is_counted(node) {
+                return false;
+            }
+
+            let position = match name {
+                "nth-child" | "nth-of-type" => {
+                    prev_element_siblings(node).filter(is_counted).count()
+                }
+                "nth-last-child" | "nth-last-of-type" => {
+                    next_element_siblings(node).filter(is_counted).count()
+                }
+                _ => return false,
+            };
+            an_plus_b.matches(position as i32 + 1)
+        }
         _ => false,
     }
 }
 
+fn prev_element_siblings(node: &NodePtr) -> impl Iterator<Item = NodePtr> {
+    node.iterate_prev_siblings()
+        .filter(|sibling| sibling.is_element())
+        .map(NodePtr)
+}
+
+fn next_element_siblings(node: &NodePtr) -> impl Iterator<Item = NodePtr> {
+    node.iterate_next_siblings()
+        .filter(|sibling| sibling.is_element())
+        .map(NodePtr)
+}
+
+/// Attributes whose values are matched case-insensitively in HTML documents
+/// https://html.spec.whatwg.org/multipage/semantics-other.html#case-sensitivity-of-selectors
+const CASE_INSENSITIVE_ATTRIBUTES: &[&str] = &[
+    "accept",
+    "accept-charset",
+    "align",
+    "alink",
+    "axis",
+    "bgcolor",
+    "charset",
+    "checked",
+    "clear",
+    "codetype",
+    "color",
+    "compact",
+    "declare",
+    "defer",
+    "dir",
+    "direction",
+    "disabled",
+    "enctype",
+    "face",
+    "frame",
+    "hreflang",
+    "http-equiv",
+    "lang",
+    "language",
+    "link",
+    "media",
+    "method",
+    "multiple",
+    "nohref",
+    "noresize",
+    "noshade",
+    "nowrap",
+    "readonly",
+    "rel",
+    "rev",
+    "rules",
+    "scope",
+    "scrolling",
+    "selected",
+    "shape",
+    "target",
+    "text",
+    "type",
+    "valign",
+    "valuetype",
+    "vlink",
+];
+
+fn is_match_attribute_selector(
+    element: &Element,
+    name: &str,
+    attribute: &Option<AttributeSelector>,
+) -> bool {
+    let actual = match element.get_attribute(name) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    let attribute = match attribute {
+        Some(attribute) => attribute,
+        // [attr] only checks for presence
+        None => return true,
+    };
+
+    let case_insensitive = match attribute.case_flag {
+        Some(AttributeCaseFlag::AsciiCaseInsensitive) => true,
+        Some(AttributeCaseFlag::CaseSensitive) => false,
+        None => CASE_INSENSITIVE_ATTRIBUTES.contains(&name),
+    };
+
+    let (actual, expected) = if case_insensitive {
+        (
+            actual.to_ascii_lowercase(),
+            attribute.value.to_ascii_lowercase(),
+        )
+    } else {
+        (actual, attribute.value.clone())
+    };
+
+    match attribute.operator {
+        AttributeOperator::Equals => actual == expected,
+        AttributeOperator::Includes => {
+            !expected.is_empty()
+                && !expected.contains(char::is_whitespace)
+                && actual.split_whitespace().any(|item| item == expected)
+        }
+        AttributeOperator::DashMatch => {
+            actual == expected || actual.starts_with(&format!("{}-", expected))
+        }
+        AttributeOperator::Prefix => !expected.is_empty() && actual.starts_with(&expected),
+        AttributeOperator::Suffix => !expected.is_empty() && actual.ends_with(&expected),
+        AttributeOperator::Substring => !expected.is_empty() && actual.contains(&expected),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,7 +355,7 @@ mod tests {
     use css::tokenizer::Tokenizer;
     use dom::create_element;
     use shared::tree_node::WeakTreeNode;
-    use test_utils::dom_creator::document;
+    use test_utils::dom_creator::{document, element};
 
     fn assert_style_rule_matched_element(rule: &CSSRule, element: &NodePtr) {
         match rule {
@@ -131,6 +363,7 @@ mod tests {
                 let selectors = &style.selectors;
                 assert!(is_match_selectors(&element, selectors));
             }
+            rule => panic!("Expected a style rule, got {:?}", rule),
         }
     }
 
@@ -140,6 +373,7 @@ mod tests {
                 let selectors = &style.selectors;
                 assert!(!is_match_selectors(&element, selectors));
             }
+            rule => panic!("Expected a style rule, got {:?}", rule),
         }
     }
 
@@ -165,7 +399,7 @@ mod tests {
     #[test]
     fn match_simple_id() {
         let element = create_element(WeakTreeNode::from(&document().0), "h1");
-        element.as_element().set_attribute("id", "button");
+        element.set_attribute("id", "button");
         let css = "h1#button { color: red; }";
 
         let tokenizer = Tokenizer::new(css.chars());
@@ -215,6 +449,107 @@ mod tests {
         assert_style_rule_matched_element(rule, &child);
     }
 
+    #[test]
+    fn match_deep_descendant() {
+        let doc = document();
+        let child = element("span", doc.clone(), vec![]);
+        let inner = element("div.inner", doc.clone(), vec![child.clone()]);
+        let outer = element("section", doc.clone(), vec![inner.clone()]);
+        let _root = element("div.outer", doc.clone(), vec![outer]);
+
+        let matched = [
+            "div span",
+            "div.outer span",
+            "div.outer > section span",
+            "div.outer div > span",
+            "section > div span",
+        ];
+        let not_matched = ["p span", "div.outer > span", "section > span", "span span"];
+
+        for css in matched {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(is_match_selector(child.clone(), &selector), "{}", css);
+        }
+        for css in not_matched {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(!is_match_selector(child.clone(), &selector), "{}", css);
+        }
+    }
+
+    #[test]
+    fn match_subsequent_sibling() {
+        let doc = document();
+        let items: Vec<NodePtr> = ["h1", "p.first", "div", "p.last"]
+            .iter()
+            .map(|selector| element(selector, doc.clone(), vec![]))
+            .collect();
+        let _parent = element("section", doc.clone(), items.clone());
+        let last = &items[3];
+
+        let matched = ["h1 ~ p", "p.first ~ p", "div + p", "h1 ~ div + p"];
+        let not_matched = ["h1 + p.last", "p.last ~ p", "span ~ p", "p.first + p"];
+
+        for css in matched {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(is_match_selector(last.clone(), &selector), "{}", css);
+        }
+        for css in not_matched {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(!is_match_selector(last.clone(), &selector), "{}", css);
+        }
+    }
+
+    #[test]
+    fn find_most_specific_matched_selector() {
+        let doc = document();
+        let child = element("span.label", doc.clone(), vec![]);
+        let _parent = element("div", doc.clone(), vec![child.clone()]);
+
+        let selectors = css::selector::parse_selectors(
+            &css::parser::Parser::<Token>::new(
+                Tokenizer::new("#missing, div span, .label".chars()).run(),
+            )
+            .parse_a_list_of_component_values(),
+        );
+        let matched = find_matched_selector(&child, &selectors, None, None).unwrap();
+        assert_eq!(
+            matched.specificity(),
+            css::selector::structs::Specificity::new(0, 1, 0)
+        );
+
+        let filter = AncestorBloomFilter::for_children_of(&_parent);
+        let matched = find_matched_selector(&child, &selectors, None, Some(&filter)).unwrap();
+        assert_eq!(
+            matched.specificity(),
+            css::selector::structs::Specificity::new(0, 1, 0)
+        );
+    }
+
+    #[test]
+    fn match_pseudo_element_selectors() {
+        let doc = document();
+        let child = element("p.note", doc.clone(), vec![]);
+        let _parent = element("div", doc.clone(), vec![child.clone()]);
+
+        let selectors = css::selector::parse_selectors(
+            &css::parser::Parser::<Token>::new(
+                Tokenizer::new("div p::before, .note::after, p".chars()).run(),
+            )
+            .parse_a_list_of_component_values(),
+        );
+
+        let matched = find_matched_selector(&child, &selectors, None, None).unwrap();
+        assert_eq!(matched, &selectors[2]);
+        let matched =
+            find_matched_selector(&child, &selectors, Some(&PseudoElement::Before), None).unwrap();
+        assert_eq!(matched, &selectors[0]);
+        let matched =
+            find_matched_selector(&child, &selectors, Some(&PseudoElement::After), None).unwrap();
+        assert_eq!(matched, &selectors[1]);
+
+        assert!(!is_match_selector(child.clone(), &selectors[0]));
+    }
+
     #[test]
     fn match_invalid_child() {
         let doc = document();
@@ -253,6 +588,130 @@ mod tests {
         assert_style_rule_not_matched_element(rule, &child);
     }
 
+    #[test]
+    fn match_hover() {
+        let doc = document();
+        let parent = create_element(WeakTreeNode::from(&doc.0), "div");
+        let child = create_element(WeakTreeNode::from(&doc.0), "button");
+        parent.append_child(child.0.clone());
+
+        let css = "div:hover > button { color: red; }";
+
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+
+        let rule = stylesheet.first().unwrap();
+
+        assert_style_rule_not_matched_element(rule, &child);
+
+        let changed = doc.as_document().set_hovered_element(Some(child.clone()));
+        assert_eq!(changed.len(), 2);
+        assert_style_rule_matched_element(rule, &child);
+
+        let changed = doc.as_document().set_hovered_element(None);
+        assert_eq!(changed.len(), 2);
+        assert_style_rule_not_matched_element(rule, &child);
+    }
+
+    #[test]
+    fn match_attribute() {
+        let element = create_element(WeakTreeNode::from(&document().0), "input");
+        element.set_attribute("type", "Text");
+        element.set_attribute("lang", "en-US");
+        element.set_attribute("data-tags", "one two");
+        element.set_attribute("class", "field wide");
+
+        let matched = [
+            "input[type]",
+            "[type=text]",
+            "[type=\"TEXT\"]",
+            "[lang|=en]",
+            "[data-tags~=two]",
+            "[data-tags^=on]",
+            "[data-tags$=wo]",
+            "[data-tags*='e t']",
+            "[data-tags='ONE TWO' i]",
+            "[class~=wide]",
+        ];
+        let not_matched = [
+            "[value]",
+            "[type=text s]",
+            "[lang|=e]",
+            "[data-tags~=tw]",
+            "[data-tags~='one two']",
+            "[data-tags^='']",
+            "[data-tags='ONE TWO']",
+        ];
+
+        for css in matched {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(is_match_selector(element.clone(), &selector), "{}", css);
+        }
+        for css in not_matched {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(!is_match_selector(element.clone(), &selector), "{}", css);
+        }
+    }
+
+    #[test]
+    fn match_empty_class_attribute() {
+        let element = create_element(WeakTreeNode::from(&document().0), "div");
+        element.set_attribute("class", "");
+
+        for css in ["[class]", "[class='']"] {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            assert!(is_match_selector(element.clone(), &selector), "{}", css);
+        }
+        let selector = css::selector::parse_selector_str("[class~='']").unwrap();
+        assert!(!is_match_selector(element, &selector));
+    }
+
+    #[test]
+    fn match_structural_pseudo_classes() {
+        let doc = document();
+        let items: Vec<NodePtr> = ["li.a", "li.b", "li.a", "p", "li.b"]
+            .iter()
+            .map(|selector| element(selector, doc.clone(), vec![]))
+            .collect();
+        let list = element("ul", doc.clone(), items.clone());
+        doc.append_child(list.0.clone());
+
+        let assert_matches = |css: &str, expected: [bool; 5]| {
+            let selector = css::selector::parse_selector_str(css).unwrap();
+            let result: Vec<bool> = items
+                .iter()
+                .map(|item| is_match_selector(item.clone(), &selector))
+                .collect();
+            assert_eq!(result, expected.to_vec(), "{}", css);
+        };
+
+        assert_matches(":first-child", [true, false, false, false, false]);
+        assert_matches(":last-child", [false, false, false, false, true]);
+        assert_matches(":only-child", [false, false, false, false, false]);
+        assert_matches("li:first-of-type", [true, false, false, false, false]);
+        assert_matches("li:last-of-type", [false, false, false, false, true]);
+        assert_matches(":only-of-type", [false, false, false, true, false]);
+        assert_matches(":nth-child(odd)", [true, false, true, false, true]);
+        assert_matches(":nth-child(-n+2)", [true, true, false, false, false]);
+        assert_matches(":nth-last-child(2)", [false, false, false, true, false]);
+        assert_matches(":nth-of-type(2n)", [false, true, false, false, true]);
+        assert_matches(":nth-last-of-type(1)", [false, false, false, true, true]);
+        assert_matches(":nth-child(2 of .b)", [false, false, false, false, true]);
+        assert_matches(":empty", [true, true, true, true, true]);
+        assert_matches(":root", [false, false, false, false, false]);
+        assert_matches(":not(.a, p)", [false, true, false, false, true]);
+        assert_matches(":is(.a, p)", [true, false, true, true, false]);
+        assert_matches(":where(.b)", [false, true, false, false, true]);
+
+        let root = css::selector::parse_selector_str(":root").unwrap();
+        assert!(is_match_selector(list.clone(), &root));
+
+        let empty = css::selector::parse_selector_str(":empty").unwrap();
+        assert!(!is_match_selector(list, &empty));
+    }
+
     #[test]
     fn match_group_of_types() {
         let doc = document();
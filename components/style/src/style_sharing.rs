@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use dom::node::NodePtr;
+
+use crate::bloom_filter::AncestorBloomFilter;
+use crate::rule_map::RuleMap;
+
+/// Number of recently styled siblings that are kept as candidates
+const CACHE_SIZE: usize = 8;
+
+/// Recently styled children of an element, whose styles can be reused by
+/// their later siblings. Siblings that no style rule can tell apart, e.g.
+/// the items of a list or the cells of a table row, get the same styles, so
+/// they don't need to be computed again.
+///
+/// A cache must only be used for the children of a single element, so that
+/// the candidates have the same ancestors & parent style as the element.
+#[derive(Default)]
+pub struct StyleSharingCache {
+    candidates: VecDeque<NodePtr>,
+}
+
+impl StyleSharingCache {
+    pub fn new() -> Self {
+        Self {
+            candidates: VecDeque::new(),
+        }
+    }
+
+    /// Set the styles of a cached sibling on the element if the rules match
+    /// both of them the same way. This includes the computed style, the
+    /// styles of the pseudo-elements & the custom properties. Return `false`
+    /// if no candidate can be shared, then the styles must be computed.
+    pub fn share_style(
+        &mut self,
+        element: &NodePtr,
+        rules: &RuleMap,
+        bloom_filter: Option<&AncestorBloomFilter>,
+    ) -> bool {
+        if !is_shareable(element) {
+            return false;
+        }
+
+        let position = self
+            .candidates
+            .iter()
+            .position(|candidate| can_share_style(element, candidate, rules, bloom_filter));
+        let candidate = match position.and_then(|position| self.candidates.remove(position)) {
+            Some(candidate) => candidate,
+            None => return false,
+        };
+
+        element.set_computed_style(candidate.computed_style().clone());
+        element.set_pseudo_element_styles(candidate.pseudo_element_styles().clone());
+        element.set_custom_properties(candidate.custom_properties());
+        // the most recently used candidate is the most likely to match
+        self.candidates.push_front(candidate);
+        true
+    }
+
+    /// Keep an element whose styles were just computed as a candidate
+    pub fn insert(&mut self, element: NodePtr) {
+        if !is_shareable(&element) {
+            return;
+        }
+        if self.candidates.len() == CACHE_SIZE {
+            self.candidates.pop_back();
+        }
+        self.candidates.push_front(element);
+    }
+}
+
+/// Elements with an ID or a `style` attribute get styles of their own
+fn is_shareable(node: &NodePtr) -> bool {
+    match node.as_element_opt() {
+        Some(element) => element.id().is_none() && !element.has_attribute("style"),
+        None => false,
+    }
+}
+
+fn can_share_style(
+    element: &NodePtr,
+    candidate: &NodePtr,
+    rules: &RuleMap,
+    bloom_filter: Option<&AncestorBloomFilter>,
+) -> bool {
+    let is_sibling = match (element.parent(), candidate.parent()) {
+        (Some(parent), Some(candidate_parent)) => Rc::ptr_eq(&parent, &candidate_parent),
+        _ => false,
+    };
+    if !is_sibling {
+        return false;
+    }
+
+    let (element_data, candidate_data) = (element.as_element(), candidate.as_element());
+    if element_data.tag_name() != candidate_data.tag_name()
+        || element_data.get_attribute("class") != candidate_data.get_attribute("class")
+    {
+        return false;
+    }
+
+    let same_attributes = rules
+        .attribute_names()
+        .iter()
+        .all(|name| element_data.get_attribute(name) == candidate_data.get_attribute(name));
+
+    same_attributes && rules.matches_same_revalidation_selectors(element, candidate, bloom_filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::{compute_pseudo_element_styles, compute_styles};
+    use crate::utils::rule_map;
+    use style_types::Property;
+    use test_utils::dom_creator::{document, element};
+
+    /// Style the children of the list with a sharing cache & return the
+    /// indexes of the children whose styles were shared
+    fn style_children(list: &NodePtr, rules: &RuleMap) -> Vec<usize> {
+        list.set_computed_style(compute_styles(list.clone(), rules, None));
+        let mut cache = StyleSharingCache::new();
+        let mut shared = Vec::new();
+        list.iterate_children()
+            .enumerate()
+            .for_each(|(index, child)| {
+                let child = NodePtr(child);
+                if cache.share_style(&child, rules, None) {
+                    shared.push(index);
+                    return;
+                }
+                child.set_computed_style(compute_styles(child.clone(), rules, None));
+                child.set_pseudo_element_styles(compute_pseudo_element_styles(
+                    child.clone(),
+                    rules,
+                    None,
+                ));
+                cache.insert(child);
+            });
+        shared
+    }
+
+    fn create_list(items: &[&str]) -> (NodePtr, Vec<NodePtr>) {
+        let doc = document();
+        let items = items
+            .iter()
+            .map(|item| element(item, doc.clone(), vec![]))
+            .collect::<Vec<NodePtr>>();
+        let list = element("ul", doc.clone(), items.clone());
+        doc.append_child(list.0.clone());
+        (list, items)
+    }
+
+    #[test]
+    fn share_style_of_identical_siblings() {
+        let (list, items) = create_list(&["li.a", "li.a", "li.b", "li.a", "li#c.a", "p.a"]);
+        let rules =
+            rule_map("li { width: 10px; } .a { height: 5px; } li::before { content: 'x'; }");
+
+        assert_eq!(style_children(&list, &rules), vec![1, 3]);
+        assert!(items[1]
+            .computed_style()
+            .shares_non_inherited_with(&items[0].computed_style()));
+        assert_eq!(
+            items[3].get_style(&Property::Height),
+            items[0].get_style(&Property::Height)
+        );
+        assert_eq!(items[3].pseudo_element_styles().len(), 1);
+    }
+
+    #[test]
+    fn revalidate_structural_and_sibling_selectors() {
+        let (list, _) = create_list(&["li", "li", "li", "li", "li", "li"]);
+        let rules = rule_map("li:nth-child(2n+1) { width: 10px; }");
+        // odd & even items share the styles of the first ones
+        assert_eq!(style_children(&list, &rules), vec![2, 3, 4, 5]);
+
+        let (list, _) = create_list(&["li", "li", "li", "li"]);
+        let rules = rule_map("li + li { width: 10px; } li:last-child::after { content: ''; }");
+        assert_eq!(style_children(&list, &rules), vec![2]);
+    }
+
+    #[test]
+    fn compare_attributes_used_by_selectors() {
+        let (list, items) = create_list(&["li", "li", "li", "li"]);
+        items[1].set_attribute("data-state", "on");
+        items[2].set_attribute("title", "item");
+        items[3].set_attribute("style", "width: 1px");
+        let rules = rule_map("[data-state=on] { width: 10px; }");
+
+        // `title` isn't used by any selector
+        assert_eq!(style_children(&list, &rules), vec![2]);
+
+        // attribute names of selectors are case-insensitive
+        let (list, items) = create_list(&["li", "li"]);
+        items[1].set_attribute("data-x", "on");
+        let rules = rule_map("[DATA-x] { width: 10px; }");
+        assert_eq!(style_children(&list, &rules), Vec::<usize>::new());
+        assert_eq!(items[1].get_style(&Property::Width).to_absolute_px(), 10.);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::cascade::{expand_shorthand, get_shorthand_longhands};
+use crate::custom_properties::has_var_references;
+use css::parser::structs::ComponentValue;
+use style_types::{Property, Value};
+
+/// Check if a declaration of a `@supports` condition would be accepted
+/// by the cascade. Values with `var()` references are assumed to be valid,
+/// like they are at parse time.
+/// https://www.w3.org/TR/css-conditional-3/#support-definition
+pub fn supports_declaration(name: &str, values: &[ComponentValue]) -> bool {
+    if name.starts_with("--") {
+        return true;
+    }
+
+    let name = name.to_lowercase();
+    if has_var_references(values) {
+        return get_shorthand_longhands(&name).is_some() || Property::parse(&name).is_some();
+    }
+
+    match expand_shorthand(&name, values) {
+        Some(expanded) => expanded.is_some(),
+        None => match Property::parse(&name) {
+            Some(property) => Value::parse(&property, values).is_some(),
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::token::Token;
+    use css::tokenizer::Tokenizer;
+    use std::rc::Rc;
+    use test_utils::dom_creator::{document, element, text};
+
+    fn supports(name: &str, css: &str) -> bool {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        supports_declaration(name, &parser.parse_a_list_of_component_values())
+    }
+
+    #[test]
+    fn check_declaration_support() {
+        assert!(supports("display", "block"));
+        assert!(supports("DISPLAY", "block"));
+        assert!(!supports("display", "nonsense"));
+        assert!(supports("color", "oklch(70% 0.1 200)"));
+        assert!(!supports("color", "10px"));
+        assert!(supports("margin", "0 auto"));
+        assert!(!supports("margin", "red"));
+        assert!(supports("width", "var(--width)"));
+        assert!(supports("--anything", "{ goes }"));
+        assert!(!supports("unknown-property", "1"));
+    }
+
+    #[test]
+    fn collect_supported_rules() {
+        let doc = document();
+        let css = "@supports (display: nonsense) { p { width: 1px; } } \
+                   @supports (margin: 0 auto) and (not (color: 10px)) { p { height: 2px; } }";
+        let style = element("style", doc.clone(), vec![text(css, doc.clone())]);
+        doc.append_child(style.0.clone());
+
+        // nothing is supported without the check of the style system
+        assert!(doc.as_document().style_rules().is_empty());
+
+        doc.as_document()
+            .set_declaration_support(Rc::new(supports_declaration));
+        let rules = doc.as_document().style_rules();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].inner.declarations[0].name, "height");
+    }
+}
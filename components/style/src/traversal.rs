@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+
+use css::selector::structs::PseudoElement;
+use dom::node::{LayoutDirty, NodePtr};
+use style_types::values::display::DisplayBox;
+use style_types::values::prelude::Display;
+use style_types::ComputedStyle;
+
+use crate::bloom_filter::AncestorBloomFilter;
+use crate::compute::{compute_pseudo_element_styles, compute_styles};
+use crate::rule_map::RuleMap;
+use crate::style_sharing::StyleSharingCache;
+
+/// Recompute the styles of the style dirty nodes of the document & of their
+/// descendants, then clear their dirty flags. The later siblings of a dirty
+/// node are restyled too, as sibling combinators & structural
+/// pseudo-classes can depend on it. The descendants of a node that is only
+/// restyled itself are visited if its style changed, as they inherit from
+/// it.
+///
+/// The nodes whose computed styles changed are marked layout dirty with the
+/// update that their boxes need. Return `true` if the computed style of any
+/// node or of its pseudo-elements changed.
+pub fn restyle_document(document: &NodePtr, rules: &RuleMap) -> bool {
+    let mut bloom_filter = AncestorBloomFilter::new();
+    if document.is_style_dirty() || document.is_self_style_dirty() {
+        restyle_node(
+            document,
+            rules,
+            &mut bloom_filter,
+            &mut StyleSharingCache::new(),
+            document.is_style_dirty(),
+        )
+    } else if document.has_style_dirty_descendants() {
+        let changed = restyle_children(document, rules, &mut bloom_filter, false, false);
+        document.clear_style_dirty();
+        changed
+    } else {
+        false
+    }
+}
+
+/// Push the element into the ancestor bloom filter while styling its children
+fn with_element_in_filter(
+    node: &NodePtr,
+    bloom_filter: &mut AncestorBloomFilter,
+    callback: impl FnOnce(&mut AncestorBloomFilter),
+) {
+    match node.as_element_opt() {
+        Some(element) => {
+            bloom_filter.push_element(element);
+            callback(bloom_filter);
+            bloom_filter.pop_element(element);
+        }
+        None => callback(bloom_filter),
+    }
+}
+
+/// Visit the children of a node. Every child is restyled if `restyle_all`,
+/// e.g. the style that they inherit changed, & their descendants too if
+/// `restyle_descendants`. Otherwise only the dirty children & their later
+/// siblings are restyled.
+fn restyle_children(
+    node: &NodePtr,
+    rules: &RuleMap,
+    bloom_filter: &mut AncestorBloomFilter,
+    restyle_all: bool,
+    restyle_descendants: bool,
+) -> bool {
+    let mut changed = false;
+    with_element_in_filter(node, bloom_filter, |bloom_filter| {
+        let mut sharing_cache = StyleSharingCache::new();
+        let mut restyle_siblings = false;
+        node.for_each_child(|child| {
+            let child = NodePtr(child);
+            restyle_siblings |= child.is_style_dirty() || child.is_self_style_dirty();
+            if restyle_descendants || child.is_style_dirty() {
+                changed |= restyle_node(&child, rules, bloom_filter, &mut sharing_cache, true);
+            } else if restyle_all || restyle_siblings {
+                // the descendants only have to be restyled if they can match
+                // differently once the child moved among its siblings
+                let restyle_descendants =
+                    restyle_siblings && rules.is_ancestor_position_dependent();
+                changed |= restyle_node(
+                    &child,
+                    rules,
+                    bloom_filter,
+                    &mut sharing_cache,
+                    restyle_descendants,
+                );
+            } else if child.has_style_dirty_descendants() {
+                changed |= restyle_children(&child, rules, bloom_filter, false, false);
+                child.clear_style_dirty();
+            }
+        })
+    });
+    changed
+}
+
+/// Recompute the style of the node, & of its descendants if
+/// `restyle_descendants`. Otherwise its children are only restyled if its
+/// style changed, or if they are dirty themselves. `sharing_cache` holds
+/// the siblings of the node that were restyled before it.
+fn restyle_node(
+    node: &NodePtr,
+    rules: &RuleMap,
+    bloom_filter: &mut AncestorBloomFilter,
+    sharing_cache: &mut StyleSharingCache,
+    restyle_descendants: bool,
+) -> bool {
+    let old_style = node.computed_style().clone();
+    let old_pseudo_element_styles = node.pseudo_element_styles().clone();
+    let old_custom_properties = node.custom_properties();
+
+    if !sharing_cache.share_style(node, rules, Some(bloom_filter)) {
+        let computed_style = compute_styles(node.clone(), rules, Some(bloom_filter));
+        node.set_computed_style(computed_style);
+        let pseudo_element_styles =
+            compute_pseudo_element_styles(node.clone(), rules, Some(bloom_filter));
+        node.set_pseudo_element_styles(pseudo_element_styles);
+        sharing_cache.insert(node.clone());
+    }
+
+    let mut changed = mark_layout_damage(node, &old_style, &old_pseudo_element_styles);
+    // the children inherit from the whole style with the `inherit` keyword
+    let inherited_style_changed =
+        old_style != *node.computed_style() || *old_custom_properties != *node.custom_properties();
+    let has_style_dirty_descendants = node.has_style_dirty_descendants();
+    node.clear_style_dirty();
+
+    // `rem` lengths of every descendant are relative to the root element
+    let is_root_element = node.is_element()
+        && node
+            .parent()
+            .map_or(false, |parent| NodePtr(parent).is_document());
+    let restyle_descendants = restyle_descendants
+        || (is_root_element && old_style.font_size() != node.computed_style().font_size());
+
+    if restyle_descendants || inherited_style_changed || has_style_dirty_descendants {
+        changed |= restyle_children(
+            node,
+            rules,
+            bloom_filter,
+            inherited_style_changed,
+            restyle_descendants,
+        );
+    }
+    changed
+}
+
+/// Mark the node layout dirty according to how its styles changed. Return
+/// `true` if the style of the node or of its pseudo-elements changed.
+fn mark_layout_damage(
+    node: &NodePtr,
+    old_style: &ComputedStyle,
+    old_pseudo_element_styles: &HashMap<PseudoElement, ComputedStyle>,
+) -> bool {
+    let style = node.computed_style();
+    if *old_style == *style {
+        if *old_pseudo_element_styles != *node.pseudo_element_styles() {
+            node.mark_layout_dirty(LayoutDirty::RebuildChildren);
+            return true;
+        }
+        return false;
+    }
+
+    let generates_other_boxes = old_style.display() != style.display()
+        || old_style.content() != style.content()
+        || old_style.counter_reset() != style.counter_reset()
+        || old_style.counter_increment() != style.counter_increment();
+
+    if generates_other_boxes {
+        // the box of the node itself is built with the boxes of its siblings
+        match node.parent() {
+            Some(parent) => NodePtr(parent).mark_layout_dirty(LayoutDirty::RebuildChildren),
+            None => node.mark_layout_dirty(LayoutDirty::RebuildChildren),
+        }
+    } else if *old_pseudo_element_styles != *node.pseudo_element_styles() {
+        node.mark_layout_dirty(LayoutDirty::RebuildChildren);
+    } else if !old_style.has_same_layout_as(&style) {
+        // a node without a box doesn't have to be laid out
+        if *style.display() != Display::Box(DisplayBox::None) {
+            node.mark_layout_dirty(LayoutDirty::Reflow);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rule_map;
+    use style_types::Property;
+    use test_utils::dom_creator::{document, element, text};
+
+    fn create_list(doc: &NodePtr, count: usize) -> (NodePtr, Vec<NodePtr>) {
+        let items = (0..count)
+            .map(|_| element("li", doc.clone(), vec![text("item", doc.clone())]))
+            .collect::<Vec<NodePtr>>();
+        let list = element("ul", doc.clone(), items.clone());
+        doc.append_child(list.0.clone());
+        (list, items)
+    }
+
+    fn clear_layout_dirty(node: &NodePtr) {
+        node.clear_layout_dirty();
+        node.for_each_child(|child| clear_layout_dirty(&NodePtr(child)));
+    }
+
+    #[test]
+    fn restyle_only_dirty_subtrees() {
+        let doc = document();
+        let (list, items) = create_list(&doc, 4);
+        let rules =
+            rule_map("li { width: 10px; } li.wide { width: 20px; } li + .wide { height: 5px; }");
+        restyle_document(&doc, &rules);
+        assert!(!doc.is_style_dirty() && !doc.has_style_dirty_descendants());
+        assert!(!list.is_style_dirty() && !list.has_style_dirty_descendants());
+        clear_layout_dirty(&doc);
+
+        // a style set without the node being dirty is kept
+        items[0].set_computed_style(ComputedStyle::initial());
+        items[2].set_attribute("class", "wide");
+        assert!(items[2].is_style_dirty());
+        assert!(list.has_style_dirty_descendants() && doc.has_style_dirty_descendants());
+
+        restyle_document(&doc, &rules);
+        assert!(!doc.has_style_dirty_descendants());
+        assert_eq!(
+            items[0].get_style(&Property::Width),
+            ComputedStyle::initial().get(&Property::Width)
+        );
+        assert_eq!(items[2].get_style(&Property::Width).to_absolute_px(), 20.);
+        assert_eq!(items[2].get_style(&Property::Height).to_absolute_px(), 5.);
+        assert_eq!(items[2].layout_dirty(), LayoutDirty::Reflow);
+        assert_eq!(items[3].layout_dirty(), LayoutDirty::Clean);
+        assert!(list.has_layout_dirty_descendants());
+    }
+
+    #[test]
+    fn restyle_after_class_change() {
+        let doc = document();
+        let (_, items) = create_list(&doc, 2);
+        let rules = rule_map("li { width: 10px; } .wide { width: 20px; }");
+        restyle_document(&doc, &rules);
+        let unchanged_style = items[0].computed_style().clone();
+
+        // attributes are only set through the node, which marks it dirty
+        items[1].set_attribute("class", "wide");
+        assert!(items[1].is_style_dirty() && !items[0].is_style_dirty());
+        assert!(doc.has_style_dirty_descendants());
+
+        restyle_document(&doc, &rules);
+        assert_eq!(items[1].get_style(&Property::Width).to_absolute_px(), 20.);
+        assert!(items[0]
+            .computed_style()
+            .shares_non_inherited_with(&unchanged_style));
+        assert!(!items[1].is_style_dirty() && !doc.has_style_dirty_descendants());
+    }
+
+    #[test]
+    fn mark_layout_damage_of_style_changes() {
+        let doc = document();
+        let (list, items) = create_list(&doc, 3);
+        let rules = rule_map(
+            "li { display: block; } \
+             li.red { color: red; background-color: blue; } \
+             li.hidden { display: none; } \
+             li.marked::before { content: '*'; }",
+        );
+        restyle_document(&doc, &rules);
+        clear_layout_dirty(&doc);
+
+        // colors are only painted
+        items[0].set_attribute("class", "red");
+        restyle_document(&doc, &rules);
+        assert_eq!(items[0].layout_dirty(), LayoutDirty::Clean);
+        assert!(!doc.has_layout_dirty_descendants());
+        // the text inherits the color
+        let text = NodePtr(items[0].first_child().unwrap());
+        assert_eq!(text.layout_dirty(), LayoutDirty::Clean);
+
+        // the list has to build another box for the item
+        items[1].set_attribute("class", "hidden");
+        restyle_document(&doc, &rules);
+        assert_eq!(list.layout_dirty(), LayoutDirty::RebuildChildren);
+        clear_layout_dirty(&doc);
+
+        items[2].set_attribute("class", "marked");
+        restyle_document(&doc, &rules);
+        assert_eq!(items[2].layout_dirty(), LayoutDirty::RebuildChildren);
+        assert_eq!(list.layout_dirty(), LayoutDirty::Clean);
+    }
+
+    #[test]
+    fn restyle_siblings_of_inserted_node() {
+        let doc = document();
+        let (list, items) = create_list(&doc, 2);
+        let rules = rule_map("li:last-child { width: 10px; }");
+        restyle_document(&doc, &rules);
+        assert_eq!(items[1].get_style(&Property::Width).to_absolute_px(), 10.);
+        clear_layout_dirty(&doc);
+
+        let item = element("li", doc.clone(), vec![]);
+        list.append_child(item.0.clone());
+        assert_eq!(list.layout_dirty(), LayoutDirty::RebuildChildren);
+
+        restyle_document(&doc, &rules);
+        assert_eq!(
+            items[1].get_style(&Property::Width),
+            items[0].get_style(&Property::Width)
+        );
+        assert_eq!(item.get_style(&Property::Width).to_absolute_px(), 10.);
+    }
+
+    #[test]
+    fn restyle_parent_of_inserted_and_removed_node() {
+        let doc = document();
+        let list = element("ul", doc.clone(), vec![]);
+        doc.append_child(list.0.clone());
+        let rules = rule_map("ul:empty { display: none; }");
+        let hidden = Display::Box(DisplayBox::None);
+        restyle_document(&doc, &rules);
+        assert_eq!(*list.computed_style().display(), hidden);
+
+        let item = element("li", doc.clone(), vec![]);
+        list.append_child(item.0.clone());
+        assert!(list.is_self_style_dirty() && !list.is_style_dirty());
+        restyle_document(&doc, &rules);
+        assert_ne!(*list.computed_style().display(), hidden);
+
+        item.detach();
+        assert!(list.is_self_style_dirty() && !list.is_style_dirty());
+        restyle_document(&doc, &rules);
+        assert_eq!(*list.computed_style().display(), hidden);
+    }
+
+    #[test]
+    fn restyle_descendants_when_root_font_size_changes() {
+        let doc = document();
+        let span = element("span", doc.clone(), vec![]);
+        let body = element("body", doc.clone(), vec![span.clone()]);
+        let html = element("html", doc.clone(), vec![body]);
+        doc.append_child(html.0.clone());
+        let rules = rule_map(
+            "html:last-child { font-size: 20px; } body, span { font-size: 10px; } \
+             span { width: 2rem; }",
+        );
+        restyle_document(&doc, &rules);
+        assert_eq!(span.get_style(&Property::Width).to_absolute_px(), 40.);
+
+        // only the root element is restyled for its new sibling, the body
+        // keeps its style
+        doc.append_child(element("div", doc.clone(), vec![]).0.clone());
+        assert!(html.is_self_style_dirty() && !html.is_style_dirty());
+        restyle_document(&doc, &rules);
+        assert_eq!(span.get_style(&Property::Width).to_absolute_px(), 32.);
+    }
+
+    #[test]
+    fn restyle_only_siblings_of_inserted_node() {
+        let doc = document();
+        let (list, items) = create_list(&doc, 3);
+        let rules = rule_map("li { color: red; } li:last-child { width: 10px; }");
+        restyle_document(&doc, &rules);
+
+        // a style set without the node being dirty is kept
+        let text = NodePtr(items[0].first_child().unwrap());
+        text.set_computed_style(ComputedStyle::initial());
+
+        let item = element("li", doc.clone(), vec![]);
+        list.append_child(item.0.clone());
+        restyle_document(&doc, &rules);
+        assert_eq!(
+            items[2].get_style(&Property::Width),
+            ComputedStyle::initial().get(&Property::Width)
+        );
+        assert_eq!(item.get_style(&Property::Width).to_absolute_px(), 10.);
+        // the style of the first item didn't change, so its text isn't restyled
+        assert_eq!(*text.computed_style(), ComputedStyle::initial());
+        assert!(!doc.has_style_dirty_descendants());
+    }
+
+    #[test]
+    fn restyle_descendants_of_siblings_with_position_dependent_rules() {
+        let doc = document();
+        let spans = (0..2)
+            .map(|_| element("span", doc.clone(), vec![]))
+            .collect::<Vec<NodePtr>>();
+        let items = spans
+            .iter()
+            .map(|span| element("li", doc.clone(), vec![span.clone()]))
+            .collect::<Vec<NodePtr>>();
+        let list = element("ul", doc.clone(), items);
+        doc.append_child(list.0.clone());
+        let rules = rule_map("li:last-child span { width: 10px; }");
+        restyle_document(&doc, &rules);
+        assert_eq!(spans[1].get_style(&Property::Width).to_absolute_px(), 10.);
+
+        let span = element("span", doc.clone(), vec![]);
+        list.append_child(element("li", doc.clone(), vec![span.clone()]).0.clone());
+        restyle_document(&doc, &rules);
+        assert_eq!(
+            spans[1].get_style(&Property::Width),
+            ComputedStyle::initial().get(&Property::Width)
+        );
+        assert_eq!(span.get_style(&Property::Width).to_absolute_px(), 10.);
+    }
+}
@@ -0,0 +1,22 @@
+use css::cssom::css_rule::CSSRule;
+use style_types::{CSSLocation, CascadeOrigin, ContextualRule, UNLAYERED};
+use test_utils::css::parse_stylesheet;
+
+use crate::rule_map::RuleMap;
+
+/// Index the style rules of an author stylesheet
+pub fn rule_map(css: &str) -> RuleMap {
+    let rules = parse_stylesheet(css)
+        .iter()
+        .filter_map(|rule| match rule {
+            CSSRule::Style(style) => Some(ContextualRule {
+                inner: style.clone(),
+                location: CSSLocation::Embedded,
+                origin: CascadeOrigin::Author,
+                layer: UNLAYERED,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<ContextualRule>>();
+    RuleMap::new(rules)
+}
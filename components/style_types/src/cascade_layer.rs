@@ -0,0 +1,89 @@
+use css::cssom::layer_rule::LayerName;
+
+/// Layer order of the rules that are not in a cascade layer. Unlayered
+/// rules come after all of the layers.
+pub const UNLAYERED: u32 = u32::MAX;
+
+/// Identifier of a layer in [`CascadeLayers`]
+pub type LayerId = usize;
+
+/// Cascade layers of an origin, nested in the order they are declared
+/// https://www.w3.org/TR/css-cascade-5/#layer-ordering
+#[derive(Debug)]
+pub struct CascadeLayers {
+    /// The first layer is the implicit outer layer of the unlayered rules
+    layers: Vec<Layer>,
+}
+
+#[derive(Debug)]
+struct Layer {
+    /// `None` for anonymous layers
+    name: Option<String>,
+    children: Vec<LayerId>,
+}
+
+impl CascadeLayers {
+    pub const ROOT: LayerId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            layers: vec![Layer {
+                name: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    /// Declare the layer inside `parent`. A layer that is already declared
+    /// keeps its position.
+    pub fn declare(&mut self, parent: LayerId, name: &LayerName) -> LayerId {
+        name.0.iter().fold(parent, |parent, name| {
+            let existing = self.layers[parent]
+                .children
+                .iter()
+                .find(|child| self.layers[**child].name.as_ref() == Some(name));
+            match existing {
+                Some(child) => *child,
+                None => self.push(parent, Some(name.clone())),
+            }
+        })
+    }
+
+    /// Declare a new anonymous layer inside `parent`
+    pub fn declare_anonymous(&mut self, parent: LayerId) -> LayerId {
+        self.push(parent, None)
+    }
+
+    /// Layer order of each layer. Nested layers come before the rules of
+    /// their parent layer, which are unlayered in the parent.
+    pub fn orders(&self) -> Vec<u32> {
+        fn visit(layers: &[Layer], id: LayerId, orders: &mut Vec<u32>, next: &mut u32) {
+            for child in &layers[id].children {
+                visit(layers, *child, orders, next);
+            }
+            orders[id] = *next;
+            *next += 1;
+        }
+
+        let mut orders = vec![0; self.layers.len()];
+        visit(&self.layers, Self::ROOT, &mut orders, &mut 0);
+        orders[Self::ROOT] = UNLAYERED;
+        orders
+    }
+
+    fn push(&mut self, parent: LayerId, name: Option<String>) -> LayerId {
+        let id = self.layers.len();
+        self.layers.push(Layer {
+            name,
+            children: Vec::new(),
+        });
+        self.layers[parent].children.push(id);
+        id
+    }
+}
+
+impl Default for CascadeLayers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,411 @@
+use std::rc::Rc;
+
+use crate::values::prelude::*;
+use crate::{Property, Value};
+
+/// A type that holds the computed value of a property
+pub trait ComputedValue: Sized {
+    /// The computed value from a value that is already absolute, `None` if
+    /// the value doesn't fit the type
+    fn from_value(value: Value) -> Option<Self>;
+
+    fn to_value(&self) -> Value;
+}
+
+macro_rules! computed_value {
+    ($($value:ident),*) => {
+        $(
+            impl ComputedValue for $value {
+                fn from_value(value: Value) -> Option<Self> {
+                    match value {
+                        Value::$value(value) => Some(value),
+                        _ => None,
+                    }
+                }
+
+                fn to_value(&self) -> Value {
+                    Value::$value(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+computed_value!(
+    Color,
+    Display,
+    BorderStyle,
+    Float,
+    Position,
+    Direction,
+    BorderRadius,
+    TextAlign,
+    Overflow,
+    FontWeight,
+    FontFamily,
+    FontStyle,
+    FontStretch,
+    LineHeight,
+    Spacing,
+    TextTransform,
+    WhiteSpace,
+    TabSize,
+    OverflowWrap,
+    WordBreak,
+    Hyphens,
+    TextDecorationLine,
+    TextDecorationStyle,
+    TextDecorationThickness,
+    Content,
+    CounterReset,
+    CounterIncrement
+);
+
+impl ComputedValue for Length {
+    /// Border width keywords are resolved to their length
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Length(length) => Some(length),
+            Value::BorderWidth(width) => Some(Length::new_px(width.to_px())),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Length(self.clone())
+    }
+}
+
+impl ComputedValue for LengthPercentage {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Length(length) => Some(LengthPercentage::Length(length)),
+            Value::Percentage(percentage) => Some(LengthPercentage::Percentage(percentage)),
+            Value::Calc(calc) => Some(LengthPercentage::Calc(calc)),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            LengthPercentage::Length(length) => Value::Length(length.clone()),
+            LengthPercentage::Percentage(percentage) => Value::Percentage(percentage.clone()),
+            LengthPercentage::Calc(calc) => Value::Calc(calc.clone()),
+        }
+    }
+}
+
+impl ComputedValue for LengthPercentageOrAuto {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Auto => Some(LengthPercentageOrAuto::Auto),
+            value => {
+                LengthPercentage::from_value(value).map(LengthPercentageOrAuto::LengthPercentage)
+            }
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            LengthPercentageOrAuto::LengthPercentage(value) => value.to_value(),
+            LengthPercentageOrAuto::Auto => Value::Auto,
+        }
+    }
+}
+
+macro_rules! computed_style {
+    (
+        inherited { $($i_property:ident: $i_field:ident: $i_type:ty,)* }
+        non_inherited { $($n_property:ident: $n_field:ident: $n_type:ty,)* }
+    ) => {
+        /// The computed values of the inherited properties, shared by an
+        /// element with its descendants until one of them declares one
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct InheritedStyle {
+            $(pub $i_field: $i_type,)*
+        }
+
+        /// The computed values of the non-inherited properties, shared by
+        /// every element that doesn't declare any of them
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct NonInheritedStyle {
+            $(pub $n_field: $n_type,)*
+        }
+
+        impl InheritedStyle {
+            fn initial() -> Self {
+                Self {
+                    $($i_field: initial_value(&Property::$i_property),)*
+                }
+            }
+        }
+
+        impl NonInheritedStyle {
+            fn initial() -> Self {
+                Self {
+                    $($n_field: initial_value(&Property::$n_property),)*
+                }
+            }
+        }
+
+        impl ComputedStyle {
+            $(
+                pub fn $i_field(&self) -> &$i_type {
+                    &self.inherited.$i_field
+                }
+            )*
+
+            $(
+                pub fn $n_field(&self) -> &$n_type {
+                    &self.non_inherited.$n_field
+                }
+            )*
+
+            /// The computed value of a property
+            pub fn get(&self, property: &Property) -> Value {
+                match property {
+                    $(Property::$i_property => self.inherited.$i_field.to_value(),)*
+                    $(Property::$n_property => self.non_inherited.$n_field.to_value(),)*
+                }
+            }
+
+            /// Set the computed value of a property. The value must be
+            /// absolute & of the type of the property, otherwise it's ignored.
+            /// The group of the property is only copied if the value changes.
+            pub fn set(&mut self, property: &Property, value: Value) {
+                match property {
+                    $(
+                        Property::$i_property => match <$i_type>::from_value(value) {
+                            Some(value) if self.inherited.$i_field != value => {
+                                Rc::make_mut(&mut self.inherited).$i_field = value;
+                            }
+                            Some(_) => {}
+                            None => log::warn!("Invalid computed value for {:?}", property),
+                        },
+                    )*
+                    $(
+                        Property::$n_property => match <$n_type>::from_value(value) {
+                            Some(value) if self.non_inherited.$n_field != value => {
+                                Rc::make_mut(&mut self.non_inherited).$n_field = value;
+                            }
+                            Some(_) => {}
+                            None => log::warn!("Invalid computed value for {:?}", property),
+                        },
+                    )*
+                }
+            }
+        }
+    };
+}
+
+computed_style! {
+    inherited {
+        Color: color: Color,
+        FontSize: font_size: Length,
+        FontWeight: font_weight: FontWeight,
+        FontFamily: font_family: FontFamily,
+        FontStyle: font_style: FontStyle,
+        FontStretch: font_stretch: FontStretch,
+        LineHeight: line_height: LineHeight,
+        LetterSpacing: letter_spacing: Spacing,
+        WordSpacing: word_spacing: Spacing,
+        TextTransform: text_transform: TextTransform,
+        WhiteSpace: white_space: WhiteSpace,
+        TabSize: tab_size: TabSize,
+        OverflowWrap: overflow_wrap: OverflowWrap,
+        WordBreak: word_break: WordBreak,
+        Hyphens: hyphens: Hyphens,
+    }
+    non_inherited {
+        BackgroundColor: background_color: Color,
+        Display: display: Display,
+        Width: width: LengthPercentageOrAuto,
+        Height: height: LengthPercentageOrAuto,
+        MarginTop: margin_top: LengthPercentageOrAuto,
+        MarginRight: margin_right: LengthPercentageOrAuto,
+        MarginBottom: margin_bottom: LengthPercentageOrAuto,
+        MarginLeft: margin_left: LengthPercentageOrAuto,
+        PaddingTop: padding_top: LengthPercentage,
+        PaddingRight: padding_right: LengthPercentage,
+        PaddingBottom: padding_bottom: LengthPercentage,
+        PaddingLeft: padding_left: LengthPercentage,
+        BorderTopWidth: border_top_width: Length,
+        BorderRightWidth: border_right_width: Length,
+        BorderBottomWidth: border_bottom_width: Length,
+        BorderLeftWidth: border_left_width: Length,
+        BorderTopStyle: border_top_style: BorderStyle,
+        BorderRightStyle: border_right_style: BorderStyle,
+        BorderBottomStyle: border_bottom_style: BorderStyle,
+        BorderLeftStyle: border_left_style: BorderStyle,
+        BorderTopColor: border_top_color: Color,
+        BorderRightColor: border_right_color: Color,
+        BorderBottomColor: border_bottom_color: Color,
+        BorderLeftColor: border_left_color: Color,
+        BorderTopLeftRadius: border_top_left_radius: BorderRadius,
+        BorderTopRightRadius: border_top_right_radius: BorderRadius,
+        BorderBottomLeftRadius: border_bottom_left_radius: BorderRadius,
+        BorderBottomRightRadius: border_bottom_right_radius: BorderRadius,
+        Position: position: Position,
+        Float: float: Float,
+        Left: left: LengthPercentageOrAuto,
+        Right: right: LengthPercentageOrAuto,
+        Top: top: LengthPercentageOrAuto,
+        Bottom: bottom: LengthPercentageOrAuto,
+        Direction: direction: Direction,
+        TextAlign: text_align: TextAlign,
+        TextDecorationLine: text_decoration_line: TextDecorationLine,
+        TextDecorationStyle: text_decoration_style: TextDecorationStyle,
+        TextDecorationColor: text_decoration_color: Color,
+        TextDecorationThickness: text_decoration_thickness: TextDecorationThickness,
+        OverflowX: overflow_x: Overflow,
+        OverflowY: overflow_y: Overflow,
+        Content: content: Content,
+        CounterReset: counter_reset: CounterReset,
+        CounterIncrement: counter_increment: CounterIncrement,
+    }
+}
+
+fn initial_value<T: ComputedValue>(property: &Property) -> T {
+    T::from_value(Value::initial(property))
+        .unwrap_or_else(|| panic!("Invalid initial value for {:?}", property))
+}
+
+/// The computed styles of an element. The inherited & non-inherited
+/// properties are in separate groups that are reference counted, so an
+/// element shares a group with its parent or with the initial style as long
+/// as it doesn't change any property of the group.
+#[derive(Debug, Clone)]
+pub struct ComputedStyle {
+    inherited: Rc<InheritedStyle>,
+    non_inherited: Rc<NonInheritedStyle>,
+}
+
+thread_local! {
+    static INITIAL_STYLE: ComputedStyle = ComputedStyle::new_initial();
+}
+
+impl ComputedStyle {
+    /// The style of an element without a parent & without any declaration
+    pub fn initial() -> Self {
+        INITIAL_STYLE.with(|style| style.clone())
+    }
+
+    fn new_initial() -> Self {
+        let mut style = Self {
+            inherited: Rc::new(InheritedStyle::initial()),
+            non_inherited: Rc::new(NonInheritedStyle::initial()),
+        };
+        style.fix_border_widths();
+        style
+    }
+
+    /// The style of a child of `parent` without any declaration
+    pub fn inherit_from(parent: &ComputedStyle) -> Self {
+        Self {
+            inherited: parent.inherited.clone(),
+            non_inherited: Self::initial().non_inherited,
+        }
+    }
+
+    pub fn inherited(&self) -> &InheritedStyle {
+        &self.inherited
+    }
+
+    pub fn non_inherited(&self) -> &NonInheritedStyle {
+        &self.non_inherited
+    }
+
+    /// The inherited properties, copied first if they are shared
+    pub fn inherited_mut(&mut self) -> &mut InheritedStyle {
+        Rc::make_mut(&mut self.inherited)
+    }
+
+    /// The non-inherited properties, copied first if they are shared
+    pub fn non_inherited_mut(&mut self) -> &mut NonInheritedStyle {
+        Rc::make_mut(&mut self.non_inherited)
+    }
+
+    /// Whether both styles point to the same inherited properties
+    pub fn shares_inherited_with(&self, other: &ComputedStyle) -> bool {
+        Rc::ptr_eq(&self.inherited, &other.inherited)
+    }
+
+    /// Whether both styles point to the same non-inherited properties
+    pub fn shares_non_inherited_with(&self, other: &ComputedStyle) -> bool {
+        Rc::ptr_eq(&self.non_inherited, &other.non_inherited)
+    }
+
+    /// Whether both styles lay out boxes the same way, i.e. they only differ
+    /// in properties that are painted, like colors, border radii & text
+    /// decorations
+    pub fn has_same_layout_as(&self, other: &ComputedStyle) -> bool {
+        let same_inherited = self.shares_inherited_with(other) || {
+            let mut inherited = self.inherited().clone();
+            inherited.color = other.inherited.color.clone();
+            inherited == *other.inherited
+        };
+
+        same_inherited
+            && (self.shares_non_inherited_with(other) || {
+                let other = other.non_inherited();
+                let mut non_inherited = self.non_inherited().clone();
+                non_inherited.background_color = other.background_color.clone();
+                non_inherited.border_top_color = other.border_top_color.clone();
+                non_inherited.border_right_color = other.border_right_color.clone();
+                non_inherited.border_bottom_color = other.border_bottom_color.clone();
+                non_inherited.border_left_color = other.border_left_color.clone();
+                non_inherited.border_top_left_radius = other.border_top_left_radius.clone();
+                non_inherited.border_top_right_radius = other.border_top_right_radius.clone();
+                non_inherited.border_bottom_left_radius = other.border_bottom_left_radius.clone();
+                non_inherited.border_bottom_right_radius = other.border_bottom_right_radius.clone();
+                non_inherited.text_decoration_line = other.text_decoration_line.clone();
+                non_inherited.text_decoration_style = other.text_decoration_style.clone();
+                non_inherited.text_decoration_color = other.text_decoration_color.clone();
+                non_inherited.text_decoration_thickness = other.text_decoration_thickness.clone();
+                non_inherited == *other
+            })
+    }
+
+    /// The computed width of a border is 0 if it has no style
+    /// https://www.w3.org/TR/css-backgrounds-3/#border-width
+    pub fn fix_border_widths(&mut self) {
+        let has_no_border = |style: &BorderStyle, width: &Length| {
+            matches!(style, BorderStyle::None | BorderStyle::Hidden) && *width.value != 0.
+        };
+        let style = &self.non_inherited;
+        let fix_top = has_no_border(&style.border_top_style, &style.border_top_width);
+        let fix_right = has_no_border(&style.border_right_style, &style.border_right_width);
+        let fix_bottom = has_no_border(&style.border_bottom_style, &style.border_bottom_width);
+        let fix_left = has_no_border(&style.border_left_style, &style.border_left_width);
+        if !(fix_top || fix_right || fix_bottom || fix_left) {
+            return;
+        }
+
+        let style = self.non_inherited_mut();
+        if fix_top {
+            style.border_top_width = Length::zero();
+        }
+        if fix_right {
+            style.border_right_width = Length::zero();
+        }
+        if fix_bottom {
+            style.border_bottom_width = Length::zero();
+        }
+        if fix_left {
+            style.border_left_width = Length::zero();
+        }
+    }
+}
+
+impl Default for ComputedStyle {
+    fn default() -> Self {
+        Self::initial()
+    }
+}
+
+impl PartialEq for ComputedStyle {
+    fn eq(&self, other: &Self) -> bool {
+        (self.shares_inherited_with(other) || self.inherited == other.inherited)
+            && (self.shares_non_inherited_with(other) || self.non_inherited == other.non_inherited)
+    }
+}
@@ -26,6 +26,9 @@ pub struct ContextualRule {
     pub inner: StyleRule,
     pub origin: CascadeOrigin,
     pub location: CSSLocation,
+    /// Order of the cascade layer of the rule within its origin, see
+    /// [`crate::CascadeLayers::orders`]
+    pub layer: u32,
 }
 
 /// Stylesheet with context for cascading
@@ -0,0 +1,9 @@
+use std::collections::HashMap;
+
+use css::parser::structs::ComponentValue;
+
+/// Computed values of the custom properties (`--*`) of an element. Values
+/// are kept as unparsed component values & substituted into `var()`
+/// references when other properties are computed.
+/// https://www.w3.org/TR/css-variables-1/
+pub type CustomProperties = HashMap<String, Vec<ComponentValue>>;
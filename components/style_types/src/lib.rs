@@ -1,7 +1,13 @@
+pub mod cascade_layer;
+pub mod computed_style;
 pub mod contextual_style;
+pub mod custom_properties;
 pub mod property;
 pub mod value;
 pub mod values;
+pub use cascade_layer::*;
+pub use computed_style::*;
 pub use contextual_style::*;
+pub use custom_properties::*;
 pub use property::*;
 pub use value::*;
@@ -42,9 +42,28 @@ pub enum Property {
     Direction,
     FontSize,
     FontWeight,
+    FontFamily,
+    FontStyle,
+    FontStretch,
+    LineHeight,
+    LetterSpacing,
+    WordSpacing,
     TextAlign,
+    TextTransform,
+    WhiteSpace,
+    TabSize,
+    OverflowWrap,
+    WordBreak,
+    Hyphens,
+    TextDecorationLine,
+    TextDecorationStyle,
+    TextDecorationColor,
+    TextDecorationThickness,
     OverflowX,
     OverflowY,
+    Content,
+    CounterReset,
+    CounterIncrement,
 }
 
 impl Property {
@@ -84,6 +103,26 @@ impl Property {
             "margin-block-start" => Some(Property::MarginTop),
             "margin-block-end" => Some(Property::MarginBottom),
             "font-weight" => Some(Property::FontWeight),
+            "font-family" => Some(Property::FontFamily),
+            "font-style" => Some(Property::FontStyle),
+            "font-stretch" => Some(Property::FontStretch),
+            "line-height" => Some(Property::LineHeight),
+            "letter-spacing" => Some(Property::LetterSpacing),
+            "word-spacing" => Some(Property::WordSpacing),
+            "text-transform" => Some(Property::TextTransform),
+            "white-space" => Some(Property::WhiteSpace),
+            "tab-size" => Some(Property::TabSize),
+            // `word-wrap` is a legacy name of `overflow-wrap`
+            "overflow-wrap" | "word-wrap" => Some(Property::OverflowWrap),
+            "word-break" => Some(Property::WordBreak),
+            "hyphens" => Some(Property::Hyphens),
+            "text-decoration-line" => Some(Property::TextDecorationLine),
+            "text-decoration-style" => Some(Property::TextDecorationStyle),
+            "text-decoration-color" => Some(Property::TextDecorationColor),
+            "text-decoration-thickness" => Some(Property::TextDecorationThickness),
+            "content" => Some(Property::Content),
+            "counter-reset" => Some(Property::CounterReset),
+            "counter-increment" => Some(Property::CounterIncrement),
             _ => {
                 log::debug!("Unsupported CSS property: {}", property);
                 None
@@ -100,6 +139,18 @@ impl Property {
             Self::FontSize => true,
             Self::Color => true,
             Self::FontWeight => true,
+            Self::FontFamily => true,
+            Self::FontStyle => true,
+            Self::FontStretch => true,
+            Self::LineHeight => true,
+            Self::LetterSpacing => true,
+            Self::WordSpacing => true,
+            Self::TextTransform => true,
+            Self::WhiteSpace => true,
+            Self::TabSize => true,
+            Self::OverflowWrap => true,
+            Self::WordBreak => true,
+            Self::Hyphens => true,
             _ => false,
         }
     }
@@ -1,9 +1,6 @@
 use css::{parser::structs::ComponentValue, tokenizer::token::Token};
 
-use crate::{
-    property::Property,
-    values::{length::LengthUnit, number::Number},
-};
+use crate::{property::Property, values::number::Number};
 
 use super::values::prelude::*;
 
@@ -36,6 +33,20 @@ macro_rules! parse_value {
             None
         }
     }};
+    (Revert; $tokens:ident) => {{
+        if parse_keyword($tokens, "revert") {
+            Some(Value::Revert)
+        } else {
+            None
+        }
+    }};
+    (RevertLayer; $tokens:ident) => {{
+        if parse_keyword($tokens, "revert-layer") {
+            Some(Value::RevertLayer)
+        } else {
+            None
+        }
+    }};
     ($value:ident; $tokens:ident) => {{
         if let Some(value) = $value::parse($tokens) {
             Some(Value::$value(value))
@@ -59,6 +70,7 @@ pub enum Value {
     Display(Display),
     Length(Length),
     Percentage(Percentage),
+    Calc(Calc),
     BorderStyle(BorderStyle),
     BorderWidth(BorderWidth),
     Float(Float),
@@ -68,161 +80,184 @@ pub enum Value {
     TextAlign(TextAlign),
     Overflow(Overflow),
     FontWeight(FontWeight),
+    FontFamily(FontFamily),
+    FontStyle(FontStyle),
+    FontStretch(FontStretch),
+    LineHeight(LineHeight),
+    Spacing(Spacing),
+    TextTransform(TextTransform),
+    WhiteSpace(WhiteSpace),
+    TabSize(TabSize),
+    OverflowWrap(OverflowWrap),
+    WordBreak(WordBreak),
+    Hyphens(Hyphens),
+    TextDecorationLine(TextDecorationLine),
+    TextDecorationStyle(TextDecorationStyle),
+    TextDecorationThickness(TextDecorationThickness),
+    Content(Content),
+    CounterReset(CounterReset),
+    CounterIncrement(CounterIncrement),
     Auto,
     Inherit,
     Initial,
     Unset,
+    /// Rolls back the cascade to the previous origin
+    /// https://www.w3.org/TR/css-cascade-4/#valdef-all-revert
+    Revert,
+    /// Rolls back the cascade to the previous cascade layer
+    /// https://www.w3.org/TR/css-cascade-5/#valdef-all-revert-layer
+    RevertLayer,
 }
 
 impl Value {
     pub fn parse(property: &Property, tokens: &[ComponentValue]) -> Option<Self> {
         match property {
             Property::BackgroundColor => parse_value!(
-                Color | Inherit | Initial | Unset;
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Color => parse_value!(
-                Color | Inherit | Initial | Unset;
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Display => parse_value!(
-                Display | Inherit | Initial | Unset;
+                Display | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Width => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Height => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::MarginTop => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::MarginRight => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::MarginBottom => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::MarginLeft => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::PaddingTop => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::PaddingRight => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::PaddingBottom => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::PaddingLeft => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderTopStyle => parse_value!(
-                BorderStyle | Inherit | Initial | Unset;
+                BorderStyle | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderRightStyle => parse_value!(
-                BorderStyle | Inherit | Initial | Unset;
+                BorderStyle | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderBottomStyle => parse_value!(
-                BorderStyle | Inherit | Initial | Unset;
+                BorderStyle | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderLeftStyle => parse_value!(
-                BorderStyle | Inherit | Initial | Unset;
+                BorderStyle | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderTopWidth => parse_value!(
-                BorderWidth | Length | Inherit | Initial | Unset;
+                BorderWidth | Length | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderRightWidth => parse_value!(
-                BorderWidth | Length | Inherit | Initial | Unset;
+                BorderWidth | Length | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderBottomWidth => parse_value!(
-                BorderWidth | Length | Inherit | Initial | Unset;
+                BorderWidth | Length | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderLeftWidth => parse_value!(
-                BorderWidth | Length | Inherit | Initial | Unset;
+                BorderWidth | Length | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderTopColor => parse_value!(
-                Color | Inherit | Initial | Unset;
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderRightColor => parse_value!(
-                Color | Inherit | Initial | Unset;
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderBottomColor => parse_value!(
-                Color | Inherit | Initial | Unset;
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderLeftColor => parse_value!(
-                Color | Inherit | Initial | Unset;
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Float => parse_value!(
-                Float | Inherit | Initial | Unset;
+                Float | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Position => parse_value!(
-                Position | Inherit | Initial | Unset;
+                Position | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Top => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Right => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Bottom => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Left => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::Direction => parse_value!(
-                Direction | Inherit | Initial | Unset;
+                Direction | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderTopLeftRadius => parse_value!(
-                BorderRadius | Inherit | Initial | Unset;
+                BorderRadius | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderTopRightRadius => parse_value!(
-                BorderRadius | Inherit | Initial | Unset;
+                BorderRadius | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderBottomLeftRadius => parse_value!(
-                BorderRadius | Inherit | Initial | Unset;
+                BorderRadius | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::BorderBottomRightRadius => parse_value!(
-                BorderRadius | Inherit | Initial | Unset;
+                BorderRadius | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::FontSize => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset | Revert | RevertLayer;
                 tokens
             ),
             Property::TextAlign => parse_value!(
@@ -230,16 +265,92 @@ impl Value {
                 tokens
             ),
             Property::OverflowX => parse_value!(
-                Overflow | Auto;
+                Overflow;
                 tokens
             ),
             Property::OverflowY => parse_value!(
-                Overflow | Auto;
+                Overflow;
                 tokens
             ),
             Property::FontWeight => parse_value!(
                 FontWeight | Inherit; tokens
             ),
+            Property::FontFamily => parse_value!(
+                Inherit | Initial | Unset | Revert | RevertLayer | FontFamily;
+                tokens
+            ),
+            Property::FontStyle => parse_value!(
+                FontStyle | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::FontStretch => parse_value!(
+                FontStretch | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::LineHeight => parse_value!(
+                LineHeight | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::LetterSpacing => parse_value!(
+                Spacing | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::WordSpacing => parse_value!(
+                Spacing | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::TextTransform => parse_value!(
+                TextTransform | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::WhiteSpace => parse_value!(
+                WhiteSpace | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::TabSize => parse_value!(
+                TabSize | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::OverflowWrap => parse_value!(
+                OverflowWrap | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::WordBreak => parse_value!(
+                WordBreak | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::Hyphens => parse_value!(
+                Hyphens | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::TextDecorationLine => parse_value!(
+                TextDecorationLine | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::TextDecorationStyle => parse_value!(
+                TextDecorationStyle | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::TextDecorationColor => parse_value!(
+                Color | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::TextDecorationThickness => parse_value!(
+                TextDecorationThickness | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::Content => parse_value!(
+                Content | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::CounterReset => parse_value!(
+                CounterReset | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
+            Property::CounterIncrement => parse_value!(
+                CounterIncrement | Inherit | Initial | Unset | Revert | RevertLayer;
+                tokens
+            ),
         }
     }
 
@@ -287,6 +398,30 @@ impl Value {
             Property::OverflowX => Value::Overflow(Overflow::Visible),
             Property::OverflowY => Value::Overflow(Overflow::Visible),
             Property::FontWeight => Value::FontWeight(FontWeight(Number(400.))),
+            // the bundled fonts are sans-serif
+            Property::FontFamily => Value::FontFamily(FontFamily::default()),
+            Property::FontStyle => Value::FontStyle(FontStyle::Normal),
+            Property::FontStretch => Value::FontStretch(FontStretch(Number(100.))),
+            Property::LineHeight => Value::LineHeight(LineHeight::Normal),
+            Property::LetterSpacing => Value::Spacing(Spacing::Normal),
+            Property::WordSpacing => Value::Spacing(Spacing::Normal),
+            Property::TextTransform => Value::TextTransform(TextTransform::None),
+            Property::WhiteSpace => Value::WhiteSpace(WhiteSpace::Normal),
+            Property::TabSize => Value::TabSize(TabSize::Number(Number(8.))),
+            Property::OverflowWrap => Value::OverflowWrap(OverflowWrap::Normal),
+            Property::WordBreak => Value::WordBreak(WordBreak::Normal),
+            Property::Hyphens => Value::Hyphens(Hyphens::Manual),
+            Property::TextDecorationLine => {
+                Value::TextDecorationLine(TextDecorationLine::default())
+            }
+            Property::TextDecorationStyle => Value::TextDecorationStyle(TextDecorationStyle::Solid),
+            Property::TextDecorationColor => Value::Color(Color::CurrentColor),
+            Property::TextDecorationThickness => {
+                Value::TextDecorationThickness(TextDecorationThickness::Auto)
+            }
+            Property::Content => Value::Content(Content::Normal),
+            Property::CounterReset => Value::CounterReset(CounterReset::none()),
+            Property::CounterIncrement => Value::CounterIncrement(CounterIncrement::none()),
         }
     }
 
@@ -301,6 +436,7 @@ impl Value {
         match self {
             Value::Length(l) => l.to_px(),
             Value::Percentage(p) => p.to_px(relative_to),
+            Value::Calc(c) => c.to_px(relative_to),
             Value::BorderWidth(w) => w.to_px(),
             Value::Auto => 0.,
             _ => unreachable!("Invalid call to_px on invalid value: {:?}", self),
@@ -309,14 +445,7 @@ impl Value {
 
     pub fn to_absolute_px(&self) -> f32 {
         match self {
-            Value::Length(Length {
-                value,
-                unit: LengthUnit::Px,
-            }) => **value,
-            Value::Length(Length {
-                value,
-                unit: LengthUnit::Pt,
-            }) => **value * (3. / 4.),
+            Value::Length(length) => length.to_px(),
             _ => unreachable!("Calling to_absolute_px for unsupported value"),
         }
     }
@@ -0,0 +1,370 @@
+use super::length::{Length, LengthUnit};
+use super::number::Number;
+use super::percentage::Percentage;
+use super::without_whitespaces;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// A math expression of lengths & percentages, parsed from `calc()`,
+/// `min()`, `max()` & `clamp()`.
+/// https://www.w3.org/TR/css-values-4/#math
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Calc {
+    Number(Number),
+    Length(Length),
+    Percentage(Percentage),
+    Sum(Box<Calc>, Box<Calc>),
+    Difference(Box<Calc>, Box<Calc>),
+    Product(Box<Calc>, Box<Calc>),
+    Quotient(Box<Calc>, Box<Calc>),
+    Min(Vec<Calc>),
+    Max(Vec<Calc>),
+    Clamp(Box<Calc>, Box<Calc>, Box<Calc>),
+}
+
+/// Type of the result of an expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CalcType {
+    Number,
+    LengthPercentage,
+}
+
+impl Calc {
+    /// Parse a math function that resolves to a length or a percentage.
+    /// The function must be the only value.
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let calc = match without_whitespaces(values).as_slice() {
+            [ComponentValue::Function(function)] => {
+                parse_math_function(&function.name, &function.value)?
+            }
+            _ => return None,
+        };
+
+        match calc.calc_type()? {
+            CalcType::LengthPercentage => Some(calc),
+            CalcType::Number => None,
+        }
+    }
+
+    pub fn has_percentage(&self) -> bool {
+        match self {
+            Calc::Percentage(_) => true,
+            Calc::Number(_) | Calc::Length(_) => false,
+            Calc::Sum(a, b)
+            | Calc::Difference(a, b)
+            | Calc::Product(a, b)
+            | Calc::Quotient(a, b) => a.has_percentage() || b.has_percentage(),
+            Calc::Min(values) | Calc::Max(values) => values.iter().any(Calc::has_percentage),
+            Calc::Clamp(min, value, max) => {
+                min.has_percentage() || value.has_percentage() || max.has_percentage()
+            }
+        }
+    }
+
+    /// Replace the lengths in the expression with the lengths returned by
+    /// `resolve`, e.g. to resolve font-relative lengths at computed-value
+    /// time.
+    pub fn map_lengths<F: Fn(&Length) -> Length>(&self, resolve: &F) -> Calc {
+        let map = |calc: &Calc| Box::new(calc.map_lengths(resolve));
+        match self {
+            Calc::Length(length) => Calc::Length(resolve(length)),
+            Calc::Number(_) | Calc::Percentage(_) => self.clone(),
+            Calc::Sum(a, b) => Calc::Sum(map(a), map(b)),
+            Calc::Difference(a, b) => Calc::Difference(map(a), map(b)),
+            Calc::Product(a, b) => Calc::Product(map(a), map(b)),
+            Calc::Quotient(a, b) => Calc::Quotient(map(a), map(b)),
+            Calc::Min(values) => Calc::Min(values.iter().map(|v| v.map_lengths(resolve)).collect()),
+            Calc::Max(values) => Calc::Max(values.iter().map(|v| v.map_lengths(resolve)).collect()),
+            Calc::Clamp(min, value, max) => Calc::Clamp(map(min), map(value), map(max)),
+        }
+    }
+
+    /// Evaluate the expression with percentages resolved against
+    /// `relative_to`. Lengths must be absolute. An infinite result, e.g.
+    /// of a division by zero, is clamped & a NaN result is zero.
+    /// https://www.w3.org/TR/css-values-4/#calc-ieee
+    pub fn to_px(&self, relative_to: f32) -> f32 {
+        let value = self.resolve(relative_to);
+        if value.is_nan() {
+            return 0.;
+        }
+        value.clamp(f32::MIN, f32::MAX)
+    }
+
+    fn resolve(&self, relative_to: f32) -> f32 {
+        let evaluate = |calc: &Calc| calc.resolve(relative_to);
+        match self {
+            Calc::Number(number) => **number,
+            Calc::Length(length) => length.to_px(),
+            Calc::Percentage(percentage) => percentage.to_px(relative_to),
+            Calc::Sum(a, b) => evaluate(a) + evaluate(b),
+            Calc::Difference(a, b) => evaluate(a) - evaluate(b),
+            Calc::Product(a, b) => evaluate(a) * evaluate(b),
+            Calc::Quotient(a, b) => evaluate(a) / evaluate(b),
+            Calc::Min(values) => values.iter().map(evaluate).fold(f32::INFINITY, f32::min),
+            Calc::Max(values) => values
+                .iter()
+                .map(evaluate)
+                .fold(f32::NEG_INFINITY, f32::max),
+            // the minimum wins over the maximum
+            Calc::Clamp(min, value, max) => evaluate(min).max(evaluate(value).min(evaluate(max))),
+        }
+    }
+
+    /// Type check the expression. Return `None` if it mixes numbers with
+    /// lengths where it can't, e.g. `1px + 2`.
+    /// https://www.w3.org/TR/css-values-4/#calc-type-checking
+    fn calc_type(&self) -> Option<CalcType> {
+        let same_type = |values: &[&Calc]| {
+            let first = values.first()?.calc_type()?;
+            for value in values {
+                if value.calc_type()? != first {
+                    return None;
+                }
+            }
+            Some(first)
+        };
+
+        match self {
+            Calc::Number(_) => Some(CalcType::Number),
+            Calc::Length(_) | Calc::Percentage(_) => Some(CalcType::LengthPercentage),
+            Calc::Sum(a, b) | Calc::Difference(a, b) => same_type(&[a, b]),
+            Calc::Product(a, b) => match (a.calc_type()?, b.calc_type()?) {
+                (CalcType::Number, b) => Some(b),
+                (a, CalcType::Number) => Some(a),
+                _ => None,
+            },
+            Calc::Quotient(a, b) => match b.calc_type()? {
+                CalcType::Number => a.calc_type(),
+                _ => None,
+            },
+            Calc::Min(values) | Calc::Max(values) => {
+                same_type(&values.iter().collect::<Vec<&Calc>>())
+            }
+            Calc::Clamp(min, value, max) => same_type(&[min, value, max]),
+        }
+    }
+}
+
+fn parse_math_function(name: &str, values: &[ComponentValue]) -> Option<Calc> {
+    match name.to_lowercase().as_str() {
+        "calc" => parse_sum(&values.iter().collect::<Vec<_>>()),
+        "min" => Some(Calc::Min(parse_arguments(values)?)),
+        "max" => Some(Calc::Max(parse_arguments(values)?)),
+        "clamp" => {
+            let mut arguments = parse_arguments(values)?;
+            if arguments.len() != 3 {
+                return None;
+            }
+            let max = arguments.pop()?;
+            let value = arguments.pop()?;
+            let min = arguments.pop()?;
+            Some(Calc::Clamp(Box::new(min), Box::new(value), Box::new(max)))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the comma separated calculations of `min()`, `max()` & `clamp()`
+fn parse_arguments(values: &[ComponentValue]) -> Option<Vec<Calc>> {
+    let values = values.iter().collect::<Vec<_>>();
+    let arguments = values
+        .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+        .map(parse_sum)
+        .collect::<Option<Vec<Calc>>>()?;
+
+    if arguments.is_empty() {
+        return None;
+    }
+    Some(arguments)
+}
+
+/// `<calc-sum> = <calc-product> [ [ '+' | '-' ] <calc-product> ]*`,
+/// where `+` & `-` must be surrounded by whitespaces
+fn parse_sum(values: &[&ComponentValue]) -> Option<Calc> {
+    let mut parts = split_operators(values, &['+', '-'])?;
+    let (_, first) = parts.remove(0);
+    let mut result = parse_product(first)?;
+
+    for (operator, values) in parts {
+        let value = Box::new(parse_product(values)?);
+        result = match operator {
+            '+' => Calc::Sum(Box::new(result), value),
+            _ => Calc::Difference(Box::new(result), value),
+        };
+    }
+    Some(result)
+}
+
+/// `<calc-product> = <calc-value> [ [ '*' | '/' ] <calc-value> ]*`
+fn parse_product(values: &[&ComponentValue]) -> Option<Calc> {
+    let values = values
+        .iter()
+        .copied()
+        .filter(|value| !is_whitespace(value))
+        .collect::<Vec<_>>();
+    let mut parts = split_operators(&values, &['*', '/'])?;
+    let (_, first) = parts.remove(0);
+    let mut result = parse_calc_value(first)?;
+
+    for (operator, values) in parts {
+        let value = Box::new(parse_calc_value(values)?);
+        result = match operator {
+            '*' => Calc::Product(Box::new(result), value),
+            _ => Calc::Quotient(Box::new(result), value),
+        };
+    }
+    Some(result)
+}
+
+fn parse_calc_value(values: &[&ComponentValue]) -> Option<Calc> {
+    match values {
+        [ComponentValue::PerservedToken(Token::Number { value, .. })] => {
+            Some(Calc::Number((*value).into()))
+        }
+        [ComponentValue::PerservedToken(Token::Percentage(value))] => {
+            Some(Calc::Percentage(Percentage((*value).into())))
+        }
+        [ComponentValue::PerservedToken(Token::Dimension { value, unit, .. })] => {
+            let unit = LengthUnit::from_str(unit)?;
+            Some(Calc::Length(Length::new(*value, unit)))
+        }
+        [ComponentValue::SimpleBlock(block)] if block.token == Token::ParentheseOpen => {
+            parse_sum(&block.value.iter().collect::<Vec<_>>())
+        }
+        [ComponentValue::Function(function)] => {
+            parse_math_function(&function.name, &function.value)
+        }
+        _ => None,
+    }
+}
+
+/// Split the values at the operators, keeping the operator that precedes
+/// each part. Return `None` if a `+` or a `-` isn't surrounded by
+/// whitespaces.
+fn split_operators<'a, 'b>(
+    values: &'b [&'a ComponentValue],
+    operators: &[char],
+) -> Option<Vec<(char, &'b [&'a ComponentValue])>> {
+    let mut parts = Vec::new();
+    let mut operator = ' ';
+    let mut start = 0;
+
+    for (index, value) in values.iter().enumerate() {
+        if let ComponentValue::PerservedToken(Token::Delim(delim)) = value {
+            if operators.contains(delim) {
+                if matches!(delim, '+' | '-') {
+                    let before = index.checked_sub(1).and_then(|index| values.get(index));
+                    let after = values.get(index + 1);
+                    let surrounded = matches!(before, Some(before) if is_whitespace(before))
+                        && matches!(after, Some(after) if is_whitespace(after));
+                    if !surrounded {
+                        return None;
+                    }
+                }
+                parts.push((operator, &values[start..index]));
+                operator = *delim;
+                start = index + 1;
+            }
+        }
+    }
+    parts.push((operator, &values[start..]));
+    Some(parts)
+}
+
+fn is_whitespace(value: &ComponentValue) -> bool {
+    matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn parse(css: &str) -> Option<Calc> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        Calc::parse(&parser.parse_a_list_of_component_values())
+    }
+
+    fn px(value: f32) -> Box<Calc> {
+        Box::new(Calc::Length(Length::new_px(value)))
+    }
+
+    #[test]
+    fn parse_operator_precedence() {
+        assert_eq!(
+            parse("calc(1px + 2px * 3)"),
+            Some(Calc::Sum(
+                px(1.),
+                Box::new(Calc::Product(px(2.), Box::new(Calc::Number(3.0.into()))))
+            ))
+        );
+        assert_eq!(parse("calc((1px + 2px) * 3)").unwrap().to_px(0.), 9.);
+        assert_eq!(parse("calc(10px - 2px - 3px)").unwrap().to_px(0.), 5.);
+        assert_eq!(parse(" calc(50% - 10px) ").unwrap().to_px(200.), 90.);
+    }
+
+    #[test]
+    fn parse_nested_math_functions() {
+        let calc = parse("calc(calc(2px * 2) + min(10px, 5%))").unwrap();
+        assert_eq!(calc.to_px(100.), 9.);
+        assert!(calc.has_percentage());
+
+        assert_eq!(parse("min(10px, 20px, 5px)").unwrap().to_px(0.), 5.);
+        assert_eq!(parse("max(10px, 50%)").unwrap().to_px(100.), 50.);
+        assert_eq!(parse("clamp(10px, 50%, 30px)").unwrap().to_px(100.), 30.);
+        assert_eq!(parse("clamp(10px, 50%, 30px)").unwrap().to_px(10.), 10.);
+        // the minimum wins over the maximum
+        assert_eq!(parse("clamp(20px, 15px, 10px)").unwrap().to_px(0.), 20.);
+        assert_eq!(parse("max(calc(1px * 3), 2px)").unwrap().to_px(0.), 3.);
+    }
+
+    #[test]
+    fn reject_invalid_syntax() {
+        assert_eq!(parse("calc()"), None);
+        assert_eq!(parse("calc(1px 2px)"), None);
+        assert_eq!(parse("calc(1px +)"), None);
+        assert_eq!(parse("calc(1px + 2px) 3px"), None);
+        assert_eq!(parse("calc(1px) calc(2px)"), None);
+        assert_eq!(parse("min()"), None);
+        assert_eq!(parse("clamp(1px, 2px)"), None);
+        assert_eq!(parse("calc(1deg)"), None);
+        assert_eq!(parse("abs(1px)"), None);
+    }
+
+    #[test]
+    fn reject_sum_operators_without_whitespaces() {
+        assert_eq!(parse("calc(1px+ 2px)"), None);
+        assert_eq!(parse("calc(1px +(2px))"), None);
+        assert_eq!(parse("calc((1px)- 2px)"), None);
+        assert_eq!(parse("min(1px, 2px+ 3px)"), None);
+        // unlike product operators
+        assert_eq!(parse("calc((1px)*2)").unwrap().to_px(0.), 2.);
+        assert_eq!(parse("calc((1px) + (2px))").unwrap().to_px(0.), 3.);
+    }
+
+    #[test]
+    fn clamp_division_by_zero() {
+        assert_eq!(parse("calc(10px / 0)").unwrap().to_px(0.), f32::MAX);
+        assert_eq!(parse("calc(-10px / (1 - 1))").unwrap().to_px(0.), f32::MIN);
+        assert_eq!(parse("calc(0px / 0)").unwrap().to_px(0.), 0.);
+        assert_eq!(parse("calc(10px + 0px / 0)").unwrap().to_px(0.), 0.);
+    }
+
+    #[test]
+    fn reject_mismatched_types() {
+        assert_eq!(parse("calc(1px + 2)"), None);
+        assert_eq!(parse("calc(1px * 2px)"), None);
+        // only numbers can divide
+        assert_eq!(parse("calc(10px / 2px)"), None);
+        assert_eq!(parse("min(1px, 2)"), None);
+        assert_eq!(parse("clamp(1px, 2, 3px)"), None);
+        // a number isn't a length
+        assert_eq!(parse("calc(2 * 3)"), None);
+
+        assert_eq!(parse("calc(10px / 4)").unwrap().to_px(0.), 2.5);
+        assert_eq!(parse("calc(2 * 50%)").unwrap().to_px(10.), 10.);
+    }
+}
@@ -0,0 +1,677 @@
+mod named;
+mod space;
+
+use super::number::Number;
+use super::without_whitespaces;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+use named::NAMED_COLORS;
+use space::{Channels, ColorSpace};
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum Color {
+    CurrentColor,
+    Rgba(Number, Number, Number, Number),
+    Transparent,
+}
+
+impl Eq for Color {}
+
+impl From<&Color> for shared::color::Color {
+    fn from(color: &Color) -> Self {
+        let default_color = shared::color::Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        };
+        let channel = |value: &Number| value.0.round().clamp(0., 255.) as u8;
+        match color {
+            Color::Rgba(r, g, b, a) => shared::color::Color {
+                r: channel(r),
+                g: channel(g),
+                b: channel(b),
+                a: channel(a),
+            },
+            _ => default_color,
+        }
+    }
+}
+
+/// A channel of a color function
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Component {
+    Number(f64),
+    Percentage(f64),
+    /// Angle in degrees
+    Angle(f64),
+    /// The `none` keyword, a missing component
+    None,
+}
+
+/// The channels & the alpha of a color function
+struct Arguments {
+    channels: [Component; 3],
+    alpha: Option<Component>,
+    /// Whether the arguments are separated by commas
+    legacy: bool,
+}
+
+impl Color {
+    /// Parse a color
+    /// https://www.w3.org/TR/css-color-4/#color-syntax
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::Function(function)) => {
+                let values = &function.value;
+                match function.name.to_lowercase().as_str() {
+                    "rgb" | "rgba" => Color::parse_rgb_function(values),
+                    "hsl" | "hsla" => Color::parse_hsl_function(values),
+                    "hwb" => Color::parse_hwb_function(values),
+                    "lab" => Color::parse_lab_function(values, ColorSpace::Lab),
+                    "lch" => Color::parse_lab_function(values, ColorSpace::Lch),
+                    "oklab" => Color::parse_lab_function(values, ColorSpace::Oklab),
+                    "oklch" => Color::parse_lab_function(values, ColorSpace::Oklch),
+                    "color-mix" => Color::parse_color_mix_function(values),
+                    _ => None,
+                }
+            }
+            Some(ComponentValue::PerservedToken(Token::Ident(keyword))) => {
+                Color::parse_color_keyword(keyword)
+            }
+            Some(ComponentValue::PerservedToken(Token::Hash(data, _))) => Color::parse_hex(data),
+            _ => None,
+        }
+    }
+
+    /// Parse `#rgb`, `#rgba`, `#rrggbb` & `#rrggbbaa`
+    fn parse_hex(hex: &str) -> Option<Self> {
+        let digits = hex
+            .chars()
+            .map(|ch| ch.to_digit(16))
+            .collect::<Option<Vec<u32>>>()?;
+
+        let channels = match digits.len() {
+            3 | 4 => digits
+                .iter()
+                .map(|digit| digit * 0x11)
+                .collect::<Vec<u32>>(),
+            6 | 8 => digits
+                .chunks(2)
+                .map(|pair| pair[0] * 0x10 + pair[1])
+                .collect::<Vec<u32>>(),
+            _ => return None,
+        };
+
+        let alpha = channels.get(3).copied().unwrap_or(255);
+        Some(Color::Rgba(
+            channels[0].into(),
+            channels[1].into(),
+            channels[2].into(),
+            alpha.into(),
+        ))
+    }
+
+    fn parse_color_keyword(keyword: &str) -> Option<Self> {
+        let keyword = keyword.trim().to_lowercase();
+        match keyword.as_str() {
+            "currentcolor" => Some(Color::CurrentColor),
+            "transparent" => Some(Color::Transparent),
+            _ => {
+                let index = NAMED_COLORS
+                    .binary_search_by(|(name, ..)| (*name).cmp(keyword.as_str()))
+                    .ok()?;
+                let (_, r, g, b) = NAMED_COLORS[index];
+                Some(Color::Rgba(
+                    (r as f32).into(),
+                    (g as f32).into(),
+                    (b as f32).into(),
+                    255.0.into(),
+                ))
+            }
+        }
+    }
+
+    /// `rgb()` & `rgba()`, which are aliases of each other
+    /// https://www.w3.org/TR/css-color-4/#rgb-functions
+    fn parse_rgb_function(values: &[ComponentValue]) -> Option<Self> {
+        let arguments = parse_arguments(values)?;
+        let channels = arguments.channels;
+
+        // the legacy syntax doesn't mix numbers & percentages
+        if arguments.legacy {
+            let is_percentage = |c: &Component| matches!(c, Component::Percentage(_));
+            if channels.iter().any(is_percentage) && !channels.iter().all(is_percentage) {
+                return None;
+            }
+        }
+
+        let channel = |component: Component| {
+            let value = number_or_percentage(component, 255.)?;
+            Some(value.clamp(0., 255.) / 255.)
+        };
+        let srgb = [
+            channel(channels[0])?,
+            channel(channels[1])?,
+            channel(channels[2])?,
+        ];
+        Some(Color::from_srgb(srgb, parse_alpha(arguments.alpha)?))
+    }
+
+    /// `hsl()` & `hsla()`, which are aliases of each other
+    /// https://www.w3.org/TR/css-color-4/#the-hsl-notation
+    fn parse_hsl_function(values: &[ComponentValue]) -> Option<Self> {
+        let arguments = parse_arguments(values)?;
+        let [hue, saturation, lightness] = arguments.channels;
+
+        // the legacy syntax requires percentages
+        let percentage = |component: Component| match component {
+            Component::Number(_) if arguments.legacy => None,
+            component => number_or_percentage(component, 100.).map(|value| value / 100.),
+        };
+        let hsl = [
+            parse_hue(hue)?,
+            percentage(saturation)?,
+            percentage(lightness)?,
+        ];
+        Color::from_channels(ColorSpace::Hsl, hsl, parse_alpha(arguments.alpha)?)
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#the-hwb-notation
+    fn parse_hwb_function(values: &[ComponentValue]) -> Option<Self> {
+        let arguments = parse_arguments(values)?;
+        if arguments.legacy {
+            return None;
+        }
+
+        let [hue, whiteness, blackness] = arguments.channels;
+        let percentage =
+            |component: Component| number_or_percentage(component, 100.).map(|value| value / 100.);
+        let hwb = [
+            parse_hue(hue)?,
+            percentage(whiteness)?,
+            percentage(blackness)?,
+        ];
+        Color::from_channels(ColorSpace::Hwb, hwb, parse_alpha(arguments.alpha)?)
+    }
+
+    /// `lab()`, `lch()`, `oklab()` & `oklch()`. Colors outside of the sRGB
+    /// gamut are gamut mapped.
+    /// https://www.w3.org/TR/css-color-4/#lab-colors
+    fn parse_lab_function(values: &[ComponentValue], space: ColorSpace) -> Option<Self> {
+        let arguments = parse_arguments(values)?;
+        if arguments.legacy {
+            return None;
+        }
+
+        // the values that 100% of each channel refers to
+        let (lightness, chroma, ab) = match space {
+            ColorSpace::Lab | ColorSpace::Lch => (100., 150., 125.),
+            _ => (1., 0.4, 0.4),
+        };
+
+        let [first, second, third] = arguments.channels;
+        let lightness = number_or_percentage(first, lightness)?.max(0.);
+        let channels = match space {
+            ColorSpace::Lch | ColorSpace::Oklch => [
+                lightness,
+                number_or_percentage(second, chroma)?.max(0.),
+                parse_hue(third)?,
+            ],
+            _ => [
+                lightness,
+                number_or_percentage(second, ab)?,
+                number_or_percentage(third, ab)?,
+            ],
+        };
+        Color::from_channels(space, channels, parse_alpha(arguments.alpha)?)
+    }
+
+    /// `color-mix(in <color-space>, <color> <percentage>?, <color> <percentage>?)`.
+    /// The colors are mixed when parsed, so mixing `currentcolor` isn't
+    /// supported. Missing components are mixed as zero, except for
+    /// powerless hues.
+    /// https://www.w3.org/TR/css-color-5/#color-mix
+    fn parse_color_mix_function(values: &[ComponentValue]) -> Option<Self> {
+        let arguments = values
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(without_whitespaces)
+            .collect::<Vec<Vec<&ComponentValue>>>();
+
+        let (space, hue_method) = match arguments.first()?.as_slice() {
+            [ComponentValue::PerservedToken(Token::Ident(keyword)), rest @ ..]
+                if keyword.eq_ignore_ascii_case("in") =>
+            {
+                parse_interpolation_method(rest)?
+            }
+            _ => return None,
+        };
+
+        if arguments.len() != 3 {
+            return None;
+        }
+        let (first, first_percentage) = parse_mix_color(&arguments[1])?;
+        let (second, second_percentage) = parse_mix_color(&arguments[2])?;
+
+        // normalize the percentages to sum to 100%. If they sum to less,
+        // the result is more transparent.
+        // https://www.w3.org/TR/css-color-5/#color-mix-percent-norm
+        let (first_percentage, second_percentage) = match (first_percentage, second_percentage) {
+            (None, None) => (50., 50.),
+            (Some(p1), None) => (p1, 100. - p1),
+            (None, Some(p2)) => (100. - p2, p2),
+            (Some(p1), Some(p2)) => (p1, p2),
+        };
+        let sum = first_percentage + second_percentage;
+        if sum <= 0. {
+            return None;
+        }
+        let alpha_multiplier = if sum < 100. { sum / 100. } else { 1. };
+        let progress = second_percentage / sum;
+
+        let (first, first_alpha) = first.to_srgb()?;
+        let (second, second_alpha) = second.to_srgb()?;
+        let mut first = space.convert_srgb(first);
+        let mut second = space.convert_srgb(second);
+
+        if let Some(index) = space.hue_index() {
+            // a powerless hue takes the hue of the other color
+            match (space.is_achromatic(first), space.is_achromatic(second)) {
+                (true, false) => first[index] = second[index],
+                (false, true) => second[index] = first[index],
+                _ => {}
+            }
+            adjust_hues(&mut first[index], &mut second[index], hue_method);
+        }
+
+        // interpolate with premultiplied alpha
+        // https://www.w3.org/TR/css-color-4/#interpolation-alpha
+        let alpha = first_alpha + (second_alpha - first_alpha) * progress;
+        let mut channels = [0.; 3];
+        for index in 0..3 {
+            if space.hue_index() == Some(index) {
+                channels[index] = first[index] + (second[index] - first[index]) * progress;
+                continue;
+            }
+            let first = first[index] * first_alpha;
+            let second = second[index] * second_alpha;
+            let value = first + (second - first) * progress;
+            channels[index] = if alpha == 0. { value } else { value / alpha };
+        }
+
+        Color::from_channels(space, channels, alpha * alpha_multiplier)
+    }
+
+    /// Gamma-encoded sRGB channels in the range 0..1 & the alpha of the
+    /// color. `currentcolor` is unknown until computed-value time.
+    fn to_srgb(&self) -> Option<(Channels, f64)> {
+        match self {
+            Color::Rgba(r, g, b, a) => Some((
+                [r.0 as f64 / 255., g.0 as f64 / 255., b.0 as f64 / 255.],
+                a.0 as f64 / 255.,
+            )),
+            Color::Transparent => Some(([0., 0., 0.], 0.)),
+            Color::CurrentColor => None,
+        }
+    }
+
+    fn from_channels(space: ColorSpace, channels: Channels, alpha: f64) -> Option<Self> {
+        if channels.iter().any(|channel| !channel.is_finite()) {
+            return None;
+        }
+        Some(Color::from_srgb(space.to_srgb(channels), alpha))
+    }
+
+    fn from_srgb(srgb: Channels, alpha: f64) -> Self {
+        let channel = |value: f64| ((value * 255.) as f32).into();
+        Color::Rgba(
+            channel(srgb[0]),
+            channel(srgb[1]),
+            channel(srgb[2]),
+            channel(alpha.clamp(0., 1.)),
+        )
+    }
+
+    pub fn transparent() -> Self {
+        Color::Transparent
+    }
+
+    pub fn black() -> Self {
+        Color::Rgba(0.0.into(), 0.0.into(), 0.0.into(), 255.0.into())
+    }
+}
+
+/// How hues are interpolated
+/// https://www.w3.org/TR/css-color-4/#hue-interpolation
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HueInterpolationMethod {
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+/// `<color-space> [<hue-interpolation-method> hue]?`
+fn parse_interpolation_method(
+    values: &[&ComponentValue],
+) -> Option<(ColorSpace, HueInterpolationMethod)> {
+    let ident = |value: &ComponentValue| match value {
+        ComponentValue::PerservedToken(Token::Ident(ident)) => Some(ident.to_lowercase()),
+        _ => None,
+    };
+
+    let space = ColorSpace::parse(&ident(values.first()?)?)?;
+    let method = match values {
+        [_] => HueInterpolationMethod::Shorter,
+        [_, method, hue] if space.hue_index().is_some() && ident(hue)? == "hue" => {
+            match ident(method)?.as_str() {
+                "shorter" => HueInterpolationMethod::Shorter,
+                "longer" => HueInterpolationMethod::Longer,
+                "increasing" => HueInterpolationMethod::Increasing,
+                "decreasing" => HueInterpolationMethod::Decreasing,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some((space, method))
+}
+
+/// `<color> && <percentage [0,100]>?`
+fn parse_mix_color(values: &[&ComponentValue]) -> Option<(Color, Option<f64>)> {
+    let (color, percentage) = match values {
+        [color] => (*color, None),
+        [ComponentValue::PerservedToken(Token::Percentage(percentage)), color]
+        | [color, ComponentValue::PerservedToken(Token::Percentage(percentage))] => {
+            (*color, Some(*percentage as f64))
+        }
+        _ => return None,
+    };
+
+    if let Some(percentage) = percentage {
+        if !(0. ..=100.).contains(&percentage) {
+            return None;
+        }
+    }
+    let color = Color::parse(std::slice::from_ref(color))?;
+    Some((color, percentage))
+}
+
+fn adjust_hues(first: &mut f64, second: &mut f64, method: HueInterpolationMethod) {
+    let delta = *second - *first;
+    match method {
+        HueInterpolationMethod::Shorter => {
+            if delta > 180. {
+                *first += 360.;
+            } else if delta < -180. {
+                *second += 360.;
+            }
+        }
+        HueInterpolationMethod::Longer => {
+            if delta > 0. && delta < 180. {
+                *first += 360.;
+            } else if delta > -180. && delta <= 0. {
+                *second += 360.;
+            }
+        }
+        HueInterpolationMethod::Increasing => {
+            if delta < 0. {
+                *second += 360.;
+            }
+        }
+        HueInterpolationMethod::Decreasing => {
+            if delta > 0. {
+                *first += 360.;
+            }
+        }
+    }
+}
+
+/// Parse the arguments of a color function, either separated by spaces
+/// with an optional `/ <alpha>`, or by commas in the legacy syntax.
+fn parse_arguments(values: &[ComponentValue]) -> Option<Arguments> {
+    let values = without_whitespaces(values);
+    let is_comma =
+        |value: &&ComponentValue| matches!(value, ComponentValue::PerservedToken(Token::Comma));
+
+    let (channels, alpha, legacy) = if values.iter().any(is_comma) {
+        let arguments = values.split(is_comma).collect::<Vec<&[&ComponentValue]>>();
+        if arguments.iter().any(|argument| argument.len() != 1) {
+            return None;
+        }
+        let arguments = arguments
+            .iter()
+            .map(|argument| argument[0])
+            .collect::<Vec<&ComponentValue>>();
+        match arguments.as_slice() {
+            [r, g, b] => (vec![*r, *g, *b], None, true),
+            [r, g, b, a] => (vec![*r, *g, *b], Some(*a), true),
+            _ => return None,
+        }
+    } else {
+        let slash = values
+            .iter()
+            .position(|value| matches!(value, ComponentValue::PerservedToken(Token::Delim('/'))));
+        match slash {
+            Some(index) => match &values[index + 1..] {
+                [alpha] => (values[..index].to_vec(), Some(*alpha), false),
+                _ => return None,
+            },
+            None => (values, None, false),
+        }
+    };
+
+    if channels.len() != 3 {
+        return None;
+    }
+    let channels = [
+        parse_component(channels[0])?,
+        parse_component(channels[1])?,
+        parse_component(channels[2])?,
+    ];
+    let alpha = match alpha {
+        Some(alpha) => Some(parse_component(alpha)?),
+        None => None,
+    };
+
+    // `none` is only valid in the modern syntax
+    if legacy && (channels.contains(&Component::None) || alpha == Some(Component::None)) {
+        return None;
+    }
+
+    Some(Arguments {
+        channels,
+        alpha,
+        legacy,
+    })
+}
+
+fn parse_component(value: &ComponentValue) -> Option<Component> {
+    match value {
+        ComponentValue::PerservedToken(Token::Number { value, .. }) => {
+            Some(Component::Number(*value as f64))
+        }
+        ComponentValue::PerservedToken(Token::Percentage(value)) => {
+            Some(Component::Percentage(*value as f64))
+        }
+        ComponentValue::PerservedToken(Token::Dimension { value, unit, .. }) => {
+            let value = *value as f64;
+            let degrees = match unit.to_lowercase().as_str() {
+                "deg" => value,
+                "grad" => value * 0.9,
+                "rad" => value.to_degrees(),
+                "turn" => value * 360.,
+                _ => return None,
+            };
+            Some(Component::Angle(degrees))
+        }
+        ComponentValue::PerservedToken(Token::Ident(ident))
+            if ident.eq_ignore_ascii_case("none") =>
+        {
+            Some(Component::None)
+        }
+        _ => None,
+    }
+}
+
+/// A number, or a percentage of `reference`. A missing component is zero.
+fn number_or_percentage(component: Component, reference: f64) -> Option<f64> {
+    match component {
+        Component::Number(value) => Some(value),
+        Component::Percentage(value) => Some(value / 100. * reference),
+        Component::None => Some(0.),
+        Component::Angle(_) => None,
+    }
+}
+
+/// A hue in degrees
+fn parse_hue(component: Component) -> Option<f64> {
+    match component {
+        Component::Number(value) | Component::Angle(value) => Some(value),
+        Component::None => Some(0.),
+        Component::Percentage(_) => None,
+    }
+}
+
+/// An alpha in the range 0..1, opaque when omitted
+fn parse_alpha(component: Option<Component>) -> Option<f64> {
+    let alpha = match component {
+        Some(component) => number_or_percentage(component, 1.)?,
+        None => 1.,
+    };
+    Some(alpha.clamp(0., 1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn rgba(css: &str) -> Option<[u8; 4]> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let color = Color::parse(&parser.parse_a_list_of_component_values())?;
+        let color = shared::color::Color::from(&color);
+        Some([color.r, color.g, color.b, color.a])
+    }
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(rgba("rebeccapurple"), Some([102, 51, 153, 255]));
+        assert_eq!(rgba("LightGoldenrodYellow"), Some([250, 250, 210, 255]));
+        assert_eq!(rgba("#0f08"), Some([0, 255, 0, 136]));
+        assert_eq!(rgba("#ff000080"), Some([255, 0, 0, 128]));
+        assert_eq!(rgba("rgba(0, 127, 255, 0.5)"), Some([0, 127, 255, 128]));
+        assert_eq!(rgba("rgb(100% 50% 0% / 25%)"), Some([255, 128, 0, 64]));
+        assert_eq!(rgba("rgb(none 255 0)"), Some([0, 255, 0, 255]));
+        assert_eq!(rgba("hsl(120deg 100% 25%)"), Some([0, 128, 0, 255]));
+        assert_eq!(
+            rgba("hsla(0.5turn, 100%, 50%, 1)"),
+            Some([0, 255, 255, 255])
+        );
+        assert_eq!(rgba("hwb(0 20% 20%)"), Some([204, 51, 51, 255]));
+        assert_eq!(rgba("oklch(62.8% 0.2577 29.23)"), Some([255, 0, 0, 255]));
+        assert_eq!(rgba("oklab(1 0 0)"), Some([255, 255, 255, 255]));
+        assert_eq!(rgba("lab(50% 0 0)"), Some([119, 119, 119, 255]));
+        assert_eq!(rgba("lch(0% 0 0)"), Some([0, 0, 0, 255]));
+        assert_eq!(
+            rgba("color-mix(in srgb, red, blue)"),
+            Some([128, 0, 128, 255])
+        );
+        assert_eq!(
+            rgba("color-mix(in srgb, red 25%, transparent 25%)"),
+            Some([255, 0, 0, 64])
+        );
+
+        // out of the sRGB gamut, the chroma is reduced
+        let color = rgba("oklch(70% 0.4 145)").unwrap();
+        assert!(color[1] > color[0] && color[1] > color[2]);
+
+        // the legacy syntax doesn't mix numbers & percentages
+        assert_eq!(rgba("rgb(255, 50%, 0)"), None);
+        assert_eq!(rgba("hsl(120, 100, 50)"), None);
+        assert_eq!(rgba("hwb(0, 20%, 20%)"), None);
+        assert_eq!(rgba("#12345"), None);
+        assert_eq!(rgba("notacolor"), None);
+    }
+
+    #[test]
+    fn gamut_map_out_of_range_colors() {
+        // reduce the chroma instead of clipping each channel
+        assert_eq!(rgba("oklch(0.7 0.4 30)"), Some([255, 88, 67, 255]));
+        assert_eq!(rgba("oklch(0.9 0.4 140)"), Some([98, 255, 49, 255]));
+        assert_eq!(rgba("lab(50 200 0)"), Some([255, 0, 137, 255]));
+        // lightness out of range is white or black
+        assert_eq!(rgba("lab(110 0 0)"), Some([255, 255, 255, 255]));
+        assert_eq!(rgba("oklch(1.2 0.1 0)"), Some([255, 255, 255, 255]));
+        assert_eq!(rgba("oklch(-0.1 0.1 0)"), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn mix_hues() {
+        let mix = |method: &str, first: &str, second: &str| {
+            rgba(&format!(
+                "color-mix(in hsl{}, {}, {})",
+                method, first, second
+            ))
+        };
+        let (orange, pink) = ("hsl(30 100% 50%)", "hsl(330 100% 50%)");
+        let (red, cyan) = (Some([255, 0, 0, 255]), Some([0, 255, 255, 255]));
+
+        assert_eq!(mix("", orange, pink), red);
+        assert_eq!(mix(" shorter hue", orange, pink), red);
+        assert_eq!(mix(" longer hue", orange, pink), cyan);
+        assert_eq!(mix(" increasing hue", orange, pink), cyan);
+        assert_eq!(mix(" increasing hue", pink, orange), red);
+        assert_eq!(mix(" decreasing hue", orange, pink), red);
+        assert_eq!(mix(" decreasing hue", pink, orange), cyan);
+        // only polar color spaces have a hue
+        assert_eq!(rgba("color-mix(in srgb longer hue, red, blue)"), None);
+    }
+
+    #[test]
+    fn mix_none_components() {
+        // the powerless hue of a gray takes the hue of the other color
+        let half_chroma = rgba("lch(50 25 120)");
+        assert_eq!(
+            rgba("color-mix(in lch, lch(50 0 none), lch(50 50 120))"),
+            half_chroma
+        );
+        assert_eq!(
+            rgba("color-mix(in lch, lch(50 0 300), lch(50 50 120))"),
+            half_chroma
+        );
+        // other missing components are mixed as zero
+        assert_eq!(
+            rgba("color-mix(in srgb, rgb(none 0 0), rgb(255 0 0))"),
+            Some([128, 0, 0, 255])
+        );
+        assert_eq!(rgba("rgb(none none none / none)"), Some([0, 0, 0, 0]));
+        // `none` is only valid in the modern syntax
+        assert_eq!(rgba("rgb(none, 0, 0)"), None);
+    }
+
+    #[test]
+    fn parse_lab_and_lch_edge_cases() {
+        let gray = Some([119, 119, 119, 255]);
+        assert_eq!(rgba("lab(50 0 0)"), gray);
+        assert_eq!(rgba("lab(50% 0 0)"), gray);
+        // the hue of a zero chroma is powerless
+        assert_eq!(rgba("lch(50 0 0)"), gray);
+        assert_eq!(rgba("lch(50 0 200)"), gray);
+        assert_eq!(rgba("lch(50 0 none)"), gray);
+        // negative chroma is clamped to zero
+        assert_eq!(rgba("lch(50 -10 0)"), gray);
+        assert_eq!(rgba("oklch(50% 0 0)"), rgba("oklab(0.5 0 0)"));
+
+        assert_eq!(rgba("lch(0 0 0)"), Some([0, 0, 0, 255]));
+        assert_eq!(rgba("lch(100 0 0)"), Some([255, 255, 255, 255]));
+        assert_eq!(rgba("lch(50 30 1turn)"), rgba("lch(50 30 0)"));
+        assert_eq!(rgba("lch(50 30 360deg)"), rgba("lch(50 30 0)"));
+        // the legacy syntax is only for rgb() & hsl()
+        assert_eq!(rgba("lab(50, 0, 0)"), None);
+        assert_eq!(rgba("lch(50 30% 10%)"), None);
+    }
+}
@@ -0,0 +1,473 @@
+/// Three channels of a color in a color space
+pub type Channels = [f64; 3];
+
+type Matrix = [[f64; 3]; 3];
+
+const LINEAR_SRGB_TO_XYZ_D65: Matrix = [
+    [0.41239079926595934, 0.357584339383878, 0.1804807884018343],
+    [0.21263900587151027, 0.715168678767756, 0.07219231536073371],
+    [0.01933081871559182, 0.11919477979462598, 0.9505321522496607],
+];
+
+const XYZ_D65_TO_LINEAR_SRGB: Matrix = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+    [
+        0.05563007969699366,
+        -0.20397695888897652,
+        1.0569715142428786,
+    ],
+];
+
+/// Bradford chromatic adaptation
+const XYZ_D65_TO_D50: Matrix = [
+    [
+        1.0479298208405488,
+        0.022946793341019088,
+        -0.05019222954313557,
+    ],
+    [
+        0.029627815688159344,
+        0.990434484573249,
+        -0.01707382502938514,
+    ],
+    [
+        -0.009243058152591178,
+        0.015055144896577895,
+        0.7518742899580008,
+    ],
+];
+
+const XYZ_D50_TO_D65: Matrix = [
+    [
+        0.9554734527042182,
+        -0.023098536874261423,
+        0.0632593086610217,
+    ],
+    [
+        -0.028369706963208136,
+        1.0099954580058226,
+        0.021041398966943008,
+    ],
+    [
+        0.012314001688319899,
+        -0.020507696433477912,
+        1.3303659366080753,
+    ],
+];
+
+const LINEAR_SRGB_TO_LMS: Matrix = [
+    [0.4122214708, 0.5363325363, 0.0514459929],
+    [0.2119034982, 0.6806995451, 0.1073969566],
+    [0.0883024619, 0.2817188376, 0.6299787005],
+];
+
+const LMS_TO_OKLAB: Matrix = [
+    [0.2104542553, 0.7936177850, -0.0040720468],
+    [1.9779984951, -2.4285922050, 0.4505937099],
+    [0.0259040371, 0.7827717662, -0.8086757660],
+];
+
+const OKLAB_TO_LMS: Matrix = [
+    [1.0, 0.3963377774, 0.2158037573],
+    [1.0, -0.1055613458, -0.0638541728],
+    [1.0, -0.0894841775, -1.2914855480],
+];
+
+const LMS_TO_LINEAR_SRGB: Matrix = [
+    [4.0767416621, -3.3077115913, 0.2309699292],
+    [-1.2684380046, 2.6097574011, -0.3413193965],
+    [-0.0041960863, -0.7034186147, 1.7076147010],
+];
+
+const D50_WHITE: Channels = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+/// Color spaces that colors can be interpolated in
+/// https://www.w3.org/TR/css-color-4/#interpolation-space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorSpace {
+    Srgb,
+    SrgbLinear,
+    Hsl,
+    Hwb,
+    Lab,
+    Lch,
+    Oklab,
+    Oklch,
+    XyzD50,
+    XyzD65,
+}
+
+impl ColorSpace {
+    pub fn parse(name: &str) -> Option<Self> {
+        let space = match name.to_lowercase().as_str() {
+            "srgb" => ColorSpace::Srgb,
+            "srgb-linear" => ColorSpace::SrgbLinear,
+            "hsl" => ColorSpace::Hsl,
+            "hwb" => ColorSpace::Hwb,
+            "lab" => ColorSpace::Lab,
+            "lch" => ColorSpace::Lch,
+            "oklab" => ColorSpace::Oklab,
+            "oklch" => ColorSpace::Oklch,
+            "xyz" | "xyz-d65" => ColorSpace::XyzD65,
+            "xyz-d50" => ColorSpace::XyzD50,
+            _ => return None,
+        };
+        Some(space)
+    }
+
+    /// Index of the hue channel of polar color spaces
+    pub fn hue_index(&self) -> Option<usize> {
+        match self {
+            ColorSpace::Hsl | ColorSpace::Hwb => Some(0),
+            ColorSpace::Lch | ColorSpace::Oklch => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Whether the hue of the color is powerless, e.g. the hue of a gray
+    pub fn is_achromatic(&self, channels: Channels) -> bool {
+        const EPSILON: f64 = 1e-5;
+        match self {
+            ColorSpace::Hsl => channels[1].abs() < EPSILON,
+            ColorSpace::Hwb => channels[1] + channels[2] >= 1. - EPSILON,
+            ColorSpace::Lch => channels[1] < EPSILON * 100.,
+            ColorSpace::Oklch => channels[1] < EPSILON,
+            _ => false,
+        }
+    }
+
+    /// Convert a gamma-encoded sRGB color to the color space. The channels
+    /// of HSL & HWB are in the range 0..1 except for the hue.
+    pub fn convert_srgb(self, srgb: Channels) -> Channels {
+        let linear = srgb_to_linear(srgb);
+        match self {
+            ColorSpace::Srgb => srgb,
+            ColorSpace::SrgbLinear => linear,
+            ColorSpace::Hsl => srgb_to_hsl(srgb),
+            ColorSpace::Hwb => srgb_to_hwb(srgb),
+            ColorSpace::Lab => linear_srgb_to_lab(linear),
+            ColorSpace::Lch => to_polar(linear_srgb_to_lab(linear)),
+            ColorSpace::Oklab => linear_srgb_to_oklab(linear),
+            ColorSpace::Oklch => to_polar(linear_srgb_to_oklab(linear)),
+            ColorSpace::XyzD50 => multiply(&XYZ_D65_TO_D50, linear_srgb_to_xyz_d65(linear)),
+            ColorSpace::XyzD65 => linear_srgb_to_xyz_d65(linear),
+        }
+    }
+
+    /// Convert a color in the color space to gamma-encoded sRGB, mapped
+    /// into the sRGB gamut
+    pub fn to_srgb(self, channels: Channels) -> Channels {
+        let linear = match self {
+            ColorSpace::Srgb => srgb_to_linear(channels),
+            ColorSpace::SrgbLinear => channels,
+            ColorSpace::Hsl => srgb_to_linear(hsl_to_srgb(channels)),
+            ColorSpace::Hwb => srgb_to_linear(hwb_to_srgb(channels)),
+            ColorSpace::Lab => lab_to_linear_srgb(channels),
+            ColorSpace::Lch => lab_to_linear_srgb(from_polar(channels)),
+            ColorSpace::Oklab => oklab_to_linear_srgb(channels),
+            ColorSpace::Oklch => oklab_to_linear_srgb(from_polar(channels)),
+            ColorSpace::XyzD50 => xyz_d65_to_linear_srgb(multiply(&XYZ_D50_TO_D65, channels)),
+            ColorSpace::XyzD65 => xyz_d65_to_linear_srgb(channels),
+        };
+        gamut_map(linear)
+    }
+}
+
+/// Map a color that may be outside of the sRGB gamut into the gamut by
+/// reducing its OKLCh chroma until clipping it is not noticeable.
+/// https://www.w3.org/TR/css-color-4/#binsearch
+fn gamut_map(linear: Channels) -> Channels {
+    const JND: f64 = 0.02;
+    const EPSILON: f64 = 0.0001;
+
+    let srgb = linear_to_srgb(linear);
+    if in_gamut(srgb) {
+        return clip(srgb);
+    }
+
+    let [lightness, chroma, hue] = to_polar(linear_srgb_to_oklab(linear));
+    if lightness >= 1. {
+        return [1., 1., 1.];
+    }
+    if lightness <= 0. {
+        return [0., 0., 0.];
+    }
+
+    let to_srgb =
+        |chroma: f64| linear_to_srgb(oklab_to_linear_srgb(from_polar([lightness, chroma, hue])));
+    let delta_e = |srgb: Channels, chroma: f64| {
+        let clipped = linear_srgb_to_oklab(srgb_to_linear(srgb));
+        let current = from_polar([lightness, chroma, hue]);
+        let delta = [
+            clipped[0] - current[0],
+            clipped[1] - current[1],
+            clipped[2] - current[2],
+        ];
+        (delta[0].powi(2) + delta[1].powi(2) + delta[2].powi(2)).sqrt()
+    };
+
+    let mut clipped = clip(srgb);
+    if delta_e(clipped, chroma) < JND {
+        return clipped;
+    }
+
+    let mut min = 0.;
+    let mut max = chroma;
+    let mut min_in_gamut = true;
+    while max - min > EPSILON {
+        let chroma = (min + max) / 2.;
+        let current = to_srgb(chroma);
+        if min_in_gamut && in_gamut(current) {
+            min = chroma;
+            continue;
+        }
+
+        clipped = clip(current);
+        let error = delta_e(clipped, chroma);
+        if error < JND {
+            if JND - error < EPSILON {
+                break;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    clipped
+}
+
+fn in_gamut(srgb: Channels) -> bool {
+    const EPSILON: f64 = 1e-6;
+    srgb.iter()
+        .all(|channel| *channel >= -EPSILON && *channel <= 1. + EPSILON)
+}
+
+fn clip(srgb: Channels) -> Channels {
+    [
+        srgb[0].clamp(0., 1.),
+        srgb[1].clamp(0., 1.),
+        srgb[2].clamp(0., 1.),
+    ]
+}
+
+fn multiply(matrix: &Matrix, channels: Channels) -> Channels {
+    let row = |row: &[f64; 3]| row[0] * channels[0] + row[1] * channels[1] + row[2] * channels[2];
+    [row(&matrix[0]), row(&matrix[1]), row(&matrix[2])]
+}
+
+fn srgb_to_linear(srgb: Channels) -> Channels {
+    let channel = |value: f64| {
+        let abs = value.abs();
+        if abs <= 0.04045 {
+            value / 12.92
+        } else {
+            value.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [channel(srgb[0]), channel(srgb[1]), channel(srgb[2])]
+}
+
+fn linear_to_srgb(linear: Channels) -> Channels {
+    let channel = |value: f64| {
+        let abs = value.abs();
+        if abs > 0.0031308 {
+            value.signum() * (1.055 * abs.powf(1. / 2.4) - 0.055)
+        } else {
+            value * 12.92
+        }
+    };
+    [channel(linear[0]), channel(linear[1]), channel(linear[2])]
+}
+
+fn linear_srgb_to_xyz_d65(linear: Channels) -> Channels {
+    multiply(&LINEAR_SRGB_TO_XYZ_D65, linear)
+}
+
+fn xyz_d65_to_linear_srgb(xyz: Channels) -> Channels {
+    multiply(&XYZ_D65_TO_LINEAR_SRGB, xyz)
+}
+
+fn linear_srgb_to_oklab(linear: Channels) -> Channels {
+    let lms = multiply(&LINEAR_SRGB_TO_LMS, linear);
+    multiply(&LMS_TO_OKLAB, [lms[0].cbrt(), lms[1].cbrt(), lms[2].cbrt()])
+}
+
+fn oklab_to_linear_srgb(oklab: Channels) -> Channels {
+    let lms = multiply(&OKLAB_TO_LMS, oklab);
+    multiply(
+        &LMS_TO_LINEAR_SRGB,
+        [lms[0].powi(3), lms[1].powi(3), lms[2].powi(3)],
+    )
+}
+
+/// https://www.w3.org/TR/css-color-4/#color-conversion-code
+fn lab_to_linear_srgb(lab: Channels) -> Channels {
+    const KAPPA: f64 = 24389. / 27.;
+    const EPSILON: f64 = 216. / 24389.;
+
+    let [lightness, a, b] = lab;
+    let f1 = (lightness + 16.) / 116.;
+    let f0 = a / 500. + f1;
+    let f2 = f1 - b / 200.;
+
+    let x = if f0.powi(3) > EPSILON {
+        f0.powi(3)
+    } else {
+        (116. * f0 - 16.) / KAPPA
+    };
+    let y = if lightness > KAPPA * EPSILON {
+        f1.powi(3)
+    } else {
+        lightness / KAPPA
+    };
+    let z = if f2.powi(3) > EPSILON {
+        f2.powi(3)
+    } else {
+        (116. * f2 - 16.) / KAPPA
+    };
+
+    let xyz_d50 = [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]];
+    xyz_d65_to_linear_srgb(multiply(&XYZ_D50_TO_D65, xyz_d50))
+}
+
+fn linear_srgb_to_lab(linear: Channels) -> Channels {
+    const KAPPA: f64 = 24389. / 27.;
+    const EPSILON: f64 = 216. / 24389.;
+
+    let xyz_d50 = multiply(&XYZ_D65_TO_D50, linear_srgb_to_xyz_d65(linear));
+    let f = |index: usize| {
+        let value = xyz_d50[index] / D50_WHITE[index];
+        if value > EPSILON {
+            value.cbrt()
+        } else {
+            (KAPPA * value + 16.) / 116.
+        }
+    };
+    let (f0, f1, f2) = (f(0), f(1), f(2));
+    [116. * f1 - 16., 500. * (f0 - f1), 200. * (f1 - f2)]
+}
+
+/// Convert rectangular coordinates to lightness, chroma & hue
+fn to_polar(channels: Channels) -> Channels {
+    let [lightness, a, b] = channels;
+    let chroma = (a * a + b * b).sqrt();
+    let hue = b.atan2(a).to_degrees();
+    [lightness, chroma, normalize_hue(hue)]
+}
+
+fn from_polar(channels: Channels) -> Channels {
+    let [lightness, chroma, hue] = channels;
+    let hue = hue.to_radians();
+    [lightness, chroma * hue.cos(), chroma * hue.sin()]
+}
+
+fn normalize_hue(hue: f64) -> f64 {
+    let hue = hue % 360.;
+    if hue < 0. {
+        hue + 360.
+    } else {
+        hue
+    }
+}
+
+/// https://www.w3.org/TR/css-color-4/#hsl-to-rgb
+fn hsl_to_srgb(hsl: Channels) -> Channels {
+    let [hue, saturation, lightness] = hsl;
+    let hue = normalize_hue(hue);
+    let saturation = saturation.max(0.);
+
+    let channel = |n: f64| {
+        let k = (n + hue / 30.) % 12.;
+        let a = saturation * lightness.min(1. - lightness);
+        lightness - a * (k - 3.).min(9. - k).clamp(-1., 1.)
+    };
+    [channel(0.), channel(8.), channel(4.)]
+}
+
+fn srgb_to_hsl(srgb: Channels) -> Channels {
+    let [red, green, blue] = srgb;
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let lightness = (max + min) / 2.;
+    let delta = max - min;
+
+    if delta == 0. {
+        return [0., 0., lightness];
+    }
+
+    let saturation = if lightness == 0. || lightness == 1. {
+        0.
+    } else {
+        (max - lightness) / lightness.min(1. - lightness)
+    };
+    let hue = if max == red {
+        (green - blue) / delta + if green < blue { 6. } else { 0. }
+    } else if max == green {
+        (blue - red) / delta + 2.
+    } else {
+        (red - green) / delta + 4.
+    };
+    [hue * 60., saturation, lightness]
+}
+
+/// https://www.w3.org/TR/css-color-4/#hwb-to-rgb
+fn hwb_to_srgb(hwb: Channels) -> Channels {
+    let [hue, whiteness, blackness] = hwb;
+    if whiteness + blackness >= 1. {
+        let gray = whiteness / (whiteness + blackness);
+        return [gray, gray, gray];
+    }
+
+    let rgb = hsl_to_srgb([hue, 1., 0.5]);
+    let channel = |value: f64| value * (1. - whiteness - blackness) + whiteness;
+    [channel(rgb[0]), channel(rgb[1]), channel(rgb[2])]
+}
+
+fn srgb_to_hwb(srgb: Channels) -> Channels {
+    let hsl = srgb_to_hsl(srgb);
+    let whiteness = srgb[0].min(srgb[1]).min(srgb[2]);
+    let blackness = 1. - srgb[0].max(srgb[1]).max(srgb[2]);
+    [hsl[0], whiteness, blackness]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamut_map_keeps_lightness_and_hue() {
+        for oklch in [[0.7, 0.4, 30.], [0.9, 0.4, 140.], [0.4, 0.3, 260.]] {
+            let srgb = ColorSpace::Oklch.to_srgb(oklch);
+            assert!(srgb.iter().all(|channel| (0. ..=1.).contains(channel)));
+
+            // the clipped color is only just noticeably different
+            let [lightness, chroma, hue] = ColorSpace::Oklch.convert_srgb(srgb);
+            assert!((lightness - oklch[0]).abs() < 0.03);
+            assert!((hue - oklch[2]).abs() < 5.);
+            assert!(chroma < oklch[1]);
+        }
+    }
+
+    #[test]
+    fn convert_srgb_roundtrip() {
+        let srgb = [0.2, 0.5, 0.8];
+        for space in [
+            ColorSpace::SrgbLinear,
+            ColorSpace::Hsl,
+            ColorSpace::Hwb,
+            ColorSpace::Lab,
+            ColorSpace::Lch,
+            ColorSpace::Oklab,
+            ColorSpace::Oklch,
+            ColorSpace::XyzD50,
+            ColorSpace::XyzD65,
+        ] {
+            let result = space.to_srgb(space.convert_srgb(srgb));
+            for (channel, expected) in result.iter().zip(srgb) {
+                assert!((channel - expected).abs() < 1e-4, "{:?}", space);
+            }
+        }
+    }
+}
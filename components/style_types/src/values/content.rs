@@ -0,0 +1,245 @@
+use super::without_whitespaces;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// Value of the `content` property
+/// https://www.w3.org/TR/css-content-3/#content-property
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Content {
+    Normal,
+    None,
+    Items(Vec<ContentItem>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContentItem {
+    String(String),
+    /// `attr(name)`
+    Attr(String),
+    /// `counter(name, style)`
+    Counter(String, CounterStyle),
+    /// `counters(name, separator, style)`
+    Counters(String, String, CounterStyle),
+    OpenQuote,
+    CloseQuote,
+    NoOpenQuote,
+    NoCloseQuote,
+}
+
+/// Subset of the predefined counter styles
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CounterStyle {
+    Decimal,
+    LowerAlpha,
+    UpperAlpha,
+    LowerRoman,
+    UpperRoman,
+    None,
+}
+
+impl Content {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let values = without_whitespaces(values);
+
+        if let [ComponentValue::PerservedToken(Token::Ident(value))] = values.as_slice() {
+            if value.eq_ignore_ascii_case("normal") {
+                return Some(Content::Normal);
+            }
+            if value.eq_ignore_ascii_case("none") {
+                return Some(Content::None);
+            }
+        }
+
+        if values.is_empty() {
+            return None;
+        }
+
+        values
+            .into_iter()
+            .map(ContentItem::parse)
+            .collect::<Option<Vec<ContentItem>>>()
+            .map(Content::Items)
+    }
+}
+
+impl ContentItem {
+    fn parse(value: &ComponentValue) -> Option<Self> {
+        match value {
+            ComponentValue::PerservedToken(Token::Str(value)) => {
+                Some(ContentItem::String(value.clone()))
+            }
+            ComponentValue::PerservedToken(Token::Ident(value)) => {
+                match value.to_lowercase().as_str() {
+                    "open-quote" => Some(ContentItem::OpenQuote),
+                    "close-quote" => Some(ContentItem::CloseQuote),
+                    "no-open-quote" => Some(ContentItem::NoOpenQuote),
+                    "no-close-quote" => Some(ContentItem::NoCloseQuote),
+                    _ => None,
+                }
+            }
+            ComponentValue::Function(function) => {
+                let arguments = function_arguments(&function.value)?;
+                match (function.name.to_lowercase().as_str(), arguments.as_slice()) {
+                    ("attr", [name]) => Some(ContentItem::Attr(ident(name)?.to_lowercase())),
+                    ("counter", [name]) => Some(ContentItem::Counter(
+                        ident(name)?.clone(),
+                        CounterStyle::Decimal,
+                    )),
+                    ("counter", [name, style]) => Some(ContentItem::Counter(
+                        ident(name)?.clone(),
+                        CounterStyle::parse(style)?,
+                    )),
+                    ("counters", [name, separator]) => Some(ContentItem::Counters(
+                        ident(name)?.clone(),
+                        string(separator)?.clone(),
+                        CounterStyle::Decimal,
+                    )),
+                    ("counters", [name, separator, style]) => Some(ContentItem::Counters(
+                        ident(name)?.clone(),
+                        string(separator)?.clone(),
+                        CounterStyle::parse(style)?,
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+fn ident(value: &ComponentValue) -> Option<&String> {
+    match value {
+        ComponentValue::PerservedToken(Token::Ident(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn string(value: &ComponentValue) -> Option<&String> {
+    match value {
+        ComponentValue::PerservedToken(Token::Str(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Comma separated arguments of a function, where each argument is a
+/// single component value. Returns `None` if an argument isn't a single
+/// value, e.g. if the arguments aren't separated by commas.
+fn function_arguments(values: &[ComponentValue]) -> Option<Vec<ComponentValue>> {
+    values
+        .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+        .map(|argument| {
+            let argument = without_whitespaces(argument);
+            match argument.as_slice() {
+                [value] => Some((*value).clone()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+impl CounterStyle {
+    fn parse(value: &ComponentValue) -> Option<Self> {
+        match ident(value)?.to_lowercase().as_str() {
+            "decimal" => Some(CounterStyle::Decimal),
+            "lower-alpha" | "lower-latin" => Some(CounterStyle::LowerAlpha),
+            "upper-alpha" | "upper-latin" => Some(CounterStyle::UpperAlpha),
+            "lower-roman" => Some(CounterStyle::LowerRoman),
+            "upper-roman" => Some(CounterStyle::UpperRoman),
+            "none" => Some(CounterStyle::None),
+            _ => None,
+        }
+    }
+
+    /// Representation of a counter value in this style. Values that can't
+    /// be represented fall back to `decimal`.
+    pub fn format(&self, value: i32) -> String {
+        match self {
+            CounterStyle::Decimal => value.to_string(),
+            CounterStyle::LowerAlpha if value > 0 => alphabetic(value),
+            CounterStyle::UpperAlpha if value > 0 => alphabetic(value).to_uppercase(),
+            CounterStyle::LowerRoman if value > 0 && value < 4000 => roman(value),
+            CounterStyle::UpperRoman if value > 0 && value < 4000 => roman(value).to_uppercase(),
+            CounterStyle::None => String::new(),
+            _ => value.to_string(),
+        }
+    }
+}
+
+fn alphabetic(mut value: i32) -> String {
+    let mut result = Vec::new();
+    while value > 0 {
+        value -= 1;
+        result.push((b'a' + (value % 26) as u8) as char);
+        value /= 26;
+    }
+    result.iter().rev().collect()
+}
+
+fn roman(mut value: i32) -> String {
+    const NUMERALS: [(i32, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut result = String::new();
+    for (number, numeral) in NUMERALS {
+        while value >= number {
+            result.push_str(numeral);
+            value -= number;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn parse(css: &str) -> Option<Content> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        Content::parse(&parser.parse_a_list_of_component_values())
+    }
+
+    #[test]
+    fn parse_counters() {
+        assert_eq!(
+            parse("counter(a) counter(b , upper-roman)"),
+            Some(Content::Items(vec![
+                ContentItem::Counter("a".to_string(), CounterStyle::Decimal),
+                ContentItem::Counter("b".to_string(), CounterStyle::UpperRoman),
+            ]))
+        );
+        assert_eq!(
+            parse("counters(a, \".\", lower-alpha)"),
+            Some(Content::Items(vec![ContentItem::Counters(
+                "a".to_string(),
+                ".".to_string(),
+                CounterStyle::LowerAlpha
+            )]))
+        );
+    }
+
+    #[test]
+    fn reject_arguments_without_commas() {
+        assert_eq!(parse("counter(a decimal)"), None);
+        assert_eq!(parse("counters(a \".\")"), None);
+        assert_eq!(parse("counters(a, \".\" decimal)"), None);
+        assert_eq!(parse("counter(a,)"), None);
+        assert_eq!(parse("counter(, a)"), None);
+        assert_eq!(parse("attr(a b)"), None);
+    }
+}
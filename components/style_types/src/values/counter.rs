@@ -0,0 +1,71 @@
+use super::without_whitespaces;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// Value of `counter-reset`, a list of counters with their initial values
+/// https://www.w3.org/TR/css-lists-3/#counter-reset
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CounterReset(pub Vec<(String, i32)>);
+
+/// Value of `counter-increment`, a list of counters with their increments
+/// https://www.w3.org/TR/css-lists-3/#increment-counter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CounterIncrement(pub Vec<(String, i32)>);
+
+impl CounterReset {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        parse_counters(values, 0).map(CounterReset)
+    }
+
+    pub fn none() -> Self {
+        CounterReset(Vec::new())
+    }
+}
+
+impl CounterIncrement {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        parse_counters(values, 1).map(CounterIncrement)
+    }
+
+    pub fn none() -> Self {
+        CounterIncrement(Vec::new())
+    }
+}
+
+/// Parse `none | [ <counter-name> <integer>? ]+`
+fn parse_counters(values: &[ComponentValue], default_value: i32) -> Option<Vec<(String, i32)>> {
+    let values = without_whitespaces(values);
+
+    if let [ComponentValue::PerservedToken(Token::Ident(value))] = values.as_slice() {
+        if value.eq_ignore_ascii_case("none") {
+            return Some(Vec::new());
+        }
+    }
+
+    let mut counters: Vec<(String, Option<i32>)> = Vec::new();
+    for value in values {
+        match value {
+            ComponentValue::PerservedToken(Token::Ident(name)) => {
+                counters.push((name.clone(), None));
+            }
+            ComponentValue::PerservedToken(Token::Number { value, .. }) if value.fract() == 0. => {
+                // an integer must follow a counter name
+                match counters.last_mut() {
+                    Some((_, counter_value @ None)) => *counter_value = Some(*value as i32),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    if counters.is_empty() {
+        return None;
+    }
+    Some(
+        counters
+            .into_iter()
+            .map(|(name, value)| (name, value.unwrap_or(default_value)))
+            .collect(),
+    )
+}
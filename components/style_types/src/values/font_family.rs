@@ -0,0 +1,113 @@
+use super::without_whitespaces;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// Value of the `font-family` property: a prioritized list of families
+/// https://www.w3.org/TR/css-fonts-4/#font-family-prop
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontFamily(pub Vec<FamilyName>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FamilyName {
+    Named(String),
+    Generic(GenericFamily),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GenericFamily {
+    Serif,
+    SansSerif,
+    Monospace,
+    Cursive,
+    Fantasy,
+    SystemUi,
+}
+
+impl FontFamily {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        values
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(FamilyName::parse)
+            .collect::<Option<Vec<FamilyName>>>()
+            .map(FontFamily)
+    }
+
+    /// Names of the families, generic families use their keyword
+    pub fn names(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|family| match family {
+                FamilyName::Named(name) => name.clone(),
+                FamilyName::Generic(generic) => generic.name().to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Default for FontFamily {
+    fn default() -> Self {
+        FontFamily(vec![FamilyName::Generic(GenericFamily::SansSerif)])
+    }
+}
+
+impl FamilyName {
+    /// Parse a quoted family name, a generic family or a sequence of
+    /// identifiers
+    fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let values = without_whitespaces(values);
+
+        match values.as_slice() {
+            [ComponentValue::PerservedToken(Token::Str(name))] => {
+                Some(FamilyName::Named(name.clone()))
+            }
+            [ComponentValue::PerservedToken(Token::Ident(name))] => {
+                match GenericFamily::parse(name) {
+                    Some(generic) => Some(FamilyName::Generic(generic)),
+                    // CSS-wide keywords can't be used as family names
+                    None if is_reserved_keyword(name) => None,
+                    None => Some(FamilyName::Named(name.clone())),
+                }
+            }
+            [] => None,
+            values => values
+                .iter()
+                .map(|value| match value {
+                    ComponentValue::PerservedToken(Token::Ident(name)) => Some(name.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<String>>>()
+                .map(|names| FamilyName::Named(names.join(" "))),
+        }
+    }
+}
+
+fn is_reserved_keyword(name: &str) -> bool {
+    ["inherit", "initial", "unset", "default"]
+        .iter()
+        .any(|keyword| name.eq_ignore_ascii_case(keyword))
+}
+
+impl GenericFamily {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "serif" => Some(GenericFamily::Serif),
+            "sans-serif" => Some(GenericFamily::SansSerif),
+            "monospace" => Some(GenericFamily::Monospace),
+            "cursive" => Some(GenericFamily::Cursive),
+            "fantasy" => Some(GenericFamily::Fantasy),
+            "system-ui" => Some(GenericFamily::SystemUi),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenericFamily::Serif => "serif",
+            GenericFamily::SansSerif => "sans-serif",
+            GenericFamily::Monospace => "monospace",
+            GenericFamily::Cursive => "cursive",
+            GenericFamily::Fantasy => "fantasy",
+            GenericFamily::SystemUi => "system-ui",
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use css::{parser::structs::ComponentValue, tokenizer::token::Token};
+use shared::font::stretch_keyword;
+
+use super::number::Number;
+
+/// Width of a font in percentage of the normal width
+/// https://www.w3.org/TR/css-fonts-4/#font-stretch-prop
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct FontStretch(pub Number);
+
+impl Eq for FontStretch {}
+
+impl FontStretch {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => {
+                stretch_keyword(value).map(|value| FontStretch(Number(value)))
+            }
+            Some(ComponentValue::PerservedToken(Token::Percentage(value))) if *value >= 0. => {
+                Some(FontStretch(Number(*value)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.0 .0
+    }
+}
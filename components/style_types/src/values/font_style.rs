@@ -0,0 +1,28 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl FontStyle {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => {
+                if value.eq_ignore_ascii_case("normal") {
+                    Some(FontStyle::Normal)
+                } else if value.eq_ignore_ascii_case("italic") {
+                    Some(FontStyle::Italic)
+                } else if value.eq_ignore_ascii_case("oblique") {
+                    Some(FontStyle::Oblique)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// https://www.w3.org/TR/css-text-3/#hyphens-property
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum Hyphens {
+    None,
+    /// Words are only hyphenated at soft hyphens
+    Manual,
+    /// Without hyphenation dictionaries, it's the same as `manual`
+    Auto,
+}
+
+impl Hyphens {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("none") => Some(Hyphens::None),
+                v if v.eq_ignore_ascii_case("manual") => Some(Hyphens::Manual),
+                v if v.eq_ignore_ascii_case("auto") => Some(Hyphens::Auto),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
@@ -12,12 +12,35 @@ impl Eq for Length {}
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum LengthUnit {
+    // font-relative lengths
     Rem,
     Em,
     Ex,
+    Ch,
+    Lh,
+    // viewport-percentage lengths. There are no dynamic toolbars, so the
+    // small, large & dynamic viewports are the same as the viewport.
+    Vw,
+    Vh,
+    Vmin,
+    Vmax,
+    Svw,
+    Svh,
+    Svmin,
+    Svmax,
+    Lvw,
+    Lvh,
+    Lvmin,
+    Lvmax,
+    Dvw,
+    Dvh,
+    Dvmin,
+    Dvmax,
+    // absolute lengths
     In,
     Cm,
     Mm,
+    Q,
     Pt,
     Pc,
     Px,
@@ -25,19 +48,75 @@ pub enum LengthUnit {
 
 impl LengthUnit {
     pub fn from_str(unit: &str) -> Option<Self> {
-        match unit {
+        match unit.to_lowercase().as_str() {
             "rem" => Some(LengthUnit::Rem),
             "em" => Some(LengthUnit::Em),
             "ex" => Some(LengthUnit::Ex),
+            "ch" => Some(LengthUnit::Ch),
+            "lh" => Some(LengthUnit::Lh),
+            "vw" => Some(LengthUnit::Vw),
+            "vh" => Some(LengthUnit::Vh),
+            "vmin" => Some(LengthUnit::Vmin),
+            "vmax" => Some(LengthUnit::Vmax),
+            "svw" => Some(LengthUnit::Svw),
+            "svh" => Some(LengthUnit::Svh),
+            "svmin" => Some(LengthUnit::Svmin),
+            "svmax" => Some(LengthUnit::Svmax),
+            "lvw" => Some(LengthUnit::Lvw),
+            "lvh" => Some(LengthUnit::Lvh),
+            "lvmin" => Some(LengthUnit::Lvmin),
+            "lvmax" => Some(LengthUnit::Lvmax),
+            "dvw" => Some(LengthUnit::Dvw),
+            "dvh" => Some(LengthUnit::Dvh),
+            "dvmin" => Some(LengthUnit::Dvmin),
+            "dvmax" => Some(LengthUnit::Dvmax),
             "in" => Some(LengthUnit::In),
             "cm" => Some(LengthUnit::Cm),
             "mm" => Some(LengthUnit::Mm),
+            "q" => Some(LengthUnit::Q),
             "pt" => Some(LengthUnit::Pt),
             "pc" => Some(LengthUnit::Pc),
             "px" => Some(LengthUnit::Px),
             _ => None,
         }
     }
+
+    /// Number of pixels in one unit of an absolute length
+    /// https://www.w3.org/TR/css-values-4/#absolute-lengths
+    pub fn px_per_unit(&self) -> Option<f32> {
+        match self {
+            LengthUnit::Px => Some(1.),
+            LengthUnit::In => Some(96.),
+            LengthUnit::Cm => Some(96. / 2.54),
+            LengthUnit::Mm => Some(96. / 25.4),
+            LengthUnit::Q => Some(96. / 101.6),
+            LengthUnit::Pt => Some(96. / 72.),
+            LengthUnit::Pc => Some(16.),
+            _ => None,
+        }
+    }
+
+    pub fn is_viewport_relative(&self) -> bool {
+        matches!(
+            self,
+            LengthUnit::Vw
+                | LengthUnit::Vh
+                | LengthUnit::Vmin
+                | LengthUnit::Vmax
+                | LengthUnit::Svw
+                | LengthUnit::Svh
+                | LengthUnit::Svmin
+                | LengthUnit::Svmax
+                | LengthUnit::Lvw
+                | LengthUnit::Lvh
+                | LengthUnit::Lvmin
+                | LengthUnit::Lvmax
+                | LengthUnit::Dvw
+                | LengthUnit::Dvh
+                | LengthUnit::Dvmin
+                | LengthUnit::Dvmax
+        )
+    }
 }
 
 impl Length {
@@ -59,18 +138,12 @@ impl Length {
         }
     }
 
+    /// Convert an absolute length to pixels. Relative lengths are resolved
+    /// to pixels at computed-value time.
     pub fn to_px(&self) -> f32 {
-        match self.unit {
-            LengthUnit::Px => *self.value,
-            _ => unreachable!("Calling to_px on non-px length unit"),
-        }
-    }
-
-    pub fn resolve(&self, font_size: f32) -> f32 {
-        match self.unit {
-            LengthUnit::Px => *self.value,
-            LengthUnit::Em | LengthUnit::Rem => *self.value * font_size,
-            _ => unimplemented!("Calling resolve on unsupported length unit"),
+        match self.unit.px_per_unit() {
+            Some(px_per_unit) => *self.value * px_per_unit,
+            None => unreachable!("Calling to_px on relative length unit: {:?}", self.unit),
         }
     }
 }
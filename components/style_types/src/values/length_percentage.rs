@@ -1,5 +1,6 @@
 use css::parser::structs::ComponentValue;
 
+use super::calc::Calc;
 use super::length::Length;
 use super::percentage::Percentage;
 
@@ -7,6 +8,7 @@ use super::percentage::Percentage;
 pub enum LengthPercentage {
     Length(Length),
     Percentage(Percentage),
+    Calc(Calc),
 }
 
 impl LengthPercentage {
@@ -14,6 +16,7 @@ impl LengthPercentage {
         match self {
             LengthPercentage::Length(l) => *l.value == 0.0,
             LengthPercentage::Percentage(p) => *p.0 == 0.0,
+            LengthPercentage::Calc(_) => false,
         }
     }
 
@@ -21,6 +24,7 @@ impl LengthPercentage {
         match self {
             LengthPercentage::Length(l) => l.to_px(),
             LengthPercentage::Percentage(p) => p.to_px(relative_to),
+            LengthPercentage::Calc(c) => c.to_px(relative_to),
         }
     }
 
@@ -29,8 +33,30 @@ impl LengthPercentage {
             Some(l) => Some(Self::Length(l)),
             None => match Percentage::parse(values) {
                 Some(p) => Some(Self::Percentage(p)),
-                None => None,
+                None => Calc::parse(values).map(Self::Calc),
             },
         }
     }
 }
+
+/// `<length-percentage> | auto`, e.g. for `width` & `margin-left`
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum LengthPercentageOrAuto {
+    LengthPercentage(LengthPercentage),
+    Auto,
+}
+
+impl LengthPercentageOrAuto {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, LengthPercentageOrAuto::Auto)
+    }
+
+    /// `auto` resolves to 0, it has to be checked for before if it has a
+    /// different meaning
+    pub fn to_px(&self, relative_to: f32) -> f32 {
+        match self {
+            LengthPercentageOrAuto::LengthPercentage(value) => value.to_px(relative_to),
+            LengthPercentageOrAuto::Auto => 0.,
+        }
+    }
+}
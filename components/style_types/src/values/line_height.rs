@@ -0,0 +1,49 @@
+use css::{parser::structs::ComponentValue, tokenizer::token::Token};
+
+use super::{length::Length, number::Number, percentage::Percentage};
+
+/// https://www.w3.org/TR/CSS2/visudet.html#propdef-line-height
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum LineHeight {
+    Normal,
+    /// Multiplied by the font size of the element. The number is inherited,
+    /// not the height it computes to.
+    Number(Number),
+    Length(Length),
+    /// Computed to a length from the font size of the element
+    Percentage(Percentage),
+}
+
+impl Eq for LineHeight {}
+
+impl LineHeight {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value)))
+                if value.eq_ignore_ascii_case("normal") =>
+            {
+                Some(LineHeight::Normal)
+            }
+            Some(ComponentValue::PerservedToken(Token::Number { value, .. })) if *value >= 0. => {
+                Some(LineHeight::Number(Number(*value)))
+            }
+            Some(ComponentValue::PerservedToken(Token::Percentage(value))) if *value >= 0. => {
+                Some(LineHeight::Percentage(Percentage(Number(*value))))
+            }
+            _ => Length::parse(values)
+                .filter(|length| *length.value >= 0.)
+                .map(LineHeight::Length),
+        }
+    }
+
+    /// The height of a line in pixels, `None` for `normal` which depends on
+    /// the metrics of the font
+    pub fn to_px(&self, font_size: f32) -> Option<f32> {
+        match self {
+            LineHeight::Normal => None,
+            LineHeight::Number(number) => Some(**number * font_size),
+            LineHeight::Length(length) => Some(length.to_px()),
+            LineHeight::Percentage(percentage) => Some(percentage.to_px(font_size)),
+        }
+    }
+}
@@ -1,18 +1,36 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
 pub mod border_radius;
 pub mod border_style;
 pub mod border_width;
+pub mod calc;
 pub mod color;
+pub mod content;
+pub mod counter;
 pub mod direction;
 pub mod display;
 pub mod float;
+pub mod font_family;
+pub mod font_stretch;
+pub mod font_style;
 pub mod font_weight;
+pub mod hyphens;
 pub mod length;
 pub mod length_percentage;
+pub mod line_height;
 pub mod number;
 pub mod overflow;
+pub mod overflow_wrap;
 pub mod percentage;
 pub mod position;
+pub mod spacing;
+pub mod tab_size;
 pub mod text_align;
+pub mod text_decoration;
+pub mod text_transform;
+pub mod white_space;
+pub mod word_break;
 
 // Let this pub because in the future we may want to use this in other places.
 // Just maybe....
@@ -20,15 +38,39 @@ pub mod prelude {
     pub use super::border_radius::BorderRadius;
     pub use super::border_style::BorderStyle;
     pub use super::border_width::BorderWidth;
+    pub use super::calc::Calc;
     pub use super::color::Color;
+    pub use super::content::{Content, ContentItem, CounterStyle};
+    pub use super::counter::{CounterIncrement, CounterReset};
     pub use super::direction::Direction;
     pub use super::display::Display;
     pub use super::float::Float;
+    pub use super::font_family::{FamilyName, FontFamily, GenericFamily};
+    pub use super::font_stretch::FontStretch;
+    pub use super::font_style::FontStyle;
     pub use super::font_weight::FontWeight;
+    pub use super::hyphens::Hyphens;
     pub use super::length::Length;
-    pub use super::length_percentage::LengthPercentage;
+    pub use super::length_percentage::{LengthPercentage, LengthPercentageOrAuto};
+    pub use super::line_height::LineHeight;
     pub use super::overflow::Overflow;
+    pub use super::overflow_wrap::OverflowWrap;
     pub use super::percentage::Percentage;
     pub use super::position::Position;
+    pub use super::spacing::Spacing;
+    pub use super::tab_size::TabSize;
     pub use super::text_align::TextAlign;
+    pub use super::text_decoration::{
+        TextDecorationLine, TextDecorationStyle, TextDecorationThickness,
+    };
+    pub use super::text_transform::TextTransform;
+    pub use super::white_space::WhiteSpace;
+    pub use super::word_break::WordBreak;
+}
+
+pub(crate) fn without_whitespaces(values: &[ComponentValue]) -> Vec<&ComponentValue> {
+    values
+        .iter()
+        .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+        .collect()
 }
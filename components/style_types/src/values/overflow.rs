@@ -7,6 +7,7 @@ pub enum Overflow {
     Hidden,
     Clip,
     Scroll,
+    Auto,
 }
 
 impl Eq for Overflow {}
@@ -19,6 +20,7 @@ impl Overflow {
                 v if v.eq_ignore_ascii_case("hidden") => Some(Overflow::Hidden),
                 v if v.eq_ignore_ascii_case("clip") => Some(Overflow::Clip),
                 v if v.eq_ignore_ascii_case("scroll") => Some(Overflow::Scroll),
+                v if v.eq_ignore_ascii_case("auto") => Some(Overflow::Auto),
                 _ => None,
             },
             _ => None,
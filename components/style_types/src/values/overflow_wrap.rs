@@ -0,0 +1,24 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// https://www.w3.org/TR/css-text-3/#overflow-wrap-property
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum OverflowWrap {
+    Normal,
+    BreakWord,
+    Anywhere,
+}
+
+impl OverflowWrap {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("normal") => Some(OverflowWrap::Normal),
+                v if v.eq_ignore_ascii_case("break-word") => Some(OverflowWrap::BreakWord),
+                v if v.eq_ignore_ascii_case("anywhere") => Some(OverflowWrap::Anywhere),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+use super::length::Length;
+
+/// Extra space between letters or words, for `letter-spacing` &
+/// `word-spacing`
+/// https://www.w3.org/TR/css-text-3/#spacing
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum Spacing {
+    Normal,
+    Length(Length),
+}
+
+impl Eq for Spacing {}
+
+impl Spacing {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value)))
+                if value.eq_ignore_ascii_case("normal") =>
+            {
+                Some(Spacing::Normal)
+            }
+            _ => Length::parse(values).map(Spacing::Length),
+        }
+    }
+
+    pub fn to_px(&self) -> f32 {
+        match self {
+            Spacing::Normal => 0.,
+            Spacing::Length(length) => length.to_px(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+use super::{length::Length, number::Number};
+
+/// The distance between tab stops
+/// https://www.w3.org/TR/css-text-3/#tab-size-property
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum TabSize {
+    /// Multiplied by the advance of a space
+    Number(Number),
+    Length(Length),
+}
+
+impl Eq for TabSize {}
+
+impl TabSize {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Number { value, .. })) if *value >= 0. => {
+                Some(TabSize::Number(Number(*value)))
+            }
+            _ => Length::parse(values)
+                .filter(|length| *length.value >= 0.)
+                .map(TabSize::Length),
+        }
+    }
+
+    /// The distance between tab stops in pixels
+    pub fn to_px(&self, space_advance: f32) -> f32 {
+        match self {
+            TabSize::Number(number) => **number * space_advance,
+            TabSize::Length(length) => length.to_px(),
+        }
+    }
+}
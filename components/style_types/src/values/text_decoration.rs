@@ -0,0 +1,128 @@
+use super::without_whitespaces;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+use super::{length::Length, number::Number, percentage::Percentage};
+
+/// The lines that decorate the text of an element
+/// https://www.w3.org/TR/css-text-decor-3/#text-decoration-line-property
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TextDecorationLine {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+/// https://www.w3.org/TR/css-text-decor-3/#text-decoration-style-property
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextDecorationStyle {
+    Solid,
+    Double,
+    Dotted,
+    Dashed,
+    Wavy,
+}
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-decoration-width-property
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum TextDecorationThickness {
+    Auto,
+    FromFont,
+    Length(Length),
+    /// Computed to a length from the font size of the element
+    Percentage(Percentage),
+}
+
+impl Eq for TextDecorationThickness {}
+
+impl TextDecorationLine {
+    /// `none` or any of `underline`, `overline` & `line-through`, each at
+    /// most once. `blink` is valid but never rendered.
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let mut line = TextDecorationLine::default();
+        let mut keywords = without_whitespaces(values).into_iter().peekable();
+        keywords.peek()?;
+
+        let mut blink = false;
+        let mut is_none = false;
+        for value in keywords {
+            let flag = match value {
+                ComponentValue::PerservedToken(Token::Ident(value)) => match value {
+                    v if v.eq_ignore_ascii_case("underline") => &mut line.underline,
+                    v if v.eq_ignore_ascii_case("overline") => &mut line.overline,
+                    v if v.eq_ignore_ascii_case("line-through") => &mut line.line_through,
+                    v if v.eq_ignore_ascii_case("blink") => &mut blink,
+                    v if v.eq_ignore_ascii_case("none") => &mut is_none,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            if *flag {
+                return None;
+            }
+            *flag = true;
+        }
+
+        if is_none && (blink || line != TextDecorationLine::default()) {
+            return None;
+        }
+        Some(line)
+    }
+
+    pub fn is_none(&self) -> bool {
+        !(self.underline || self.overline || self.line_through)
+    }
+}
+
+impl TextDecorationStyle {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("solid") => Some(TextDecorationStyle::Solid),
+                v if v.eq_ignore_ascii_case("double") => Some(TextDecorationStyle::Double),
+                v if v.eq_ignore_ascii_case("dotted") => Some(TextDecorationStyle::Dotted),
+                v if v.eq_ignore_ascii_case("dashed") => Some(TextDecorationStyle::Dashed),
+                v if v.eq_ignore_ascii_case("wavy") => Some(TextDecorationStyle::Wavy),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<&TextDecorationStyle> for shared::primitive::LineStyle {
+    fn from(style: &TextDecorationStyle) -> Self {
+        match style {
+            TextDecorationStyle::Solid => shared::primitive::LineStyle::Solid,
+            TextDecorationStyle::Double => shared::primitive::LineStyle::Double,
+            TextDecorationStyle::Dotted => shared::primitive::LineStyle::Dotted,
+            TextDecorationStyle::Dashed => shared::primitive::LineStyle::Dashed,
+            TextDecorationStyle::Wavy => shared::primitive::LineStyle::Wavy,
+        }
+    }
+}
+
+impl TextDecorationThickness {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("auto") => Some(TextDecorationThickness::Auto),
+                v if v.eq_ignore_ascii_case("from-font") => Some(TextDecorationThickness::FromFont),
+                _ => None,
+            },
+            Some(ComponentValue::PerservedToken(Token::Percentage(value))) => Some(
+                TextDecorationThickness::Percentage(Percentage(Number(*value))),
+            ),
+            _ => Length::parse(values).map(TextDecorationThickness::Length),
+        }
+    }
+
+    /// The thickness of the lines in pixels, `None` if it's up to the font
+    pub fn to_px(&self, font_size: f32) -> Option<f32> {
+        match self {
+            TextDecorationThickness::Auto | TextDecorationThickness::FromFont => None,
+            TextDecorationThickness::Length(length) => Some(length.to_px()),
+            TextDecorationThickness::Percentage(percentage) => Some(percentage.to_px(font_size)),
+        }
+    }
+}
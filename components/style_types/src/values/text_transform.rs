@@ -0,0 +1,52 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// https://www.w3.org/TR/css-text-3/#text-transform-property
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum TextTransform {
+    None,
+    Capitalize,
+    Uppercase,
+    Lowercase,
+}
+
+impl TextTransform {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("none") => Some(TextTransform::None),
+                v if v.eq_ignore_ascii_case("capitalize") => Some(TextTransform::Capitalize),
+                v if v.eq_ignore_ascii_case("uppercase") => Some(TextTransform::Uppercase),
+                v if v.eq_ignore_ascii_case("lowercase") => Some(TextTransform::Lowercase),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Transform the text. `capitalize` uppercases the first letter of
+    /// every word, words being separated by whitespaces. As words can span
+    /// several text runs, e.g. `a<b>bc</b>`, `word_start` is whether the
+    /// text starts a word & is updated for the text that follows.
+    pub fn apply(&self, text: &str, word_start: &mut bool) -> String {
+        let mut result = String::with_capacity(text.len());
+        for c in text.chars() {
+            if *word_start && c.is_alphanumeric() {
+                match self {
+                    TextTransform::Capitalize => result.extend(c.to_uppercase()),
+                    _ => result.push(c),
+                }
+                *word_start = false;
+            } else {
+                *word_start = *word_start || c.is_whitespace();
+                result.push(c);
+            }
+        }
+
+        match self {
+            TextTransform::None | TextTransform::Capitalize => result,
+            TextTransform::Uppercase => result.to_uppercase(),
+            TextTransform::Lowercase => result.to_lowercase(),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// https://www.w3.org/TR/css-text-3/#white-space-property
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum WhiteSpace {
+    Normal,
+    Pre,
+    Nowrap,
+    PreWrap,
+    BreakSpaces,
+    PreLine,
+}
+
+impl WhiteSpace {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("normal") => Some(WhiteSpace::Normal),
+                v if v.eq_ignore_ascii_case("pre") => Some(WhiteSpace::Pre),
+                v if v.eq_ignore_ascii_case("nowrap") => Some(WhiteSpace::Nowrap),
+                v if v.eq_ignore_ascii_case("pre-wrap") => Some(WhiteSpace::PreWrap),
+                v if v.eq_ignore_ascii_case("break-spaces") => Some(WhiteSpace::BreakSpaces),
+                v if v.eq_ignore_ascii_case("pre-line") => Some(WhiteSpace::PreLine),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether sequences of spaces & tabs collapse into a single space
+    pub fn collapses_spaces(&self) -> bool {
+        matches!(
+            self,
+            WhiteSpace::Normal | WhiteSpace::Nowrap | WhiteSpace::PreLine
+        )
+    }
+
+    /// Whether segment breaks are kept as forced line breaks
+    pub fn preserves_segment_breaks(&self) -> bool {
+        !matches!(self, WhiteSpace::Normal | WhiteSpace::Nowrap)
+    }
+
+    /// Whether lines can wrap at soft wrap opportunities
+    pub fn wraps(&self) -> bool {
+        !matches!(self, WhiteSpace::Pre | WhiteSpace::Nowrap)
+    }
+}
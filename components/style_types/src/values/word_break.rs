@@ -0,0 +1,27 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// https://www.w3.org/TR/css-text-3/#word-break-property
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum WordBreak {
+    Normal,
+    KeepAll,
+    BreakAll,
+    /// Deprecated, it's `normal` with `overflow-wrap: anywhere`
+    BreakWord,
+}
+
+impl WordBreak {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("normal") => Some(WordBreak::Normal),
+                v if v.eq_ignore_ascii_case("keep-all") => Some(WordBreak::KeepAll),
+                v if v.eq_ignore_ascii_case("break-all") => Some(WordBreak::BreakAll),
+                v if v.eq_ignore_ascii_case("break-word") => Some(WordBreak::BreakWord),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
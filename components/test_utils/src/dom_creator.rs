@@ -39,8 +39,7 @@ pub fn element(selector: &str, doc: NodePtr, children: Vec<NodePtr>) -> NodePtr
     for part in selector_parts {
         match part.selector_type() {
             SimpleSelectorType::ID => {
-                node.as_element()
-                    .set_attribute("id", &part.value().clone().unwrap());
+                node.set_attribute("id", &part.value().clone().unwrap());
             }
             SimpleSelectorType::Class => {
                 classes.push(part.value().clone().unwrap());
@@ -50,8 +49,7 @@ pub fn element(selector: &str, doc: NodePtr, children: Vec<NodePtr>) -> NodePtr
     }
 
     if classes.len() > 0 {
-        node.as_element()
-            .set_attribute("class", &classes.join(" ").to_string());
+        node.set_attribute("class", &classes.join(" ").to_string());
     }
 
     for child in children {
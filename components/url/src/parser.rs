@@ -716,4 +716,15 @@ mod tests {
 
         assert_eq!(url.as_str(), "http://google.com/index.html");
     }
+
+    #[test]
+    fn produce_query_before_fragment() {
+        let input_url = "https://a/b?c=d#e";
+
+        let url = URLParser::parse(input_url, None).unwrap();
+
+        assert_eq!(url.query, Some("c=d".to_string()));
+        assert_eq!(url.fragment, Some("e".to_string()));
+        assert_eq!(url.as_str(), input_url);
+    }
 }
@@ -69,12 +69,12 @@ impl Url {
             format!("/{}", self.path)
         };
         result.push_str(&path);
-        if let Some(fragment) = &self.fragment {
-            result.push_str(&format!("#{}", fragment));
-        }
         if let Some(query) = &self.query {
             result.push_str(&format!("?{}", query));
         }
+        if let Some(fragment) = &self.fragment {
+            result.push_str(&format!("#{}", fragment));
+        }
         result
     }
 }
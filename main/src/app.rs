@@ -33,6 +33,8 @@ pub enum Message {
     WindowResized(u32, u32),
     MouseScrolled(f32, f32),
     MouseMoved(f32, f32),
+    MousePressed,
+    MouseReleased,
     KeyPressed(KeyCode, Modifiers),
     TitleChanged(String),
     ReloadTriggered,
@@ -90,6 +92,12 @@ impl Application for Moon {
             Message::MouseMoved(x, y) => {
                 self.browser.handle_mouse_move(Point::new(x, y));
             }
+            Message::MousePressed => {
+                self.browser.handle_mouse_down();
+            }
+            Message::MouseReleased => {
+                self.browser.handle_mouse_up();
+            }
             Message::KeyPressed(KeyCode::F5, _) | Message::ReloadTriggered => {
                 self.browser.reload();
             }
@@ -123,6 +131,7 @@ impl Application for Moon {
                 TabEvent::FrameReceived(data) => Message::ContentDataChanged(data),
                 TabEvent::TitleChanged(new_title) => Message::TitleChanged(new_title),
                 TabEvent::URLChanged(new_url) => Message::URLInputContentChanged(new_url.as_str()),
+                TabEvent::Crashed => Message::TitleChanged(String::from("Page crashed")),
                 _ => Message::NoOp,
             },
         );
@@ -138,6 +147,12 @@ impl Application for Moon {
             Event::Mouse(iced::mouse::Event::CursorMoved { position }) => {
                 Message::MouseMoved(position.x, position.y)
             }
+            Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left)) => {
+                Message::MousePressed
+            }
+            Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) => {
+                Message::MouseReleased
+            }
             Event::Keyboard(iced::keyboard::Event::KeyPressed {
                 key_code,
                 modifiers,
@@ -1,16 +1,57 @@
+use std::io::ErrorKind;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use flume::{Receiver, Sender};
+use loader::resource_loop::{
+    error::LoadError,
+    request::{Bytes, FetchListener, LoadRequest},
+};
 use shared::primitive::Size;
 
-use render::{InputEvent, OutputEvent, RenderEngine};
+use render::{
+    ipc::{BrowserMessage, IpcChannel, RendererMessage, SharedBitmap},
+    style_settings::StyleSettings,
+    InputEvent, OutputEvent, RenderEngine,
+};
+
+/// How long the browser waits for a render process to connect to it
+const RENDER_PROCESS_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct RenderClient {
     event_sender: Sender<InputEvent>,
     event_receiver: Receiver<OutputEvent>,
     ready_receiver: Receiver<()>,
+    render_process: Option<Child>,
 }
 
 impl RenderClient {
-    pub fn new() -> Self {
+    /// Start a render engine, with the style settings of the browser
+    pub fn new(resource_loop_tx: Sender<LoadRequest>) -> Self {
+        let client = RenderClient::new_render_engine(resource_loop_tx);
+        // a render engine that already exited is reported while waiting
+        // for it to be ready
+        client.set_style_settings(StyleSettings::from_env());
+        client
+    }
+
+    fn new_render_engine(resource_loop_tx: Sender<LoadRequest>) -> Self {
+        if std::env::var("MULTI_PROCESS").is_ok() {
+            match RenderClient::new_out_of_process(resource_loop_tx.clone()) {
+                Ok(client) => return client,
+                Err(e) => log::error!(
+                    "Unable to start render process, fallback to in-process rendering: {}",
+                    e
+                ),
+            }
+        }
+        RenderClient::new_in_process(resource_loop_tx)
+    }
+
+    fn new_in_process(resource_loop_tx: Sender<LoadRequest>) -> Self {
         let (render_input_tx, render_input_rx) = flume::unbounded();
         let (render_output_tx, render_output_rx) = flume::unbounded();
 
@@ -20,7 +61,7 @@ impl RenderClient {
         let _ = std::thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
-                let render_engine = RenderEngine::new(Size::new(1., 1.)).await;
+                let render_engine = RenderEngine::new(Size::new(1., 1.), resource_loop_tx).await;
 
                 ready_tx.send(()).unwrap();
 
@@ -35,46 +76,239 @@ impl RenderClient {
             event_sender: render_input_tx,
             event_receiver: render_output_rx,
             ready_receiver: ready_rx,
+            render_process: None,
         }
     }
 
-    pub fn wait_till_ready(&self) {
-        self.ready_receiver
-            .recv()
-            .expect("Error while waiting for render client to be ready")
+    /// Run the render engine in a child `moon` process & talk to it through a Unix socket
+    fn new_out_of_process(resource_loop_tx: Sender<LoadRequest>) -> anyhow::Result<Self> {
+        static NEXT_RENDER_PROCESS_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let socket_path = std::env::temp_dir().join(format!(
+            "moon-{}-renderer-{}.sock",
+            std::process::id(),
+            NEXT_RENDER_PROCESS_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path)?;
+
+        let mut render_process = Command::new(std::env::current_exe()?)
+            .arg("renderer")
+            .arg("--socket")
+            .arg(&socket_path)
+            .spawn()?;
+        let render_process_pid = render_process.id();
+
+        let accepted = accept_render_process(&listener, &mut render_process);
+        let _ = std::fs::remove_file(&socket_path);
+        let stream = match accepted {
+            Ok(stream) => stream,
+            Err(e) => {
+                let _ = render_process.kill();
+                let _ = render_process.wait();
+                return Err(e);
+            }
+        };
+
+        let mut receiver = IpcChannel::new(stream);
+        let sender = Arc::new(Mutex::new(receiver.try_clone()?));
+
+        let (render_input_tx, render_input_rx) = flume::unbounded::<InputEvent>();
+        let (render_output_tx, render_output_rx) = flume::unbounded();
+        let (ready_tx, ready_rx) = flume::bounded(1);
+
+        {
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for event in render_input_rx.iter() {
+                    if let Err(e) = sender.lock().unwrap().send(&BrowserMessage::Input(event)) {
+                        log::error!("Unable to send input event to render process: {}", e);
+                        break;
+                    }
+                }
+            });
+        }
+
+        std::thread::spawn(move || loop {
+            let message = match receiver.recv::<RendererMessage>() {
+                Ok(message) => message,
+                Err(e) => {
+                    log::error!("Render process disconnected: {}", e);
+                    break;
+                }
+            };
+
+            let event = match message {
+                RendererMessage::Ready => {
+                    let _ = ready_tx.send(());
+                    continue;
+                }
+                RendererMessage::Fetch { request_id, url } => {
+                    let listener = RemoteFetchListener {
+                        request_id,
+                        sender: sender.clone(),
+                    };
+                    let _ = resource_loop_tx.send(LoadRequest::new(url, Arc::new(listener)));
+                    continue;
+                }
+                RendererMessage::FrameRendered { bitmap_id, len } => {
+                    let frame = SharedBitmap::read(render_process_pid, bitmap_id, len as usize);
+                    let _ = sender
+                        .lock()
+                        .unwrap()
+                        .send(&BrowserMessage::FrameReleased(bitmap_id));
+                    match frame {
+                        Ok(frame) => OutputEvent::FrameRendered(frame),
+                        Err(e) => {
+                            log::error!("Unable to read frame from render process: {}", e);
+                            continue;
+                        }
+                    }
+                }
+                RendererMessage::TitleChanged(title) => OutputEvent::TitleChanged(title),
+                RendererMessage::URLChanged(url) => OutputEvent::URLChanged(url),
+                RendererMessage::LoadingStarted => OutputEvent::LoadingStarted,
+                RendererMessage::LoadingFinished => OutputEvent::LoadingFinished,
+            };
+
+            if render_output_tx.send(event).is_err() {
+                break;
+            }
+        });
+
+        Ok(Self {
+            event_sender: render_input_tx,
+            event_receiver: render_output_rx,
+            ready_receiver: ready_rx,
+            render_process: Some(render_process),
+        })
+    }
+
+    /// Wait for the render engine to start. Return `false` if it exited
+    /// before being ready.
+    pub fn wait_till_ready(&self) -> bool {
+        match self.ready_receiver.recv() {
+            Ok(()) => true,
+            Err(_) => {
+                log::error!("Render engine exited before being ready");
+                false
+            }
+        }
     }
 
     pub fn events(&self) -> Receiver<OutputEvent> {
         self.event_receiver.clone()
     }
 
-    pub fn resize(&self, size: Size) {
-        self.event_sender
-            .send(InputEvent::ViewportResize(size))
-            .expect("Unable to resize");
+    pub fn resize(&self, size: Size) -> bool {
+        self.send(InputEvent::ViewportResize(size), "resize event")
+    }
+
+    pub fn scroll(&self, y: f32) -> bool {
+        self.send(InputEvent::Scroll(y), "scroll event")
+    }
+
+    pub fn load_raw_url(&self, url: String) -> bool {
+        self.send(InputEvent::LoadRawURL(url), "URL to load")
+    }
+
+    pub fn mouse_move(&self, coord: shared::primitive::Point) -> bool {
+        self.send(InputEvent::MouseMove(coord), "mouse move event")
+    }
+
+    pub fn mouse_down(&self) -> bool {
+        self.send(InputEvent::MouseDown, "mouse down event")
+    }
+
+    pub fn mouse_up(&self) -> bool {
+        self.send(InputEvent::MouseUp, "mouse up event")
     }
 
-    pub fn scroll(&self, y: f32) {
-        self.event_sender
-            .send(InputEvent::Scroll(y))
-            .expect("Unable to send scroll event");
+    /// Set the user agent & user stylesheets that are applied to the pages
+    pub fn set_style_settings(&self, style_settings: StyleSettings) -> bool {
+        self.send(
+            InputEvent::SetStyleSettings(style_settings),
+            "style settings",
+        )
     }
 
-    pub fn load_raw_url(&self, url: String) {
-        self.event_sender
-            .send(InputEvent::LoadRawURL(url))
-            .expect("Unable to load URL");
+    pub fn reload(&self) -> bool {
+        self.send(InputEvent::Reload, "reload event")
     }
 
-    pub fn mouse_move(&self, coord: shared::primitive::Point) {
-        self.event_sender
-            .send(InputEvent::MouseMove(coord))
-            .expect("Unable to send mouse move event");
+    /// Send an input event to the render engine. Return `false` if the
+    /// render engine is disconnected, e.g. its process crashed.
+    fn send(&self, event: InputEvent, description: &str) -> bool {
+        if self.event_sender.send(event).is_err() {
+            log::error!("Unable to send {}: render engine disconnected", description);
+            return false;
+        }
+        true
     }
+}
+
+/// Wait for the render process to connect to the listener. Fails if the
+/// process exits or doesn't connect in time, instead of blocking forever.
+fn accept_render_process(
+    listener: &UnixListener,
+    render_process: &mut Child,
+) -> anyhow::Result<UnixStream> {
+    listener.set_nonblocking(true)?;
+    let deadline = Instant::now() + RENDER_PROCESS_CONNECT_TIMEOUT;
+
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                return Ok(stream);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
 
-    pub fn reload(&self) {
-        self.event_sender
-            .send(InputEvent::Reload)
-            .expect("Unable to send mouse move event");
+        if let Some(status) = render_process.try_wait()? {
+            anyhow::bail!("Render process exited before connecting: {}", status);
+        }
+        if Instant::now() >= deadline {
+            anyhow::bail!("Render process didn't connect in time");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+impl Drop for RenderClient {
+    fn drop(&mut self) {
+        if let Some(render_process) = &mut self.render_process {
+            let _ = render_process.kill();
+            let _ = render_process.wait();
+        }
+    }
+}
+
+/// Forward the result of a load request made by a render process back to it
+struct RemoteFetchListener {
+    request_id: u64,
+    sender: Arc<Mutex<IpcChannel>>,
+}
+
+impl FetchListener for RemoteFetchListener {
+    fn on_finished(&self, bytes: Bytes) {
+        let message = BrowserMessage::FetchFinished {
+            request_id: self.request_id,
+            bytes,
+        };
+        if let Err(e) = self.sender.lock().unwrap().send(&message) {
+            log::error!("Unable to send fetched resource to render process: {}", e);
+        }
+    }
+
+    fn on_errored(&self, error: LoadError) {
+        let message = BrowserMessage::FetchErrored {
+            request_id: self.request_id,
+            error,
+        };
+        if let Err(e) = self.sender.lock().unwrap().send(&message) {
+            log::error!("Unable to send fetch error to render process: {}", e);
+        }
     }
 }
@@ -1,6 +1,7 @@
 use std::path::Path;
 
 use flume::{Receiver, Sender};
+use loader::resource_loop::ResourceLoop;
 use shared::primitive::{Point, Size};
 use url::{parser::URLParser, Url};
 
@@ -45,6 +46,20 @@ impl BrowserHandler {
         });
     }
 
+    pub fn handle_mouse_down(&self) {
+        self.update(move |browser| {
+            let active_tab = browser.get_active_tab();
+            active_tab.handle_mouse_down().unwrap();
+        });
+    }
+
+    pub fn handle_mouse_up(&self) {
+        self.update(move |browser| {
+            let active_tab = browser.get_active_tab();
+            active_tab.handle_mouse_up().unwrap();
+        });
+    }
+
     pub fn view_source_current_tab(&self) {
         self.update(|browser| {
             let active_tab = browser.get_active_tab();
@@ -108,7 +123,11 @@ impl Browser {
         )
         .unwrap();
 
-        let initial_tab = BrowserTab::new(home_url.clone());
+        // A single resource loop shared by every tab, whether its render
+        // engine runs in this process or in a separate render process.
+        let resource_loop_tx = ResourceLoop::new().start_loop();
+
+        let initial_tab = BrowserTab::new(home_url.clone(), resource_loop_tx);
         let initial_tab_handler = start_tab(initial_tab);
 
         Self {
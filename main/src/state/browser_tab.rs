@@ -1,7 +1,9 @@
+use std::cell::Cell;
 use std::sync::{Arc, Mutex};
 
 use crate::render_client::RenderClient;
 use flume::{Receiver, Selector, Sender};
+use loader::resource_loop::request::LoadRequest;
 use render::OutputEvent;
 use shared::primitive::{Point, Size};
 use url::Url;
@@ -10,6 +12,8 @@ pub enum TabAction {
     Resize(Size),
     Scroll(f32),
     MouseMove(Point),
+    MouseDown,
+    MouseUp,
     Goto(String),
     Reload,
 }
@@ -20,6 +24,8 @@ pub enum TabEvent {
     TitleChanged(String),
     LoadingStart,
     LoadingFinished,
+    /// The render engine of the tab crashed or disconnected
+    Crashed,
 }
 
 pub struct TabHandler {
@@ -61,6 +67,16 @@ impl TabHandler {
         self.sender.send(TabAction::MouseMove(mouse_coord))?;
         Ok(())
     }
+
+    pub fn handle_mouse_down(&self) -> anyhow::Result<()> {
+        self.sender.send(TabAction::MouseDown)?;
+        Ok(())
+    }
+
+    pub fn handle_mouse_up(&self) -> anyhow::Result<()> {
+        self.sender.send(TabAction::MouseUp)?;
+        Ok(())
+    }
 }
 
 pub struct TabInfo {
@@ -70,14 +86,17 @@ pub struct TabInfo {
 pub struct BrowserTab {
     info: Arc<TabInfo>,
     client: RenderClient,
+    /// Whether the render engine of the tab crashed. A crashed tab ignores
+    /// its actions.
+    crashed: Cell<bool>,
     action_channel: (Sender<TabAction>, Receiver<TabAction>),
     event_channel: (Sender<TabEvent>, Receiver<TabEvent>),
 }
 
 impl BrowserTab {
-    pub fn new(url: Url) -> Self {
-        let client = RenderClient::new();
-        client.wait_till_ready();
+    pub fn new(url: Url, resource_loop_tx: Sender<LoadRequest>) -> Self {
+        let client = RenderClient::new(resource_loop_tx);
+        let crashed = !client.wait_till_ready();
 
         let info = TabInfo {
             url: Mutex::new(url),
@@ -86,6 +105,7 @@ impl BrowserTab {
         Self {
             info: Arc::new(info),
             client,
+            crashed: Cell::new(crashed),
             action_channel: flume::unbounded(),
             event_channel: flume::unbounded(),
         }
@@ -98,19 +118,32 @@ impl BrowserTab {
         enum Event {
             TabAction(TabAction),
             RenderEngineEvent(OutputEvent),
+            RenderEngineDisconnected,
+        }
+
+        if self.crashed.get() {
+            self.emit_event(TabEvent::Crashed)?;
         }
 
         loop {
-            let event = Selector::new()
-                .recv(&tab_action_rx, |event| event.map(|e| Event::TabAction(e)))
-                .recv(&render_engine_events, |event| {
-                    event.map(|e| Event::RenderEngineEvent(e))
-                })
-                .wait()?;
+            // the events of a crashed render engine aren't waited for
+            let event = if self.crashed.get() {
+                Event::TabAction(tab_action_rx.recv()?)
+            } else {
+                Selector::new()
+                    .recv(&tab_action_rx, |event| event.map(|e| Event::TabAction(e)))
+                    .recv(&render_engine_events, |event| {
+                        Ok(event.map_or(Event::RenderEngineDisconnected, |e| {
+                            Event::RenderEngineEvent(e)
+                        }))
+                    })
+                    .wait()?
+            };
 
             match event {
                 Event::TabAction(event) => self.handle_tab_action(event)?,
                 Event::RenderEngineEvent(event) => self.handle_render_engine_event(event)?,
+                Event::RenderEngineDisconnected => self.crash()?,
             }
         }
     }
@@ -132,12 +165,21 @@ impl BrowserTab {
     }
 
     fn handle_tab_action(&self, event: TabAction) -> anyhow::Result<()> {
-        match event {
+        if self.crashed.get() {
+            return Ok(());
+        }
+
+        let sent = match event {
             TabAction::Resize(new_size) => self.client.resize(new_size),
             TabAction::Scroll(y) => self.client.scroll(y),
             TabAction::MouseMove(coord) => self.client.mouse_move(coord),
-            TabAction::Goto(url) => self.goto(url)?,
-            TabAction::Reload => self.reload()?,
+            TabAction::MouseDown => self.client.mouse_down(),
+            TabAction::MouseUp => self.client.mouse_up(),
+            TabAction::Goto(url) => self.client.load_raw_url(url),
+            TabAction::Reload => self.client.reload(),
+        };
+        if !sent {
+            self.crash()?;
         }
         Ok(())
     }
@@ -159,16 +201,13 @@ impl BrowserTab {
         sender.send(event)?;
         Ok(())
     }
-}
 
-impl BrowserTab {
-    fn reload(&self) -> anyhow::Result<()> {
-        self.client.reload();
-        Ok(())
-    }
-
-    fn goto(&self, url: String) -> anyhow::Result<()> {
-        self.client.load_raw_url(url);
-        Ok(())
+    /// Mark the tab crashed once its render engine is disconnected
+    fn crash(&self) -> anyhow::Result<()> {
+        if self.crashed.replace(true) {
+            return Ok(());
+        }
+        log::error!("Render engine of the tab disconnected, the tab crashed");
+        self.emit_event(TabEvent::Crashed)
     }
 }
@@ -1,17 +1,22 @@
 use super::page::Page;
+use super::style_settings::StyleSettings;
 use flume::{Receiver, Sender};
 use gfx::Bitmap;
-use loader::resource_loop::{request::LoadRequest, ResourceLoop};
+use loader::resource_loop::request::LoadRequest;
 use shared::primitive::{Point, Size};
 use url::Url;
 
+#[derive(Debug)]
 pub enum InputEvent {
     ViewportResize(Size),
     Scroll(f32),
     MouseMove(Point),
+    MouseDown,
+    MouseUp,
     LoadHTML { html: String, base_url: Url },
     LoadRawURL(String),
     Reload,
+    SetStyleSettings(StyleSettings),
 }
 
 pub enum OutputEvent {
@@ -28,10 +33,8 @@ pub struct RenderEngine {
 }
 
 impl RenderEngine {
-    pub async fn new(viewport: Size) -> RenderEngine {
+    pub async fn new(viewport: Size, resource_loop_tx: Sender<LoadRequest>) -> RenderEngine {
         let page = Page::new(viewport).await;
-        let resource_loop = ResourceLoop::new();
-        let resource_loop_tx = resource_loop.start_loop();
         Self {
             page,
             resource_loop_tx,
@@ -64,8 +67,19 @@ impl RenderEngine {
                 self.emit_new_frame(event_emitter)?;
             }
             InputEvent::MouseMove(coord) => {
-                self.page.handle_mouse_move(coord).await;
-                self.emit_new_frame(event_emitter)?;
+                if self.page.handle_mouse_move(coord).await {
+                    self.emit_new_frame(event_emitter)?;
+                }
+            }
+            InputEvent::MouseDown => {
+                if self.page.handle_mouse_down().await {
+                    self.emit_new_frame(event_emitter)?;
+                }
+            }
+            InputEvent::MouseUp => {
+                if self.page.handle_mouse_up().await {
+                    self.emit_new_frame(event_emitter)?;
+                }
             }
             InputEvent::LoadHTML { html, base_url } => {
                 self.emit_loading_started(event_emitter)?;
@@ -95,6 +109,10 @@ impl RenderEngine {
                 self.emit_new_title(event_emitter)?;
                 self.emit_new_url(event_emitter)?;
             }
+            InputEvent::SetStyleSettings(style_settings) => {
+                self.page.set_style_settings(style_settings).await;
+                self.emit_new_frame(event_emitter)?;
+            }
         }
         Ok(())
     }
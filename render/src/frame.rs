@@ -1,14 +1,17 @@
+use css::media_query::structs::MediaEnvironment;
 use dom::node::NodePtr;
 use gfx::Bitmap;
 use layout::layout_box::LayoutBoxPtr;
 use shared::primitive::{Point, Size};
 
-use crate::pipeline::{Pipeline, PipelineRunOptions};
+use crate::pipeline::Pipeline;
 
 pub struct Frame {
     document: Option<NodePtr>,
     size: Size,
     bitmap: Option<Bitmap>,
+    mouse_position: Option<Point>,
+    forced_colors: bool,
 }
 
 impl Frame {
@@ -17,6 +20,8 @@ impl Frame {
             document: None,
             size: init_size,
             bitmap: None,
+            mouse_position: None,
+            forced_colors: false,
         }
     }
 
@@ -26,22 +31,109 @@ impl Frame {
 
     pub async fn resize(&mut self, new_size: Size, pipeline: &mut Pipeline) {
         self.size = new_size.clone();
-        self.render_frame(
-            pipeline,
-            PipelineRunOptions {
-                skip_style_calculation: true,
-                skip_layout_calculation: false,
-            },
-        )
-        .await;
+
+        // styles only have to be recomputed if the result of a media query
+        // changed, which changes the style rules that apply, or if they use
+        // viewport-relative lengths
+        if let Some(document) = self.document() {
+            let media_changed = document
+                .as_document()
+                .set_media_environment(self.media_environment());
+            if media_changed {
+                document.as_document().invalidate_style();
+            } else if document.as_document().is_viewport_dependent() {
+                document.mark_style_dirty();
+            }
+        }
+
+        self.render_frame(pipeline).await;
     }
 
-    pub async fn handle_mouse_move(&self, coord: Point, pipeline: &mut Pipeline) {
-        if let Some(root_node) = pipeline.content() {
-            root_node.handle_mouse_move(&coord);
+    /// Update the hovered element. Return `true` if the frame was re-rendered.
+    pub async fn handle_mouse_move(&mut self, coord: Point, pipeline: &mut Pipeline) -> bool {
+        let root_node = match pipeline.content() {
+            Some(root_node) => root_node,
+            None => return false,
+        };
+        let document = match self.document() {
+            Some(document) => document,
+            None => return false,
+        };
+
+        self.mouse_position = Some(coord.clone());
+        root_node.handle_mouse_move(&coord);
+
+        // text is hovered through its parent element
+        let hovered_element = root_node.hovered_node().and_then(|node| {
+            if node.is_element() {
+                Some(node)
+            } else {
+                node.parent().map(NodePtr)
+            }
+        });
+
+        let changed_elements = document.as_document().set_hovered_element(hovered_element);
+        if changed_elements.is_empty() {
+            return false;
+        }
+
+        self.restyle_for_state_change(document, &changed_elements, "hover", pipeline)
+            .await
+    }
+
+    /// Activate the hovered element. Return `true` if the frame was re-rendered.
+    pub async fn handle_mouse_down(&mut self, pipeline: &mut Pipeline) -> bool {
+        match self.document() {
+            Some(document) => {
+                let hovered_element = document.as_document().hovered_element();
+                self.set_active_element(document, hovered_element, pipeline)
+                    .await
+            }
+            None => false,
+        }
+    }
+
+    /// Deactivate the active element. Return `true` if the frame was re-rendered.
+    pub async fn handle_mouse_up(&mut self, pipeline: &mut Pipeline) -> bool {
+        match self.document() {
+            Some(document) => self.set_active_element(document, None, pipeline).await,
+            None => false,
+        }
+    }
+
+    async fn set_active_element(
+        &mut self,
+        document: NodePtr,
+        element: Option<NodePtr>,
+        pipeline: &mut Pipeline,
+    ) -> bool {
+        let changed_elements = document.as_document().set_active_element(element);
+        if changed_elements.is_empty() {
+            return false;
+        }
+
+        self.restyle_for_state_change(document, &changed_elements, "active", pipeline)
+            .await
+    }
+
+    /// Restyle the elements whose state changed & re-render the frame if
+    /// any computed style changed. Return `true` if the frame was re-rendered.
+    async fn restyle_for_state_change(
+        &mut self,
+        document: NodePtr,
+        changed_elements: &[NodePtr],
+        pseudo_class: &str,
+        pipeline: &mut Pipeline,
+    ) -> bool {
+        if !pipeline.invalidate_state_change(document.clone(), changed_elements, pseudo_class) {
+            return false;
         }
 
-        // TODO: Re-render the frame if needed
+        let need_redraw = pipeline.update_styles(&document);
+        if need_redraw {
+            self.render_frame(pipeline).await;
+        }
+        need_redraw
     }
 
     pub async fn scroll(&mut self, delta_y: f32, pipeline: &mut Pipeline) {
@@ -65,40 +157,144 @@ impl Frame {
             return;
         }
 
-        self.render_frame(
-            pipeline,
-            PipelineRunOptions {
-                skip_style_calculation: true,
-                skip_layout_calculation: true,
-            },
-        )
-        .await;
+        self.render_frame(pipeline).await;
     }
 
     pub async fn set_document(&mut self, document: NodePtr, pipeline: &mut Pipeline) {
         self.document = Some(document.clone());
-        self.render_frame(
-            pipeline,
-            PipelineRunOptions {
-                skip_style_calculation: false,
-                skip_layout_calculation: false,
-            },
-        )
-        .await;
+        document
+            .as_document()
+            .set_media_environment(self.media_environment());
+        self.render_frame(pipeline).await;
     }
 
     pub fn document(&self) -> Option<NodePtr> {
         self.document.clone()
     }
 
+    /// Set whether the forced colors mode is active. The styles are
+    /// recomputed with the next rendering if a media query depends on it.
+    pub fn set_forced_colors(&mut self, forced_colors: bool) {
+        self.forced_colors = forced_colors;
+        if let Some(document) = self.document() {
+            let document = document.as_document();
+            if document.set_media_environment(self.media_environment()) {
+                document.invalidate_style();
+            }
+        }
+    }
+
+    fn media_environment(&self) -> MediaEnvironment {
+        MediaEnvironment {
+            forced_colors: self.forced_colors,
+            ..MediaEnvironment::new(self.size.width, self.size.height)
+        }
+    }
+
     pub fn bitmap(&self) -> Option<&Bitmap> {
         self.bitmap.as_ref()
     }
 
-    async fn render_frame(&mut self, pipeline: &mut Pipeline, opts: PipelineRunOptions) {
+    /// Re-render the frame if its document was edited, e.g. a stylesheet
+    /// was loaded or an attribute changed. Return `true` if the frame was
+    /// re-rendered.
+    pub async fn update_rendering(&mut self, pipeline: &mut Pipeline) -> bool {
+        let needs_update = match self.document() {
+            Some(document) => {
+                document.as_document().is_style_invalidated() || document.needs_update()
+            }
+            None => false,
+        };
+        if needs_update {
+            self.render_frame(pipeline).await;
+        }
+        needs_update
+    }
+
+    async fn render_frame(&mut self, pipeline: &mut Pipeline) {
         if let Some(document) = self.document() {
-            let bitmap = pipeline.run(document, &self.size(), opts).await;
+            let bitmap = pipeline.run(document, &self.size()).await;
             self.bitmap = Some(bitmap);
+
+            // the boxes under the mouse may have changed, restore the mouse
+            // over state
+            if let (Some(root_node), Some(coord)) = (pipeline.content(), &self.mouse_position) {
+                root_node.handle_mouse_move(coord);
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use style_types::Property;
+    use test_utils::dom_creator::{document, element, text};
+
+    #[test]
+    fn restyle_when_media_queries_change() {
+        std::env::set_var("CPU_RENDERING", "1");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let doc = document();
+            let css = "p { width: 1px; } @media (min-width: 500px) { p { width: 2px; } }";
+            let style = element("style", doc.clone(), vec![text(css, doc.clone())]);
+            let p = element("p", doc.clone(), vec![]);
+            let html = element("html", doc.clone(), vec![p.clone()]);
+            doc.append_child(style.0.clone());
+            doc.append_child(html.0.clone());
+
+            let width = || p.get_style(&Property::Width).to_absolute_px();
+            let mut pipeline = Pipeline::new().await;
+            let mut frame = Frame::new(Size::new(400., 300.));
+            frame.set_document(doc.clone(), &mut pipeline).await;
+            assert_eq!(width(), 1.);
+
+            // across the breakpoint
+            frame.resize(Size::new(600., 300.), &mut pipeline).await;
+            assert_eq!(width(), 2.);
+            frame.resize(Size::new(800., 300.), &mut pipeline).await;
+            assert_eq!(width(), 2.);
+            frame.resize(Size::new(400., 300.), &mut pipeline).await;
+            assert_eq!(width(), 1.);
+        });
+    }
+
+    #[test]
+    fn redraw_only_when_hover_changes_styles() {
+        std::env::set_var("CPU_RENDERING", "1");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let doc = document();
+            let css = "style { display: none; } html, p { display: block; } \
+                       p { height: 10px; } p.link:hover { color: red; }";
+            let style = element("style", doc.clone(), vec![text(css, doc.clone())]);
+            let plain = element("p", doc.clone(), vec![]);
+            let link = element("p", doc.clone(), vec![]);
+            link.set_attribute("class", "link");
+            let html = element(
+                "html",
+                doc.clone(),
+                vec![style, plain.clone(), link.clone()],
+            );
+            doc.append_child(html.0.clone());
+
+            let mut pipeline = Pipeline::new().await;
+            let mut frame = Frame::new(Size::new(400., 300.));
+            frame.set_document(doc.clone(), &mut pipeline).await;
+
+            // no :hover rule matches the plain paragraph or its ancestors
+            let redrawn = frame
+                .handle_mouse_move(Point::new(5., 5.), &mut pipeline)
+                .await;
+            assert!(!redrawn);
+            assert!(plain.as_element().is_hovered());
+
+            let redrawn = frame
+                .handle_mouse_move(Point::new(5., 15.), &mut pipeline)
+                .await;
+            assert!(redrawn);
+            assert!(link.as_element().is_hovered());
+        });
+    }
+}
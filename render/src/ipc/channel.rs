@@ -0,0 +1,174 @@
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+
+/// Maximum size of a single message. Bitmaps go through shared memory, so
+/// anything bigger than this is most likely a corrupted stream.
+const MAX_MESSAGE_SIZE: u32 = 256 * 1024 * 1024;
+
+pub trait Encode {
+    fn encode(&self, encoder: &mut Encoder);
+}
+
+pub trait Decode: Sized {
+    fn decode(decoder: &mut Decoder) -> io::Result<Self>;
+}
+
+/// One end of a message channel between two processes
+pub struct IpcChannel {
+    stream: UnixStream,
+}
+
+impl IpcChannel {
+    pub fn new(stream: UnixStream) -> Self {
+        Self { stream }
+    }
+
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self::new(self.stream.try_clone()?))
+    }
+
+    pub fn send<M: Encode>(&mut self, message: &M) -> io::Result<()> {
+        let mut encoder = Encoder::new();
+        message.encode(&mut encoder);
+        let bytes = encoder.finish();
+
+        self.stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        self.stream.write_all(&bytes)?;
+        self.stream.flush()
+    }
+
+    pub fn recv<M: Decode>(&mut self) -> io::Result<M> {
+        let mut len = [0; 4];
+        self.stream.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len);
+
+        if len > MAX_MESSAGE_SIZE {
+            return Err(invalid_data("IPC message is too large"));
+        }
+
+        let mut bytes = vec![0; len as usize];
+        self.stream.read_exact(&mut bytes)?;
+        M::decode(&mut Decoder::new(&bytes))
+    }
+}
+
+#[derive(Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.write_u64(value.len() as u64);
+        self.bytes.extend_from_slice(value);
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+}
+
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid_data("Unexpected end of IPC message"))?;
+        let result = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(result)
+    }
+
+    pub fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    pub fn read_u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    pub fn read_f32(&mut self) -> io::Result<f32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(f32::from_le_bytes(bytes))
+    }
+
+    pub fn read_bytes(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.read_u64()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    pub fn read_string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.read_bytes()?).map_err(|_| invalid_data("Invalid UTF-8 string"))
+    }
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_primitives() {
+        let mut encoder = Encoder::new();
+        encoder.write_u8(7);
+        encoder.write_u32(42);
+        encoder.write_u64(u64::MAX);
+        encoder.write_f32(1.5);
+        encoder.write_str("moon");
+        let bytes = encoder.finish();
+
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(decoder.read_u8().unwrap(), 7);
+        assert_eq!(decoder.read_u32().unwrap(), 42);
+        assert_eq!(decoder.read_u64().unwrap(), u64::MAX);
+        assert_eq!(decoder.read_f32().unwrap(), 1.5);
+        assert_eq!(decoder.read_string().unwrap(), "moon");
+        assert!(decoder.read_u8().is_err());
+    }
+}
@@ -0,0 +1,449 @@
+use std::io;
+
+use loader::resource_loop::{error::LoadError, request::Bytes};
+use shared::color::Color;
+use shared::primitive::{Point, Size};
+use url::{parser::URLParser, Url};
+
+use super::channel::{invalid_data, Decode, Decoder, Encode, Encoder};
+use crate::style_settings::{ForcedColors, StyleSettings};
+use crate::InputEvent;
+
+/// Messages sent from the browser process to a render process
+#[derive(Debug)]
+pub enum BrowserMessage {
+    Input(InputEvent),
+    FetchFinished {
+        request_id: u64,
+        bytes: Bytes,
+    },
+    FetchErrored {
+        request_id: u64,
+        error: LoadError,
+    },
+    /// The browser has copied the frame out of the shared bitmap
+    /// and the render process can reuse it.
+    FrameReleased(u32),
+}
+
+/// Messages sent from a render process to the browser process
+#[derive(Debug)]
+pub enum RendererMessage {
+    Ready,
+    /// A new frame has been written to the shared bitmap with this id
+    FrameRendered {
+        bitmap_id: u32,
+        len: u64,
+    },
+    TitleChanged(String),
+    URLChanged(Url),
+    LoadingStarted,
+    LoadingFinished,
+    Fetch {
+        request_id: u64,
+        url: Url,
+    },
+}
+
+impl Encode for BrowserMessage {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            BrowserMessage::Input(event) => {
+                encoder.write_u8(0);
+                encode_input_event(event, encoder);
+            }
+            BrowserMessage::FetchFinished { request_id, bytes } => {
+                encoder.write_u8(1);
+                encoder.write_u64(*request_id);
+                encoder.write_bytes(bytes);
+            }
+            BrowserMessage::FetchErrored { request_id, error } => {
+                encoder.write_u8(2);
+                encoder.write_u64(*request_id);
+                encode_load_error(error, encoder);
+            }
+            BrowserMessage::FrameReleased(bitmap_id) => {
+                encoder.write_u8(3);
+                encoder.write_u32(*bitmap_id);
+            }
+        }
+    }
+}
+
+impl Decode for BrowserMessage {
+    fn decode(decoder: &mut Decoder) -> io::Result<Self> {
+        match decoder.read_u8()? {
+            0 => Ok(BrowserMessage::Input(decode_input_event(decoder)?)),
+            1 => Ok(BrowserMessage::FetchFinished {
+                request_id: decoder.read_u64()?,
+                bytes: decoder.read_bytes()?,
+            }),
+            2 => Ok(BrowserMessage::FetchErrored {
+                request_id: decoder.read_u64()?,
+                error: decode_load_error(decoder)?,
+            }),
+            3 => Ok(BrowserMessage::FrameReleased(decoder.read_u32()?)),
+            _ => Err(invalid_data("Unknown browser message")),
+        }
+    }
+}
+
+impl Encode for RendererMessage {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            RendererMessage::Ready => encoder.write_u8(0),
+            RendererMessage::FrameRendered { bitmap_id, len } => {
+                encoder.write_u8(1);
+                encoder.write_u32(*bitmap_id);
+                encoder.write_u64(*len);
+            }
+            RendererMessage::TitleChanged(title) => {
+                encoder.write_u8(2);
+                encoder.write_str(title);
+            }
+            RendererMessage::URLChanged(url) => {
+                encoder.write_u8(3);
+                encoder.write_str(&url.as_str());
+            }
+            RendererMessage::LoadingStarted => encoder.write_u8(4),
+            RendererMessage::LoadingFinished => encoder.write_u8(5),
+            RendererMessage::Fetch { request_id, url } => {
+                encoder.write_u8(6);
+                encoder.write_u64(*request_id);
+                encoder.write_str(&url.as_str());
+            }
+        }
+    }
+}
+
+impl Decode for RendererMessage {
+    fn decode(decoder: &mut Decoder) -> io::Result<Self> {
+        match decoder.read_u8()? {
+            0 => Ok(RendererMessage::Ready),
+            1 => Ok(RendererMessage::FrameRendered {
+                bitmap_id: decoder.read_u32()?,
+                len: decoder.read_u64()?,
+            }),
+            2 => Ok(RendererMessage::TitleChanged(decoder.read_string()?)),
+            3 => Ok(RendererMessage::URLChanged(decode_url(decoder)?)),
+            4 => Ok(RendererMessage::LoadingStarted),
+            5 => Ok(RendererMessage::LoadingFinished),
+            6 => Ok(RendererMessage::Fetch {
+                request_id: decoder.read_u64()?,
+                url: decode_url(decoder)?,
+            }),
+            _ => Err(invalid_data("Unknown renderer message")),
+        }
+    }
+}
+
+fn encode_input_event(event: &InputEvent, encoder: &mut Encoder) {
+    match event {
+        InputEvent::ViewportResize(size) => {
+            encoder.write_u8(0);
+            encoder.write_f32(size.width);
+            encoder.write_f32(size.height);
+        }
+        InputEvent::Scroll(delta_y) => {
+            encoder.write_u8(1);
+            encoder.write_f32(*delta_y);
+        }
+        InputEvent::MouseMove(coord) => {
+            encoder.write_u8(2);
+            encoder.write_f32(coord.x);
+            encoder.write_f32(coord.y);
+        }
+        InputEvent::LoadHTML { html, base_url } => {
+            encoder.write_u8(3);
+            encoder.write_str(html);
+            encoder.write_str(&base_url.as_str());
+        }
+        InputEvent::LoadRawURL(url) => {
+            encoder.write_u8(4);
+            encoder.write_str(url);
+        }
+        InputEvent::Reload => encoder.write_u8(5),
+        InputEvent::MouseDown => encoder.write_u8(6),
+        InputEvent::MouseUp => encoder.write_u8(7),
+        InputEvent::SetStyleSettings(style_settings) => {
+            encoder.write_u8(8);
+            encode_style_settings(style_settings, encoder);
+        }
+    }
+}
+
+fn decode_input_event(decoder: &mut Decoder) -> io::Result<InputEvent> {
+    match decoder.read_u8()? {
+        0 => Ok(InputEvent::ViewportResize(Size::new(
+            decoder.read_f32()?,
+            decoder.read_f32()?,
+        ))),
+        1 => Ok(InputEvent::Scroll(decoder.read_f32()?)),
+        2 => Ok(InputEvent::MouseMove(Point::new(
+            decoder.read_f32()?,
+            decoder.read_f32()?,
+        ))),
+        3 => Ok(InputEvent::LoadHTML {
+            html: decoder.read_string()?,
+            base_url: decode_url(decoder)?,
+        }),
+        4 => Ok(InputEvent::LoadRawURL(decoder.read_string()?)),
+        5 => Ok(InputEvent::Reload),
+        6 => Ok(InputEvent::MouseDown),
+        7 => Ok(InputEvent::MouseUp),
+        8 => Ok(InputEvent::SetStyleSettings(decode_style_settings(
+            decoder,
+        )?)),
+        _ => Err(invalid_data("Unknown input event")),
+    }
+}
+
+fn encode_style_settings(style_settings: &StyleSettings, encoder: &mut Encoder) {
+    encode_optional_str(style_settings.user_agent_css.as_deref(), encoder);
+    encoder.write_u32(style_settings.extra_user_agent_css.len() as u32);
+    for css in &style_settings.extra_user_agent_css {
+        encoder.write_str(css);
+    }
+    encode_optional_str(style_settings.user_css.as_deref(), encoder);
+    match &style_settings.forced_colors {
+        Some(forced_colors) => {
+            encoder.write_u8(1);
+            encode_color(&forced_colors.canvas, encoder);
+            encode_color(&forced_colors.canvas_text, encoder);
+            encode_color(&forced_colors.link_text, encoder);
+        }
+        None => encoder.write_u8(0),
+    }
+}
+
+fn decode_style_settings(decoder: &mut Decoder) -> io::Result<StyleSettings> {
+    let user_agent_css = decode_optional_string(decoder)?;
+    let extra_user_agent_css = (0..decoder.read_u32()?)
+        .map(|_| decoder.read_string())
+        .collect::<io::Result<Vec<String>>>()?;
+    let user_css = decode_optional_string(decoder)?;
+    let forced_colors = match decoder.read_u8()? {
+        0 => None,
+        _ => Some(ForcedColors {
+            canvas: decode_color(decoder)?,
+            canvas_text: decode_color(decoder)?,
+            link_text: decode_color(decoder)?,
+        }),
+    };
+    Ok(StyleSettings {
+        user_agent_css,
+        extra_user_agent_css,
+        user_css,
+        forced_colors,
+    })
+}
+
+fn encode_optional_str(value: Option<&str>, encoder: &mut Encoder) {
+    match value {
+        Some(value) => {
+            encoder.write_u8(1);
+            encoder.write_str(value);
+        }
+        None => encoder.write_u8(0),
+    }
+}
+
+fn decode_optional_string(decoder: &mut Decoder) -> io::Result<Option<String>> {
+    match decoder.read_u8()? {
+        0 => Ok(None),
+        _ => Ok(Some(decoder.read_string()?)),
+    }
+}
+
+fn encode_color(color: &Color, encoder: &mut Encoder) {
+    encoder.write_u8(color.r);
+    encoder.write_u8(color.g);
+    encoder.write_u8(color.b);
+    encoder.write_u8(color.a);
+}
+
+fn decode_color(decoder: &mut Decoder) -> io::Result<Color> {
+    Ok(Color {
+        r: decoder.read_u8()?,
+        g: decoder.read_u8()?,
+        b: decoder.read_u8()?,
+        a: decoder.read_u8()?,
+    })
+}
+
+fn encode_load_error(error: &LoadError, encoder: &mut Encoder) {
+    match error {
+        LoadError::UnsupportedProtocol(protocol) => {
+            encoder.write_u8(0);
+            encoder.write_str(protocol);
+        }
+        LoadError::IOError(error) => {
+            encoder.write_u8(1);
+            encoder.write_str(error);
+        }
+        LoadError::InvalidURL(url) => {
+            encoder.write_u8(2);
+            encoder.write_str(url);
+        }
+        LoadError::LoaderDisconnected => encoder.write_u8(3),
+    }
+}
+
+fn decode_load_error(decoder: &mut Decoder) -> io::Result<LoadError> {
+    match decoder.read_u8()? {
+        0 => Ok(LoadError::UnsupportedProtocol(decoder.read_string()?)),
+        1 => Ok(LoadError::IOError(decoder.read_string()?)),
+        2 => Ok(LoadError::InvalidURL(decoder.read_string()?)),
+        3 => Ok(LoadError::LoaderDisconnected),
+        _ => Err(invalid_data("Unknown load error")),
+    }
+}
+
+fn decode_url(decoder: &mut Decoder) -> io::Result<Url> {
+    let raw_url = decoder.read_string()?;
+    URLParser::parse(&raw_url, None).ok_or_else(|| invalid_data("Invalid URL in IPC message"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use super::*;
+
+    fn url(raw_url: &str) -> Url {
+        URLParser::parse(raw_url, None).unwrap()
+    }
+
+    fn encode<M: Encode>(message: &M) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        message.encode(&mut encoder);
+        encoder.finish()
+    }
+
+    fn assert_roundtrip<M: Encode + Decode + Debug>(message: M) {
+        let bytes = encode(&message);
+        let decoded = M::decode(&mut Decoder::new(&bytes)).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", message));
+
+        // every prefix of the message is rejected
+        for len in 0..bytes.len() {
+            assert!(M::decode(&mut Decoder::new(&bytes[..len])).is_err());
+        }
+    }
+
+    fn input_events() -> Vec<InputEvent> {
+        vec![
+            InputEvent::ViewportResize(Size::new(800., 600.)),
+            InputEvent::Scroll(-12.5),
+            InputEvent::MouseMove(Point::new(3., 4.)),
+            InputEvent::MouseDown,
+            InputEvent::MouseUp,
+            InputEvent::LoadHTML {
+                html: "<p>moon</p>".to_string(),
+                base_url: url("https://example.com/index.html"),
+            },
+            InputEvent::LoadRawURL("example.com".to_string()),
+            InputEvent::Reload,
+            InputEvent::SetStyleSettings(StyleSettings::default()),
+            InputEvent::SetStyleSettings(StyleSettings {
+                user_agent_css: Some("p { color: red; }".to_string()),
+                extra_user_agent_css: vec!["a {}".to_string(), "b {}".to_string()],
+                user_css: Some("body { font-size: 20px; }".to_string()),
+                forced_colors: Some(ForcedColors {
+                    canvas: Color {
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: 255,
+                    },
+                    canvas_text: Color {
+                        r: 255,
+                        g: 255,
+                        b: 255,
+                        a: 255,
+                    },
+                    link_text: Color {
+                        r: 255,
+                        g: 255,
+                        b: 0,
+                        a: 128,
+                    },
+                }),
+            }),
+        ]
+    }
+
+    #[test]
+    fn encode_decode_browser_messages() {
+        for event in input_events() {
+            assert_roundtrip(BrowserMessage::Input(event));
+        }
+        assert_roundtrip(BrowserMessage::FetchFinished {
+            request_id: 7,
+            bytes: vec![0, 1, 2, 255],
+        });
+        let errors = vec![
+            LoadError::UnsupportedProtocol("gopher".to_string()),
+            LoadError::IOError("not found".to_string()),
+            LoadError::InvalidURL("http://".to_string()),
+            LoadError::LoaderDisconnected,
+        ];
+        for error in errors {
+            assert_roundtrip(BrowserMessage::FetchErrored {
+                request_id: u64::MAX,
+                error,
+            });
+        }
+        assert_roundtrip(BrowserMessage::FrameReleased(2));
+    }
+
+    #[test]
+    fn encode_decode_renderer_messages() {
+        assert_roundtrip(RendererMessage::Ready);
+        assert_roundtrip(RendererMessage::FrameRendered {
+            bitmap_id: 1,
+            len: 800 * 600 * 4,
+        });
+        assert_roundtrip(RendererMessage::TitleChanged("Moon".to_string()));
+        assert_roundtrip(RendererMessage::URLChanged(url(
+            "https://example.com/a?b#c",
+        )));
+        assert_roundtrip(RendererMessage::LoadingStarted);
+        assert_roundtrip(RendererMessage::LoadingFinished);
+        assert_roundtrip(RendererMessage::Fetch {
+            request_id: 3,
+            url: url("https://example.com/style.css"),
+        });
+    }
+
+    #[test]
+    fn reject_invalid_messages() {
+        let decode_browser = |bytes: &[u8]| BrowserMessage::decode(&mut Decoder::new(bytes));
+        let decode_renderer = |bytes: &[u8]| RendererMessage::decode(&mut Decoder::new(bytes));
+
+        // unknown tags
+        assert!(decode_browser(&[4]).is_err());
+        assert!(decode_renderer(&[7]).is_err());
+        assert!(decode_browser(&[0, 9]).is_err());
+        let mut fetch_errored = encode(&BrowserMessage::FetchErrored {
+            request_id: 0,
+            error: LoadError::LoaderDisconnected,
+        });
+        *fetch_errored.last_mut().unwrap() = 4;
+        assert!(decode_browser(&fetch_errored).is_err());
+
+        // a length that is larger than the message
+        let mut title = encode(&RendererMessage::TitleChanged("Moon".to_string()));
+        title[1] = 200;
+        assert!(decode_renderer(&title).is_err());
+
+        // invalid UTF-8 & URLs
+        let mut title = encode(&RendererMessage::TitleChanged("Moon".to_string()));
+        *title.last_mut().unwrap() = 0xff;
+        assert!(decode_renderer(&title).is_err());
+        let mut encoder = Encoder::new();
+        encoder.write_u8(3);
+        encoder.write_str("not a url");
+        assert!(decode_renderer(&encoder.finish()).is_err());
+    }
+}
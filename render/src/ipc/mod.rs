@@ -0,0 +1,17 @@
+/// Inter-process communication between the browser process and a render engine
+/// running in a child `moon` process.
+///
+/// Messages are framed with a length prefix and sent over a Unix socket. Rendered
+/// bitmaps are too large to push through the socket for every frame, so they are
+/// written to shared memory instead & only the buffer id travels over the socket.
+/// Resource requests made by the child are proxied back to the browser process so
+/// that there is only one `ResourceLoop` for the whole browser.
+mod channel;
+mod message;
+mod render_process;
+mod shared_bitmap;
+
+pub use channel::*;
+pub use message::*;
+pub use render_process::*;
+pub use shared_bitmap::*;
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use flume::{Receiver, Sender};
+use loader::resource_loop::request::{FetchListener, LoadRequest};
+use shared::primitive::Size;
+
+use super::channel::IpcChannel;
+use super::message::{BrowserMessage, RendererMessage};
+use super::shared_bitmap::SharedBitmap;
+use crate::{OutputEvent, RenderEngine};
+
+/// The most shared bitmaps a render process allocates for its frames
+const MAX_SHARED_BITMAPS: usize = 3;
+/// How long a frame waits for the browser to release a bitmap before it's
+/// dropped
+const BITMAP_RELEASE_TIMEOUT: Duration = Duration::from_secs(1);
+
+type PendingFetches = Arc<Mutex<HashMap<u64, Arc<dyn FetchListener>>>>;
+
+/// Entry point of a render process. Connect to the browser process through
+/// the socket & run a render engine until the browser disconnects.
+pub fn run_render_process(socket_path: &Path) -> anyhow::Result<()> {
+    let channel = IpcChannel::new(UnixStream::connect(socket_path)?);
+    let sender = Arc::new(Mutex::new(channel.try_clone()?));
+
+    let (input_tx, input_rx) = flume::unbounded();
+    let (output_tx, output_rx) = flume::unbounded();
+    let (resource_loop_tx, resource_loop_rx) = flume::unbounded();
+    let (released_tx, released_rx) = flume::unbounded();
+    let pending_fetches: PendingFetches = Arc::new(Mutex::new(HashMap::new()));
+
+    {
+        let pending_fetches = pending_fetches.clone();
+        std::thread::spawn(move || {
+            receive_browser_messages(channel, input_tx, released_tx, pending_fetches)
+        });
+    }
+
+    {
+        let sender = sender.clone();
+        std::thread::spawn(move || proxy_load_requests(resource_loop_rx, sender, pending_fetches));
+    }
+
+    {
+        let sender = sender.clone();
+        std::thread::spawn(move || send_output_events(output_rx, released_rx, sender));
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let render_engine = RenderEngine::new(Size::new(1., 1.), resource_loop_tx).await;
+
+        sender.lock().unwrap().send(&RendererMessage::Ready)?;
+
+        // run render engine until the browser process disconnects
+        render_engine.run(input_rx, output_tx).await
+    })
+}
+
+fn receive_browser_messages(
+    mut channel: IpcChannel,
+    input_tx: Sender<crate::InputEvent>,
+    released_tx: Sender<u32>,
+    pending_fetches: PendingFetches,
+) {
+    loop {
+        let message = match channel.recv::<BrowserMessage>() {
+            Ok(message) => message,
+            Err(e) => {
+                log::info!("Browser process disconnected: {}", e);
+                break;
+            }
+        };
+
+        match message {
+            BrowserMessage::Input(event) => {
+                if input_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BrowserMessage::FetchFinished { request_id, bytes } => {
+                if let Some(listener) = pending_fetches.lock().unwrap().remove(&request_id) {
+                    listener.on_finished(bytes);
+                }
+            }
+            BrowserMessage::FetchErrored { request_id, error } => {
+                if let Some(listener) = pending_fetches.lock().unwrap().remove(&request_id) {
+                    listener.on_errored(error);
+                }
+            }
+            BrowserMessage::FrameReleased(bitmap_id) => {
+                let _ = released_tx.send(bitmap_id);
+            }
+        }
+    }
+}
+
+fn proxy_load_requests(
+    resource_loop_rx: Receiver<LoadRequest>,
+    sender: Arc<Mutex<IpcChannel>>,
+    pending_fetches: PendingFetches,
+) {
+    for (request_id, request) in resource_loop_rx.iter().enumerate() {
+        let request_id = request_id as u64;
+
+        pending_fetches
+            .lock()
+            .unwrap()
+            .insert(request_id, request.listener());
+
+        let message = RendererMessage::Fetch {
+            request_id,
+            url: request.url().clone(),
+        };
+
+        if let Err(e) = sender.lock().unwrap().send(&message) {
+            log::error!("Unable to proxy load request: {}", e);
+            break;
+        }
+    }
+}
+
+fn send_output_events(
+    output_rx: Receiver<OutputEvent>,
+    released_rx: Receiver<u32>,
+    sender: Arc<Mutex<IpcChannel>>,
+) {
+    let mut bitmaps: Vec<SharedBitmap> = Vec::new();
+    let mut free_bitmaps: Vec<u32> = Vec::new();
+
+    for event in output_rx.iter() {
+        let message = match event {
+            OutputEvent::FrameRendered(frame) => {
+                free_bitmaps.extend(released_rx.try_iter());
+
+                // reuse a bitmap that the browser has released, or allocate a new
+                // one if the browser is still reading all of them. Once there are
+                // enough bitmaps, wait for the browser to release one instead.
+                let bitmap_id = match free_bitmaps.pop() {
+                    Some(id) => id,
+                    None if bitmaps.len() < MAX_SHARED_BITMAPS => {
+                        match SharedBitmap::create(bitmaps.len() as u32) {
+                            Ok(bitmap) => {
+                                bitmaps.push(bitmap);
+                                bitmaps.len() as u32 - 1
+                            }
+                            Err(e) => {
+                                log::error!("Unable to allocate shared bitmap: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                    None => match released_rx.recv_timeout(BITMAP_RELEASE_TIMEOUT) {
+                        Ok(id) => id,
+                        Err(_) => {
+                            log::warn!("No shared bitmap was released, dropping frame");
+                            continue;
+                        }
+                    },
+                };
+
+                if let Err(e) = bitmaps[bitmap_id as usize].write(&frame) {
+                    log::error!("Unable to write frame to shared bitmap: {}", e);
+                    free_bitmaps.push(bitmap_id);
+                    continue;
+                }
+
+                RendererMessage::FrameRendered {
+                    bitmap_id,
+                    len: frame.len() as u64,
+                }
+            }
+            OutputEvent::TitleChanged(title) => RendererMessage::TitleChanged(title),
+            OutputEvent::URLChanged(url) => RendererMessage::URLChanged(url),
+            OutputEvent::LoadingStarted => RendererMessage::LoadingStarted,
+            OutputEvent::LoadingFinished => RendererMessage::LoadingFinished,
+        };
+
+        if let Err(e) = sender.lock().unwrap().send(&message) {
+            log::error!("Unable to send output event to browser: {}", e);
+            break;
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::PathBuf;
+
+use gfx::Bitmap;
+use memmap2::{Mmap, MmapMut};
+
+/// A bitmap buffer backed by a memory mapped file that both the render
+/// process & the browser process can map.
+///
+/// The render process owns the buffer & removes the backing file on drop.
+pub struct SharedBitmap {
+    path: PathBuf,
+    file: File,
+    mmap: Option<MmapMut>,
+}
+
+impl SharedBitmap {
+    /// Path of the backing file for a shared bitmap of a render process
+    pub fn path(owner_pid: u32, bitmap_id: u32) -> PathBuf {
+        // /dev/shm is a memory backed filesystem on Linux. Fallback to the temp
+        // directory on systems without it (still shared, just not as cheap).
+        let shm_dir = PathBuf::from("/dev/shm");
+        let dir = if shm_dir.is_dir() {
+            shm_dir
+        } else {
+            std::env::temp_dir()
+        };
+        dir.join(format!("moon-{}-{}.bitmap", owner_pid, bitmap_id))
+    }
+
+    pub fn create(bitmap_id: u32) -> io::Result<Self> {
+        let path = SharedBitmap::path(std::process::id(), bitmap_id);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            mmap: None,
+        })
+    }
+
+    pub fn write(&mut self, bitmap: &[u8]) -> io::Result<()> {
+        let capacity = self.mmap.as_ref().map(|mmap| mmap.len()).unwrap_or(0);
+
+        if bitmap.len() > capacity {
+            self.mmap = None;
+            self.file.set_len(bitmap.len() as u64)?;
+            // Safety: the file is only resized by this process, and only while
+            // the browser is not reading the buffer (see `FrameReleased`).
+            self.mmap = Some(unsafe { MmapMut::map_mut(&self.file)? });
+        }
+
+        if let Some(mmap) = &mut self.mmap {
+            mmap[..bitmap.len()].copy_from_slice(bitmap);
+        }
+        Ok(())
+    }
+
+    /// Copy a frame out of the shared bitmap of another process
+    pub fn read(owner_pid: u32, bitmap_id: u32, len: usize) -> io::Result<Bitmap> {
+        let file = File::open(SharedBitmap::path(owner_pid, bitmap_id))?;
+        // Safety: the owner does not write to this buffer until we release it.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        if len > mmap.len() {
+            return Err(super::channel::invalid_data(
+                "Frame is larger than its bitmap",
+            ));
+        }
+        Ok(mmap[..len].to_vec())
+    }
+}
+
+impl Drop for SharedBitmap {
+    fn drop(&mut self) {
+        self.mmap = None;
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
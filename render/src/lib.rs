@@ -1,6 +1,8 @@
 mod engine;
 mod frame;
+pub mod ipc;
 pub mod page;
 mod pipeline;
+pub mod style_settings;
 
 pub use engine::*;
@@ -12,27 +12,29 @@ use loader::{
     },
 };
 use shared::{byte_string::ByteString, primitive::Size, tree_node::TreeNode};
-use style_types::{CSSLocation, CascadeOrigin, ContextualStyleSheet};
 use url::{parser::URLParser, Url};
 
 use crate::pipeline::Pipeline;
+use crate::style_settings::StyleSettings;
 
 use super::frame::Frame;
 
-const USER_AGENT_STYLES: &str = include_str!("./html.css");
-
 pub struct Page {
     url: Option<Url>,
     main_frame: Frame,
     pipeline: Pipeline,
+    style_settings: StyleSettings,
 }
 
 impl Page {
+    /// Create a page with the default style settings, until the browser
+    /// sends its settings
     pub async fn new(init_size: Size) -> Page {
         Page {
             url: None,
             main_frame: Frame::new(init_size),
             pipeline: Pipeline::new().await,
+            style_settings: StyleSettings::default(),
         }
     }
 
@@ -44,10 +46,18 @@ impl Page {
         self.main_frame.scroll(y, &mut self.pipeline).await;
     }
 
-    pub async fn handle_mouse_move(&mut self, coord: shared::primitive::Point) {
+    pub async fn handle_mouse_move(&mut self, coord: shared::primitive::Point) -> bool {
         self.main_frame
             .handle_mouse_move(coord, &mut self.pipeline)
-            .await;
+            .await
+    }
+
+    pub async fn handle_mouse_down(&mut self) -> bool {
+        self.main_frame.handle_mouse_down(&mut self.pipeline).await
+    }
+
+    pub async fn handle_mouse_up(&mut self) -> bool {
+        self.main_frame.handle_mouse_up(&mut self.pipeline).await
     }
 
     pub async fn load_html(
@@ -64,12 +74,7 @@ impl Page {
             .as_document()
             .set_loader(DocumentLoader::new(resource_loop_tx));
 
-        let tokenizer = css::tokenizer::Tokenizer::new(USER_AGENT_STYLES.chars());
-        let mut parser = css::parser::Parser::<css::tokenizer::token::Token>::new(tokenizer.run());
-        let stylesheet = parser.parse_a_css_stylesheet();
-        let stylesheet =
-            ContextualStyleSheet::new(stylesheet, CascadeOrigin::UserAgent, CSSLocation::External);
-        document.as_document().set_user_agent_stylesheet(stylesheet);
+        self.style_settings.apply(document.as_document());
 
         log::debug!("Base URL: {}", base_url);
         document.as_document().set_base(Some(base_url));
@@ -115,6 +120,28 @@ impl Page {
         }
     }
 
+    /// Replace the user agent & user stylesheets of the page & re-render
+    /// it with them
+    pub async fn set_style_settings(&mut self, style_settings: StyleSettings) {
+        if let Some(document) = self.main_frame.document() {
+            style_settings.apply(document.as_document());
+        }
+        self.main_frame
+            .set_forced_colors(style_settings.forced_colors.is_some());
+        self.style_settings = style_settings;
+        self.update_rendering().await;
+    }
+
+    /// Apply the pending edits of the document's styles. Return `true` if
+    /// the page was re-rendered.
+    pub async fn update_rendering(&mut self) -> bool {
+        self.main_frame.update_rendering(&mut self.pipeline).await
+    }
+
+    pub fn document(&self) -> Option<NodePtr> {
+        self.main_frame.document()
+    }
+
     pub fn bitmap(&self) -> Option<&Bitmap> {
         self.main_frame.bitmap()
     }
@@ -191,12 +218,7 @@ impl Page {
             NodeData::Document(Document::new()),
         )));
 
-        let tokenizer = css::tokenizer::Tokenizer::new(USER_AGENT_STYLES.chars());
-        let mut parser = css::parser::Parser::<css::tokenizer::token::Token>::new(tokenizer.run());
-        let stylesheet = parser.parse_a_css_stylesheet();
-        let stylesheet =
-            ContextualStyleSheet::new(stylesheet, CascadeOrigin::UserAgent, CSSLocation::External);
-        document.as_document().set_user_agent_stylesheet(stylesheet);
+        self.style_settings.apply(document.as_document());
 
         let tokenizer = html::tokenizer::Tokenizer::new(error_page.chars());
         let tree_builder = html::tree_builder::TreeBuilder::new(tokenizer, document);
@@ -1,54 +1,65 @@
+use std::rc::Rc;
+
 use dom::node::NodePtr;
-use gfx::{Bitmap, Canvas, CanvasCPU, TextMeasure};
+use gfx::{Bitmap, Canvas, CanvasCPU, FontDatabase, TextMeasure};
 use layout::{
     dump_layout,
     formatting_context::{establish_context, FormattingContextType},
     layout_box::{LayoutBox, LayoutBoxPtr},
     layout_context::LayoutContext,
+    tree_builder::LayoutTreeUpdate,
 };
 use painting::Painter;
 use shared::{
     primitive::{Rect, Size},
     tree_node::TreeNode,
 };
-use style_types::ContextualRule;
+use style::rule_map::RuleMap;
 
 pub struct Pipeline {
     painter: Painter,
+    /// Fonts shared by text measurement & painting
+    font_database: FontDatabase,
     layout_tree: Option<LayoutBoxPtr>,
-}
-
-pub struct PipelineRunOptions {
-    pub skip_style_calculation: bool,
-    pub skip_layout_calculation: bool,
+    /// The document the layout tree was built for
+    layout_document: Option<NodePtr>,
+    /// The viewport the layout tree was laid out in
+    viewport_size: Option<Size>,
+    /// Whether the generated content of the layout tree depends on the
+    /// document order, so that its boxes can't be rebuilt in place
+    layout_depends_on_document_order: bool,
+    /// The style rules of the document the styles were last computed for.
+    /// They are re-indexed when a stylesheet of the document is loaded or
+    /// edited.
+    rule_map: Option<(NodePtr, Rc<RuleMap>)>,
 }
 
 impl Pipeline {
     pub async fn new() -> Pipeline {
+        let font_database = FontDatabase::new();
         let painter = if std::env::var("CPU_RENDERING").is_ok() {
-            Painter::new(CanvasCPU::new())
+            Painter::new(CanvasCPU::new(font_database.clone()))
         } else {
-            Painter::new(Canvas::new().await)
+            Painter::new(Canvas::new(font_database.clone()).await)
         };
         Pipeline {
             painter,
+            font_database,
             layout_tree: None,
+            layout_document: None,
+            viewport_size: None,
+            layout_depends_on_document_order: false,
+            rule_map: None,
         }
     }
 
-    pub async fn run(
-        &mut self,
-        document_node: NodePtr,
-        size: &Size,
-        opts: PipelineRunOptions,
-    ) -> Bitmap {
-        if !opts.skip_style_calculation {
-            self.calculate_styles(document_node.clone());
-        }
-
-        if !opts.skip_layout_calculation {
-            self.layout_tree = self.calculate_layout(document_node, size);
-        }
+    /// Render the document. Only the styles of the dirty nodes are
+    /// recomputed & only the dirty parts of the layout tree are rebuilt.
+    /// The layout is global: the whole tree is laid out again if any box
+    /// changed.
+    pub async fn run(&mut self, document_node: NodePtr, size: &Size) -> Bitmap {
+        self.update_styles(&document_node);
+        self.update_layout(&document_node, size);
 
         self.painter.resize(size.clone());
         if let Some(node) = &self.layout_tree {
@@ -61,36 +72,125 @@ impl Pipeline {
         self.layout_tree.clone()
     }
 
-    fn calculate_styles(&self, document_node: NodePtr) {
+    /// Index the style rules of the document. Every style of the document
+    /// has to be recomputed with them.
+    fn build_rule_map(&mut self, document_node: &NodePtr) -> Rc<RuleMap> {
         let document = document_node.as_document();
-        let style_rules = document.style_rules();
+        document.set_declaration_support(Rc::new(style::supports::supports_declaration));
+        let rule_map = Rc::new(RuleMap::new(document.style_rules()));
+        document.clear_style_invalidation();
+        document_node.mark_style_dirty();
+
+        // web fonts are loaded with their stylesheet, & only the ones of
+        // the document are available to its text
+        self.font_database.set_web_faces(document.font_faces());
+        document.set_font_metrics_provider(Rc::new(self.font_database.clone()));
+
+        self.rule_map = Some((document_node.clone(), rule_map.clone()));
+        rule_map
+    }
+
+    /// The indexed style rules of the document, which are only rebuilt if
+    /// a stylesheet was loaded or edited since the styles were last computed
+    fn rule_map(&mut self, document_node: &NodePtr) -> Rc<RuleMap> {
+        if let Some((document, rule_map)) = &self.rule_map {
+            if Rc::ptr_eq(&document.0, &document_node.0)
+                && !document_node.as_document().is_style_invalidated()
+            {
+                return rule_map.clone();
+            }
+        }
+        self.build_rule_map(document_node)
+    }
 
-        fn compute_styles(element: NodePtr, style_rules: &[ContextualRule]) {
-            let computed_styles = style::compute::compute_styles(element.clone(), &style_rules);
-            element.set_computed_styles(computed_styles);
+    /// Recompute the styles of the style dirty nodes. Return `true` if the
+    /// computed style of any node changed.
+    pub fn update_styles(&mut self, document_node: &NodePtr) -> bool {
+        let style_rules = self.rule_map(document_node);
+        if document_node.is_style_dirty() {
+            // recorded again while computing the styles
+            document_node.as_document().set_viewport_dependent(false);
+        }
+        style::traversal::restyle_document(document_node, &style_rules)
+    }
+
+    /// Mark the elements whose state (e.g. `:hover`) changed style dirty, if
+    /// any style rule depends on the state. Return `true` if the elements
+    /// have to be restyled, which doesn't mean that their styles change.
+    pub fn invalidate_state_change(
+        &mut self,
+        document_node: NodePtr,
+        changed_elements: &[NodePtr],
+        pseudo_class: &str,
+    ) -> bool {
+        let style_rules = self.rule_map(&document_node);
+
+        let is_state_dependent = style_rules.rules().iter().any(|rule| {
+            rule.inner
+                .selectors
+                .iter()
+                .any(|selector| selector.has_pseudo_class(pseudo_class))
+        });
 
-            element.for_each_child(|child| compute_styles(NodePtr(child), style_rules))
+        if !is_state_dependent {
+            return false;
         }
 
-        compute_styles(document_node, &style_rules);
+        for element in changed_elements {
+            element.mark_style_dirty();
+        }
+        true
     }
 
-    fn calculate_layout(&self, document_node: NodePtr, size: &Size) -> Option<LayoutBoxPtr> {
-        let constructed_tree = layout::tree_builder::TreeBuilder::new().build(document_node);
-        let mut text_measure = TextMeasure::new();
-        let layout_tree = constructed_tree.map(|tree| {
-            let mut layout_context = LayoutContext {
-                viewport: Rect {
-                    x: 0.,
-                    y: 0.,
-                    width: size.width,
-                    height: size.height,
-                },
-                measure_text_fn: Box::new(move |content, font_size| {
-                    text_measure.measure(content, font_size)
-                }),
+    /// Update the boxes of the layout dirty nodes & lay out the whole tree
+    /// again if any box changed, or if the viewport is resized
+    fn update_layout(&mut self, document_node: &NodePtr, size: &Size) {
+        let is_same_document = match &self.layout_document {
+            Some(document) => Rc::ptr_eq(&document.0, &document_node.0),
+            None => false,
+        };
+
+        let update = match &self.layout_tree {
+            Some(layout_tree) if is_same_document => layout::tree_builder::update_layout_tree(
+                layout_tree,
+                document_node,
+                self.layout_depends_on_document_order,
+            ),
+            _ => LayoutTreeUpdate::Rebuild,
+        };
+
+        if update == LayoutTreeUpdate::Rebuild {
+            // keep the scroll position when the tree of the same document is
+            // built again. A new document starts from the top.
+            let scroll_top = match (&self.layout_tree, is_same_document) {
+                (Some(layout_tree), true) => layout_tree.scroll_top(),
+                _ => 0.,
             };
 
+            self.layout_document = Some(document_node.clone());
+            self.layout_tree = self.build_layout_tree(document_node.clone());
+
+            if let Some(layout_tree) = &self.layout_tree {
+                self.calculate_layout(layout_tree, size);
+                layout_tree.scroll(scroll_top);
+            }
+        } else if let Some(layout_tree) = &self.layout_tree {
+            if update == LayoutTreeUpdate::Relayout || self.viewport_size.as_ref() != Some(size) {
+                self.calculate_layout(layout_tree, size);
+            }
+        }
+        self.viewport_size = Some(size.clone());
+    }
+
+    /// Build the layout tree under an anonymous initial block box
+    fn build_layout_tree(&mut self, document_node: NodePtr) -> Option<LayoutBoxPtr> {
+        layout::tree_builder::clear_layout_dirty(&document_node);
+
+        let mut tree_builder = layout::tree_builder::TreeBuilder::new();
+        let constructed_tree = tree_builder.build(document_node);
+        self.layout_depends_on_document_order = tree_builder.depends_on_document_order();
+
+        constructed_tree.map(|tree| {
             let initial_block_box = LayoutBoxPtr(TreeNode::new(LayoutBox::new_anonymous(
                 layout::layout_box::BoxData::block_box(),
             )));
@@ -101,14 +201,26 @@ impl Pipeline {
                 initial_block_box.clone(),
             );
             initial_block_box
-                .formatting_context()
-                .run(&mut layout_context, initial_block_box.clone());
+        })
+    }
 
-            dump_layout!(initial_block_box);
+    /// Lay out the whole tree from its initial block box
+    fn calculate_layout(&self, initial_block_box: &LayoutBoxPtr, size: &Size) {
+        let mut text_measure = TextMeasure::new(self.font_database.clone());
+        let mut layout_context = LayoutContext {
+            viewport: Rect {
+                x: 0.,
+                y: 0.,
+                width: size.width,
+                height: size.height,
+            },
+            measure_text_fn: Box::new(move |content, font| text_measure.measure(content, font)),
+        };
 
-            initial_block_box
-        });
+        initial_block_box
+            .formatting_context()
+            .run(&mut layout_context, initial_block_box.clone());
 
-        layout_tree
+        dump_layout!(initial_block_box);
     }
 }
@@ -0,0 +1,221 @@
+use dom::document::Document;
+use shared::color::Color;
+use style_types::{CSSLocation, CascadeOrigin, ContextualStyleSheet};
+
+const USER_AGENT_STYLES: &str = include_str!("./html.css");
+
+/// The stylesheets that the browser applies to every page besides the
+/// stylesheets of the page itself
+#[derive(Debug, Clone, Default)]
+pub struct StyleSettings {
+    /// Replaces the default user agent stylesheet
+    pub user_agent_css: Option<String>,
+    /// Applied after the user agent stylesheet, with the user agent origin
+    pub extra_user_agent_css: Vec<String>,
+    pub user_css: Option<String>,
+    /// Replace the colors of the page with the palette, e.g. for a high
+    /// contrast mode
+    pub forced_colors: Option<ForcedColors>,
+}
+
+/// The palette of the forced colors mode
+#[derive(Debug, Clone)]
+pub struct ForcedColors {
+    pub canvas: Color,
+    pub canvas_text: Color,
+    pub link_text: Color,
+}
+
+impl Default for ForcedColors {
+    /// White text on a black background, with yellow links
+    fn default() -> Self {
+        let rgb = |r, g, b| Color { r, g, b, a: 255 };
+        Self {
+            canvas: rgb(0, 0, 0),
+            canvas_text: rgb(255, 255, 255),
+            link_text: rgb(255, 255, 0),
+        }
+    }
+}
+
+impl StyleSettings {
+    /// Load the settings of the browser from its environment, which it
+    /// sends to the render engine when starting it:
+    /// - `USER_AGENT_STYLESHEET`: path of a stylesheet replacing the default
+    ///   user agent stylesheet
+    /// - `USER_STYLESHEET`: path of the user stylesheet
+    /// - `FORCED_COLORS`: enable the forced colors mode
+    ///
+    /// A stylesheet that can't be read is ignored.
+    pub fn from_env() -> Self {
+        let read_stylesheet = |name: &str| {
+            let path = std::env::var(name).ok()?;
+            match std::fs::read_to_string(&path) {
+                Ok(css) => Some(css),
+                Err(e) => {
+                    log::error!("Unable to read stylesheet {}: {}", path, e);
+                    None
+                }
+            }
+        };
+
+        Self {
+            user_agent_css: read_stylesheet("USER_AGENT_STYLESHEET"),
+            extra_user_agent_css: Vec::new(),
+            user_css: read_stylesheet("USER_STYLESHEET"),
+            forced_colors: std::env::var("FORCED_COLORS")
+                .ok()
+                .map(|_| ForcedColors::default()),
+        }
+    }
+
+    /// Set the user agent & user stylesheets of the document
+    pub fn apply(&self, document: &Document) {
+        let user_agent_css = self.user_agent_css.as_deref().unwrap_or(USER_AGENT_STYLES);
+        let mut user_agent_stylesheets =
+            vec![parse_stylesheet(user_agent_css, CascadeOrigin::UserAgent)];
+        for css in &self.extra_user_agent_css {
+            user_agent_stylesheets.push(parse_stylesheet(css, CascadeOrigin::UserAgent));
+        }
+        // important user agent declarations win over every other origin
+        if let Some(forced_colors) = &self.forced_colors {
+            user_agent_stylesheets.push(parse_stylesheet(
+                &forced_colors.css(),
+                CascadeOrigin::UserAgent,
+            ));
+        }
+        document.set_user_agent_stylesheets(user_agent_stylesheets);
+
+        let user_stylesheets = self
+            .user_css
+            .iter()
+            .map(|css| parse_stylesheet(css, CascadeOrigin::User))
+            .collect();
+        document.set_user_stylesheets(user_stylesheets);
+    }
+}
+
+impl ForcedColors {
+    /// The stylesheet that forces the palette on every element
+    fn css(&self) -> String {
+        let canvas = css_color(&self.canvas);
+        let canvas_text = css_color(&self.canvas_text);
+        let link_text = css_color(&self.link_text);
+        format!(
+            "* {{ color: {text} !important; background-color: transparent !important; border-color: {text} !important; }}\n\
+             html {{ background-color: {canvas} !important; }}\n\
+             a[href], a[href] * {{ color: {link} !important; border-color: {link} !important; }}\n",
+            text = canvas_text,
+            canvas = canvas,
+            link = link_text
+        )
+    }
+}
+
+fn css_color(color: &Color) -> String {
+    format!(
+        "rgba({}, {}, {}, {})",
+        color.r,
+        color.g,
+        color.b,
+        color.a as f32 / 255.
+    )
+}
+
+fn parse_stylesheet(css: &str, origin: CascadeOrigin) -> ContextualStyleSheet {
+    let tokenizer = css::tokenizer::Tokenizer::new(css.chars());
+    let mut parser = css::parser::Parser::<css::tokenizer::token::Token>::new(tokenizer.run());
+    let stylesheet = parser.parse_a_css_stylesheet();
+    ContextualStyleSheet::new(stylesheet, origin, CSSLocation::External)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dom::node::NodePtr;
+    use style::rule_map::RuleMap;
+    use style::traversal::restyle_document;
+    use style_types::{Property, Value};
+    use test_utils::dom_creator::{document, element, text};
+
+    /// Create a document with a paragraph & a link, styled with the
+    /// settings & the author stylesheet
+    fn styled_document(settings: &StyleSettings, author_css: &str) -> (NodePtr, NodePtr, NodePtr) {
+        let doc = document();
+        settings.apply(doc.as_document());
+
+        let style = element("style", doc.clone(), vec![text(author_css, doc.clone())]);
+        let p = element("p", doc.clone(), vec![]);
+        let a = element("a", doc.clone(), vec![]);
+        a.set_attribute("href", "https://example.com");
+        let html = element("html", doc.clone(), vec![p.clone(), a.clone()]);
+        doc.append_child(style.0.clone());
+        doc.append_child(html.0.clone());
+
+        restyle_document(&doc, &RuleMap::new(doc.as_document().style_rules()));
+        (html, p, a)
+    }
+
+    fn px(node: &NodePtr, property: Property) -> f32 {
+        node.get_style(&property).to_absolute_px()
+    }
+
+    fn rgba(node: &NodePtr, property: Property) -> [u8; 4] {
+        match node.get_style(&property) {
+            Value::Color(color) => {
+                let color = Color::from(&color);
+                [color.r, color.g, color.b, color.a]
+            }
+            value => panic!("{:?} isn't a color: {:?}", property, value),
+        }
+    }
+
+    #[test]
+    fn apply_user_stylesheets_between_user_agent_and_author() {
+        let settings = StyleSettings {
+            user_agent_css: Some(
+                "p { width: 1px; height: 1px; padding-top: 1px !important; }".to_string(),
+            ),
+            extra_user_agent_css: vec!["p { margin-top: 1px; }".to_string()],
+            user_css: Some(
+                "p { width: 2px; height: 2px; padding-top: 2px !important; \
+                 padding-left: 2px !important; }"
+                    .to_string(),
+            ),
+            forced_colors: None,
+        };
+        let (_, p, _) = styled_document(
+            &settings,
+            "p { height: 3px; padding-top: 3px !important; padding-left: 3px !important; }",
+        );
+
+        assert_eq!(px(&p, Property::MarginTop), 1.);
+        // normal user declarations win over the user agent ones only
+        assert_eq!(px(&p, Property::Width), 2.);
+        assert_eq!(px(&p, Property::Height), 3.);
+        // important user declarations win over the author ones, but not
+        // over the important user agent ones
+        assert_eq!(px(&p, Property::PaddingLeft), 2.);
+        assert_eq!(px(&p, Property::PaddingTop), 1.);
+    }
+
+    #[test]
+    fn force_colors() {
+        let settings = StyleSettings {
+            forced_colors: Some(ForcedColors::default()),
+            ..StyleSettings::default()
+        };
+        let author_css = "html { background-color: white; } \
+                          p, a { color: red !important; background-color: blue !important; }";
+        let (html, p, a) = styled_document(&settings, author_css);
+
+        assert_eq!(rgba(&html, Property::BackgroundColor), [0, 0, 0, 255]);
+        assert_eq!(rgba(&p, Property::Color), [255, 255, 255, 255]);
+        assert_eq!(rgba(&p, Property::BackgroundColor), [0, 0, 0, 0]);
+        assert_eq!(rgba(&a, Property::Color), [255, 255, 0, 255]);
+
+        // without forced colors, the author colors are used
+        let (_, p, _) = styled_document(&StyleSettings::default(), author_css);
+        assert_eq!(rgba(&p, Property::Color), [255, 0, 0, 255]);
+    }
+}
@@ -3,6 +3,7 @@ use std::str::FromStr;
 
 pub enum Action {
     RenderOnce(RenderOnceParams),
+    StartRenderer(String),
     StartMain,
 }
 
@@ -31,6 +32,11 @@ pub fn get_action<'a>(matches: ArgMatches<'a>) -> Action {
         }
     }
 
+    if let Some(matches) = matches.subcommand_matches("renderer") {
+        let socket_path: String = get_arg(&matches, "socket").unwrap();
+        return Action::StartRenderer(socket_path);
+    }
+
     Action::StartMain
 }
 
@@ -31,9 +31,20 @@ pub fn accept_cli<'a>() -> ArgMatches<'a> {
         .arg(once_flag.clone())
         .arg(ouput_arg.clone());
 
+    let socket_arg = Arg::with_name("socket")
+        .long("socket")
+        .required(true)
+        .takes_value(true);
+
+    let renderer_subcommand = App::new("renderer")
+        .about("Start a render process of Moon that connects to the browser through a socket")
+        .author(AUTHOR)
+        .arg(socket_arg);
+
     App::new("Moon Renderer")
         .author(AUTHOR)
         .about("Moon web browser!")
         .subcommand(render_once_subcommand)
+        .subcommand(renderer_subcommand)
         .get_matches()
 }
@@ -6,6 +6,7 @@ use render::page::Page;
 use shared::primitive::Size;
 use simplelog::*;
 use std::io::Read;
+use std::path::Path;
 use url::parser::URLParser;
 
 fn read_file(path: String) -> String {
@@ -63,6 +64,11 @@ fn main() {
                 buffer.save(output_path).unwrap();
             });
         }
+        cli::Action::StartRenderer(socket_path) => {
+            if let Err(e) = render::ipc::run_render_process(Path::new(&socket_path)) {
+                log::error!("Render process exited with error: {}", e);
+            }
+        }
         cli::Action::StartMain => {
             main::start_main().expect("Browser crashed with error");
         }